@@ -0,0 +1,129 @@
+//! Throughput of the Redis stores under concurrent requests, compared with the single blocking connection behind a
+//! lock they used before. Needs Redis at `REDIS_HOST_NAME`; run with `cargo bench --bench redis_stores`.
+
+use {
+    auth_service::{
+        domain::data_stores::{BannedTokenStore, LoginAttemptId, TwoFactorCode, TwoFactorStore},
+        get_redis_client,
+        services::{RedisBannedTokenStore, RedisTwoFactorStore},
+        utils::constants::REDIS_HOST_NAME,
+    },
+    redis::{Client, Connection, TypedCommands},
+    secrecy::SecretBox,
+    std::{
+        future::Future,
+        sync::Arc,
+        time::{Duration, Instant},
+    },
+    tokio::{sync::RwLock, task::JoinSet},
+    uuid::Uuid,
+};
+
+const TASKS: usize = 64;
+const REQUESTS_PER_TASK: usize = 200;
+
+/// The layout every Redis store had before: one blocking connection, write-locked for every command.
+struct LockedConnection(RwLock<Connection>);
+
+#[tokio::main]
+async fn main() {
+    let client = get_redis_client(REDIS_HOST_NAME.as_str()).expect("Failed to get Redis client");
+    let user_id = Uuid::new_v4();
+    let jti = Uuid::new_v4().to_string();
+    let token = Uuid::new_v4().to_string();
+    let two_factor_key = format!("two_factor:{user_id}");
+
+    let locked = Arc::new(LockedConnection(RwLock::new(connect(&client))));
+    let banned_token_store = Arc::new(RedisBannedTokenStore::new(connect_manager(&client).await));
+    let two_factor_store = Arc::new(RedisTwoFactorStore::new(connect_manager(&client).await));
+
+    two_factor_store
+        .add_code(user_id, LoginAttemptId::default(), TwoFactorCode::default())
+        .await
+        .expect("Failed to add 2FA code");
+
+    report(
+        "banned token check, locked connection",
+        run({
+            let locked = locked.clone();
+            let jti = jti.clone();
+
+            move || {
+                let locked = locked.clone();
+                let keys = vec![format!("banned_jti:{jti}")];
+
+                async move { locked.0.write().await.exists(keys).expect("Failed to check token") }
+            }
+        })
+        .await,
+    );
+    report(
+        "banned token check, connection manager",
+        run(move || {
+            let store = banned_token_store.clone();
+            let jti = jti.clone();
+            let token = SecretBox::new(Box::new(token.clone()));
+
+            async move { store.check(&jti, &token).await.expect("Failed to check token") }
+        })
+        .await,
+    );
+    report(
+        "2FA code lookup, locked connection",
+        run(move || {
+            let locked = locked.clone();
+            let key = two_factor_key.clone();
+
+            async move { locked.0.write().await.get(key).expect("Failed to get 2FA code") }
+        })
+        .await,
+    );
+    report(
+        "2FA code lookup, connection manager",
+        run(move || {
+            let store = two_factor_store.clone();
+
+            async move { store.get_code(&user_id).await.expect("Failed to get 2FA code") }
+        })
+        .await,
+    );
+}
+
+/// Runs `TASKS` concurrent tasks sending `REQUESTS_PER_TASK` requests each and returns the elapsed time.
+async fn run<F, Fut, T>(request: F) -> Duration
+where
+    F: Fn() -> Fut + Clone + Send + 'static,
+    Fut: Future<Output = T> + Send,
+    T: Send + 'static,
+{
+    let started = Instant::now();
+    let mut tasks = JoinSet::new();
+
+    for _ in 0..TASKS {
+        let request = request.clone();
+
+        tasks.spawn(async move {
+            for _ in 0..REQUESTS_PER_TASK {
+                request().await;
+            }
+        });
+    }
+
+    tasks.join_all().await;
+
+    started.elapsed()
+}
+
+fn report(name: &str, elapsed: Duration) {
+    let requests = (TASKS * REQUESTS_PER_TASK) as f64;
+
+    println!("{name:<40} {:>10.0} req/s", requests / elapsed.as_secs_f64());
+}
+
+fn connect(client: &Client) -> Connection {
+    client.get_connection().expect("Failed to get Redis connection")
+}
+
+async fn connect_manager(client: &Client) -> redis::aio::ConnectionManager {
+    client.get_connection_manager().await.expect("Failed to get Redis connection")
+}
@@ -0,0 +1,4 @@
+alter table users add column if not exists email_verified boolean not null default false;
+
+-- accounts created before verification existed are trusted as-is
+update users set email_verified = true;
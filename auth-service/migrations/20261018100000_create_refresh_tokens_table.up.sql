@@ -0,0 +1,11 @@
+create table if not exists refresh_tokens(
+    token text not null primary key,
+    family_id uuid not null,
+    email text not null references users(email) on delete cascade,
+    used boolean not null default false,
+    revoked boolean not null default false,
+    expires_at timestamptz not null
+);
+
+create index if not exists refresh_tokens_family_id_idx on refresh_tokens(family_id);
+create index if not exists refresh_tokens_email_idx on refresh_tokens(email);
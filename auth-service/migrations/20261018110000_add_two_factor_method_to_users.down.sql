@@ -0,0 +1,9 @@
+drop table if exists totp_secrets;
+
+alter table users add column requires_2fa boolean not null default false;
+
+update users set requires_2fa = true where two_factor_method <> 'none';
+
+alter table users drop column two_factor_method;
+
+drop type if exists two_factor_method;
@@ -0,0 +1,13 @@
+create type two_factor_method as enum ('none', 'email', 'totp');
+
+alter table users add column two_factor_method two_factor_method not null default 'none';
+
+update users set two_factor_method = 'email' where requires_2fa;
+
+alter table users drop column requires_2fa;
+
+create table if not exists totp_secrets(
+    email text not null primary key references users(email) on delete cascade,
+    secret bytea,
+    pending_secret bytea
+);
@@ -0,0 +1,7 @@
+create table if not exists recovery_codes(
+    id bigserial primary key,
+    email text not null references users(email) on delete cascade,
+    code_hash text not null
+);
+
+create index if not exists recovery_codes_email_idx on recovery_codes(email);
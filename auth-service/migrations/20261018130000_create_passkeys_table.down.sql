@@ -0,0 +1,4 @@
+drop table if exists passkeys;
+
+-- enum values cannot be dropped, so 'passkey' stays on the type but is no longer assigned
+update users set two_factor_method = 'none' where two_factor_method = 'passkey';
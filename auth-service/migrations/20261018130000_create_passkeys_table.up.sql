@@ -0,0 +1,10 @@
+alter type two_factor_method add value if not exists 'passkey';
+
+create table if not exists passkeys(
+    credential_id bytea not null primary key,
+    email text not null references users(email) on delete cascade,
+    passkey jsonb not null,
+    created_at timestamptz not null default now()
+);
+
+create index if not exists passkeys_email_idx on passkeys(email);
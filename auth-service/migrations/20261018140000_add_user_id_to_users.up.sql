@@ -0,0 +1,2 @@
+-- stable identifier for tokens, so claims no longer carry the email address
+alter table users add column if not exists user_id uuid not null unique default gen_random_uuid();
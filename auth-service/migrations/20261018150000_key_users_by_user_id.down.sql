@@ -0,0 +1,39 @@
+drop index if exists users_email_lower_idx;
+alter table refresh_tokens drop constraint if exists refresh_tokens_user_id_fkey;
+alter table totp_secrets drop constraint if exists totp_secrets_user_id_fkey;
+alter table recovery_codes drop constraint if exists recovery_codes_user_id_fkey;
+alter table passkeys drop constraint if exists passkeys_user_id_fkey;
+alter table users drop constraint users_pkey;
+alter table users add primary key (email);
+alter table users add constraint users_user_id_key unique (user_id);
+
+alter table refresh_tokens add column email text;
+update refresh_tokens set email = users.email from users where users.user_id = refresh_tokens.user_id;
+alter table refresh_tokens alter column email set not null;
+alter table refresh_tokens add foreign key (email) references users(email) on delete cascade;
+drop index if exists refresh_tokens_user_id_idx;
+alter table refresh_tokens drop column user_id;
+create index if not exists refresh_tokens_email_idx on refresh_tokens(email);
+
+alter table totp_secrets add column email text;
+update totp_secrets set email = users.email from users where users.user_id = totp_secrets.user_id;
+alter table totp_secrets drop column user_id;
+alter table totp_secrets alter column email set not null;
+alter table totp_secrets add primary key (email);
+alter table totp_secrets add foreign key (email) references users(email) on delete cascade;
+
+alter table recovery_codes add column email text;
+update recovery_codes set email = users.email from users where users.user_id = recovery_codes.user_id;
+alter table recovery_codes alter column email set not null;
+alter table recovery_codes add foreign key (email) references users(email) on delete cascade;
+drop index if exists recovery_codes_user_id_idx;
+alter table recovery_codes drop column user_id;
+create index if not exists recovery_codes_email_idx on recovery_codes(email);
+
+alter table passkeys add column email text;
+update passkeys set email = users.email from users where users.user_id = passkeys.user_id;
+alter table passkeys alter column email set not null;
+alter table passkeys add foreign key (email) references users(email) on delete cascade;
+drop index if exists passkeys_user_id_idx;
+alter table passkeys drop column user_id;
+create index if not exists passkeys_email_idx on passkeys(email);
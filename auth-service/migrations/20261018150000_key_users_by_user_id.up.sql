@@ -0,0 +1,39 @@
+-- user_id becomes the primary key and every table referencing users points at it, so the email can change
+alter table refresh_tokens add column user_id uuid;
+update refresh_tokens set user_id = users.user_id from users where users.email = refresh_tokens.email;
+alter table refresh_tokens alter column user_id set not null;
+drop index if exists refresh_tokens_email_idx;
+alter table refresh_tokens drop column email;
+
+alter table totp_secrets add column user_id uuid;
+update totp_secrets set user_id = users.user_id from users where users.email = totp_secrets.email;
+alter table totp_secrets alter column user_id set not null;
+alter table totp_secrets drop column email;
+alter table totp_secrets add primary key (user_id);
+
+alter table recovery_codes add column user_id uuid;
+update recovery_codes set user_id = users.user_id from users where users.email = recovery_codes.email;
+alter table recovery_codes alter column user_id set not null;
+drop index if exists recovery_codes_email_idx;
+alter table recovery_codes drop column email;
+
+alter table passkeys add column user_id uuid;
+update passkeys set user_id = users.user_id from users where users.email = passkeys.email;
+alter table passkeys alter column user_id set not null;
+drop index if exists passkeys_email_idx;
+alter table passkeys drop column email;
+
+alter table users drop constraint users_pkey;
+alter table users drop constraint users_user_id_key;
+alter table users add primary key (user_id);
+-- fails if addresses differing only in case were registered; merge those accounts by hand first
+create unique index users_email_lower_idx on users (lower(email));
+
+alter table refresh_tokens add foreign key (user_id) references users(user_id) on delete cascade;
+alter table totp_secrets add foreign key (user_id) references users(user_id) on delete cascade;
+alter table recovery_codes add foreign key (user_id) references users(user_id) on delete cascade;
+alter table passkeys add foreign key (user_id) references users(user_id) on delete cascade;
+
+create index if not exists refresh_tokens_user_id_idx on refresh_tokens(user_id);
+create index if not exists recovery_codes_user_id_idx on recovery_codes(user_id);
+create index if not exists passkeys_user_id_idx on passkeys(user_id);
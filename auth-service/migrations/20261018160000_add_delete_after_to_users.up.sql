@@ -0,0 +1,4 @@
+-- accounts the owner deleted stay hidden until this moment and are purged afterwards
+alter table users add column if not exists delete_after timestamptz;
+
+create index if not exists users_delete_after_idx on users(delete_after) where delete_after is not null;
@@ -0,0 +1,32 @@
+create table if not exists roles(
+    name text not null primary key,
+    description text not null
+);
+
+create table if not exists permissions(
+    name text not null primary key,
+    description text not null
+);
+
+create table if not exists role_permissions(
+    role text not null references roles(name) on delete cascade,
+    permission text not null references permissions(name) on delete cascade,
+    primary key (role, permission)
+);
+
+create table if not exists user_roles(
+    user_id uuid not null references users(user_id) on delete cascade,
+    role text not null references roles(name) on delete cascade,
+    primary key (user_id, role)
+);
+
+insert into roles (name, description) values ('admin', 'Manages users and their roles') on conflict do nothing;
+
+insert into permissions (name, description) values
+    ('users:read', 'List and view users'),
+    ('users:write', 'Change and lock users'),
+    ('roles:read', 'List roles and their permissions'),
+    ('roles:write', 'Assign roles to users and revoke them')
+on conflict do nothing;
+
+insert into role_permissions (role, permission) select 'admin', name from permissions on conflict do nothing;
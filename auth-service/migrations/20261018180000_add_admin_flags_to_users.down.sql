@@ -0,0 +1,3 @@
+alter table users drop column if exists password_reset_required;
+
+alter table users drop column if exists disabled;
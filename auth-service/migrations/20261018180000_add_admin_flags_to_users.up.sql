@@ -0,0 +1,3 @@
+-- set by operators through the admin API; both keep the user from logging in
+alter table users add column if not exists disabled boolean not null default false;
+alter table users add column if not exists password_reset_required boolean not null default false;
@@ -0,0 +1,11 @@
+alter table users add column disabled boolean not null default false;
+
+update users set disabled = true where status = 'disabled';
+
+alter table users drop column suspended_until;
+
+alter table users drop column status_reason;
+
+alter table users drop column status;
+
+drop type if exists account_status;
@@ -0,0 +1,12 @@
+create type account_status as enum ('active', 'suspended', 'disabled', 'pending_verification');
+
+alter table users add column status account_status not null default 'active';
+-- why the account was suspended or disabled, and when a suspension lifts by itself
+alter table users add column status_reason text;
+alter table users add column suspended_until timestamptz;
+
+update users set status = 'pending_verification' where not email_verified;
+
+update users set status = 'disabled' where disabled;
+
+alter table users drop column disabled;
@@ -0,0 +1,5 @@
+delete from permissions where name = 'audit:read';
+
+drop table if exists audit_events;
+drop function if exists reject_audit_event_change;
+drop type if exists audit_event_type;
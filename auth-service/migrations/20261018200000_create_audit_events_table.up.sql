@@ -0,0 +1,39 @@
+create type audit_event_type as enum (
+    'signup',
+    'login_succeeded',
+    'login_failed',
+    'two_factor_sent',
+    'two_factor_verified',
+    'two_factor_failed',
+    'logout',
+    'token_verification_failed',
+    'admin_action'
+);
+
+-- events outlive the users they are about, so user ids are not foreign keys
+create table if not exists audit_events(
+    id bigint generated always as identity primary key,
+    event_type audit_event_type not null,
+    occurred_at timestamptz not null,
+    user_id uuid,
+    actor_id uuid,
+    ip inet not null,
+    user_agent text,
+    request_id uuid not null,
+    details text
+);
+
+create index if not exists audit_events_user_id_idx on audit_events (user_id, id);
+
+create or replace function reject_audit_event_change() returns trigger as $$
+begin
+    raise exception 'audit events are append-only';
+end;
+$$ language plpgsql;
+
+create trigger audit_events_append_only before update or delete on audit_events
+    for each row execute function reject_audit_event_change();
+
+insert into permissions (name, description) values ('audit:read', 'Query the security audit log') on conflict do nothing;
+
+insert into role_permissions (role, permission) values ('admin', 'audit:read') on conflict do nothing;
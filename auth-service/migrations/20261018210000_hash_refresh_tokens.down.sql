@@ -0,0 +1,3 @@
+-- hashes cannot be turned back into tokens, so every refresh token is dropped and users have to log in again
+delete from refresh_tokens;
+alter table refresh_tokens rename column token_hash to token;
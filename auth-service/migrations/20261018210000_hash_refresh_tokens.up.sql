@@ -0,0 +1,3 @@
+-- only hashes of the bearer tokens are kept, so reading the table does not hand out usable tokens
+alter table refresh_tokens rename column token to token_hash;
+update refresh_tokens set token_hash = encode(sha256(convert_to(token_hash, 'UTF8')), 'hex');
@@ -0,0 +1 @@
+alter table totp_secrets drop column last_time_step;
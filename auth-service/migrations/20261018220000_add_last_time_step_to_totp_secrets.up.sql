@@ -0,0 +1,2 @@
+-- latest time step a code was accepted for, so a captured code cannot be replayed within its window
+alter table totp_secrets add column last_time_step bigint;
@@ -0,0 +1,7 @@
+-- keys every instance signs and verifies JWTs with; private keys are encrypted like TOTP secrets
+create table if not exists jwt_signing_keys(
+    kid text not null primary key,
+    private_key bytea not null,
+    activates_at timestamptz not null,
+    retired_at timestamptz
+);
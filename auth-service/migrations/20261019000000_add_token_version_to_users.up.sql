@@ -0,0 +1,2 @@
+-- redis only caches token versions from here on; versions it kept before are carried over by the next bump
+alter table users add column if not exists token_version bigint not null default 0;
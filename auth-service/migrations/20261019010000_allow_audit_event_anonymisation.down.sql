@@ -0,0 +1,5 @@
+create or replace function reject_audit_event_change() returns trigger as $$
+begin
+    raise exception 'audit events are append-only';
+end;
+$$ language plpgsql;
@@ -0,0 +1,19 @@
+-- events stay append-only, except that the personal data of deleted users may be erased from them
+create or replace function reject_audit_event_change() returns trigger as $$
+begin
+    if tg_op = 'UPDATE' and
+        new.id = old.id and
+        new.event_type = old.event_type and
+        new.occurred_at = old.occurred_at and
+        new.request_id = old.request_id and
+        new.details is not distinct from old.details and
+        (new.user_id is null or new.user_id = old.user_id) and
+        (new.actor_id is null or new.actor_id = old.actor_id) and
+        (new.ip = old.ip or new.ip = '0.0.0.0'::inet) and
+        (new.user_agent is null or new.user_agent = old.user_agent) then
+        return new;
+    end if;
+
+    raise exception 'audit events are append-only';
+end;
+$$ language plpgsql;
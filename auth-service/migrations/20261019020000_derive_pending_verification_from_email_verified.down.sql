@@ -0,0 +1,3 @@
+alter table users drop constraint if exists users_status_check;
+
+update users set status = 'pending_verification' where status = 'active' and not email_verified;
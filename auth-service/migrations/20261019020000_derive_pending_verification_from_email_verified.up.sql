@@ -0,0 +1,5 @@
+-- accounts an admin has not blocked are pending verification exactly while email_verified is false, so the status
+-- column no longer keeps a copy of that
+update users set status = 'active' where status = 'pending_verification';
+
+alter table users add constraint users_status_check check (status <> 'pending_verification');
@@ -1,31 +1,116 @@
 use {
-    crate::domain::{
-        data_stores::{BannedTokenStore, TwoFactorStore, UserStore},
-        email_client::EmailClient,
+    crate::{
+        domain::{
+            data_stores::{
+                AuditLog, BannedTokenStore, EmailChangeStore, EmailVerificationStore, LoginThrottleStore,
+                MagicLinkStore, PasskeyCeremonyStore, PasskeyStore, PasswordResetStore, RateLimitStore,
+                RecoveryCodeStore, RefreshTokenStore, RoleStore, SessionStore, SigningKeyStore, TotpSecretStore,
+                TwoFactorStore, UserStore,
+            },
+            email_client::EmailClient,
+        },
+        services::UserStatusCache,
+        utils::constants::ACCOUNT_DELETION_GRACE_SECONDS,
     },
     std::sync::Arc,
+    webauthn_rs::Webauthn,
 };
 
 pub type UserStoreType = Arc<dyn UserStore>;
 pub type BannedTokenStoreType = Arc<dyn BannedTokenStore>;
 pub type TwoFactorStoreType = Arc<dyn TwoFactorStore>;
+pub type PasswordResetStoreType = Arc<dyn PasswordResetStore>;
+pub type EmailVerificationStoreType = Arc<dyn EmailVerificationStore>;
+pub type EmailChangeStoreType = Arc<dyn EmailChangeStore>;
+pub type RefreshTokenStoreType = Arc<dyn RefreshTokenStore>;
+pub type SessionStoreType = Arc<dyn SessionStore>;
+pub type TotpSecretStoreType = Arc<dyn TotpSecretStore>;
+pub type RecoveryCodeStoreType = Arc<dyn RecoveryCodeStore>;
+pub type PasskeyStoreType = Arc<dyn PasskeyStore>;
+pub type PasskeyCeremonyStoreType = Arc<dyn PasskeyCeremonyStore>;
+pub type MagicLinkStoreType = Arc<dyn MagicLinkStore>;
+pub type RoleStoreType = Arc<dyn RoleStore>;
+pub type AuditLogType = Arc<dyn AuditLog>;
+pub type SigningKeyStoreType = Arc<dyn SigningKeyStore>;
+pub type LoginThrottleStoreType = Arc<dyn LoginThrottleStore>;
+pub type RateLimitStoreType = Arc<dyn RateLimitStore>;
 pub type EmailClientType = Arc<dyn EmailClient>;
+pub type WebauthnType = Arc<Webauthn>;
 
 #[derive(Clone)]
 pub struct AppState {
     pub user_store: UserStoreType,
     pub banned_token_store: BannedTokenStoreType,
     pub two_factor_store: TwoFactorStoreType,
+    pub password_reset_store: PasswordResetStoreType,
+    pub email_verification_store: EmailVerificationStoreType,
+    pub email_change_store: EmailChangeStoreType,
+    pub refresh_token_store: RefreshTokenStoreType,
+    pub session_store: SessionStoreType,
+    pub totp_secret_store: TotpSecretStoreType,
+    pub recovery_code_store: RecoveryCodeStoreType,
+    pub passkey_store: PasskeyStoreType,
+    pub passkey_ceremony_store: PasskeyCeremonyStoreType,
+    pub magic_link_store: MagicLinkStoreType,
+    pub role_store: RoleStoreType,
+    pub audit_log: AuditLogType,
+    pub signing_key_store: SigningKeyStoreType,
+    pub login_throttle_store: LoginThrottleStoreType,
+    pub rate_limit_store: RateLimitStoreType,
     pub email_client: EmailClientType,
+    pub webauthn: WebauthnType,
+    pub user_status_cache: Arc<UserStatusCache>,
+    /// How long deleted accounts can be restored before they are purged; with 0 they are removed right away.
+    pub account_deletion_grace_seconds: u64,
 }
 
 impl AppState {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         banned_token_store: BannedTokenStoreType,
         user_store: UserStoreType,
         two_factor_store: TwoFactorStoreType,
+        password_reset_store: PasswordResetStoreType,
+        email_verification_store: EmailVerificationStoreType,
+        email_change_store: EmailChangeStoreType,
+        refresh_token_store: RefreshTokenStoreType,
+        session_store: SessionStoreType,
+        totp_secret_store: TotpSecretStoreType,
+        recovery_code_store: RecoveryCodeStoreType,
+        passkey_store: PasskeyStoreType,
+        passkey_ceremony_store: PasskeyCeremonyStoreType,
+        magic_link_store: MagicLinkStoreType,
+        role_store: RoleStoreType,
+        audit_log: AuditLogType,
+        signing_key_store: SigningKeyStoreType,
+        login_throttle_store: LoginThrottleStoreType,
+        rate_limit_store: RateLimitStoreType,
         email_client: EmailClientType,
+        webauthn: WebauthnType,
     ) -> Self {
-        Self { banned_token_store, user_store, two_factor_store, email_client }
+        Self {
+            banned_token_store,
+            user_store,
+            two_factor_store,
+            password_reset_store,
+            email_verification_store,
+            email_change_store,
+            refresh_token_store,
+            session_store,
+            totp_secret_store,
+            recovery_code_store,
+            passkey_store,
+            passkey_ceremony_store,
+            magic_link_store,
+            role_store,
+            audit_log,
+            signing_key_store,
+            login_throttle_store,
+            rate_limit_store,
+            email_client,
+            webauthn,
+            user_status_cache: Arc::new(UserStatusCache::default()),
+            account_deletion_grace_seconds: *ACCOUNT_DELETION_GRACE_SECONDS,
+        }
     }
 }
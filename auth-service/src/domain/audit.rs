@@ -0,0 +1,68 @@
+use {
+    serde::{Deserialize, Serialize},
+    std::net::IpAddr,
+    uuid::Uuid,
+};
+
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize, sqlx::Type)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "audit_event_type", rename_all = "snake_case")]
+pub enum AuditEventType {
+    Signup,
+    /// A session was started, whichever way the user logged in.
+    LoginSucceeded,
+    LoginFailed,
+    /// An emailed 2FA code was sent.
+    TwoFactorSent,
+    TwoFactorVerified,
+    TwoFactorFailed,
+    Logout,
+    TokenVerificationFailed,
+    AdminAction,
+}
+
+/// Security relevant event, recorded in the append-only audit log.
+#[derive(Clone, Debug, PartialEq)]
+pub struct AuditEvent {
+    pub event_type: AuditEventType,
+    /// Unix timestamp the event happened at.
+    pub occurred_at: i64,
+    /// User the event is about; unknown for failed logins with an address nobody signed up with.
+    pub user_id: Option<Uuid>,
+    /// Admin who took an admin action, unless they used the admin API key.
+    pub actor_id: Option<Uuid>,
+    pub ip: IpAddr,
+    pub user_agent: Option<String>,
+    /// Id of the request the event happened in, as found in the request's tracing span.
+    pub request_id: Uuid,
+    /// Why something failed, or which admin action was taken.
+    pub details: Option<String>,
+}
+
+impl AuditEvent {
+    pub fn user(self, user_id: Uuid) -> Self {
+        Self { user_id: Some(user_id), ..self }
+    }
+
+    pub fn actor(self, actor_id: Option<Uuid>) -> Self {
+        Self { actor_id, ..self }
+    }
+
+    pub fn details(self, details: impl Into<String>) -> Self {
+        Self { details: Some(details.into()), ..self }
+    }
+}
+
+/// Narrows an audit log query down to events matching every field that is set.
+#[derive(Clone, Debug, Default)]
+pub struct AuditEventFilter {
+    pub user_id: Option<Uuid>,
+    pub event_type: Option<AuditEventType>,
+}
+
+/// One page of audit events, newest first, along with how many match across all pages.
+#[derive(Clone, Debug, PartialEq)]
+pub struct AuditEventPage {
+    pub events: Vec<AuditEvent>,
+    pub total: u64,
+}
@@ -1,17 +1,30 @@
 use {
-    crate::domain::{
-        email::Email,
-        password::Password,
-        user::{User, UserRow},
+    crate::{
+        domain::{
+            audit::{AuditEvent, AuditEventFilter, AuditEventPage},
+            email::Email,
+            password::Password,
+            rate_limit::{RateLimitDecision, RateLimitPolicy},
+            role::Role,
+            totp::TotpSecret,
+            user::{AccountStatus, TwoFactorMethod, User, UserPage, UserRow},
+        },
+        utils::keys::SigningKey,
     },
     color_eyre::eyre::Report,
     rand::{Rng, rng},
     secrecy::{ExposeSecret, SecretBox},
     serde::{Deserialize, Deserializer, Serialize},
+    sha2::{Digest, Sha256},
+    std::net::IpAddr,
     thiserror::Error,
     uuid::Uuid,
+    webauthn_rs::prelude::{Passkey, PasskeyAuthentication, PasskeyRegistration},
 };
 
+const RECOVERY_CODE_ALPHABET: &str = "abcdefghjkmnpqrstuvwxyz23456789";
+const RECOVERY_CODE_LENGTH: usize = 11;
+
 #[derive(Debug, Error)]
 pub enum UserStoreError {
     #[error("Invalid credentials")]
@@ -38,37 +51,485 @@ pub enum TwoFactorStoreError {
     UnexpectedError(#[source] Report),
 }
 
+#[derive(Debug, Error)]
+pub enum EmailVerificationStoreError {
+    #[error("Email verification token not found")]
+    TokenNotFound,
+    #[error("Unexpected error")]
+    UnexpectedError(#[source] Report),
+}
+
+#[derive(Debug, Error)]
+pub enum RefreshTokenStoreError {
+    #[error("Refresh token not found")]
+    TokenNotFound,
+    #[error("Refresh token reused")]
+    TokenReused,
+    #[error("Unexpected error")]
+    UnexpectedError(#[source] Report),
+}
+
+#[derive(Debug, Error)]
+pub enum PasswordResetStoreError {
+    #[error("Password reset token not found")]
+    TokenNotFound,
+    #[error("Unexpected error")]
+    UnexpectedError(#[source] Report),
+}
+
+#[derive(Debug, Error)]
+pub enum TotpSecretStoreError {
+    #[error("TOTP secret not found")]
+    SecretNotFound,
+    #[error("TOTP code already used")]
+    CodeReused,
+    #[error("Unexpected error")]
+    UnexpectedError(#[source] Report),
+}
+
+#[derive(Debug, Error)]
+pub enum RecoveryCodeStoreError {
+    #[error("Recovery code not found")]
+    CodeNotFound,
+    #[error("Unexpected error")]
+    UnexpectedError(#[source] Report),
+}
+
+#[derive(Debug, Error)]
+pub enum PasskeyStoreError {
+    #[error("Passkey already registered")]
+    CredentialAlreadyExists,
+    #[error("Unexpected error")]
+    UnexpectedError(#[source] Report),
+}
+
+#[derive(Debug, Error)]
+pub enum PasskeyCeremonyStoreError {
+    #[error("Passkey ceremony not found")]
+    CeremonyNotFound,
+    #[error("Unexpected error")]
+    UnexpectedError(#[source] Report),
+}
+
+#[derive(Debug, Error)]
+pub enum MagicLinkStoreError {
+    #[error("Magic link token not found")]
+    TokenNotFound,
+    #[error("Unexpected error")]
+    UnexpectedError(#[source] Report),
+}
+
+#[derive(Debug, Error)]
+pub enum SessionStoreError {
+    #[error("Session not found")]
+    SessionNotFound,
+    #[error("Unexpected error")]
+    UnexpectedError(#[source] Report),
+}
+
+#[derive(Debug, Error)]
+pub enum EmailChangeStoreError {
+    #[error("Email change not found")]
+    ChangeNotFound,
+    #[error("Unexpected error")]
+    UnexpectedError(#[source] Report),
+}
+
+#[derive(Debug, Error)]
+pub enum RoleStoreError {
+    #[error("Role not found")]
+    RoleNotFound,
+    #[error("User not found")]
+    UserNotFound,
+    #[error("Unexpected error")]
+    UnexpectedError(#[source] Report),
+}
+
+#[derive(Debug, Error)]
+pub enum RateLimitStoreError {
+    #[error("Unexpected error")]
+    UnexpectedError(#[source] Report),
+}
+
+#[derive(Debug, Error)]
+pub enum LoginThrottleStoreError {
+    #[error("Unexpected error")]
+    UnexpectedError(#[source] Report),
+}
+
+#[derive(Debug, Error)]
+pub enum AuditLogError {
+    #[error("Unexpected error")]
+    UnexpectedError(#[source] Report),
+}
+
+#[derive(Debug, Error)]
+pub enum SigningKeyStoreError {
+    #[error("Unexpected error")]
+    UnexpectedError(#[source] Report),
+}
+
+/// What a failed login counter is kept for.
+#[derive(Debug)]
+pub enum ThrottleKey {
+    /// Counted per address regardless of its casing, the same way users are looked up by email.
+    Account(Email),
+    Ip(IpAddr),
+    /// Wrong codes entered for the pending 2FA attempt of an account.
+    TwoFactor(Uuid),
+}
+
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct FailedAttempts {
+    pub count: u32,
+    /// Unix timestamp of the latest failure.
+    pub last_failure_at: i64,
+}
+
 #[derive(Debug)]
 pub struct LoginAttemptId(SecretBox<String>);
 
 #[derive(Debug)]
 pub struct TwoFactorCode(SecretBox<String>);
 
+#[derive(Debug)]
+pub struct PasswordResetToken(SecretBox<String>);
+
+#[derive(Debug)]
+pub struct EmailVerificationToken(SecretBox<String>);
+
+#[derive(Debug)]
+pub struct RefreshToken(SecretBox<String>);
+
+#[derive(Debug)]
+pub struct EmailChangeToken(SecretBox<String>);
+
+/// Device a user signed in on. Lives from login until its refresh tokens run out or it is revoked.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct Session {
+    pub session_id: Uuid,
+    pub user_id: Uuid,
+    /// `jti` of the latest auth token issued to the session.
+    pub jti: String,
+    pub created_at: i64,
+    pub refreshed_at: i64,
+    pub ip: IpAddr,
+    pub user_agent: Option<String>,
+}
+
+/// Email change waiting for the new address to be confirmed.
+#[derive(Clone, Debug)]
+pub struct PendingEmailChange {
+    pub new_email: Email,
+    /// Sent to the new address, applies the change.
+    pub confirm_token: EmailChangeToken,
+    /// Sent to the old address, discards the change.
+    pub cancel_token: EmailChangeToken,
+}
+
+#[derive(Debug)]
+pub struct RecoveryCode(SecretBox<String>);
+
+/// Signed token embedded in a magic link.
+#[derive(Debug)]
+pub struct MagicLinkToken(SecretBox<String>);
+
+/// Random value kept in a cookie of the browser that requested a magic link.
+#[derive(Debug)]
+pub struct MagicLinkNonce(SecretBox<String>);
+
 #[async_trait::async_trait]
 pub trait UserStore: Send + Sync {
     async fn add_user(&self, user: User) -> Result<(), UserStoreError>;
     async fn get_user(&self, email: &Email) -> Result<UserRow, UserStoreError>;
+    async fn get_user_by_id(&self, user_id: &Uuid) -> Result<UserRow, UserStoreError>;
     async fn validate_user(&self, email: &Email, password: &Password) -> Result<(), UserStoreError>;
+    async fn update_password(&self, email: &Email, password: Password) -> Result<(), UserStoreError>;
+    /// Marks the email address as verified, activating the account if it was waiting for that.
+    async fn verify_email(&self, email: &Email) -> Result<(), UserStoreError>;
+    async fn update_two_factor_method(&self, email: &Email, method: TwoFactorMethod) -> Result<(), UserStoreError>;
+    async fn update_email(&self, user_id: &Uuid, email: &Email) -> Result<(), UserStoreError>;
+
+    /// Users whose email contains `search`, ordered by email. Users scheduled for deletion are left out.
+    async fn list_users(&self, search: Option<&str>, offset: u64, limit: u64) -> Result<UserPage, UserStoreError>;
+
+    /// Sets the account status along with why it was set and, for suspensions, when it lifts.
+    async fn update_status(
+        &self,
+        user_id: &Uuid,
+        status: AccountStatus,
+        reason: Option<String>,
+        suspended_until: Option<i64>,
+    ) -> Result<(), UserStoreError>;
+
+    /// Keeps the user from logging in with their password until `update_password` sets a new one.
+    async fn require_password_reset(&self, user_id: &Uuid) -> Result<(), UserStoreError>;
+
+    /// Hides the user from every lookup and lets `purge_deleted_users` remove it once `grace_seconds` have passed.
+    async fn schedule_deletion(&self, user_id: &Uuid, grace_seconds: u64) -> Result<(), UserStoreError>;
+
+    /// User registered under `email` whose deletion is scheduled and whose grace period has not ended yet.
+    async fn get_deleted_user(&self, email: &Email) -> Result<UserRow, UserStoreError>;
+
+    /// Cancels the scheduled deletion of the user, unless its grace period has ended already.
+    async fn restore_user(&self, user_id: &Uuid) -> Result<(), UserStoreError>;
+
+    /// Version auth tokens of the user have to carry; tokens carrying any other are rejected. Users scheduled for
+    /// deletion keep theirs.
+    async fn get_token_version(&self, user_id: &Uuid) -> Result<u64, UserStoreError>;
+
+    /// Invalidates every auth token issued to the user so far by raising the version past both the stored one and
+    /// `known`, a version seen elsewhere such as in a cache. Returns the new version.
+    async fn bump_token_version(&self, user_id: &Uuid, known: u64) -> Result<u64, UserStoreError>;
+
+    async fn delete_user(&self, user_id: &Uuid) -> Result<(), UserStoreError>;
+
+    /// Removes every user whose grace period ended and returns their ids.
+    async fn purge_deleted_users(&self) -> Result<Vec<Uuid>, UserStoreError>;
 }
 
 #[async_trait::async_trait]
 pub trait BannedTokenStore: Send + Sync {
-    async fn register(&self, tokens: Vec<&SecretBox<String>>) -> Result<(), BannedTokenStoreError>;
-    async fn check(&self, token: &SecretBox<String>) -> Result<bool, BannedTokenStoreError>;
+    /// Bans the token with the given `jti` until `expires_at`, the unix timestamp it expires at anyway.
+    async fn register(&self, jti: &str, expires_at: i64) -> Result<(), BannedTokenStoreError>;
+
+    /// Whether the token with the given `jti` is banned. `token` is only matched against bans recorded before they
+    /// were keyed by `jti`.
+    async fn check(&self, jti: &str, token: &SecretBox<String>) -> Result<bool, BannedTokenStoreError>;
+
+    /// Cached copy of the token version the user store keeps, if there is one.
+    async fn get_token_version(&self, user_id: &Uuid) -> Result<Option<u64>, BannedTokenStoreError>;
+
+    /// Caches `version` unless a higher one is cached already, so a stale read cannot undo a bump.
+    async fn cache_token_version(&self, user_id: &Uuid, version: u64) -> Result<(), BannedTokenStoreError>;
+
+    /// Rejects every auth token issued to the session so far.
+    async fn revoke_session(&self, session_id: &Uuid) -> Result<(), BannedTokenStoreError>;
+
+    async fn check_session(&self, session_id: &Uuid) -> Result<bool, BannedTokenStoreError>;
 }
 
 #[async_trait::async_trait]
 pub trait TwoFactorStore: Send + Sync {
     async fn add_code(
         &self,
-        email: Email,
+        user_id: Uuid,
         attempt_id: LoginAttemptId,
         code: TwoFactorCode,
     ) -> Result<(), TwoFactorStoreError>;
 
-    async fn remove_code(&self, email: &Email) -> Result<(), TwoFactorStoreError>;
+    async fn remove_code(&self, user_id: &Uuid) -> Result<(), TwoFactorStoreError>;
+
+    async fn get_code(&self, user_id: &Uuid) -> Result<(LoginAttemptId, TwoFactorCode), TwoFactorStoreError>;
+}
+
+#[async_trait::async_trait]
+pub trait PasswordResetStore: Send + Sync {
+    async fn add_token(&self, email: Email, token: PasswordResetToken) -> Result<(), PasswordResetStoreError>;
+
+    async fn remove_token(&self, email: &Email) -> Result<(), PasswordResetStoreError>;
+
+    async fn get_token(&self, email: &Email) -> Result<PasswordResetToken, PasswordResetStoreError>;
+
+    /// Returns the pending token and removes it, so each token can only be used once.
+    async fn take_token(&self, email: &Email) -> Result<PasswordResetToken, PasswordResetStoreError>;
+}
+
+#[async_trait::async_trait]
+pub trait RefreshTokenStore: Send + Sync {
+    /// Stores `token` as the first member of a new token family, identified by the session it was issued to.
+    async fn add_token(
+        &self,
+        user_id: Uuid,
+        session_id: Uuid,
+        token: RefreshToken,
+    ) -> Result<(), RefreshTokenStoreError>;
+
+    /// Consumes `token` and stores `new_token` in its family, returning the user and session it belongs to.
+    /// Presenting an already consumed token revokes the family.
+    async fn rotate_token(
+        &self,
+        token: &RefreshToken,
+        new_token: RefreshToken,
+    ) -> Result<(Uuid, Uuid), RefreshTokenStoreError>;
+
+    async fn revoke_token(&self, token: &RefreshToken) -> Result<(), RefreshTokenStoreError>;
+
+    async fn revoke_user(&self, user_id: &Uuid, except: Option<&RefreshToken>) -> Result<(), RefreshTokenStoreError>;
+
+    async fn revoke_session(&self, session_id: &Uuid) -> Result<(), RefreshTokenStoreError>;
 
-    async fn get_code(&self, email: &Email) -> Result<(LoginAttemptId, TwoFactorCode), TwoFactorStoreError>;
+    /// Number of token families of the user that can still be refreshed.
+    async fn count_sessions(&self, user_id: &Uuid) -> Result<u64, RefreshTokenStoreError>;
+}
+
+#[async_trait::async_trait]
+pub trait EmailVerificationStore: Send + Sync {
+    async fn add_token(&self, email: Email, token: EmailVerificationToken) -> Result<(), EmailVerificationStoreError>;
+
+    async fn remove_token(&self, email: &Email) -> Result<(), EmailVerificationStoreError>;
+
+    /// Returns the pending token along with the unix timestamp it was issued at.
+    async fn get_token(&self, email: &Email) -> Result<(EmailVerificationToken, i64), EmailVerificationStoreError>;
+
+    /// Returns the pending token and removes it, so each token can only be used once.
+    async fn take_token(&self, email: &Email) -> Result<EmailVerificationToken, EmailVerificationStoreError>;
+}
+
+#[async_trait::async_trait]
+pub trait TotpSecretStore: Send + Sync {
+    /// Stores `secret` as pending until it is confirmed, leaving any active secret in place.
+    async fn add_secret(&self, user_id: &Uuid, secret: TotpSecret) -> Result<(), TotpSecretStoreError>;
+
+    async fn get_pending_secret(&self, user_id: &Uuid) -> Result<TotpSecret, TotpSecretStoreError>;
+
+    /// Promotes the pending secret to the active one.
+    async fn confirm_secret(&self, user_id: &Uuid) -> Result<(), TotpSecretStoreError>;
+
+    async fn get_secret(&self, user_id: &Uuid) -> Result<TotpSecret, TotpSecretStoreError>;
+
+    /// Records `time_step` as the latest one a code of the active secret was accepted for, failing with `CodeReused`
+    /// unless it is later than the one recorded before, so every code is only accepted once.
+    async fn record_time_step(&self, user_id: &Uuid, time_step: u64) -> Result<(), TotpSecretStoreError>;
+}
+
+#[async_trait::async_trait]
+pub trait RecoveryCodeStore: Send + Sync {
+    /// Replaces every stored code of the user, only keeping hashes of `codes`.
+    async fn replace_codes(&self, user_id: &Uuid, codes: Vec<RecoveryCode>) -> Result<(), RecoveryCodeStoreError>;
+
+    /// Removes the stored code matching `code` so it cannot be used again.
+    async fn consume_code(&self, user_id: &Uuid, code: &RecoveryCode) -> Result<(), RecoveryCodeStoreError>;
+
+    async fn count_codes(&self, user_id: &Uuid) -> Result<u64, RecoveryCodeStoreError>;
+}
+
+#[async_trait::async_trait]
+pub trait PasskeyStore: Send + Sync {
+    async fn add_passkey(&self, user_id: &Uuid, passkey: Passkey) -> Result<(), PasskeyStoreError>;
+
+    async fn get_passkeys(&self, user_id: &Uuid) -> Result<Vec<Passkey>, PasskeyStoreError>;
+
+    /// Persists the counter and backup state changes reported by a successful authentication.
+    async fn update_passkey(&self, user_id: &Uuid, passkey: &Passkey) -> Result<(), PasskeyStoreError>;
+}
+
+#[async_trait::async_trait]
+pub trait PasskeyCeremonyStore: Send + Sync {
+    async fn add_registration(
+        &self,
+        email: &Email,
+        state: PasskeyRegistration,
+    ) -> Result<(), PasskeyCeremonyStoreError>;
+
+    /// Returns the pending registration and removes it, so each challenge can only be answered once.
+    async fn take_registration(&self, email: &Email) -> Result<PasskeyRegistration, PasskeyCeremonyStoreError>;
+
+    async fn add_authentication(
+        &self,
+        email: &Email,
+        state: PasskeyAuthentication,
+    ) -> Result<(), PasskeyCeremonyStoreError>;
+
+    /// Returns the pending authentication and removes it, so each challenge can only be answered once.
+    async fn take_authentication(&self, email: &Email) -> Result<PasskeyAuthentication, PasskeyCeremonyStoreError>;
+}
+
+#[async_trait::async_trait]
+pub trait RateLimitStore: Send + Sync {
+    /// Takes a token from the bucket stored under `key`, creating a full bucket if there is none.
+    async fn acquire(&self, key: &str, policy: &RateLimitPolicy) -> Result<RateLimitDecision, RateLimitStoreError>;
+}
+
+#[async_trait::async_trait]
+pub trait LoginThrottleStore: Send + Sync {
+    /// Counts a failure for `key` and returns the updated counter.
+    async fn record_failure(&self, key: &ThrottleKey) -> Result<FailedAttempts, LoginThrottleStoreError>;
+
+    async fn get_failures(&self, key: &ThrottleKey) -> Result<FailedAttempts, LoginThrottleStoreError>;
+
+    async fn reset(&self, key: &ThrottleKey) -> Result<(), LoginThrottleStoreError>;
+}
+
+#[async_trait::async_trait]
+pub trait MagicLinkStore: Send + Sync {
+    /// Stores `token` as the only valid magic link for `email`, replacing any earlier one.
+    async fn add_token(&self, email: Email, token: MagicLinkToken) -> Result<(), MagicLinkStoreError>;
+
+    /// Returns the pending token and removes it, so each link can only be used once.
+    async fn take_token(&self, email: &Email) -> Result<MagicLinkToken, MagicLinkStoreError>;
+}
+
+#[async_trait::async_trait]
+pub trait SessionStore: Send + Sync {
+    async fn add_session(&self, session: Session) -> Result<(), SessionStoreError>;
+
+    /// Records `jti` as the latest auth token of the session, issued at `issued_at`.
+    async fn record_token(
+        &self,
+        user_id: &Uuid,
+        session_id: &Uuid,
+        jti: String,
+        issued_at: i64,
+    ) -> Result<(), SessionStoreError>;
+
+    /// Returns the sessions still live, oldest first with ties broken by session id.
+    async fn get_sessions(&self, user_id: &Uuid) -> Result<Vec<Session>, SessionStoreError>;
+
+    async fn remove_session(&self, user_id: &Uuid, session_id: &Uuid) -> Result<(), SessionStoreError>;
+
+    /// Removes every session of the user but `except`.
+    async fn remove_sessions(&self, user_id: &Uuid, except: Option<&Uuid>) -> Result<(), SessionStoreError>;
+}
+
+#[async_trait::async_trait]
+pub trait EmailChangeStore: Send + Sync {
+    /// Stores `change` as the only pending email change of the user, replacing any earlier one.
+    async fn add_change(&self, user_id: Uuid, change: PendingEmailChange) -> Result<(), EmailChangeStoreError>;
+
+    async fn get_change(&self, user_id: &Uuid) -> Result<PendingEmailChange, EmailChangeStoreError>;
+
+    async fn remove_change(&self, user_id: &Uuid) -> Result<(), EmailChangeStoreError>;
+}
+
+#[async_trait::async_trait]
+pub trait RoleStore: Send + Sync {
+    async fn get_roles(&self) -> Result<Vec<Role>, RoleStoreError>;
+
+    /// Names of the roles assigned to the user, not including the default role every user holds.
+    async fn get_user_roles(&self, user_id: &Uuid) -> Result<Vec<String>, RoleStoreError>;
+
+    /// Assigns `role` to the user, doing nothing if it is already assigned.
+    async fn assign_role(&self, user_id: &Uuid, role: &str) -> Result<(), RoleStoreError>;
+
+    /// Revokes `role` from the user, doing nothing if it is not assigned.
+    async fn revoke_role(&self, user_id: &Uuid, role: &str) -> Result<(), RoleStoreError>;
+
+    /// Permissions granted by any of `roles`, skipping roles that do not exist.
+    async fn get_permissions(&self, roles: &[String]) -> Result<Vec<String>, RoleStoreError>;
+}
+
+/// Append-only record of security relevant events; events are never changed or removed once recorded.
+#[async_trait::async_trait]
+pub trait AuditLog: Send + Sync {
+    async fn record(&self, event: AuditEvent) -> Result<(), AuditLogError>;
+
+    /// Events matching `filter`, newest first.
+    async fn query(&self, filter: &AuditEventFilter, offset: u64, limit: u64) -> Result<AuditEventPage, AuditLogError>;
+
+    /// Erases a removed user from the events about them or taken by them, along with the address and user agent of
+    /// the requests they made. The events themselves are kept.
+    async fn anonymise_user(&self, user_id: &Uuid) -> Result<(), AuditLogError>;
+}
+
+/// JWT signing keys shared by every instance of the service, so they survive restarts and all instances sign and
+/// verify with the same keys.
+#[async_trait::async_trait]
+pub trait SigningKeyStore: Send + Sync {
+    /// Adds `key`, doing nothing if a key with the same `kid` is stored already.
+    async fn add_key(&self, key: &SigningKey) -> Result<(), SigningKeyStoreError>;
+
+    async fn get_keys(&self) -> Result<Vec<SigningKey>, SigningKeyStoreError>;
 }
 
 impl PartialEq for UserStoreError {
@@ -99,6 +560,136 @@ impl PartialEq for TwoFactorStoreError {
     }
 }
 
+impl PartialEq for PasswordResetStoreError {
+    fn eq(&self, other: &Self) -> bool {
+        matches!(
+            (self, other),
+            (Self::TokenNotFound, Self::TokenNotFound) | (Self::UnexpectedError(_), Self::UnexpectedError(_))
+        )
+    }
+}
+
+impl PartialEq for EmailVerificationStoreError {
+    fn eq(&self, other: &Self) -> bool {
+        matches!(
+            (self, other),
+            (Self::TokenNotFound, Self::TokenNotFound) | (Self::UnexpectedError(_), Self::UnexpectedError(_))
+        )
+    }
+}
+
+impl PartialEq for RefreshTokenStoreError {
+    fn eq(&self, other: &Self) -> bool {
+        matches!(
+            (self, other),
+            (Self::TokenNotFound, Self::TokenNotFound) |
+                (Self::TokenReused, Self::TokenReused) |
+                (Self::UnexpectedError(_), Self::UnexpectedError(_))
+        )
+    }
+}
+
+impl PartialEq for TotpSecretStoreError {
+    fn eq(&self, other: &Self) -> bool {
+        matches!(
+            (self, other),
+            (Self::SecretNotFound, Self::SecretNotFound) |
+                (Self::CodeReused, Self::CodeReused) |
+                (Self::UnexpectedError(_), Self::UnexpectedError(_))
+        )
+    }
+}
+
+impl PartialEq for RecoveryCodeStoreError {
+    fn eq(&self, other: &Self) -> bool {
+        matches!(
+            (self, other),
+            (Self::CodeNotFound, Self::CodeNotFound) | (Self::UnexpectedError(_), Self::UnexpectedError(_))
+        )
+    }
+}
+
+impl PartialEq for PasskeyStoreError {
+    fn eq(&self, other: &Self) -> bool {
+        matches!(
+            (self, other),
+            (Self::CredentialAlreadyExists, Self::CredentialAlreadyExists) |
+                (Self::UnexpectedError(_), Self::UnexpectedError(_))
+        )
+    }
+}
+
+impl PartialEq for PasskeyCeremonyStoreError {
+    fn eq(&self, other: &Self) -> bool {
+        matches!(
+            (self, other),
+            (Self::CeremonyNotFound, Self::CeremonyNotFound) | (Self::UnexpectedError(_), Self::UnexpectedError(_))
+        )
+    }
+}
+
+impl PartialEq for RateLimitStoreError {
+    fn eq(&self, other: &Self) -> bool {
+        matches!((self, other), (Self::UnexpectedError(_), Self::UnexpectedError(_)))
+    }
+}
+
+impl PartialEq for LoginThrottleStoreError {
+    fn eq(&self, other: &Self) -> bool {
+        matches!((self, other), (Self::UnexpectedError(_), Self::UnexpectedError(_)))
+    }
+}
+
+impl PartialEq for MagicLinkStoreError {
+    fn eq(&self, other: &Self) -> bool {
+        matches!(
+            (self, other),
+            (Self::TokenNotFound, Self::TokenNotFound) | (Self::UnexpectedError(_), Self::UnexpectedError(_))
+        )
+    }
+}
+
+impl PartialEq for SessionStoreError {
+    fn eq(&self, other: &Self) -> bool {
+        matches!(
+            (self, other),
+            (Self::SessionNotFound, Self::SessionNotFound) | (Self::UnexpectedError(_), Self::UnexpectedError(_))
+        )
+    }
+}
+
+impl PartialEq for EmailChangeStoreError {
+    fn eq(&self, other: &Self) -> bool {
+        matches!(
+            (self, other),
+            (Self::ChangeNotFound, Self::ChangeNotFound) | (Self::UnexpectedError(_), Self::UnexpectedError(_))
+        )
+    }
+}
+
+impl PartialEq for RoleStoreError {
+    fn eq(&self, other: &Self) -> bool {
+        matches!(
+            (self, other),
+            (Self::RoleNotFound, Self::RoleNotFound) |
+                (Self::UserNotFound, Self::UserNotFound) |
+                (Self::UnexpectedError(_), Self::UnexpectedError(_))
+        )
+    }
+}
+
+impl PartialEq for AuditLogError {
+    fn eq(&self, other: &Self) -> bool {
+        matches!((self, other), (Self::UnexpectedError(_), Self::UnexpectedError(_)))
+    }
+}
+
+impl PartialEq for SigningKeyStoreError {
+    fn eq(&self, other: &Self) -> bool {
+        matches!((self, other), (Self::UnexpectedError(_), Self::UnexpectedError(_)))
+    }
+}
+
 impl LoginAttemptId {
     pub fn parse(maybe_uuid: &str) -> Result<Self, String> {
         match Uuid::parse_str(maybe_uuid) {
@@ -194,3 +785,238 @@ impl<'a> Deserialize<'a> for TwoFactorCode {
         TwoFactorCode::parse(&maybe_code).map_err(serde::de::Error::custom)
     }
 }
+
+impl PasswordResetToken {
+    pub fn parse(maybe_uuid: &str) -> Result<Self, String> {
+        match Uuid::parse_str(maybe_uuid) {
+            Ok(uuid) => Ok(Self(SecretBox::new(Box::new(uuid.to_string())))),
+            Err(error) => Err(error.to_string()),
+        }
+    }
+}
+
+impl AsRef<SecretBox<String>> for PasswordResetToken {
+    fn as_ref(&self) -> &SecretBox<String> {
+        &self.0
+    }
+}
+
+impl Default for PasswordResetToken {
+    fn default() -> Self {
+        Self(SecretBox::new(Box::new(Uuid::new_v4().to_string())))
+    }
+}
+
+impl PartialEq for PasswordResetToken {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.expose_secret() == other.0.expose_secret()
+    }
+}
+
+impl Clone for PasswordResetToken {
+    fn clone(&self) -> Self {
+        Self(SecretBox::new(Box::new(self.0.expose_secret().clone())))
+    }
+}
+
+impl EmailVerificationToken {
+    pub fn parse(maybe_uuid: &str) -> Result<Self, String> {
+        match Uuid::parse_str(maybe_uuid) {
+            Ok(uuid) => Ok(Self(SecretBox::new(Box::new(uuid.to_string())))),
+            Err(error) => Err(error.to_string()),
+        }
+    }
+}
+
+impl AsRef<SecretBox<String>> for EmailVerificationToken {
+    fn as_ref(&self) -> &SecretBox<String> {
+        &self.0
+    }
+}
+
+impl Default for EmailVerificationToken {
+    fn default() -> Self {
+        Self(SecretBox::new(Box::new(Uuid::new_v4().to_string())))
+    }
+}
+
+impl PartialEq for EmailVerificationToken {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.expose_secret() == other.0.expose_secret()
+    }
+}
+
+impl Clone for EmailVerificationToken {
+    fn clone(&self) -> Self {
+        Self(SecretBox::new(Box::new(self.0.expose_secret().clone())))
+    }
+}
+
+impl RefreshToken {
+    pub fn parse(maybe_uuid: &str) -> Result<Self, String> {
+        match Uuid::parse_str(maybe_uuid) {
+            Ok(uuid) => Ok(Self(SecretBox::new(Box::new(uuid.to_string())))),
+            Err(error) => Err(error.to_string()),
+        }
+    }
+
+    /// Hex encoded SHA-256 of the token, which is what stores keep so a leaked store yields no usable tokens.
+    pub fn hash(&self) -> String {
+        format!("{:x}", Sha256::digest(self.0.expose_secret().as_bytes()))
+    }
+}
+
+impl AsRef<SecretBox<String>> for RefreshToken {
+    fn as_ref(&self) -> &SecretBox<String> {
+        &self.0
+    }
+}
+
+impl Default for RefreshToken {
+    fn default() -> Self {
+        Self(SecretBox::new(Box::new(Uuid::new_v4().to_string())))
+    }
+}
+
+impl PartialEq for RefreshToken {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.expose_secret() == other.0.expose_secret()
+    }
+}
+
+impl Clone for RefreshToken {
+    fn clone(&self) -> Self {
+        Self(SecretBox::new(Box::new(self.0.expose_secret().clone())))
+    }
+}
+
+impl EmailChangeToken {
+    pub fn parse(maybe_uuid: &str) -> Result<Self, String> {
+        match Uuid::parse_str(maybe_uuid) {
+            Ok(uuid) => Ok(Self(SecretBox::new(Box::new(uuid.to_string())))),
+            Err(error) => Err(error.to_string()),
+        }
+    }
+}
+
+impl AsRef<SecretBox<String>> for EmailChangeToken {
+    fn as_ref(&self) -> &SecretBox<String> {
+        &self.0
+    }
+}
+
+impl Default for EmailChangeToken {
+    fn default() -> Self {
+        Self(SecretBox::new(Box::new(Uuid::new_v4().to_string())))
+    }
+}
+
+impl PartialEq for EmailChangeToken {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.expose_secret() == other.0.expose_secret()
+    }
+}
+
+impl Clone for EmailChangeToken {
+    fn clone(&self) -> Self {
+        Self(SecretBox::new(Box::new(self.0.expose_secret().clone())))
+    }
+}
+
+impl RecoveryCode {
+    pub fn parse(maybe_code: &str) -> Result<Self, String> {
+        let code = maybe_code.trim().to_lowercase();
+        let is_valid = code.len() == RECOVERY_CODE_LENGTH &&
+            code.chars().enumerate().all(|(i, c)| match i == RECOVERY_CODE_LENGTH / 2 {
+                true => c == '-',
+                false => RECOVERY_CODE_ALPHABET.contains(c),
+            });
+
+        if !is_valid {
+            return Err(String::from("Invalid recovery code"));
+        }
+
+        Ok(Self(SecretBox::new(Box::new(code))))
+    }
+}
+
+impl AsRef<SecretBox<String>> for RecoveryCode {
+    fn as_ref(&self) -> &SecretBox<String> {
+        &self.0
+    }
+}
+
+impl Default for RecoveryCode {
+    fn default() -> Self {
+        let alphabet = RECOVERY_CODE_ALPHABET.as_bytes();
+        let mut rng = rng();
+        let code = (0..RECOVERY_CODE_LENGTH)
+            .map(|i| match i == RECOVERY_CODE_LENGTH / 2 {
+                true => '-',
+                false => alphabet[rng.random_range(0..alphabet.len())] as char,
+            })
+            .collect::<String>();
+
+        Self(SecretBox::new(Box::new(code)))
+    }
+}
+
+impl PartialEq for RecoveryCode {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.expose_secret() == other.0.expose_secret()
+    }
+}
+
+impl Clone for RecoveryCode {
+    fn clone(&self) -> Self {
+        Self(SecretBox::new(Box::new(self.0.expose_secret().clone())))
+    }
+}
+
+impl MagicLinkToken {
+    pub fn parse(maybe_token: &str) -> Result<Self, String> {
+        match maybe_token.is_empty() {
+            true => Err("Magic link token must not be empty".to_owned()),
+            false => Ok(Self(SecretBox::new(Box::new(maybe_token.to_owned())))),
+        }
+    }
+}
+
+impl AsRef<SecretBox<String>> for MagicLinkToken {
+    fn as_ref(&self) -> &SecretBox<String> {
+        &self.0
+    }
+}
+
+impl PartialEq for MagicLinkToken {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.expose_secret() == other.0.expose_secret()
+    }
+}
+
+impl Clone for MagicLinkToken {
+    fn clone(&self) -> Self {
+        Self(SecretBox::new(Box::new(self.0.expose_secret().clone())))
+    }
+}
+
+impl MagicLinkNonce {
+    pub fn parse(maybe_uuid: &str) -> Result<Self, String> {
+        match Uuid::parse_str(maybe_uuid) {
+            Ok(uuid) => Ok(Self(SecretBox::new(Box::new(uuid.to_string())))),
+            Err(error) => Err(error.to_string()),
+        }
+    }
+}
+
+impl AsRef<SecretBox<String>> for MagicLinkNonce {
+    fn as_ref(&self) -> &SecretBox<String> {
+        &self.0
+    }
+}
+
+impl Default for MagicLinkNonce {
+    fn default() -> Self {
+        Self(SecretBox::new(Box::new(Uuid::new_v4().to_string())))
+    }
+}
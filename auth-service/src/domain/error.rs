@@ -2,6 +2,16 @@ use {color_eyre::eyre::Report, thiserror::Error};
 
 #[derive(Debug, Error)]
 pub enum AuthAPIError {
+    #[error("Account disabled")]
+    AccountDisabled,
+    #[error("Account locked")]
+    AccountLocked,
+    #[error("Account suspended")]
+    AccountSuspended,
+    #[error("Forbidden")]
+    Forbidden,
+    #[error("Email not verified")]
+    EmailNotVerified,
     #[error("Incorrect credentials")]
     IncorrectCredentials,
     #[error("Invalid credentials")]
@@ -12,8 +22,18 @@ pub enum AuthAPIError {
     MalformedToken,
     #[error("Missing token")]
     MissingToken,
+    #[error("Password reset required")]
+    PasswordResetRequired,
+    #[error("Role not found")]
+    RoleNotFound,
+    #[error("Session not found")]
+    SessionNotFound,
+    #[error("Too many requests")]
+    TooManyRequests,
     #[error("User already exists")]
     UserAlreadyExists,
+    #[error("User not found")]
+    UserNotFound,
     #[error("Unexpected error")]
     UnexpectedError(#[source] Report),
 }
@@ -1,6 +1,10 @@
+pub mod audit;
 pub mod data_stores;
 pub mod error;
 pub mod password;
+pub mod rate_limit;
+pub mod role;
+pub mod totp;
 pub mod user;
 
 pub mod email;
@@ -39,15 +39,19 @@ impl Password {
     }
 
     pub fn hash(&self) -> Result<String> {
-        let salt = SaltString::generate(&mut OsRng);
-        let password_hash = Argon2::new(Algorithm::Argon2id, Version::V0x13, Params::new(15000, 2, 1, None)?)
-            .hash_password(self.as_ref().expose_secret().as_bytes(), &salt)?
-            .to_string();
-
-        Ok(password_hash)
+        hash_secret(self.as_ref().expose_secret())
     }
 }
 
+pub(crate) fn hash_secret(secret: &str) -> Result<String> {
+    let salt = SaltString::generate(&mut OsRng);
+    let hash = Argon2::new(Algorithm::Argon2id, Version::V0x13, Params::new(15000, 2, 1, None)?)
+        .hash_password(secret.as_bytes(), &salt)?
+        .to_string();
+
+    Ok(hash)
+}
+
 fn validate_password(secret: &SecretBox<String>) -> bool {
     secret.expose_secret().len() >= 8
 }
@@ -0,0 +1,65 @@
+/// Token bucket limits: a full bucket allows `capacity` requests in a burst and regains one token per
+/// `refill_interval_ms`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RateLimitPolicy {
+    pub capacity: u32,
+    pub refill_interval_ms: u64,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RateLimitDecision {
+    pub allowed: bool,
+    pub limit: u32,
+    pub remaining: u32,
+    /// Seconds until the bucket is full again.
+    pub reset_after: u64,
+    /// Seconds until the next request is allowed, zero if this one was.
+    pub retry_after: u64,
+}
+
+impl RateLimitDecision {
+    /// Decision for a request that was `allowed` or not, leaving `tokens` in the bucket.
+    pub fn new(policy: &RateLimitPolicy, allowed: bool, tokens: f64) -> Self {
+        let interval = policy.refill_interval_ms.max(1) as f64;
+        let retry_after = match allowed {
+            true => 0,
+            false => ((1.0 - tokens) * interval / 1000.0).ceil() as u64,
+        };
+
+        Self {
+            allowed,
+            limit: policy.capacity,
+            remaining: tokens.floor() as u32,
+            reset_after: ((policy.capacity as f64 - tokens) * interval / 1000.0).ceil() as u64,
+            retry_after,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TokenBucket {
+    pub tokens: f64,
+    /// Unix timestamp in milliseconds of the last update.
+    pub updated_at: i64,
+}
+
+impl TokenBucket {
+    pub fn new(policy: &RateLimitPolicy, now: i64) -> Self {
+        Self { tokens: policy.capacity as f64, updated_at: now }
+    }
+
+    /// Refills the bucket up to `now` and takes a token if one is available.
+    pub fn take(self, policy: &RateLimitPolicy, now: i64) -> (Self, RateLimitDecision) {
+        let interval = policy.refill_interval_ms.max(1) as f64;
+        let capacity = policy.capacity as f64;
+        let elapsed = (now - self.updated_at).max(0) as f64;
+        let tokens = (self.tokens + elapsed / interval).min(capacity);
+        let allowed = tokens >= 1.0;
+        let tokens = match allowed {
+            true => tokens - 1.0,
+            false => tokens,
+        };
+
+        (Self { tokens, updated_at: now }, RateLimitDecision::new(policy, allowed, tokens))
+    }
+}
@@ -0,0 +1,48 @@
+use serde::{Deserialize, Serialize};
+
+/// Role every user holds without it being assigned.
+pub const DEFAULT_ROLE: &str = "user";
+/// Role seeded by the migrations, granted every permission.
+pub const ADMIN_ROLE: &str = "admin";
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct Role {
+    pub name: String,
+    pub description: String,
+    pub permissions: Vec<String>,
+}
+
+/// Permission a route requires through `RequirePermission`.
+pub trait Permission: Send + Sync {
+    const NAME: &'static str;
+}
+
+pub struct AuditRead;
+
+pub struct UsersRead;
+
+pub struct UsersWrite;
+
+pub struct RolesRead;
+
+pub struct RolesWrite;
+
+impl Permission for AuditRead {
+    const NAME: &'static str = "audit:read";
+}
+
+impl Permission for UsersRead {
+    const NAME: &'static str = "users:read";
+}
+
+impl Permission for UsersWrite {
+    const NAME: &'static str = "users:write";
+}
+
+impl Permission for RolesRead {
+    const NAME: &'static str = "roles:read";
+}
+
+impl Permission for RolesWrite {
+    const NAME: &'static str = "roles:write";
+}
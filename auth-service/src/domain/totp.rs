@@ -0,0 +1,79 @@
+use {
+    crate::{
+        domain::{data_stores::TwoFactorCode, email::Email},
+        utils::{
+            constants::TOTP_ISSUER,
+            encryption::{decrypt, encrypt},
+        },
+    },
+    chrono::Utc,
+    color_eyre::{Result, eyre::WrapErr},
+    rand::{Rng, rng},
+    secrecy::{ExposeSecret, SecretBox},
+    totp_rs::{Algorithm, TOTP},
+};
+
+const SECRET_LENGTH: usize = 20;
+const DIGITS: usize = 6;
+const STEP_SECONDS: u64 = 30;
+
+#[derive(Debug)]
+pub struct TotpSecret(SecretBox<Vec<u8>>);
+
+impl TotpSecret {
+    pub fn encrypt(&self, key: &SecretBox<String>) -> Result<Vec<u8>> {
+        encrypt(self.0.expose_secret(), key).wrap_err("Failed to encrypt TOTP secret")
+    }
+
+    pub fn decrypt(encrypted: &[u8], key: &SecretBox<String>) -> Result<Self> {
+        let secret = decrypt(encrypted, key).wrap_err("Failed to decrypt TOTP secret")?;
+
+        Ok(Self(SecretBox::new(Box::new(secret))))
+    }
+
+    pub fn otpauth_uri(&self, email: &Email) -> Result<SecretBox<String>> {
+        Ok(SecretBox::new(Box::new(self.totp(email)?.get_url())))
+    }
+
+    pub fn to_base32(&self, email: &Email) -> Result<SecretBox<String>> {
+        Ok(SecretBox::new(Box::new(self.totp(email)?.get_secret_base32())))
+    }
+
+    /// Accepts codes from up to `skew` steps either side of the current one, returning the time step the code
+    /// belongs to so callers can keep it from being accepted twice.
+    pub fn verify(&self, email: &Email, code: &TwoFactorCode, skew: u8) -> Result<Option<u64>> {
+        let totp = self.totp(email)?;
+        let current_step = Utc::now().timestamp() as u64 / STEP_SECONDS;
+        let steps = current_step.saturating_sub(skew as u64)..=current_step + skew as u64;
+
+        Ok(steps.into_iter().find(|step| totp.check(code.as_ref().expose_secret(), step * STEP_SECONDS)))
+    }
+
+    fn totp(&self, email: &Email) -> Result<TOTP> {
+        Ok(TOTP::new(
+            Algorithm::SHA1,
+            DIGITS,
+            0,
+            STEP_SECONDS,
+            self.0.expose_secret().to_owned(),
+            Some(TOTP_ISSUER.to_owned()),
+            email.as_ref().expose_secret().to_owned(),
+        )?)
+    }
+}
+
+impl Default for TotpSecret {
+    fn default() -> Self {
+        let mut secret = vec![0; SECRET_LENGTH];
+
+        rng().fill(secret.as_mut_slice());
+
+        Self(SecretBox::new(Box::new(secret)))
+    }
+}
+
+impl Clone for TotpSecret {
+    fn clone(&self) -> Self {
+        Self(SecretBox::new(Box::new(self.0.expose_secret().clone())))
+    }
+}
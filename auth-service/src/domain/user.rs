@@ -1,29 +1,77 @@
 use {
     crate::domain::{email::Email, password::Password},
     argon2::{Argon2, PasswordHash, PasswordVerifier},
+    chrono::Utc,
     color_eyre::Result,
     secrecy::{ExposeSecret, SecretBox},
+    serde::{Deserialize, Serialize},
     tokio::task::spawn_blocking,
     tracing::{Span, instrument},
+    uuid::Uuid,
 };
 
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Serialize, sqlx::Type)]
+#[serde(rename_all = "lowercase")]
+#[sqlx(type_name = "two_factor_method", rename_all = "lowercase")]
+pub enum TwoFactorMethod {
+    #[default]
+    None,
+    Email,
+    Totp,
+    Passkey,
+}
+
+/// Whether the account may be used. Only active accounts can log in.
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Serialize, sqlx::Type)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "account_status", rename_all = "snake_case")]
+pub enum AccountStatus {
+    #[default]
+    Active,
+    /// Blocked by an admin, possibly until a set time.
+    Suspended,
+    /// Blocked by an admin until they enable the account again.
+    Disabled,
+    /// Signed up but has not verified their email address yet. Never stored; an account that is otherwise active is
+    /// pending verification for as long as its email is unverified.
+    PendingVerification,
+}
+
 #[derive(Clone, Debug, PartialEq)]
 pub struct User {
     pub email: Email,
     pub password: Password,
-    pub requires_2fa: bool,
+    pub two_factor_method: TwoFactorMethod,
 }
 
 #[derive(Clone, Debug, PartialEq)]
 pub struct UserRow {
+    pub user_id: Uuid,
     pub email: String,
     pub password_hash: String,
-    pub requires_2fa: bool,
+    pub two_factor_method: TwoFactorMethod,
+    pub email_verified: bool,
+    /// Status set by an admin; use `current_status` for the one in effect.
+    pub status: AccountStatus,
+    /// Why an admin suspended or disabled the account.
+    pub status_reason: Option<String>,
+    /// Unix timestamp a suspension lifts at; suspensions without one last until lifted by an admin.
+    pub suspended_until: Option<i64>,
+    /// Set by an admin; the user has to reset their password before logging in with one again.
+    pub password_reset_required: bool,
+}
+
+/// One page of the users matching an admin search.
+#[derive(Debug)]
+pub struct UserPage {
+    pub users: Vec<UserRow>,
+    /// Matching users across all pages.
+    pub total: u64,
 }
 
 impl User {
-    pub fn new(email: &Email, password: &Password, requires_2fa: bool) -> Self {
-        Self { email: email.clone(), password: password.clone(), requires_2fa }
+    pub fn new(email: &Email, password: &Password, two_factor_method: TwoFactorMethod) -> Self {
+        Self { email: email.clone(), password: password.clone(), two_factor_method }
     }
 
     #[instrument(name = "Convert user to row", skip_all)]
@@ -33,14 +81,40 @@ impl User {
         let password_hash = spawn_blocking(move || current_span.in_scope(|| password.hash())).await??;
 
         Ok(UserRow {
+            user_id: Uuid::new_v4(),
             email: self.email.as_ref().expose_secret().to_owned(),
             password_hash,
-            requires_2fa: self.requires_2fa,
+            two_factor_method: self.two_factor_method,
+            email_verified: false,
+            status: AccountStatus::Active,
+            status_reason: None,
+            suspended_until: None,
+            password_reset_required: false,
         })
     }
 }
 
+impl AccountStatus {
+    /// Status in effect now, counting suspensions whose `suspended_until` has passed as lifted and active accounts
+    /// without a verified email as pending verification.
+    pub fn in_effect(self, suspended_until: Option<i64>, email_verified: bool) -> Self {
+        let status = match (self, suspended_until) {
+            (Self::Suspended, Some(until)) if until <= Utc::now().timestamp() => Self::Active,
+            (status, _) => status,
+        };
+
+        match status {
+            Self::Active if !email_verified => Self::PendingVerification,
+            status => status,
+        }
+    }
+}
+
 impl UserRow {
+    pub fn current_status(&self) -> AccountStatus {
+        self.status.in_effect(self.suspended_until, self.email_verified)
+    }
+
     #[instrument(name = "Verify password hash", skip_all)]
     pub async fn verify_password_hash(&self, target: &SecretBox<String>) -> Result<()> {
         let current_span = Span::current();
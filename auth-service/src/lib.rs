@@ -7,24 +7,42 @@ pub mod utils;
 use {
     crate::{
         domain::error::AuthAPIError,
-        routes::{login, logout, signup, verify_2fa, verify_token},
-        utils::tracing::{make_span_with_request_id, on_request, on_response},
+        routes::{
+            assign_role, cancel_email_change, change_email, change_password, confirm_email_change,
+            confirm_password_reset, confirm_totp, consume_magic_link, delete_account, disable_user, enable_user,
+            enroll_totp, export_account, finish_passkey_login, finish_passkey_registration, force_password_reset,
+            get_user, jwks, list_audit_events, list_roles, list_sessions, list_users, login, logout, logout_all,
+            refresh, regenerate_recovery_codes, request_magic_link, request_password_reset, resend_verification_email,
+            restore_account, revoke_other_sessions, revoke_role, revoke_session, revoke_user_sessions,
+            rotate_signing_key, set_two_factor_method, set_user_two_factor, signup, start_passkey_login,
+            start_passkey_registration, suspend_user, unlock_user, verify_2fa, verify_email, verify_token,
+        },
+        utils::{
+            constants::WEBAUTHN_RP_NAME,
+            rate_limit::rate_limit,
+            tracing::{make_span_with_request_id, on_request, on_response, set_request_id},
+        },
     },
     app_state::AppState,
     axum::{
         Json, Router,
         http::{Method, StatusCode},
+        middleware::{from_fn, from_fn_with_state},
         response::{IntoResponse, Response},
-        routing::post,
+        routing::{get, post},
     },
     redis::{Client, RedisResult},
     secrecy::{ExposeSecret, SecretBox},
     serde::{Deserialize, Serialize},
     sqlx::{PgPool, postgres::PgPoolOptions},
-    std::{error::Error, io::Error as IoError},
+    std::{error::Error, io::Error as IoError, net::SocketAddr},
     tokio::net::TcpListener,
     tower_http::{cors::CorsLayer, services::ServeDir, trace::TraceLayer},
     tracing::{error, info},
+    webauthn_rs::{
+        Webauthn, WebauthnBuilder,
+        prelude::{Url, WebauthnResult},
+    },
 };
 
 pub struct Application {
@@ -48,9 +66,51 @@ impl Application {
             .fallback_service(ServeDir::new("assets"))
             .route("/signup", post(signup))
             .route("/login", post(login))
+            .route("/login/magic-link", post(request_magic_link))
+            .route("/login/magic-link/consume", post(consume_magic_link))
             .route("/logout", post(logout))
+            .route("/logout-all", post(logout_all))
             .route("/verify-2fa", post(verify_2fa))
             .route("/verify-token", post(verify_token))
+            .route("/password-reset/request", post(request_password_reset))
+            .route("/password-reset/confirm", post(confirm_password_reset))
+            .route("/verify-email", post(verify_email))
+            .route("/verify-email/resend", post(resend_verification_email))
+            .route("/change-password", post(change_password))
+            .route("/change-email", post(change_email))
+            .route("/change-email/confirm", post(confirm_email_change))
+            .route("/change-email/cancel", post(cancel_email_change))
+            .route("/account/export", get(export_account))
+            .route("/account/delete", post(delete_account))
+            .route("/account/restore", post(restore_account))
+            .route("/refresh", post(refresh))
+            .route("/sessions", get(list_sessions))
+            .route("/sessions/revoke", post(revoke_session))
+            .route("/sessions/revoke-others", post(revoke_other_sessions))
+            .route("/2fa/method", post(set_two_factor_method))
+            .route("/2fa/totp/enroll", post(enroll_totp))
+            .route("/2fa/totp/confirm", post(confirm_totp))
+            .route("/2fa/recovery-codes", post(regenerate_recovery_codes))
+            .route("/passkey/register/start", post(start_passkey_registration))
+            .route("/passkey/register/finish", post(finish_passkey_registration))
+            .route("/passkey/login/start", post(start_passkey_login))
+            .route("/passkey/login/finish", post(finish_passkey_login))
+            .route("/.well-known/jwks.json", get(jwks))
+            .route("/admin/keys/rotate", post(rotate_signing_key))
+            .route("/admin/roles", get(list_roles))
+            .route("/admin/roles/assign", post(assign_role))
+            .route("/admin/roles/revoke", post(revoke_role))
+            .route("/admin/users", get(list_users))
+            .route("/admin/users/{user_id}", get(get_user))
+            .route("/admin/users/{user_id}/disable", post(disable_user))
+            .route("/admin/users/{user_id}/enable", post(enable_user))
+            .route("/admin/users/{user_id}/suspend", post(suspend_user))
+            .route("/admin/users/{user_id}/password-reset", post(force_password_reset))
+            .route("/admin/users/{user_id}/2fa", post(set_user_two_factor))
+            .route("/admin/users/{user_id}/unlock", post(unlock_user))
+            .route("/admin/users/{user_id}/sessions/revoke", post(revoke_user_sessions))
+            .route("/admin/audit-events", get(list_audit_events))
+            .layer(from_fn_with_state(app_state.clone(), rate_limit))
             .with_state(app_state)
             .layer(cors)
             .layer(
@@ -58,7 +118,8 @@ impl Application {
                     .make_span_with(make_span_with_request_id)
                     .on_request(on_request)
                     .on_response(on_response),
-            );
+            )
+            .layer(from_fn(set_request_id));
 
         let listener = tokio::net::TcpListener::bind(address).await?;
         let address = listener.local_addr()?.to_string();
@@ -69,7 +130,7 @@ impl Application {
     pub async fn run(self) -> Result<(), IoError> {
         info!("listening on {}", &self.address);
 
-        Ok(axum::serve(self.listener, self.router).await?)
+        axum::serve(self.listener, self.router.into_make_service_with_connect_info::<SocketAddr>()).await
     }
 }
 
@@ -78,12 +139,22 @@ impl IntoResponse for AuthAPIError {
         log_error_chain(&self);
 
         let (status, error_message) = match self {
+            AuthAPIError::AccountDisabled => (StatusCode::FORBIDDEN, "Account disabled"),
+            AuthAPIError::AccountLocked => (StatusCode::LOCKED, "Account locked"),
+            AuthAPIError::AccountSuspended => (StatusCode::FORBIDDEN, "Account suspended"),
+            AuthAPIError::EmailNotVerified => (StatusCode::FORBIDDEN, "Email not verified"),
+            AuthAPIError::Forbidden => (StatusCode::FORBIDDEN, "Forbidden"),
             AuthAPIError::IncorrectCredentials => (StatusCode::UNAUTHORIZED, "Incorrect credentials"),
             AuthAPIError::InvalidCredentials => (StatusCode::BAD_REQUEST, "Invalid credentials"),
             AuthAPIError::InvalidToken => (StatusCode::UNAUTHORIZED, "Invalid token"),
             AuthAPIError::MissingToken => (StatusCode::BAD_REQUEST, "Missing token"),
             AuthAPIError::MalformedToken => (StatusCode::UNPROCESSABLE_ENTITY, "Malformed token"),
+            AuthAPIError::PasswordResetRequired => (StatusCode::FORBIDDEN, "Password reset required"),
+            AuthAPIError::RoleNotFound => (StatusCode::NOT_FOUND, "Role not found"),
+            AuthAPIError::SessionNotFound => (StatusCode::NOT_FOUND, "Session not found"),
+            AuthAPIError::TooManyRequests => (StatusCode::TOO_MANY_REQUESTS, "Too many requests"),
             AuthAPIError::UserAlreadyExists => (StatusCode::CONFLICT, "User already exists"),
+            AuthAPIError::UserNotFound => (StatusCode::NOT_FOUND, "User not found"),
             AuthAPIError::UnexpectedError(_) => (StatusCode::INTERNAL_SERVER_ERROR, "Unexpected error"),
         };
         let body = Json(ErrorResponse { error: error_message.to_string() });
@@ -102,6 +173,10 @@ pub fn get_redis_client(host: &str) -> RedisResult<Client> {
     redis::Client::open(redis_url)
 }
 
+pub fn get_webauthn(rp_id: &str, rp_origin: &Url) -> WebauthnResult<Webauthn> {
+    WebauthnBuilder::new(rp_id, rp_origin)?.rp_name(WEBAUTHN_RP_NAME).build()
+}
+
 fn log_error_chain(e: &(dyn Error + 'static)) {
     let separator = "\n---------------------------------------------\n";
     let mut report = format!("{separator}{e:?}\n");
@@ -1,48 +1,132 @@
 use {
     auth_service::{
         Application,
-        app_state::AppState,
+        app_state::{AppState, EmailClientType, UserStoreType, WebauthnType},
         domain::email::Email,
-        get_postgres_pool, get_redis_client,
-        services::{PostgresUserStore, RedisBannedTokenStore, RedisTwoFactorStore, Resend},
+        get_postgres_pool, get_redis_client, get_webauthn,
+        routes::purge_deleted_accounts,
+        services::{
+            HashmapEmailChangeStore, HashmapEmailVerificationStore, HashmapLoginThrottleStore, HashmapMagicLinkStore,
+            HashmapPasskeyCeremonyStore, HashmapPasskeyStore, HashmapPasswordResetStore, HashmapRecoveryCodeStore,
+            HashmapRefreshTokenStore, HashmapRoleStore, HashmapSessionStore, HashmapSigningKeyStore,
+            HashmapTotpSecretStore, HashmapTwoFactorStore, HashmapUserStore, HashsetBannedTokenStore, InMemoryAuditLog,
+            InMemoryRateLimitStore, PostgresAuditLog, PostgresPasskeyStore, PostgresRecoveryCodeStore,
+            PostgresRefreshTokenStore, PostgresRoleStore, PostgresSigningKeyStore, PostgresTotpSecretStore,
+            PostgresUserStore, RedisBannedTokenStore, RedisEmailChangeStore, RedisEmailVerificationStore,
+            RedisLoginThrottleStore, RedisMagicLinkStore, RedisPasskeyCeremonyStore, RedisPasswordResetStore,
+            RedisRateLimitStore, RedisSessionStore, RedisTwoFactorStore, Resend,
+        },
         utils::{
             constants::{
-                DATABASE_URL, REDIS_HOST_NAME, RESEND_SENDER_API_KEY,
+                ACCOUNT_DELETION_GRACE_SECONDS, ACCOUNT_PURGE_INTERVAL, DATABASE_URL, JWT_KEY_RING,
+                JWT_KEY_ROTATION_SECONDS, REDIS_HOST_NAME, RESEND_SENDER_API_KEY, TOTP_ENCRYPTION_KEY, WEBAUTHN_RP_ID,
+                WEBAUTHN_RP_ORIGIN,
                 prod::{self, email_client::SENDER},
             },
+            keys::{load_key_ring, sync_key_ring},
             tracing::init_tracing,
         },
     },
+    chrono::Utc,
     color_eyre::install,
-    redis::Connection as RedisConnection,
+    redis::aio::ConnectionManager,
     secrecy::SecretBox,
     sqlx::{PgPool, migrate},
-    std::sync::Arc,
+    std::{env::args, sync::Arc},
+    tracing::{error, info},
+    webauthn_rs::{Webauthn, prelude::Url},
 };
 
+/// Starts the service without Postgres and Redis.
+const IN_MEMORY_FLAG: &str = "--in-memory";
+
 #[tokio::main]
 async fn main() {
     install().expect("Failed to install color_eyre");
     init_tracing().expect("Failed to initialise tracing");
 
-    let pool = configure_postgresql().await;
-    let user_store = PostgresUserStore::new(pool);
-    let banned_token_store = RedisBannedTokenStore::new(configure_redis());
-    let two_factor_store = RedisTwoFactorStore::new(configure_redis());
-    let app_state = AppState::new(
-        Arc::new(banned_token_store),
-        Arc::new(user_store),
-        Arc::new(two_factor_store),
-        Arc::new(Resend::new(
-            Email::parse(&SecretBox::new(Box::new(SENDER.to_owned()))).unwrap(),
-            &RESEND_SENDER_API_KEY,
-        )),
-    );
+    let email_client = Arc::new(Resend::new(
+        Email::parse(&SecretBox::new(Box::new(SENDER.to_owned()))).unwrap(),
+        &RESEND_SENDER_API_KEY,
+    ));
+    let webauthn = Arc::new(configure_webauthn());
+    let app_state = match args().any(|arg| arg == IN_MEMORY_FLAG) {
+        true => configure_in_memory_state(email_client, webauthn),
+        false => configure_state(email_client, webauthn).await,
+    };
+
+    load_key_ring(&JWT_KEY_RING, app_state.signing_key_store.as_ref()).await.expect("Failed to load JWT signing keys");
+    println!("Loaded JWT signing key {}", JWT_KEY_RING.active(Utc::now().timestamp()).expect("No JWT signing key").kid);
+    tokio::spawn(sync_key_ring(&JWT_KEY_RING, app_state.signing_key_store.clone(), *JWT_KEY_ROTATION_SECONDS));
+
+    if *ACCOUNT_DELETION_GRACE_SECONDS > 0 {
+        tokio::spawn(purge_deleted_users(app_state.clone()));
+    }
+
     let app = Application::build(app_state, prod::APP_ADDRESS).await.expect("Failed to build app.");
 
     app.run().await.expect("Failed to run app.")
 }
 
+/// Backs every store with Postgres and Redis.
+async fn configure_state(email_client: EmailClientType, webauthn: WebauthnType) -> AppState {
+    let pool = configure_postgresql().await;
+    let redis = configure_redis().await;
+
+    AppState::new(
+        Arc::new(RedisBannedTokenStore::new(redis.clone())),
+        Arc::new(PostgresUserStore::new(pool.clone())),
+        Arc::new(RedisTwoFactorStore::new(redis.clone())),
+        Arc::new(RedisPasswordResetStore::new(redis.clone())),
+        Arc::new(RedisEmailVerificationStore::new(redis.clone())),
+        Arc::new(RedisEmailChangeStore::new(redis.clone())),
+        Arc::new(PostgresRefreshTokenStore::new(pool.clone())),
+        Arc::new(RedisSessionStore::new(redis.clone())),
+        Arc::new(PostgresTotpSecretStore::new(pool.clone(), &TOTP_ENCRYPTION_KEY)),
+        Arc::new(PostgresRecoveryCodeStore::new(pool.clone())),
+        Arc::new(PostgresPasskeyStore::new(pool.clone())),
+        Arc::new(RedisPasskeyCeremonyStore::new(redis.clone())),
+        Arc::new(RedisMagicLinkStore::new(redis.clone())),
+        Arc::new(PostgresRoleStore::new(pool.clone())),
+        Arc::new(PostgresAuditLog::new(pool.clone())),
+        Arc::new(PostgresSigningKeyStore::new(pool, &TOTP_ENCRYPTION_KEY)),
+        Arc::new(RedisLoginThrottleStore::new(redis.clone())),
+        Arc::new(RedisRateLimitStore::new(redis)),
+        email_client,
+        webauthn,
+    )
+}
+
+/// Keeps everything in process memory, so the service runs without Postgres and Redis but forgets all state on
+/// restart.
+fn configure_in_memory_state(email_client: EmailClientType, webauthn: WebauthnType) -> AppState {
+    println!("Keeping all state in memory...");
+    let user_store: UserStoreType = Arc::new(HashmapUserStore::default());
+
+    AppState::new(
+        Arc::new(HashsetBannedTokenStore::default()),
+        user_store.clone(),
+        Arc::new(HashmapTwoFactorStore::default()),
+        Arc::new(HashmapPasswordResetStore::default()),
+        Arc::new(HashmapEmailVerificationStore::default()),
+        Arc::new(HashmapEmailChangeStore::default()),
+        Arc::new(HashmapRefreshTokenStore::default()),
+        Arc::new(HashmapSessionStore::default()),
+        Arc::new(HashmapTotpSecretStore::default()),
+        Arc::new(HashmapRecoveryCodeStore::default()),
+        Arc::new(HashmapPasskeyStore::default()),
+        Arc::new(HashmapPasskeyCeremonyStore::default()),
+        Arc::new(HashmapMagicLinkStore::default()),
+        Arc::new(HashmapRoleStore::new(user_store)),
+        Arc::new(InMemoryAuditLog::default()),
+        Arc::new(HashmapSigningKeyStore::default()),
+        Arc::new(HashmapLoginThrottleStore::default()),
+        Arc::new(InMemoryRateLimitStore::default()),
+        email_client,
+        webauthn,
+    )
+}
+
 async fn configure_postgresql() -> PgPool {
     println!("Configuring database...");
     let pool = get_postgres_pool(&DATABASE_URL).await.expect("Failed to create Postgres connection pool");
@@ -53,9 +137,31 @@ async fn configure_postgresql() -> PgPool {
     pool
 }
 
-fn configure_redis() -> RedisConnection {
+/// Removes accounts whose deletion grace period has ended.
+async fn purge_deleted_users(state: AppState) {
+    let mut ticker = tokio::time::interval(ACCOUNT_PURGE_INTERVAL);
+
+    loop {
+        ticker.tick().await;
+
+        match purge_deleted_accounts(&state).await {
+            Ok(count) => info!(count, "Purged deleted users"),
+            Err(e) => error!(error = %e, "Failed to purge deleted users"),
+        }
+    }
+}
+
+fn configure_webauthn() -> Webauthn {
+    let rp_origin = Url::parse(&WEBAUTHN_RP_ORIGIN).expect("WEBAUTHN_RP_ORIGIN must be a valid URL");
+
+    get_webauthn(&WEBAUTHN_RP_ID, &rp_origin).expect("Failed to configure WebAuthn")
+}
+
+/// Shared by every Redis store; it multiplexes their commands and reconnects after Redis restarts.
+async fn configure_redis() -> ConnectionManager {
     get_redis_client(REDIS_HOST_NAME.as_str())
         .expect("Failed to get Redis client")
-        .get_connection()
+        .get_connection_manager()
+        .await
         .expect("Failed to get Redis connection")
 }
@@ -0,0 +1,255 @@
+use {
+    crate::{
+        app_state::AppState,
+        domain::{
+            audit::AuditEventFilter,
+            data_stores::{MagicLinkStoreError, ThrottleKey, TotpSecretStoreError, UserStoreError},
+            email::Email,
+            error::AuthAPIError,
+            user::TwoFactorMethod,
+        },
+        routes::{AuditEventResponse, SessionResponse},
+        utils::{
+            auth::{AuthenticatedUser, ClientInfo, bump_token_version},
+            constants::{JWT_COOKIE_NAME, REFRESH_COOKIE_NAME},
+            throttle::{check_login_throttle, record_login_failure},
+        },
+    },
+    axum::{Json, extract::State, http::StatusCode, response::IntoResponse},
+    axum_extra::extract::{CookieJar, cookie::Cookie},
+    base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD},
+    color_eyre::eyre::{Context, Result},
+    secrecy::SecretBox,
+    serde::{Deserialize, Serialize},
+    tracing::{error, instrument},
+    uuid::Uuid,
+};
+
+#[derive(Deserialize)]
+pub struct DeleteAccountRequest {
+    pub password: SecretBox<String>,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Serialize)]
+pub struct DeleteAccountResponse {
+    pub message: String,
+}
+
+#[derive(Deserialize)]
+pub struct RestoreAccountRequest {
+    pub email: SecretBox<String>,
+    pub password: SecretBox<String>,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Serialize)]
+pub struct RestoreAccountResponse {
+    pub message: String,
+}
+
+/// Everything the service stores about a user.
+#[derive(Debug, Deserialize, PartialEq, Serialize)]
+pub struct AccountExport {
+    pub profile: ProfileExport,
+    #[serde(rename = "twoFactor")]
+    pub two_factor: TwoFactorExport,
+    /// Sessions the user is logged in with, oldest first.
+    pub sessions: Vec<SessionResponse>,
+    /// Audit events about the user, newest first.
+    #[serde(rename = "auditEvents")]
+    pub audit_events: Vec<AuditEventResponse>,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Serialize)]
+pub struct ProfileExport {
+    #[serde(rename = "userId")]
+    pub user_id: Uuid,
+    pub email: String,
+    #[serde(rename = "emailVerified")]
+    pub email_verified: bool,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Serialize)]
+pub struct TwoFactorExport {
+    pub method: TwoFactorMethod,
+    #[serde(rename = "totpEnabled")]
+    pub totp_enabled: bool,
+    #[serde(rename = "recoveryCodesRemaining")]
+    pub recovery_codes_remaining: u64,
+    /// Base64url encoded credential IDs of the registered passkeys.
+    pub passkeys: Vec<String>,
+}
+
+#[instrument(name = "Export account", skip_all)]
+pub async fn export_account(
+    state: State<AppState>,
+    AuthenticatedUser { user_id, session_id, .. }: AuthenticatedUser,
+) -> Result<impl IntoResponse, AuthAPIError> {
+    let user = state.user_store.get_user_by_id(&user_id).await.map_err(|e| AuthAPIError::UnexpectedError(e.into()))?;
+    let totp_enabled = match state.totp_secret_store.get_secret(&user_id).await {
+        Ok(_) => true,
+        Err(TotpSecretStoreError::SecretNotFound) => false,
+        Err(e) => return Err(AuthAPIError::UnexpectedError(e.into())),
+    };
+    let recovery_codes_remaining =
+        state.recovery_code_store.count_codes(&user_id).await.map_err(|e| AuthAPIError::UnexpectedError(e.into()))?;
+    let passkeys =
+        state.passkey_store.get_passkeys(&user_id).await.map_err(|e| AuthAPIError::UnexpectedError(e.into()))?;
+    let sessions =
+        state.session_store.get_sessions(&user_id).await.map_err(|e| AuthAPIError::UnexpectedError(e.into()))?;
+    let audit_events = state
+        .audit_log
+        .query(&AuditEventFilter { user_id: Some(user_id), ..Default::default() }, 0, i64::MAX as u64)
+        .await
+        .map_err(|e| AuthAPIError::UnexpectedError(e.into()))?
+        .events;
+
+    Ok((
+        StatusCode::OK,
+        Json(AccountExport {
+            profile: ProfileExport { user_id, email: user.email, email_verified: user.email_verified },
+            two_factor: TwoFactorExport {
+                method: user.two_factor_method,
+                totp_enabled,
+                recovery_codes_remaining,
+                passkeys: passkeys.iter().map(|passkey| URL_SAFE_NO_PAD.encode(passkey.cred_id())).collect(),
+            },
+            sessions: sessions.into_iter().map(|session| SessionResponse::new(session, &session_id)).collect(),
+            audit_events: audit_events.into_iter().map(AuditEventResponse::from).collect(),
+        }),
+    ))
+}
+
+#[instrument(name = "Delete account", skip_all)]
+pub async fn delete_account(
+    state: State<AppState>,
+    jar: CookieJar,
+    AuthenticatedUser { user_id, .. }: AuthenticatedUser,
+    Json(request): Json<DeleteAccountRequest>,
+) -> Result<(CookieJar, impl IntoResponse), AuthAPIError> {
+    let user = state.user_store.get_user_by_id(&user_id).await.map_err(|e| AuthAPIError::UnexpectedError(e.into()))?;
+
+    if user.verify_password_hash(&request.password).await.is_err() {
+        return Err(AuthAPIError::IncorrectCredentials);
+    }
+
+    if let Err(e) = bump_token_version(&state, &user_id).await {
+        return Err(AuthAPIError::UnexpectedError(e));
+    }
+
+    if let Err(e) = state.refresh_token_store.revoke_user(&user_id, None).await {
+        return Err(AuthAPIError::UnexpectedError(e.into()));
+    }
+
+    if let Err(e) = state.session_store.remove_sessions(&user_id, None).await {
+        return Err(AuthAPIError::UnexpectedError(e.into()));
+    }
+
+    if let Err(e) = state.two_factor_store.remove_code(&user_id).await {
+        return Err(AuthAPIError::UnexpectedError(e.into()));
+    }
+
+    if let Err(e) = state.email_change_store.remove_change(&user_id).await {
+        return Err(AuthAPIError::UnexpectedError(e.into()));
+    }
+
+    remove_email_data(&state, &user.email).await?;
+
+    // removed last, since the token version and everything above are keyed by the account; audit events are only
+    // anonymised once the account is gone, so a restored account keeps its history
+    match state.account_deletion_grace_seconds {
+        0 => {
+            state.user_store.delete_user(&user_id).await.map_err(|e| AuthAPIError::UnexpectedError(e.into()))?;
+            state.audit_log.anonymise_user(&user_id).await.map_err(|e| AuthAPIError::UnexpectedError(e.into()))?;
+        }
+        grace_seconds => state
+            .user_store
+            .schedule_deletion(&user_id, grace_seconds)
+            .await
+            .map_err(|e| AuthAPIError::UnexpectedError(e.into()))?,
+    }
+
+    let jar = jar.remove(Cookie::from(JWT_COOKIE_NAME)).remove(Cookie::from(REFRESH_COOKIE_NAME));
+
+    Ok((jar, (StatusCode::OK, Json(DeleteAccountResponse { message: "Account deleted".to_string() }))))
+}
+
+/// Removes accounts whose deletion grace period has ended and anonymises their audit events, returning how many
+/// were removed.
+pub async fn purge_deleted_accounts(state: &AppState) -> Result<u64> {
+    let user_ids = state.user_store.purge_deleted_users().await.wrap_err("Failed to purge deleted users")?;
+
+    // the accounts are gone already, so the rest of them are anonymised even if one fails
+    for user_id in &user_ids {
+        if let Err(e) = state.audit_log.anonymise_user(user_id).await {
+            error!(%user_id, "Failed to anonymise audit events of purged user: {e:?}");
+        }
+    }
+
+    Ok(user_ids.len() as u64)
+}
+
+/// Cancels a deletion during its grace period. Deleting revoked every session, so the user logs in again afterwards.
+#[instrument(name = "Restore account", skip_all)]
+pub async fn restore_account(
+    state: State<AppState>,
+    client: ClientInfo,
+    Json(request): Json<RestoreAccountRequest>,
+) -> Result<impl IntoResponse, AuthAPIError> {
+    let Ok(email) = Email::parse(&request.email)
+    else {
+        return Err(AuthAPIError::InvalidCredentials);
+    };
+
+    check_login_throttle(&state, &email, client.ip).await?;
+
+    // unknown addresses fail like wrong passwords, so the response does not tell which accounts await deletion
+    let user = match state.user_store.get_deleted_user(&email).await {
+        Ok(user) => user,
+        Err(UserStoreError::UserNotFound) => {
+            record_login_failure(&state, &email, client.ip).await?;
+
+            return Err(AuthAPIError::IncorrectCredentials);
+        }
+        Err(e) => return Err(AuthAPIError::UnexpectedError(e.into())),
+    };
+
+    if user.verify_password_hash(&request.password).await.is_err() {
+        record_login_failure(&state, &email, client.ip).await?;
+
+        return Err(AuthAPIError::IncorrectCredentials);
+    }
+
+    state
+        .login_throttle_store
+        .reset(&ThrottleKey::Account(email))
+        .await
+        .map_err(|e| AuthAPIError::UnexpectedError(e.into()))?;
+    state.user_store.restore_user(&user.user_id).await.map_err(|e| AuthAPIError::UnexpectedError(e.into()))?;
+
+    Ok((StatusCode::OK, Json(RestoreAccountResponse { message: "Account restored".to_string() })))
+}
+
+/// Removes the tokens and login failure counters kept for the address, so none of them applies to the deleted
+/// account or carries over to whoever signs up with the address later.
+async fn remove_email_data(state: &AppState, email: &str) -> Result<(), AuthAPIError> {
+    let email = Email::parse(&SecretBox::new(Box::new(email.to_owned()))).map_err(AuthAPIError::UnexpectedError)?;
+
+    if let Err(e) = state.password_reset_store.remove_token(&email).await {
+        return Err(AuthAPIError::UnexpectedError(e.into()));
+    }
+
+    if let Err(e) = state.email_verification_store.remove_token(&email).await {
+        return Err(AuthAPIError::UnexpectedError(e.into()));
+    }
+
+    match state.magic_link_store.take_token(&email).await {
+        Ok(_) | Err(MagicLinkStoreError::TokenNotFound) => {}
+        Err(e) => return Err(AuthAPIError::UnexpectedError(e.into())),
+    }
+
+    if let Err(e) = state.login_throttle_store.reset(&ThrottleKey::Account(email)).await {
+        return Err(AuthAPIError::UnexpectedError(e.into()));
+    }
+
+    Ok(())
+}
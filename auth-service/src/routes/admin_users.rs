@@ -0,0 +1,364 @@
+use {
+    crate::{
+        app_state::AppState,
+        domain::{
+            data_stores::{PasswordResetToken, ThrottleKey, UserStoreError},
+            email::Email,
+            error::AuthAPIError,
+            role::{UsersRead, UsersWrite},
+            user::{AccountStatus, TwoFactorMethod, UserRow},
+        },
+        utils::{
+            audit::record_admin_action,
+            auth::{ClientInfo, RequirePermission, bump_token_version},
+            constants::{DEFAULT_PAGE_SIZE, MAX_PAGE_SIZE},
+        },
+    },
+    axum::{
+        Json,
+        extract::{Path, Query, State},
+        http::StatusCode,
+        response::IntoResponse,
+    },
+    secrecy::{ExposeSecret, SecretBox},
+    serde::{Deserialize, Serialize},
+    tracing::instrument,
+    uuid::Uuid,
+};
+
+#[derive(Deserialize)]
+pub struct ListUsersQuery {
+    /// Part of the email address to match, ignoring case.
+    pub search: Option<String>,
+    /// Starts at 1.
+    pub page: Option<u64>,
+    #[serde(rename = "perPage")]
+    pub per_page: Option<u64>,
+}
+
+#[derive(Deserialize)]
+pub struct SetTwoFactorRequest {
+    #[serde(rename = "requires2FA")]
+    pub requires_2fa: bool,
+}
+
+#[derive(Deserialize)]
+pub struct SuspendUserRequest {
+    pub reason: String,
+    /// Unix timestamp the suspension lifts at; without one it lasts until the user is enabled again.
+    pub until: Option<i64>,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Serialize)]
+pub struct AdminUserResponse {
+    #[serde(rename = "userId")]
+    pub user_id: Uuid,
+    pub email: String,
+    #[serde(rename = "emailVerified")]
+    pub email_verified: bool,
+    #[serde(rename = "twoFactorMethod")]
+    pub two_factor_method: TwoFactorMethod,
+    pub status: AccountStatus,
+    #[serde(rename = "statusReason")]
+    pub status_reason: Option<String>,
+    #[serde(rename = "suspendedUntil")]
+    pub suspended_until: Option<i64>,
+    #[serde(rename = "passwordResetRequired")]
+    pub password_reset_required: bool,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Serialize)]
+pub struct AdminUsersResponse {
+    pub users: Vec<AdminUserResponse>,
+    pub total: u64,
+    pub page: u64,
+    #[serde(rename = "perPage")]
+    pub per_page: u64,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Serialize)]
+pub struct AdminUserDetailsResponse {
+    #[serde(flatten)]
+    pub user: AdminUserResponse,
+    /// Roles assigned to the user, not including the default role.
+    pub roles: Vec<String>,
+    /// Number of sessions the user is signed in with.
+    pub sessions: usize,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Serialize)]
+pub struct AdminUserActionResponse {
+    pub message: String,
+}
+
+impl From<UserRow> for AdminUserResponse {
+    fn from(user: UserRow) -> Self {
+        let status = user.current_status();
+
+        Self {
+            user_id: user.user_id,
+            email: user.email,
+            email_verified: user.email_verified,
+            two_factor_method: user.two_factor_method,
+            status,
+            status_reason: user.status_reason,
+            suspended_until: user.suspended_until,
+            password_reset_required: user.password_reset_required,
+        }
+    }
+}
+
+#[instrument(name = "List users", skip_all)]
+pub async fn list_users(
+    state: State<AppState>,
+    _: RequirePermission<UsersRead>,
+    Query(query): Query<ListUsersQuery>,
+) -> Result<impl IntoResponse, AuthAPIError> {
+    let page = query.page.unwrap_or(1).max(1);
+    let per_page = query.per_page.unwrap_or(DEFAULT_PAGE_SIZE).clamp(1, MAX_PAGE_SIZE);
+    let search = query.search.as_deref().filter(|search| !search.is_empty());
+    let result = state
+        .user_store
+        .list_users(search, (page - 1).saturating_mul(per_page), per_page)
+        .await
+        .map_err(|e| AuthAPIError::UnexpectedError(e.into()))?;
+
+    Ok((
+        StatusCode::OK,
+        Json(AdminUsersResponse {
+            users: result.users.into_iter().map(AdminUserResponse::from).collect(),
+            total: result.total,
+            page,
+            per_page,
+        }),
+    ))
+}
+
+#[instrument(name = "Get user", skip_all)]
+pub async fn get_user(
+    state: State<AppState>,
+    _: RequirePermission<UsersRead>,
+    Path(user_id): Path<Uuid>,
+) -> Result<impl IntoResponse, AuthAPIError> {
+    let user = find_user(&state, &user_id).await?;
+    let roles = state.role_store.get_user_roles(&user_id).await.map_err(|e| AuthAPIError::UnexpectedError(e.into()))?;
+    let sessions =
+        state.session_store.get_sessions(&user_id).await.map_err(|e| AuthAPIError::UnexpectedError(e.into()))?;
+
+    Ok((StatusCode::OK, Json(AdminUserDetailsResponse { user: user.into(), roles, sessions: sessions.len() })))
+}
+
+#[instrument(name = "Disable user", skip_all)]
+pub async fn disable_user(
+    state: State<AppState>,
+    client: ClientInfo,
+    admin: RequirePermission<UsersWrite>,
+    Path(user_id): Path<Uuid>,
+) -> Result<impl IntoResponse, AuthAPIError> {
+    set_status(&state, &user_id, AccountStatus::Disabled, None, None).await?;
+    end_all_sessions(&state, &user_id).await?;
+
+    record_admin_action(&state, &client, &admin, user_id, "disable").await;
+
+    Ok(action_response("User disabled"))
+}
+
+/// Blocks the user from logging in and rejects the auth tokens they hold, without ending their sessions; once the
+/// suspension lifts they carry on by refreshing.
+#[instrument(name = "Suspend user", skip_all)]
+pub async fn suspend_user(
+    state: State<AppState>,
+    client: ClientInfo,
+    admin: RequirePermission<UsersWrite>,
+    Path(user_id): Path<Uuid>,
+    Json(request): Json<SuspendUserRequest>,
+) -> Result<impl IntoResponse, AuthAPIError> {
+    let reason = request.reason;
+
+    set_status(&state, &user_id, AccountStatus::Suspended, Some(reason.clone()), request.until).await?;
+
+    // other instances may have the user's status cached, but they all check the token version
+    if let Err(e) = bump_token_version(&state, &user_id).await {
+        return Err(AuthAPIError::UnexpectedError(e));
+    }
+
+    record_admin_action(&state, &client, &admin, user_id, &format!("suspend: {reason}")).await;
+
+    Ok(action_response("User suspended"))
+}
+
+#[instrument(name = "Enable user", skip_all)]
+pub async fn enable_user(
+    state: State<AppState>,
+    client: ClientInfo,
+    admin: RequirePermission<UsersWrite>,
+    Path(user_id): Path<Uuid>,
+) -> Result<impl IntoResponse, AuthAPIError> {
+    // users who never verified their email go back to waiting for that
+    set_status(&state, &user_id, AccountStatus::Active, None, None).await?;
+
+    record_admin_action(&state, &client, &admin, user_id, "enable").await;
+
+    Ok(action_response("User enabled"))
+}
+
+/// Signs the user out everywhere and emails them a reset token; their password stops working until they use it.
+#[instrument(name = "Force password reset", skip_all)]
+pub async fn force_password_reset(
+    state: State<AppState>,
+    client: ClientInfo,
+    admin: RequirePermission<UsersWrite>,
+    Path(user_id): Path<Uuid>,
+) -> Result<impl IntoResponse, AuthAPIError> {
+    let user = find_user(&state, &user_id).await?;
+    let email = parse_email(user.email)?;
+
+    match state.user_store.require_password_reset(&user_id).await {
+        Ok(()) => {}
+        Err(UserStoreError::UserNotFound) => return Err(AuthAPIError::UserNotFound),
+        Err(e) => return Err(AuthAPIError::UnexpectedError(e.into())),
+    }
+
+    end_all_sessions(&state, &user_id).await?;
+
+    let token = PasswordResetToken::default();
+
+    state
+        .password_reset_store
+        .add_token(email.clone(), token.clone())
+        .await
+        .map_err(|e| AuthAPIError::UnexpectedError(e.into()))?;
+
+    state
+        .email_client
+        .send_email(&email, "Your password reset token", token.as_ref().expose_secret())
+        .await
+        .map_err(AuthAPIError::UnexpectedError)?;
+
+    record_admin_action(&state, &client, &admin, user_id, "password-reset").await;
+
+    Ok(action_response("Password reset required"))
+}
+
+/// Turns email 2FA on for users without 2FA, or turns off whichever method the user has.
+#[instrument(name = "Set user 2FA", skip_all)]
+pub async fn set_user_two_factor(
+    state: State<AppState>,
+    client: ClientInfo,
+    admin: RequirePermission<UsersWrite>,
+    Path(user_id): Path<Uuid>,
+    Json(request): Json<SetTwoFactorRequest>,
+) -> Result<impl IntoResponse, AuthAPIError> {
+    let user = find_user(&state, &user_id).await?;
+    let method = match (request.requires_2fa, user.two_factor_method) {
+        (true, TwoFactorMethod::None) => TwoFactorMethod::Email,
+        (true, method) => method,
+        (false, _) => TwoFactorMethod::None,
+    };
+
+    state
+        .user_store
+        .update_two_factor_method(&parse_email(user.email)?, method)
+        .await
+        .map_err(|e| AuthAPIError::UnexpectedError(e.into()))?;
+
+    // recovery codes only make sense while 2FA is on, like when users turn it off themselves
+    if method == TwoFactorMethod::None {
+        state
+            .recovery_code_store
+            .replace_codes(&user_id, vec![])
+            .await
+            .map_err(|e| AuthAPIError::UnexpectedError(e.into()))?;
+    }
+
+    let action = if request.requires_2fa { "2fa: on" } else { "2fa: off" };
+
+    record_admin_action(&state, &client, &admin, user_id, action).await;
+
+    Ok(action_response("2FA updated"))
+}
+
+/// Clears the failed login and 2FA counters that lock the account.
+#[instrument(name = "Unlock user", skip_all)]
+pub async fn unlock_user(
+    state: State<AppState>,
+    client: ClientInfo,
+    admin: RequirePermission<UsersWrite>,
+    Path(user_id): Path<Uuid>,
+) -> Result<impl IntoResponse, AuthAPIError> {
+    let user = find_user(&state, &user_id).await?;
+
+    for key in [ThrottleKey::Account(parse_email(user.email)?), ThrottleKey::TwoFactor(user_id)] {
+        state.login_throttle_store.reset(&key).await.map_err(|e| AuthAPIError::UnexpectedError(e.into()))?;
+    }
+
+    record_admin_action(&state, &client, &admin, user_id, "unlock").await;
+
+    Ok(action_response("User unlocked"))
+}
+
+#[instrument(name = "Revoke user sessions", skip_all)]
+pub async fn revoke_user_sessions(
+    state: State<AppState>,
+    client: ClientInfo,
+    admin: RequirePermission<UsersWrite>,
+    Path(user_id): Path<Uuid>,
+) -> Result<impl IntoResponse, AuthAPIError> {
+    find_user(&state, &user_id).await?;
+    end_all_sessions(&state, &user_id).await?;
+
+    record_admin_action(&state, &client, &admin, user_id, "sessions/revoke").await;
+
+    Ok(action_response("Sessions revoked"))
+}
+
+async fn find_user(state: &AppState, user_id: &Uuid) -> Result<UserRow, AuthAPIError> {
+    match state.user_store.get_user_by_id(user_id).await {
+        Ok(user) => Ok(user),
+        Err(UserStoreError::UserNotFound) => Err(AuthAPIError::UserNotFound),
+        Err(e) => Err(AuthAPIError::UnexpectedError(e.into())),
+    }
+}
+
+async fn set_status(
+    state: &AppState,
+    user_id: &Uuid,
+    status: AccountStatus,
+    reason: Option<String>,
+    suspended_until: Option<i64>,
+) -> Result<(), AuthAPIError> {
+    let result = state.user_store.update_status(user_id, status, reason, suspended_until).await;
+
+    state.user_status_cache.invalidate(user_id).await;
+
+    match result {
+        Ok(()) => Ok(()),
+        Err(UserStoreError::UserNotFound) => Err(AuthAPIError::UserNotFound),
+        Err(e) => Err(AuthAPIError::UnexpectedError(e.into())),
+    }
+}
+
+/// Rejects every auth token of the user and revokes all of their refresh tokens and sessions.
+async fn end_all_sessions(state: &AppState, user_id: &Uuid) -> Result<(), AuthAPIError> {
+    if let Err(e) = bump_token_version(state, user_id).await {
+        return Err(AuthAPIError::UnexpectedError(e));
+    }
+
+    if let Err(e) = state.refresh_token_store.revoke_user(user_id, None).await {
+        return Err(AuthAPIError::UnexpectedError(e.into()));
+    }
+
+    if let Err(e) = state.session_store.remove_sessions(user_id, None).await {
+        return Err(AuthAPIError::UnexpectedError(e.into()));
+    }
+
+    Ok(())
+}
+
+fn parse_email(email: String) -> Result<Email, AuthAPIError> {
+    Email::parse(&SecretBox::new(Box::new(email))).map_err(AuthAPIError::UnexpectedError)
+}
+
+fn action_response(message: &str) -> (StatusCode, Json<AdminUserActionResponse>) {
+    (StatusCode::OK, Json(AdminUserActionResponse { message: message.to_string() }))
+}
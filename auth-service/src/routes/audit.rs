@@ -0,0 +1,104 @@
+use {
+    crate::{
+        app_state::AppState,
+        domain::{
+            audit::{AuditEvent, AuditEventFilter, AuditEventType},
+            error::AuthAPIError,
+            role::AuditRead,
+        },
+        utils::{
+            auth::RequirePermission,
+            constants::{DEFAULT_PAGE_SIZE, MAX_PAGE_SIZE},
+        },
+    },
+    axum::{
+        Json,
+        extract::{Query, State},
+        http::StatusCode,
+        response::IntoResponse,
+    },
+    serde::{Deserialize, Serialize},
+    std::net::IpAddr,
+    tracing::instrument,
+    uuid::Uuid,
+};
+
+#[derive(Deserialize)]
+pub struct AuditEventsQuery {
+    #[serde(rename = "userId")]
+    pub user_id: Option<Uuid>,
+    #[serde(rename = "eventType")]
+    pub event_type: Option<AuditEventType>,
+    /// Starts at 1.
+    pub page: Option<u64>,
+    #[serde(rename = "perPage")]
+    pub per_page: Option<u64>,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Serialize)]
+pub struct AuditEventResponse {
+    #[serde(rename = "eventType")]
+    pub event_type: AuditEventType,
+    #[serde(rename = "occurredAt")]
+    pub occurred_at: i64,
+    #[serde(rename = "userId")]
+    pub user_id: Option<Uuid>,
+    #[serde(rename = "actorId")]
+    pub actor_id: Option<Uuid>,
+    pub ip: IpAddr,
+    #[serde(rename = "userAgent")]
+    pub user_agent: Option<String>,
+    #[serde(rename = "requestId")]
+    pub request_id: Uuid,
+    pub details: Option<String>,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Serialize)]
+pub struct AuditEventsResponse {
+    pub events: Vec<AuditEventResponse>,
+    pub total: u64,
+    pub page: u64,
+    #[serde(rename = "perPage")]
+    pub per_page: u64,
+}
+
+impl From<AuditEvent> for AuditEventResponse {
+    fn from(event: AuditEvent) -> Self {
+        Self {
+            event_type: event.event_type,
+            occurred_at: event.occurred_at,
+            user_id: event.user_id,
+            actor_id: event.actor_id,
+            ip: event.ip,
+            user_agent: event.user_agent,
+            request_id: event.request_id,
+            details: event.details,
+        }
+    }
+}
+
+#[instrument(name = "List audit events", skip_all)]
+pub async fn list_audit_events(
+    state: State<AppState>,
+    _: RequirePermission<AuditRead>,
+    Query(query): Query<AuditEventsQuery>,
+) -> Result<impl IntoResponse, AuthAPIError> {
+    let page = query.page.unwrap_or(1).max(1);
+    let per_page = query.per_page.unwrap_or(DEFAULT_PAGE_SIZE).clamp(1, MAX_PAGE_SIZE);
+    let filter = AuditEventFilter { user_id: query.user_id, event_type: query.event_type };
+    let result = state
+        .audit_log
+        .query(&filter, (page - 1).saturating_mul(per_page), per_page)
+        .await
+        .map_err(|e| AuthAPIError::UnexpectedError(e.into()))?;
+
+    Ok((
+        StatusCode::OK,
+        Json(AuditEventsResponse {
+            events: result.events.into_iter().map(AuditEventResponse::from).collect(),
+            total: result.total,
+            page,
+            per_page,
+        }),
+    ))
+}
@@ -0,0 +1,158 @@
+use {
+    crate::{
+        app_state::AppState,
+        domain::{
+            data_stores::{EmailChangeStoreError, EmailChangeToken, PendingEmailChange, UserStoreError},
+            email::Email,
+            error::AuthAPIError,
+        },
+        utils::{
+            auth::{AuthenticatedUser, bump_token_version},
+            constants::CHANGE_EMAIL_URL,
+        },
+    },
+    axum::{Json, extract::State, http::StatusCode, response::IntoResponse},
+    secrecy::{ExposeSecret, SecretBox},
+    serde::{Deserialize, Serialize},
+    tracing::instrument,
+    uuid::Uuid,
+};
+
+#[derive(Deserialize)]
+pub struct ChangeEmailRequest {
+    #[serde(rename = "newEmail")]
+    pub new_email: SecretBox<String>,
+    pub password: SecretBox<String>,
+}
+
+#[derive(Deserialize)]
+pub struct EmailChangeTokenRequest {
+    #[serde(rename = "userId")]
+    pub user_id: String,
+    pub token: SecretBox<String>,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Serialize)]
+pub struct ChangeEmailResponse {
+    pub message: String,
+}
+
+#[instrument(name = "Change email", skip_all)]
+pub async fn change_email(
+    state: State<AppState>,
+    AuthenticatedUser { user_id, email, .. }: AuthenticatedUser,
+    Json(request): Json<ChangeEmailRequest>,
+) -> Result<impl IntoResponse, AuthAPIError> {
+    let Ok(new_email) = Email::parse(&request.new_email)
+    else {
+        return Err(AuthAPIError::InvalidCredentials);
+    };
+    let user = state.user_store.get_user_by_id(&user_id).await.map_err(|e| AuthAPIError::UnexpectedError(e.into()))?;
+
+    if user.verify_password_hash(&request.password).await.is_err() {
+        return Err(AuthAPIError::IncorrectCredentials);
+    }
+
+    if state.user_store.get_user(&new_email).await.is_ok() {
+        return Err(AuthAPIError::UserAlreadyExists);
+    }
+
+    let change = PendingEmailChange {
+        new_email: new_email.clone(),
+        confirm_token: EmailChangeToken::default(),
+        cancel_token: EmailChangeToken::default(),
+    };
+    let confirm_link = change_link("confirm", &user_id, &change.confirm_token);
+    let cancel_link = change_link("cancel", &user_id, &change.cancel_token);
+
+    state.email_change_store.add_change(user_id, change).await.map_err(|e| AuthAPIError::UnexpectedError(e.into()))?;
+
+    state
+        .email_client
+        .send_email(&new_email, "Confirm your new email address", &confirm_link)
+        .await
+        .map_err(AuthAPIError::UnexpectedError)?;
+
+    state
+        .email_client
+        .send_email(
+            &email,
+            "Your email address is being changed",
+            &format!(
+                "A change of your login email to {} was requested. If this wasn't you, cancel it: {cancel_link}",
+                new_email.as_ref().expose_secret()
+            ),
+        )
+        .await
+        .map_err(AuthAPIError::UnexpectedError)?;
+
+    Ok((StatusCode::OK, Json(ChangeEmailResponse { message: "Confirmation email sent".to_string() })))
+}
+
+#[instrument(name = "Confirm email change", skip_all)]
+pub async fn confirm_email_change(
+    state: State<AppState>,
+    Json(request): Json<EmailChangeTokenRequest>,
+) -> Result<impl IntoResponse, AuthAPIError> {
+    let (user_id, change) = take_change(&state, &request, |change| &change.confirm_token).await?;
+
+    state.user_store.update_email(&user_id, &change.new_email).await.map_err(|e| match e {
+        UserStoreError::UserAlreadyExists => AuthAPIError::UserAlreadyExists,
+        e => AuthAPIError::UnexpectedError(e.into()),
+    })?;
+
+    // every session was issued to the old address, so the user has to log in again with the new one
+    if let Err(e) = bump_token_version(&state, &user_id).await {
+        return Err(AuthAPIError::UnexpectedError(e));
+    }
+
+    if let Err(e) = state.refresh_token_store.revoke_user(&user_id, None).await {
+        return Err(AuthAPIError::UnexpectedError(e.into()));
+    }
+
+    if let Err(e) = state.session_store.remove_sessions(&user_id, None).await {
+        return Err(AuthAPIError::UnexpectedError(e.into()));
+    }
+
+    Ok((StatusCode::OK, Json(ChangeEmailResponse { message: "Email changed successfully!".to_string() })))
+}
+
+#[instrument(name = "Cancel email change", skip_all)]
+pub async fn cancel_email_change(
+    state: State<AppState>,
+    Json(request): Json<EmailChangeTokenRequest>,
+) -> Result<impl IntoResponse, AuthAPIError> {
+    take_change(&state, &request, |change| &change.cancel_token).await?;
+
+    Ok((StatusCode::OK, Json(ChangeEmailResponse { message: "Email change cancelled".to_string() })))
+}
+
+/// Removes the pending change of the user if `request` carries the token picked by `expected`.
+async fn take_change(
+    state: &AppState,
+    request: &EmailChangeTokenRequest,
+    expected: fn(&PendingEmailChange) -> &EmailChangeToken,
+) -> Result<(Uuid, PendingEmailChange), AuthAPIError> {
+    let (Ok(user_id), Ok(token)) =
+        (Uuid::parse_str(&request.user_id), EmailChangeToken::parse(request.token.expose_secret()))
+    else {
+        return Err(AuthAPIError::InvalidToken);
+    };
+    let change = match state.email_change_store.get_change(&user_id).await {
+        Ok(change) => change,
+        Err(EmailChangeStoreError::ChangeNotFound) => return Err(AuthAPIError::InvalidToken),
+        Err(e) => return Err(AuthAPIError::UnexpectedError(e.into())),
+    };
+
+    if *expected(&change) != token {
+        return Err(AuthAPIError::InvalidToken);
+    }
+
+    state.email_change_store.remove_change(&user_id).await.map_err(|e| AuthAPIError::UnexpectedError(e.into()))?;
+
+    Ok((user_id, change))
+}
+
+fn change_link(action: &str, user_id: &Uuid, token: &EmailChangeToken) -> String {
+    format!("{}/{action}?userId={user_id}&token={}", CHANGE_EMAIL_URL.as_str(), token.as_ref().expose_secret())
+}
@@ -0,0 +1,74 @@
+use {
+    crate::{
+        app_state::AppState,
+        domain::{data_stores::RefreshToken, error::AuthAPIError, password::Password},
+        utils::{
+            auth::{AuthenticatedUser, bump_token_version, continue_session},
+            constants::REFRESH_COOKIE_NAME,
+        },
+    },
+    axum::{Json, extract::State, http::StatusCode, response::IntoResponse},
+    axum_extra::extract::CookieJar,
+    secrecy::SecretBox,
+    serde::{Deserialize, Serialize},
+    tracing::instrument,
+};
+
+#[derive(Deserialize)]
+pub struct ChangePasswordRequest {
+    #[serde(rename = "currentPassword")]
+    pub current_password: SecretBox<String>,
+    #[serde(rename = "newPassword")]
+    pub new_password: SecretBox<String>,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Serialize)]
+pub struct ChangePasswordResponse {
+    pub message: String,
+}
+
+#[instrument(name = "Change password", skip_all)]
+pub async fn change_password(
+    state: State<AppState>,
+    jar: CookieJar,
+    AuthenticatedUser { user_id, session_id, email, .. }: AuthenticatedUser,
+    Json(request): Json<ChangePasswordRequest>,
+) -> Result<(CookieJar, impl IntoResponse), AuthAPIError> {
+    let Ok(new_password) = Password::parse(&request.new_password)
+    else {
+        return Err(AuthAPIError::InvalidCredentials);
+    };
+    let user = state.user_store.get_user(&email).await.map_err(|e| AuthAPIError::UnexpectedError(e.into()))?;
+
+    if user.verify_password_hash(&request.current_password).await.is_err() {
+        return Err(AuthAPIError::IncorrectCredentials);
+    }
+
+    state
+        .user_store
+        .update_password(&email, new_password)
+        .await
+        .map_err(|e| AuthAPIError::UnexpectedError(e.into()))?;
+
+    if let Err(e) = bump_token_version(&state, &user_id).await {
+        return Err(AuthAPIError::UnexpectedError(e));
+    }
+
+    let refresh_token = jar.get(REFRESH_COOKIE_NAME).and_then(|cookie| RefreshToken::parse(cookie.value()).ok());
+
+    if let Err(e) = state.refresh_token_store.revoke_user(&user_id, refresh_token.as_ref()).await {
+        return Err(AuthAPIError::UnexpectedError(e.into()));
+    }
+
+    if let Err(e) = state.session_store.remove_sessions(&user_id, Some(&session_id)).await {
+        return Err(AuthAPIError::UnexpectedError(e.into()));
+    }
+
+    // the bump also invalidated the current token, so the current session gets a fresh one
+    let auth_cookie = continue_session(&state, &user_id, &session_id).await.map_err(AuthAPIError::UnexpectedError)?;
+
+    Ok((
+        jar.add(auth_cookie),
+        (StatusCode::OK, Json(ChangePasswordResponse { message: "Password changed successfully!".to_string() })),
+    ))
+}
@@ -0,0 +1,36 @@
+use {
+    crate::{
+        app_state::AppState,
+        domain::error::AuthAPIError,
+        utils::{auth::AdminCredential, constants::JWT_KEY_RING, keys::rotate_key_ring},
+    },
+    axum::{Json, extract::State, http::StatusCode, response::IntoResponse},
+    chrono::Utc,
+    serde::{Deserialize, Serialize},
+    tracing::{info, instrument},
+};
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct RotateSigningKeyResponse {
+    pub kid: String,
+}
+
+/// Publishes the public keys that verify issued JWTs so other services can validate them offline.
+#[instrument(name = "JWKS", skip_all)]
+pub async fn jwks() -> impl IntoResponse {
+    (StatusCode::OK, Json(JWT_KEY_RING.jwks(Utc::now().timestamp())))
+}
+
+#[instrument(name = "Rotate signing key", skip_all)]
+pub async fn rotate_signing_key(
+    State(state): State<AppState>,
+    _: AdminCredential,
+) -> Result<impl IntoResponse, AuthAPIError> {
+    let kid = rotate_key_ring(&JWT_KEY_RING, state.signing_key_store.as_ref(), Utc::now().timestamp())
+        .await
+        .map_err(|e| AuthAPIError::UnexpectedError(e.into()))?;
+
+    info!(kid, "Rotated JWT signing key");
+
+    Ok((StatusCode::OK, Json(RotateSigningKeyResponse { kid })))
+}
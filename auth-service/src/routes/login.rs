@@ -2,12 +2,18 @@ use {
     crate::{
         app_state::AppState,
         domain::{
-            data_stores::{LoginAttemptId, TwoFactorCode},
+            audit::{AuditEvent, AuditEventType},
+            data_stores::{LoginAttemptId, ThrottleKey, TwoFactorCode},
             email::Email,
             error::AuthAPIError,
             password::Password,
+            user::TwoFactorMethod,
+        },
+        utils::{
+            audit::record_event,
+            auth::{ClientInfo, check_account_status, start_session},
+            throttle::{check_login_throttle, record_login_failure},
         },
-        utils::auth::generate_auth_cookie,
     },
     axum::{
         Json,
@@ -19,6 +25,7 @@ use {
     secrecy::{ExposeSecret, SecretBox},
     serde::{Deserialize, Serialize},
     tracing::instrument,
+    uuid::Uuid,
 };
 
 #[derive(Deserialize)]
@@ -44,11 +51,14 @@ pub struct TwoFactorAuthResponse {
     pub message: String,
     #[serde(rename = "loginAttemptId")]
     pub login_attempt_id: LoginAttemptId,
+    #[serde(rename = "twoFactorMethod")]
+    pub two_factor_method: TwoFactorMethod,
 }
 
 #[instrument(name = "Signup", skip_all)]
 pub async fn login(
     state: State<AppState>,
+    client: ClientInfo,
     jar: CookieJar,
     payload: Result<Json<LoginRequest>, JsonRejection>,
 ) -> Result<(CookieJar, impl IntoResponse), AuthAPIError> {
@@ -64,18 +74,39 @@ pub async fn login(
     else {
         return Err(AuthAPIError::InvalidCredentials);
     };
+
+    if let Err(e) = check_login_throttle(&state, &email, client.ip).await {
+        return Err(login_failed(&state, &client, None, e).await);
+    }
+
     let (user, password) = match (state.user_store.get_user(&email).await, Password::parse(&request.password)) {
         (Ok(user), Ok(password)) => (user, password),
-        _ => return Err(AuthAPIError::InvalidCredentials),
+        _ => return Err(login_failed(&state, &client, None, AuthAPIError::InvalidCredentials).await),
     };
 
     if user.verify_password_hash(password.as_ref()).await.is_err() {
-        return Err(AuthAPIError::IncorrectCredentials);
+        record_login_failure(&state, &email, client.ip).await?;
+
+        return Err(login_failed(&state, &client, Some(user.user_id), AuthAPIError::IncorrectCredentials).await);
+    }
+
+    state
+        .login_throttle_store
+        .reset(&ThrottleKey::Account(email.clone()))
+        .await
+        .map_err(|e| AuthAPIError::UnexpectedError(e.into()))?;
+
+    if let Err(e) = check_account_status(user.current_status()) {
+        return Err(login_failed(&state, &client, Some(user.user_id), e).await);
     }
 
-    let (status, response) = (match user.requires_2fa {
-        true => handle_2fa(&email, &state).await,
-        false => handle_no_2fa().await,
+    if user.password_reset_required {
+        return Err(login_failed(&state, &client, Some(user.user_id), AuthAPIError::PasswordResetRequired).await);
+    }
+
+    let (status, response) = (match user.two_factor_method {
+        TwoFactorMethod::None => handle_no_2fa().await,
+        method => handle_2fa(&email, user.user_id, &state, method, &client).await,
     })
     .map_err(|e| AuthAPIError::UnexpectedError(e.into()))?;
 
@@ -83,33 +114,66 @@ pub async fn login(
         return Ok((jar, (status, Json(response))));
     }
 
-    let auth_cookie = generate_auth_cookie(&email).map_err(|e| AuthAPIError::UnexpectedError(e.into()))?;
+    let (auth_cookie, refresh_cookie) =
+        start_session(&state, &user.user_id, client).await.map_err(AuthAPIError::UnexpectedError)?;
+
+    Ok((jar.add(auth_cookie).add(refresh_cookie), (status, Json(response))))
+}
+
+/// Records the failed login attempt and hands back the error it failed with.
+async fn login_failed(
+    state: &AppState,
+    client: &ClientInfo,
+    user_id: Option<Uuid>,
+    error: AuthAPIError,
+) -> AuthAPIError {
+    let event = AuditEvent { user_id, ..client.event(AuditEventType::LoginFailed) };
 
-    Ok((jar.add(auth_cookie), (status, Json(response))))
+    record_event(state, event.details(error.to_string())).await;
+
+    error
 }
 
 #[instrument(name = "Handle 2FA", skip_all)]
-async fn handle_2fa(email: &Email, state: &AppState) -> Result<(StatusCode, LoginResponse), AuthAPIError> {
+pub(crate) async fn handle_2fa(
+    email: &Email,
+    user_id: Uuid,
+    state: &AppState,
+    method: TwoFactorMethod,
+    client: &ClientInfo,
+) -> Result<(StatusCode, LoginResponse), AuthAPIError> {
     let attempt_id = LoginAttemptId::default();
     let code = TwoFactorCode::default();
 
     state
         .two_factor_store
-        .add_code(email.clone(), attempt_id.clone(), code.clone())
+        .add_code(user_id, attempt_id.clone(), code.clone())
         .await
         .map_err(|e| AuthAPIError::UnexpectedError(e.into()))?;
 
     state
-        .email_client
-        .send_email(email, "Your 2FA", code.as_ref().expose_secret())
+        .login_throttle_store
+        .reset(&ThrottleKey::TwoFactor(user_id))
         .await
-        .map_err(AuthAPIError::UnexpectedError)?;
+        .map_err(|e| AuthAPIError::UnexpectedError(e.into()))?;
+
+    // TOTP and passkey users answer with their authenticator, so the stored code is never sent
+    if method == TwoFactorMethod::Email {
+        state
+            .email_client
+            .send_email(email, "Your 2FA", code.as_ref().expose_secret())
+            .await
+            .map_err(AuthAPIError::UnexpectedError)?;
+
+        record_event(state, client.event(AuditEventType::TwoFactorSent).user(user_id)).await;
+    }
 
     Ok((
         StatusCode::PARTIAL_CONTENT,
         LoginResponse::TwoFactorAuth(TwoFactorAuthResponse {
             message: "2FA required".to_string(),
             login_attempt_id: attempt_id,
+            two_factor_method: method,
         }),
     ))
 }
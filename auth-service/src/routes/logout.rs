@@ -1,17 +1,30 @@
 use {
     crate::{
         app_state::AppState,
-        domain::error::AuthAPIError,
-        utils::{auth::validate_token, constants::JWT_COOKIE_NAME},
+        domain::{
+            audit::AuditEventType,
+            data_stores::{RefreshToken, SessionStoreError},
+            error::AuthAPIError,
+        },
+        utils::{
+            audit::record_event,
+            auth::{AuthenticatedUser, ClientInfo, bump_token_version, validate_token},
+            constants::{JWT_COOKIE_NAME, REFRESH_COOKIE_NAME},
+        },
     },
     axum::{extract::State, http::StatusCode, response::IntoResponse},
     axum_extra::extract::{CookieJar, cookie::Cookie},
     secrecy::SecretBox,
     tracing::instrument,
+    uuid::Uuid,
 };
 
 #[instrument(name = "Logout", skip_all)]
-pub async fn logout(state: State<AppState>, jar: CookieJar) -> Result<(CookieJar, impl IntoResponse), AuthAPIError> {
+pub async fn logout(
+    state: State<AppState>,
+    client: ClientInfo,
+    jar: CookieJar,
+) -> Result<(CookieJar, impl IntoResponse), AuthAPIError> {
     let Some(cookie) = jar.get(JWT_COOKIE_NAME)
     else {
         return Err(AuthAPIError::MissingToken);
@@ -22,16 +35,65 @@ pub async fn logout(state: State<AppState>, jar: CookieJar) -> Result<(CookieJar
         return Err(AuthAPIError::MissingToken);
     }
 
-    if validate_token(Some(state.banned_token_store.clone()), &SecretBox::new(Box::new(token.to_owned())))
-        .await
-        .is_err()
-    {
+    let Ok(claims) = validate_token(Some(&state), &SecretBox::new(Box::new(token.to_owned()))).await
+    else {
+        return Err(AuthAPIError::InvalidToken);
+    };
+    let (Ok(user_id), Ok(session_id)) = (Uuid::parse_str(&claims.sub), Uuid::parse_str(&claims.sid))
+    else {
         return Err(AuthAPIError::InvalidToken);
+    };
+
+    if let Err(e) = state.banned_token_store.register(&claims.jti, claims.exp as i64).await {
+        return Err(AuthAPIError::UnexpectedError(e.into()));
+    }
+
+    match state.session_store.remove_session(&user_id, &session_id).await {
+        Ok(()) | Err(SessionStoreError::SessionNotFound) => {}
+        Err(e) => return Err(AuthAPIError::UnexpectedError(e.into())),
+    }
+
+    record_event(&state, client.event(AuditEventType::Logout).user(user_id)).await;
+
+    let jar = jar.remove(Cookie::from((JWT_COOKIE_NAME, token)));
+    let Some(cookie) = jar.get(REFRESH_COOKIE_NAME)
+    else {
+        return Ok((jar, StatusCode::OK));
+    };
+    let refresh_token = cookie.value().to_owned();
+
+    if let Ok(token) = RefreshToken::parse(&refresh_token) {
+        if let Err(e) = state.refresh_token_store.revoke_token(&token).await {
+            return Err(AuthAPIError::UnexpectedError(e.into()));
+        }
+    }
+
+    Ok((jar.remove(Cookie::from((REFRESH_COOKIE_NAME, refresh_token))), StatusCode::OK))
+}
+
+/// Logs the user out of every session by invalidating all of their outstanding tokens.
+#[instrument(name = "Logout all", skip_all)]
+pub async fn logout_all(
+    state: State<AppState>,
+    client: ClientInfo,
+    jar: CookieJar,
+    AuthenticatedUser { user_id, .. }: AuthenticatedUser,
+) -> Result<(CookieJar, impl IntoResponse), AuthAPIError> {
+    if let Err(e) = bump_token_version(&state, &user_id).await {
+        return Err(AuthAPIError::UnexpectedError(e));
+    }
+
+    if let Err(e) = state.refresh_token_store.revoke_user(&user_id, None).await {
+        return Err(AuthAPIError::UnexpectedError(e.into()));
     }
 
-    if let Err(e) = state.banned_token_store.register(vec![&SecretBox::new(Box::new(token.clone()))]).await {
+    if let Err(e) = state.session_store.remove_sessions(&user_id, None).await {
         return Err(AuthAPIError::UnexpectedError(e.into()));
     }
 
-    Ok((jar.remove(Cookie::from((JWT_COOKIE_NAME, token))), StatusCode::OK))
+    record_event(&state, client.event(AuditEventType::Logout).user(user_id).details("All sessions")).await;
+
+    let jar = jar.remove(Cookie::from(JWT_COOKIE_NAME)).remove(Cookie::from(REFRESH_COOKIE_NAME));
+
+    Ok((jar, StatusCode::OK))
 }
@@ -0,0 +1,140 @@
+use {
+    crate::{
+        app_state::AppState,
+        domain::{
+            data_stores::{MagicLinkNonce, MagicLinkStoreError, MagicLinkToken},
+            email::Email,
+            error::AuthAPIError,
+            user::TwoFactorMethod,
+        },
+        routes::{LoginResponse, RegularAuthResponse, handle_2fa},
+        utils::{
+            auth::{
+                ClientInfo, check_account_status, create_magic_link_nonce_cookie, generate_magic_link_token,
+                start_session, validate_magic_link_token,
+            },
+            constants::{MAGIC_LINK_NONCE_COOKIE_NAME, MAGIC_LINK_URL},
+        },
+    },
+    axum::{Json, extract::State, http::StatusCode, response::IntoResponse},
+    axum_extra::extract::{CookieJar, cookie::Cookie},
+    secrecy::{ExposeSecret, SecretBox},
+    serde::{Deserialize, Serialize},
+    tracing::instrument,
+};
+
+#[derive(Deserialize)]
+pub struct MagicLinkRequest {
+    pub email: SecretBox<String>,
+}
+
+#[derive(Deserialize)]
+pub struct ConsumeMagicLinkRequest {
+    pub token: SecretBox<String>,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Serialize)]
+pub struct MagicLinkResponse {
+    pub message: String,
+}
+
+#[instrument(name = "Request magic link", skip_all)]
+pub async fn request_magic_link(
+    state: State<AppState>,
+    jar: CookieJar,
+    Json(request): Json<MagicLinkRequest>,
+) -> Result<impl IntoResponse, AuthAPIError> {
+    let Ok(email) = Email::parse(&request.email)
+    else {
+        return Err(AuthAPIError::InvalidCredentials);
+    };
+    let nonce = MagicLinkNonce::default();
+
+    // respond identically whether or not the account exists so the endpoint can't be used to probe for users
+    if let Ok(user) = state.user_store.get_user(&email).await {
+        if user.email_verified {
+            let token = generate_magic_link_token(&email, &nonce).map_err(AuthAPIError::UnexpectedError)?;
+            let link = format!("{}?token={}", MAGIC_LINK_URL.as_str(), token.as_ref().expose_secret());
+
+            state
+                .magic_link_store
+                .add_token(email.clone(), token)
+                .await
+                .map_err(|e| AuthAPIError::UnexpectedError(e.into()))?;
+
+            state
+                .email_client
+                .send_email(&email, "Your login link", &link)
+                .await
+                .map_err(AuthAPIError::UnexpectedError)?;
+        }
+    }
+
+    Ok((
+        jar.add(create_magic_link_nonce_cookie(&nonce)),
+        (
+            StatusCode::OK,
+            Json(MagicLinkResponse { message: "If the account exists, a login link has been sent".to_string() }),
+        ),
+    ))
+}
+
+#[instrument(name = "Consume magic link", skip_all)]
+pub async fn consume_magic_link(
+    state: State<AppState>,
+    client: ClientInfo,
+    jar: CookieJar,
+    Json(request): Json<ConsumeMagicLinkRequest>,
+) -> Result<impl IntoResponse, AuthAPIError> {
+    // the nonce cookie binds the link to the browser that requested it
+    let Some(Ok(nonce)) = jar.get(MAGIC_LINK_NONCE_COOKIE_NAME).map(|cookie| MagicLinkNonce::parse(cookie.value()))
+    else {
+        return Err(AuthAPIError::InvalidToken);
+    };
+    let Ok(token) = MagicLinkToken::parse(request.token.expose_secret())
+    else {
+        return Err(AuthAPIError::InvalidToken);
+    };
+    let Ok(claims) = validate_magic_link_token(&token, &nonce)
+    else {
+        return Err(AuthAPIError::InvalidToken);
+    };
+    let Ok(email) = Email::parse(&SecretBox::new(Box::new(claims.sub)))
+    else {
+        return Err(AuthAPIError::InvalidToken);
+    };
+    let stored_token = match state.magic_link_store.take_token(&email).await {
+        Ok(stored_token) => stored_token,
+        Err(MagicLinkStoreError::TokenNotFound) => return Err(AuthAPIError::InvalidToken),
+        Err(e) => return Err(AuthAPIError::UnexpectedError(e.into())),
+    };
+
+    if token != stored_token {
+        return Err(AuthAPIError::InvalidToken);
+    }
+
+    let jar = jar.remove(Cookie::build(MAGIC_LINK_NONCE_COOKIE_NAME).path("/"));
+    let user = state.user_store.get_user(&email).await.map_err(|e| AuthAPIError::UnexpectedError(e.into()))?;
+
+    check_account_status(user.current_status())?;
+
+    // the link stands in for the password only, so 2FA still applies
+    if user.two_factor_method != TwoFactorMethod::None {
+        let (status, response) = handle_2fa(&email, user.user_id, &state, user.two_factor_method, &client).await?;
+
+        return Ok((jar, (status, Json(response))));
+    }
+
+    let (auth_cookie, refresh_cookie) =
+        start_session(&state, &user.user_id, client).await.map_err(AuthAPIError::UnexpectedError)?;
+
+    Ok((
+        jar.add(auth_cookie).add(refresh_cookie),
+        (
+            StatusCode::OK,
+            Json(LoginResponse::RegularAuth(RegularAuthResponse {
+                message: "User logged in successfully!".to_string(),
+            })),
+        ),
+    ))
+}
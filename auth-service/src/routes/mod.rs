@@ -1,12 +1,44 @@
+mod account;
+mod admin_users;
+mod audit;
+mod change_email;
+mod change_password;
+mod jwks;
 mod login;
 mod logout;
+mod magic_link;
+mod passkey;
+mod password_reset;
+mod recovery_codes;
+mod refresh;
+mod roles;
+mod sessions;
 mod signup;
+mod totp;
+mod two_factor_method;
 mod verify_2fa;
+mod verify_email;
 mod verify_token;
 
 // re-export items from sub-modules
+pub use account::*;
+pub use admin_users::*;
+pub use audit::*;
+pub use change_email::*;
+pub use change_password::*;
+pub use jwks::*;
 pub use login::*;
 pub use logout::*;
+pub use magic_link::*;
+pub use passkey::*;
+pub use password_reset::*;
+pub use recovery_codes::*;
+pub use refresh::*;
+pub use roles::*;
+pub use sessions::*;
 pub use signup::*;
+pub use totp::*;
+pub use two_factor_method::*;
 pub use verify_2fa::*;
+pub use verify_email::*;
 pub use verify_token::*;
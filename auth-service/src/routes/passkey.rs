@@ -0,0 +1,168 @@
+use {
+    crate::{
+        app_state::AppState,
+        domain::{
+            data_stores::{LoginAttemptId, PasskeyCeremonyStoreError, PasskeyStoreError},
+            email::Email,
+            error::AuthAPIError,
+        },
+        utils::auth::{AuthenticatedUser, ClientInfo, check_account_status, start_session},
+    },
+    axum::{Json, extract::State, http::StatusCode, response::IntoResponse},
+    axum_extra::extract::CookieJar,
+    secrecy::ExposeSecret,
+    serde::{Deserialize, Serialize},
+    tracing::instrument,
+    webauthn_rs::prelude::{PublicKeyCredential, RegisterPublicKeyCredential},
+};
+
+#[derive(Deserialize)]
+pub struct StartPasskeyLoginRequest {
+    pub email: Email,
+}
+
+#[derive(Deserialize)]
+pub struct FinishPasskeyLoginRequest {
+    pub email: Email,
+    pub credential: PublicKeyCredential,
+    /// Present when the passkey answers the 2FA challenge of a password login.
+    #[serde(rename = "loginAttemptId")]
+    pub login_attempt_id: Option<LoginAttemptId>,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Serialize)]
+pub struct PasskeyResponse {
+    pub message: String,
+}
+
+#[instrument(name = "Start passkey registration", skip_all)]
+pub async fn start_passkey_registration(
+    state: State<AppState>,
+    AuthenticatedUser { user_id, email, .. }: AuthenticatedUser,
+) -> Result<impl IntoResponse, AuthAPIError> {
+    let passkeys =
+        state.passkey_store.get_passkeys(&user_id).await.map_err(|e| AuthAPIError::UnexpectedError(e.into()))?;
+    let exclude_credentials = passkeys.iter().map(|passkey| passkey.cred_id().clone()).collect::<Vec<_>>();
+    let name = email.as_ref().expose_secret();
+    let (challenge, registration) = state
+        .webauthn
+        .start_passkey_registration(user_id, name, name, Some(exclude_credentials))
+        .map_err(|e| AuthAPIError::UnexpectedError(e.into()))?;
+
+    state
+        .passkey_ceremony_store
+        .add_registration(&email, registration)
+        .await
+        .map_err(|e| AuthAPIError::UnexpectedError(e.into()))?;
+
+    Ok((StatusCode::OK, Json(challenge)))
+}
+
+#[instrument(name = "Finish passkey registration", skip_all)]
+pub async fn finish_passkey_registration(
+    state: State<AppState>,
+    AuthenticatedUser { user_id, email, .. }: AuthenticatedUser,
+    Json(credential): Json<RegisterPublicKeyCredential>,
+) -> Result<impl IntoResponse, AuthAPIError> {
+    let registration = match state.passkey_ceremony_store.take_registration(&email).await {
+        Ok(registration) => registration,
+        Err(PasskeyCeremonyStoreError::CeremonyNotFound) => return Err(AuthAPIError::InvalidCredentials),
+        Err(e) => return Err(AuthAPIError::UnexpectedError(e.into())),
+    };
+    let Ok(passkey) = state.webauthn.finish_passkey_registration(&credential, &registration)
+    else {
+        return Err(AuthAPIError::InvalidCredentials);
+    };
+
+    match state.passkey_store.add_passkey(&user_id, passkey).await {
+        Ok(()) => {}
+        Err(PasskeyStoreError::CredentialAlreadyExists) => return Err(AuthAPIError::InvalidCredentials),
+        Err(e) => return Err(AuthAPIError::UnexpectedError(e.into())),
+    }
+
+    Ok((StatusCode::CREATED, Json(PasskeyResponse { message: "Passkey registered successfully!".to_string() })))
+}
+
+#[instrument(name = "Start passkey login", skip_all)]
+pub async fn start_passkey_login(
+    state: State<AppState>,
+    Json(request): Json<StartPasskeyLoginRequest>,
+) -> Result<impl IntoResponse, AuthAPIError> {
+    let Ok(user) = state.user_store.get_user(&request.email).await
+    else {
+        return Err(AuthAPIError::InvalidCredentials);
+    };
+    let passkeys =
+        state.passkey_store.get_passkeys(&user.user_id).await.map_err(|e| AuthAPIError::UnexpectedError(e.into()))?;
+
+    if passkeys.is_empty() {
+        return Err(AuthAPIError::InvalidCredentials);
+    }
+
+    let (challenge, authentication) =
+        state.webauthn.start_passkey_authentication(&passkeys).map_err(|e| AuthAPIError::UnexpectedError(e.into()))?;
+
+    state
+        .passkey_ceremony_store
+        .add_authentication(&request.email, authentication)
+        .await
+        .map_err(|e| AuthAPIError::UnexpectedError(e.into()))?;
+
+    Ok((StatusCode::OK, Json(challenge)))
+}
+
+#[instrument(name = "Finish passkey login", skip_all)]
+pub async fn finish_passkey_login(
+    state: State<AppState>,
+    client: ClientInfo,
+    jar: CookieJar,
+    Json(request): Json<FinishPasskeyLoginRequest>,
+) -> Result<impl IntoResponse, AuthAPIError> {
+    let email = request.email;
+    let authentication = match state.passkey_ceremony_store.take_authentication(&email).await {
+        Ok(authentication) => authentication,
+        Err(PasskeyCeremonyStoreError::CeremonyNotFound) => return Err(AuthAPIError::IncorrectCredentials),
+        Err(e) => return Err(AuthAPIError::UnexpectedError(e.into())),
+    };
+    let Ok(result) = state.webauthn.finish_passkey_authentication(&request.credential, &authentication)
+    else {
+        return Err(AuthAPIError::IncorrectCredentials);
+    };
+    let user = state.user_store.get_user(&email).await.map_err(|e| AuthAPIError::UnexpectedError(e.into()))?;
+
+    check_account_status(user.current_status())?;
+
+    let mut passkeys =
+        state.passkey_store.get_passkeys(&user.user_id).await.map_err(|e| AuthAPIError::UnexpectedError(e.into()))?;
+
+    for passkey in passkeys.iter_mut() {
+        if passkey.update_credential(&result) == Some(true) {
+            state
+                .passkey_store
+                .update_passkey(&user.user_id, passkey)
+                .await
+                .map_err(|e| AuthAPIError::UnexpectedError(e.into()))?;
+        }
+    }
+
+    if let Some(login_attempt_id) = request.login_attempt_id {
+        let Ok((attempt_id, _)) = state.two_factor_store.get_code(&user.user_id).await
+        else {
+            return Err(AuthAPIError::IncorrectCredentials);
+        };
+
+        if login_attempt_id != attempt_id {
+            return Err(AuthAPIError::IncorrectCredentials);
+        }
+
+        state.two_factor_store.remove_code(&user.user_id).await.map_err(|e| AuthAPIError::UnexpectedError(e.into()))?;
+    }
+
+    let (auth_cookie, refresh_cookie) =
+        start_session(&state, &user.user_id, client).await.map_err(AuthAPIError::UnexpectedError)?;
+
+    Ok((
+        jar.add(auth_cookie).add(refresh_cookie),
+        (StatusCode::OK, Json(PasskeyResponse { message: "User logged in successfully!".to_string() })),
+    ))
+}
@@ -0,0 +1,111 @@
+use {
+    crate::{
+        app_state::AppState,
+        domain::{
+            data_stores::{PasswordResetStoreError, PasswordResetToken},
+            email::Email,
+            error::AuthAPIError,
+            password::Password,
+        },
+        utils::auth::bump_token_version,
+    },
+    axum::{Json, extract::State, http::StatusCode, response::IntoResponse},
+    secrecy::{ExposeSecret, SecretBox},
+    serde::{Deserialize, Serialize},
+    tracing::instrument,
+};
+
+#[derive(Deserialize)]
+pub struct RequestPasswordResetRequest {
+    pub email: SecretBox<String>,
+}
+
+#[derive(Deserialize)]
+pub struct ConfirmPasswordResetRequest {
+    pub email: SecretBox<String>,
+    pub token: SecretBox<String>,
+    #[serde(rename = "newPassword")]
+    pub new_password: SecretBox<String>,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Serialize)]
+pub struct PasswordResetResponse {
+    pub message: String,
+}
+
+#[instrument(name = "Request password reset", skip_all)]
+pub async fn request_password_reset(
+    state: State<AppState>,
+    Json(request): Json<RequestPasswordResetRequest>,
+) -> Result<impl IntoResponse, AuthAPIError> {
+    let Ok(email) = Email::parse(&request.email)
+    else {
+        return Err(AuthAPIError::InvalidCredentials);
+    };
+
+    // respond identically whether or not the account exists so the endpoint can't be used to probe for users
+    if state.user_store.get_user(&email).await.is_ok() {
+        let token = PasswordResetToken::default();
+
+        state
+            .password_reset_store
+            .add_token(email.clone(), token.clone())
+            .await
+            .map_err(|e| AuthAPIError::UnexpectedError(e.into()))?;
+
+        state
+            .email_client
+            .send_email(&email, "Your password reset token", token.as_ref().expose_secret())
+            .await
+            .map_err(AuthAPIError::UnexpectedError)?;
+    }
+
+    Ok((
+        StatusCode::OK,
+        Json(PasswordResetResponse {
+            message: "If the account exists, a password reset token has been sent".to_string(),
+        }),
+    ))
+}
+
+#[instrument(name = "Confirm password reset", skip_all)]
+pub async fn confirm_password_reset(
+    state: State<AppState>,
+    Json(request): Json<ConfirmPasswordResetRequest>,
+) -> Result<impl IntoResponse, AuthAPIError> {
+    let (Ok(email), Ok(password)) = (Email::parse(&request.email), Password::parse(&request.new_password))
+    else {
+        return Err(AuthAPIError::InvalidCredentials);
+    };
+    let Ok(token) = PasswordResetToken::parse(request.token.expose_secret())
+    else {
+        return Err(AuthAPIError::InvalidToken);
+    };
+    let stored_token = match state.password_reset_store.take_token(&email).await {
+        Ok(stored_token) => stored_token,
+        Err(PasswordResetStoreError::TokenNotFound) => return Err(AuthAPIError::InvalidToken),
+        Err(e) => return Err(AuthAPIError::UnexpectedError(e.into())),
+    };
+
+    if token != stored_token {
+        return Err(AuthAPIError::InvalidToken);
+    }
+
+    state.user_store.update_password(&email, password).await.map_err(|e| AuthAPIError::UnexpectedError(e.into()))?;
+
+    let user = state.user_store.get_user(&email).await.map_err(|e| AuthAPIError::UnexpectedError(e.into()))?;
+
+    if let Err(e) = bump_token_version(&state, &user.user_id).await {
+        return Err(AuthAPIError::UnexpectedError(e));
+    }
+
+    if let Err(e) = state.refresh_token_store.revoke_user(&user.user_id, None).await {
+        return Err(AuthAPIError::UnexpectedError(e.into()));
+    }
+
+    if let Err(e) = state.session_store.remove_sessions(&user.user_id, None).await {
+        return Err(AuthAPIError::UnexpectedError(e.into()));
+    }
+
+    Ok((StatusCode::OK, Json(PasswordResetResponse { message: "Password reset successfully!".to_string() })))
+}
@@ -0,0 +1,50 @@
+use {
+    crate::{
+        app_state::AppState,
+        domain::{data_stores::RecoveryCode, error::AuthAPIError, user::TwoFactorMethod},
+        utils::auth::AuthenticatedUser,
+    },
+    axum::{Json, extract::State, http::StatusCode, response::IntoResponse},
+    secrecy::ExposeSecret,
+    serde::{Deserialize, Serialize},
+    tracing::instrument,
+    uuid::Uuid,
+};
+
+const RECOVERY_CODE_COUNT: usize = 10;
+
+#[derive(Debug, Deserialize, PartialEq, Serialize)]
+pub struct RecoveryCodesResponse {
+    #[serde(rename = "recoveryCodes")]
+    pub recovery_codes: Vec<String>,
+}
+
+#[instrument(name = "Regenerate recovery codes", skip_all)]
+pub async fn regenerate_recovery_codes(
+    state: State<AppState>,
+    AuthenticatedUser { user_id, .. }: AuthenticatedUser,
+) -> Result<impl IntoResponse, AuthAPIError> {
+    let user = state.user_store.get_user_by_id(&user_id).await.map_err(|e| AuthAPIError::UnexpectedError(e.into()))?;
+
+    if user.two_factor_method == TwoFactorMethod::None {
+        return Err(AuthAPIError::InvalidCredentials);
+    }
+
+    let recovery_codes = generate_recovery_codes(&user_id, &state).await?;
+
+    Ok((StatusCode::OK, Json(RecoveryCodesResponse { recovery_codes })))
+}
+
+#[instrument(name = "Generate recovery codes", skip_all)]
+pub(crate) async fn generate_recovery_codes(user_id: &Uuid, state: &AppState) -> Result<Vec<String>, AuthAPIError> {
+    let codes = (0..RECOVERY_CODE_COUNT).map(|_| RecoveryCode::default()).collect::<Vec<_>>();
+    let recovery_codes = codes.iter().map(|code| code.as_ref().expose_secret().to_owned()).collect();
+
+    state
+        .recovery_code_store
+        .replace_codes(user_id, codes)
+        .await
+        .map_err(|e| AuthAPIError::UnexpectedError(e.into()))?;
+
+    Ok(recovery_codes)
+}
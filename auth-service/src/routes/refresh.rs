@@ -0,0 +1,58 @@
+use {
+    crate::{
+        app_state::AppState,
+        domain::{
+            data_stores::{RefreshToken, RefreshTokenStoreError, UserStoreError},
+            error::AuthAPIError,
+        },
+        utils::{
+            auth::{check_account_status, continue_session, create_refresh_cookie},
+            constants::REFRESH_COOKIE_NAME,
+        },
+    },
+    axum::{
+        extract::State,
+        http::StatusCode,
+        response::{IntoResponse, Response},
+    },
+    axum_extra::extract::CookieJar,
+    tracing::instrument,
+};
+
+#[instrument(name = "Refresh", skip_all)]
+pub async fn refresh(state: State<AppState>, jar: CookieJar) -> Result<(CookieJar, Response), AuthAPIError> {
+    let Some(cookie) = jar.get(REFRESH_COOKIE_NAME)
+    else {
+        return Err(AuthAPIError::MissingToken);
+    };
+    let Ok(token) = RefreshToken::parse(cookie.value())
+    else {
+        return Err(AuthAPIError::InvalidToken);
+    };
+    let new_token = RefreshToken::default();
+    let (user_id, session_id) = match state.refresh_token_store.rotate_token(&token, new_token.clone()).await {
+        Ok(ids) => ids,
+        Err(RefreshTokenStoreError::TokenNotFound | RefreshTokenStoreError::TokenReused) => {
+            return Err(AuthAPIError::InvalidToken);
+        }
+        Err(e) => return Err(AuthAPIError::UnexpectedError(e.into())),
+    };
+
+    // the status cache may not have caught up with a suspension made on another instance yet, and this hands out a
+    // fresh auth token, so the status is read from the user store instead
+    let user = match state.user_store.get_user_by_id(&user_id).await {
+        Ok(user) => user,
+        Err(UserStoreError::UserNotFound) => return Err(AuthAPIError::InvalidToken),
+        Err(e) => return Err(AuthAPIError::UnexpectedError(e.into())),
+    };
+
+    if let Err(e) = check_account_status(user.current_status()) {
+        // the token is consumed already, so the rotated one goes back with the rejection to let the session go on
+        // once the account can be used again
+        return Ok((jar.add(create_refresh_cookie(&new_token)), e.into_response()));
+    }
+
+    let auth_cookie = continue_session(&state, &user_id, &session_id).await.map_err(AuthAPIError::UnexpectedError)?;
+
+    Ok((jar.add(auth_cookie).add(create_refresh_cookie(&new_token)), StatusCode::OK.into_response()))
+}
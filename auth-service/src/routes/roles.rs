@@ -0,0 +1,92 @@
+use {
+    crate::{
+        app_state::AppState,
+        domain::{
+            data_stores::{RoleStoreError, UserStoreError},
+            error::AuthAPIError,
+            role::{Role, RolesRead, RolesWrite},
+        },
+        utils::{
+            audit::record_admin_action,
+            auth::{ClientInfo, RequirePermission, bump_token_version},
+        },
+    },
+    axum::{Json, extract::State, http::StatusCode, response::IntoResponse},
+    serde::{Deserialize, Serialize},
+    tracing::instrument,
+    uuid::Uuid,
+};
+
+#[derive(Deserialize)]
+pub struct RoleAssignmentRequest {
+    #[serde(rename = "userId")]
+    pub user_id: Uuid,
+    pub role: String,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Serialize)]
+pub struct RolesResponse {
+    pub roles: Vec<Role>,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Serialize)]
+pub struct RoleAssignmentResponse {
+    pub message: String,
+}
+
+#[instrument(name = "List roles", skip_all)]
+pub async fn list_roles(
+    state: State<AppState>,
+    _: RequirePermission<RolesRead>,
+) -> Result<impl IntoResponse, AuthAPIError> {
+    let roles = state.role_store.get_roles().await.map_err(|e| AuthAPIError::UnexpectedError(e.into()))?;
+
+    Ok((StatusCode::OK, Json(RolesResponse { roles })))
+}
+
+#[instrument(name = "Assign role", skip_all)]
+pub async fn assign_role(
+    state: State<AppState>,
+    client: ClientInfo,
+    admin: RequirePermission<RolesWrite>,
+    Json(request): Json<RoleAssignmentRequest>,
+) -> Result<impl IntoResponse, AuthAPIError> {
+    // users pending deletion can no longer sign in, so they are not given roles either
+    match state.user_store.get_user_by_id(&request.user_id).await {
+        Ok(_) => {}
+        Err(UserStoreError::UserNotFound) => return Err(AuthAPIError::UserNotFound),
+        Err(e) => return Err(AuthAPIError::UnexpectedError(e.into())),
+    }
+
+    match state.role_store.assign_role(&request.user_id, &request.role).await {
+        Ok(()) => {}
+        Err(RoleStoreError::RoleNotFound) => return Err(AuthAPIError::RoleNotFound),
+        Err(RoleStoreError::UserNotFound) => return Err(AuthAPIError::UserNotFound),
+        Err(e) => return Err(AuthAPIError::UnexpectedError(e.into())),
+    }
+
+    record_admin_action(&state, &client, &admin, request.user_id, &format!("roles/assign: {}", request.role)).await;
+
+    Ok((StatusCode::OK, Json(RoleAssignmentResponse { message: "Role assigned".to_string() })))
+}
+
+#[instrument(name = "Revoke role", skip_all)]
+pub async fn revoke_role(
+    state: State<AppState>,
+    client: ClientInfo,
+    admin: RequirePermission<RolesWrite>,
+    Json(request): Json<RoleAssignmentRequest>,
+) -> Result<impl IntoResponse, AuthAPIError> {
+    if let Err(e) = state.role_store.revoke_role(&request.user_id, &request.role).await {
+        return Err(AuthAPIError::UnexpectedError(e.into()));
+    }
+
+    // auth tokens already issued still carry the role, so they are rejected and reissued on refresh without it
+    if let Err(e) = bump_token_version(&state, &request.user_id).await {
+        return Err(AuthAPIError::UnexpectedError(e));
+    }
+
+    record_admin_action(&state, &client, &admin, request.user_id, &format!("roles/revoke: {}", request.role)).await;
+
+    Ok((StatusCode::OK, Json(RoleAssignmentResponse { message: "Role revoked".to_string() })))
+}
@@ -0,0 +1,131 @@
+use {
+    crate::{
+        app_state::AppState,
+        domain::{
+            data_stores::{RefreshToken, Session, SessionStoreError},
+            error::AuthAPIError,
+        },
+        utils::{auth::AuthenticatedUser, constants::REFRESH_COOKIE_NAME},
+    },
+    axum::{Json, extract::State, http::StatusCode, response::IntoResponse},
+    axum_extra::extract::CookieJar,
+    serde::{Deserialize, Serialize},
+    std::net::IpAddr,
+    tracing::instrument,
+    uuid::Uuid,
+};
+
+#[derive(Deserialize)]
+pub struct RevokeSessionRequest {
+    #[serde(rename = "sessionId")]
+    pub session_id: Uuid,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Serialize)]
+pub struct SessionsResponse {
+    pub sessions: Vec<SessionResponse>,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Serialize)]
+pub struct SessionResponse {
+    #[serde(rename = "sessionId")]
+    pub session_id: Uuid,
+    #[serde(rename = "createdAt")]
+    pub created_at: i64,
+    #[serde(rename = "refreshedAt")]
+    pub refreshed_at: i64,
+    pub ip: IpAddr,
+    #[serde(rename = "userAgent")]
+    pub user_agent: Option<String>,
+    /// Whether this is the session the request was made with.
+    pub current: bool,
+}
+
+impl SessionResponse {
+    /// Describes `session` to the user of session `current_session_id`.
+    pub fn new(session: Session, current_session_id: &Uuid) -> Self {
+        Self {
+            current: session.session_id == *current_session_id,
+            session_id: session.session_id,
+            created_at: session.created_at,
+            refreshed_at: session.refreshed_at,
+            ip: session.ip,
+            user_agent: session.user_agent,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, PartialEq, Serialize)]
+pub struct RevokeSessionResponse {
+    pub message: String,
+}
+
+#[instrument(name = "List sessions", skip_all)]
+pub async fn list_sessions(
+    state: State<AppState>,
+    AuthenticatedUser { user_id, session_id, .. }: AuthenticatedUser,
+) -> Result<impl IntoResponse, AuthAPIError> {
+    let sessions =
+        state.session_store.get_sessions(&user_id).await.map_err(|e| AuthAPIError::UnexpectedError(e.into()))?;
+    let sessions = sessions.into_iter().map(|session| SessionResponse::new(session, &session_id)).collect();
+
+    Ok((StatusCode::OK, Json(SessionsResponse { sessions })))
+}
+
+#[instrument(name = "Revoke session", skip_all)]
+pub async fn revoke_session(
+    state: State<AppState>,
+    AuthenticatedUser { user_id, .. }: AuthenticatedUser,
+    Json(request): Json<RevokeSessionRequest>,
+) -> Result<impl IntoResponse, AuthAPIError> {
+    // removing through the user's own registry keeps users from revoking each other's sessions
+    match state.session_store.remove_session(&user_id, &request.session_id).await {
+        Ok(()) => {}
+        Err(SessionStoreError::SessionNotFound) => return Err(AuthAPIError::SessionNotFound),
+        Err(e) => return Err(AuthAPIError::UnexpectedError(e.into())),
+    }
+
+    end_session(&state, &request.session_id).await?;
+
+    Ok((StatusCode::OK, Json(RevokeSessionResponse { message: "Session revoked".to_string() })))
+}
+
+#[instrument(name = "Revoke other sessions", skip_all)]
+pub async fn revoke_other_sessions(
+    state: State<AppState>,
+    jar: CookieJar,
+    AuthenticatedUser { user_id, session_id, .. }: AuthenticatedUser,
+) -> Result<impl IntoResponse, AuthAPIError> {
+    let sessions =
+        state.session_store.get_sessions(&user_id).await.map_err(|e| AuthAPIError::UnexpectedError(e.into()))?;
+
+    for session in sessions.iter().filter(|session| session.session_id != session_id) {
+        end_session(&state, &session.session_id).await?;
+    }
+
+    // also covers refresh tokens of sessions started before the registry existed
+    let refresh_token = jar.get(REFRESH_COOKIE_NAME).and_then(|cookie| RefreshToken::parse(cookie.value()).ok());
+
+    if let Err(e) = state.refresh_token_store.revoke_user(&user_id, refresh_token.as_ref()).await {
+        return Err(AuthAPIError::UnexpectedError(e.into()));
+    }
+
+    if let Err(e) = state.session_store.remove_sessions(&user_id, Some(&session_id)).await {
+        return Err(AuthAPIError::UnexpectedError(e.into()));
+    }
+
+    Ok((StatusCode::OK, Json(RevokeSessionResponse { message: "Other sessions revoked".to_string() })))
+}
+
+/// Rejects the session's auth tokens and revokes its refresh token family.
+async fn end_session(state: &AppState, session_id: &Uuid) -> Result<(), AuthAPIError> {
+    if let Err(e) = state.banned_token_store.revoke_session(session_id).await {
+        return Err(AuthAPIError::UnexpectedError(e.into()));
+    }
+
+    if let Err(e) = state.refresh_token_store.revoke_session(session_id).await {
+        return Err(AuthAPIError::UnexpectedError(e.into()));
+    }
+
+    Ok(())
+}
@@ -1,7 +1,16 @@
 use {
     crate::{
         app_state::AppState,
-        domain::{email::Email, error::AuthAPIError, password::Password, user::User},
+        domain::{
+            audit::AuditEventType,
+            data_stores::UserStoreError,
+            email::Email,
+            error::AuthAPIError,
+            password::Password,
+            user::{TwoFactorMethod, User},
+        },
+        routes::send_verification_email,
+        utils::{audit::record_event, auth::ClientInfo},
     },
     axum::{Json, extract::State, http::StatusCode, response::IntoResponse},
     secrecy::SecretBox,
@@ -25,6 +34,7 @@ pub struct SignupResponse {
 #[instrument(name = "Signup", skip_all)]
 pub async fn signup(
     state: State<AppState>,
+    client: ClientInfo,
     Json(request): Json<SignupRequest>,
 ) -> Result<impl IntoResponse, AuthAPIError> {
     let Ok(email) = Email::parse(&request.email)
@@ -40,9 +50,22 @@ pub async fn signup(
         return Err(AuthAPIError::UserAlreadyExists);
     }
 
-    let user = User::new(&email, &password, request.requires_2fa);
+    let two_factor_method = match request.requires_2fa {
+        true => TwoFactorMethod::Email,
+        false => TwoFactorMethod::None,
+    };
+    let user = User::new(&email, &password, two_factor_method);
+
+    state.user_store.add_user(user).await.map_err(|e| match e {
+        UserStoreError::UserAlreadyExists => AuthAPIError::UserAlreadyExists,
+        e => AuthAPIError::UnexpectedError(e.into()),
+    })?;
+
+    let user = state.user_store.get_user(&email).await.map_err(|e| AuthAPIError::UnexpectedError(e.into()))?;
+
+    record_event(&state, client.event(AuditEventType::Signup).user(user.user_id)).await;
 
-    state.user_store.add_user(user).await.map_err(|e| AuthAPIError::UnexpectedError(e.into()))?;
+    send_verification_email(&email, &state).await?;
 
     Ok((StatusCode::CREATED, Json(SignupResponse { message: "User created successfully!".to_string() })))
 }
@@ -0,0 +1,94 @@
+use {
+    crate::{
+        app_state::AppState,
+        domain::{
+            data_stores::{TotpSecretStoreError, TwoFactorCode},
+            error::AuthAPIError,
+            totp::TotpSecret,
+            user::TwoFactorMethod,
+        },
+        routes::generate_recovery_codes,
+        utils::{auth::AuthenticatedUser, constants::TOTP_SKEW},
+    },
+    axum::{Json, extract::State, http::StatusCode, response::IntoResponse},
+    secrecy::ExposeSecret,
+    serde::{Deserialize, Serialize},
+    tracing::instrument,
+};
+
+#[derive(Deserialize)]
+pub struct ConfirmTotpRequest {
+    pub code: TwoFactorCode,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Serialize)]
+pub struct EnrollTotpResponse {
+    pub secret: String,
+    #[serde(rename = "otpauthUri")]
+    pub otpauth_uri: String,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Serialize)]
+pub struct ConfirmTotpResponse {
+    pub message: String,
+    #[serde(rename = "recoveryCodes")]
+    pub recovery_codes: Vec<String>,
+}
+
+#[instrument(name = "Enroll TOTP", skip_all)]
+pub async fn enroll_totp(
+    state: State<AppState>,
+    AuthenticatedUser { user_id, email, .. }: AuthenticatedUser,
+) -> Result<impl IntoResponse, AuthAPIError> {
+    let secret = TotpSecret::default();
+    let encoded = secret.to_base32(&email).map_err(AuthAPIError::UnexpectedError)?;
+    let otpauth_uri = secret.otpauth_uri(&email).map_err(AuthAPIError::UnexpectedError)?;
+
+    state.totp_secret_store.add_secret(&user_id, secret).await.map_err(|e| AuthAPIError::UnexpectedError(e.into()))?;
+
+    Ok((
+        StatusCode::OK,
+        Json(EnrollTotpResponse {
+            secret: encoded.expose_secret().to_owned(),
+            otpauth_uri: otpauth_uri.expose_secret().to_owned(),
+        }),
+    ))
+}
+
+#[instrument(name = "Confirm TOTP", skip_all)]
+pub async fn confirm_totp(
+    state: State<AppState>,
+    AuthenticatedUser { user_id, email, .. }: AuthenticatedUser,
+    Json(request): Json<ConfirmTotpRequest>,
+) -> Result<impl IntoResponse, AuthAPIError> {
+    let secret = match state.totp_secret_store.get_pending_secret(&user_id).await {
+        Ok(secret) => secret,
+        Err(TotpSecretStoreError::SecretNotFound) => return Err(AuthAPIError::InvalidCredentials),
+        Err(e) => return Err(AuthAPIError::UnexpectedError(e.into())),
+    };
+
+    let Some(time_step) = secret.verify(&email, &request.code, *TOTP_SKEW).map_err(AuthAPIError::UnexpectedError)?
+    else {
+        return Err(AuthAPIError::IncorrectCredentials);
+    };
+
+    state.totp_secret_store.confirm_secret(&user_id).await.map_err(|e| AuthAPIError::UnexpectedError(e.into()))?;
+    state
+        .totp_secret_store
+        .record_time_step(&user_id, time_step)
+        .await
+        .map_err(|e| AuthAPIError::UnexpectedError(e.into()))?;
+
+    state
+        .user_store
+        .update_two_factor_method(&email, TwoFactorMethod::Totp)
+        .await
+        .map_err(|e| AuthAPIError::UnexpectedError(e.into()))?;
+
+    let recovery_codes = generate_recovery_codes(&user_id, &state).await?;
+
+    Ok((
+        StatusCode::OK,
+        Json(ConfirmTotpResponse { message: "TOTP enabled successfully!".to_string(), recovery_codes }),
+    ))
+}
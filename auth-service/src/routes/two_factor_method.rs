@@ -0,0 +1,76 @@
+use {
+    crate::{
+        app_state::AppState,
+        domain::{data_stores::TotpSecretStoreError, error::AuthAPIError, user::TwoFactorMethod},
+        routes::generate_recovery_codes,
+        utils::auth::AuthenticatedUser,
+    },
+    axum::{Json, extract::State, http::StatusCode, response::IntoResponse},
+    serde::{Deserialize, Serialize},
+    tracing::instrument,
+};
+
+#[derive(Deserialize)]
+pub struct TwoFactorMethodRequest {
+    pub method: TwoFactorMethod,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Serialize)]
+pub struct TwoFactorMethodResponse {
+    pub message: String,
+    #[serde(rename = "recoveryCodes", skip_serializing_if = "Option::is_none")]
+    pub recovery_codes: Option<Vec<String>>,
+}
+
+#[instrument(name = "Set 2FA method", skip_all)]
+pub async fn set_two_factor_method(
+    state: State<AppState>,
+    AuthenticatedUser { user_id, email, .. }: AuthenticatedUser,
+    Json(request): Json<TwoFactorMethodRequest>,
+) -> Result<impl IntoResponse, AuthAPIError> {
+    if request.method == TwoFactorMethod::Totp {
+        match state.totp_secret_store.get_secret(&user_id).await {
+            Ok(_) => {}
+            Err(TotpSecretStoreError::SecretNotFound) => return Err(AuthAPIError::InvalidCredentials),
+            Err(e) => return Err(AuthAPIError::UnexpectedError(e.into())),
+        }
+    }
+
+    if request.method == TwoFactorMethod::Passkey {
+        let passkeys =
+            state.passkey_store.get_passkeys(&user_id).await.map_err(|e| AuthAPIError::UnexpectedError(e.into()))?;
+
+        if passkeys.is_empty() {
+            return Err(AuthAPIError::InvalidCredentials);
+        }
+    }
+
+    let user = state.user_store.get_user_by_id(&user_id).await.map_err(|e| AuthAPIError::UnexpectedError(e.into()))?;
+
+    state
+        .user_store
+        .update_two_factor_method(&email, request.method)
+        .await
+        .map_err(|e| AuthAPIError::UnexpectedError(e.into()))?;
+
+    // recovery codes are issued when 2FA gets enabled and dropped when it gets disabled
+    let recovery_codes = match (user.two_factor_method, request.method) {
+        (TwoFactorMethod::None, TwoFactorMethod::None) => None,
+        (TwoFactorMethod::None, _) => Some(generate_recovery_codes(&user_id, &state).await?),
+        (_, TwoFactorMethod::None) => {
+            state
+                .recovery_code_store
+                .replace_codes(&user_id, vec![])
+                .await
+                .map_err(|e| AuthAPIError::UnexpectedError(e.into()))?;
+
+            None
+        }
+        _ => None,
+    };
+
+    Ok((
+        StatusCode::OK,
+        Json(TwoFactorMethodResponse { message: "2FA method updated successfully!".to_string(), recovery_codes }),
+    ))
+}
@@ -2,11 +2,20 @@ use {
     crate::{
         app_state::AppState,
         domain::{
-            data_stores::{LoginAttemptId, TwoFactorCode},
+            audit::AuditEventType,
+            data_stores::{
+                LoginAttemptId, RecoveryCode, RecoveryCodeStoreError, ThrottleKey, TotpSecretStoreError, TwoFactorCode,
+            },
             email::Email,
             error::AuthAPIError,
+            user::TwoFactorMethod,
+        },
+        utils::{
+            audit::record_event,
+            auth::{ClientInfo, check_account_status, start_session},
+            constants::TOTP_SKEW,
+            throttle::{check_login_throttle, record_two_factor_failure},
         },
-        utils::auth::generate_auth_cookie,
     },
     axum::{
         Json,
@@ -15,8 +24,9 @@ use {
         response::IntoResponse,
     },
     axum_extra::extract::CookieJar,
-    serde::Deserialize,
+    serde::{Deserialize, Deserializer},
     tracing::instrument,
+    uuid::Uuid,
 };
 
 #[derive(Deserialize)]
@@ -25,7 +35,28 @@ pub struct Verify2FARequest {
     #[serde(rename = "loginAttemptId")]
     login_attempt_id: LoginAttemptId,
     #[serde(rename = "2FACode")]
-    two_factor_code: TwoFactorCode,
+    two_factor_code: SecondFactor,
+}
+
+pub enum SecondFactor {
+    TwoFactorCode(TwoFactorCode),
+    RecoveryCode(RecoveryCode),
+}
+
+impl<'a> Deserialize<'a> for SecondFactor {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'a>,
+    {
+        let maybe_code = String::deserialize(deserializer)?;
+
+        match RecoveryCode::parse(&maybe_code) {
+            Ok(code) => Ok(SecondFactor::RecoveryCode(code)),
+            Err(_) => {
+                TwoFactorCode::parse(&maybe_code).map(SecondFactor::TwoFactorCode).map_err(serde::de::Error::custom)
+            }
+        }
+    }
 }
 
 pub struct ValidatedJson<T>(pub T);
@@ -54,26 +85,96 @@ where
 #[instrument(name = "Verify two factor", skip_all)]
 pub async fn verify_2fa(
     state: State<AppState>,
+    client: ClientInfo,
     jar: CookieJar,
     ValidatedJson(request): ValidatedJson<Verify2FARequest>,
 ) -> Result<impl IntoResponse, AuthAPIError> {
-    let Ok((attempt_id, code)) = state.two_factor_store.get_code(&request.email).await
+    check_login_throttle(&state, &request.email, client.ip).await?;
+
+    let Ok(user) = state.user_store.get_user(&request.email).await
+    else {
+        return Err(AuthAPIError::IncorrectCredentials);
+    };
+    let Ok((attempt_id, code)) = state.two_factor_store.get_code(&user.user_id).await
     else {
         return Err(AuthAPIError::IncorrectCredentials);
     };
 
-    if request.login_attempt_id != attempt_id || request.two_factor_code != code {
+    if request.login_attempt_id != attempt_id {
         return Err(AuthAPIError::IncorrectCredentials);
     }
 
-    let auth_cookie = match generate_auth_cookie(&request.email) {
-        Ok(auth_cookie) => auth_cookie,
+    let is_valid = match (&request.two_factor_code, user.two_factor_method) {
+        (SecondFactor::RecoveryCode(recovery_code), _) => {
+            consume_recovery_code(&state, &user.user_id, recovery_code).await?
+        }
+        (SecondFactor::TwoFactorCode(two_factor_code), TwoFactorMethod::Totp) => {
+            verify_totp(&state, &request.email, &user.user_id, two_factor_code).await?
+        }
+        // Passkey users finish through /passkey/login/finish; only recovery codes are accepted here
+        (SecondFactor::TwoFactorCode(_), TwoFactorMethod::Passkey) => false,
+        (SecondFactor::TwoFactorCode(two_factor_code), _) => *two_factor_code == code,
+    };
+
+    if !is_valid {
+        record_two_factor_failure(&state, &user.user_id, client.ip).await?;
+        record_event(&state, client.event(AuditEventType::TwoFactorFailed).user(user.user_id)).await;
+
+        return Err(AuthAPIError::IncorrectCredentials);
+    }
+
+    record_event(&state, client.event(AuditEventType::TwoFactorVerified).user(user.user_id)).await;
+
+    check_account_status(user.current_status())?;
+
+    let (auth_cookie, refresh_cookie) = match start_session(&state, &user.user_id, client).await {
+        Ok(cookies) => cookies,
         Err(e) => return Err(AuthAPIError::UnexpectedError(e)),
     };
 
-    if let Err(e) = state.two_factor_store.remove_code(&request.email).await {
+    if let Err(e) = state.two_factor_store.remove_code(&user.user_id).await {
         return Err(AuthAPIError::UnexpectedError(e.into()));
     };
 
-    Ok((jar.add(auth_cookie), StatusCode::OK))
+    state
+        .login_throttle_store
+        .reset(&ThrottleKey::TwoFactor(user.user_id))
+        .await
+        .map_err(|e| AuthAPIError::UnexpectedError(e.into()))?;
+
+    Ok((jar.add(auth_cookie).add(refresh_cookie), StatusCode::OK))
+}
+
+#[instrument(name = "Verify TOTP code", skip_all)]
+async fn verify_totp(
+    state: &AppState,
+    email: &Email,
+    user_id: &Uuid,
+    code: &TwoFactorCode,
+) -> Result<bool, AuthAPIError> {
+    let secret = match state.totp_secret_store.get_secret(user_id).await {
+        Ok(secret) => secret,
+        Err(TotpSecretStoreError::SecretNotFound) => return Ok(false),
+        Err(e) => return Err(AuthAPIError::UnexpectedError(e.into())),
+    };
+
+    let Some(time_step) = secret.verify(email, code, *TOTP_SKEW).map_err(AuthAPIError::UnexpectedError)?
+    else {
+        return Ok(false);
+    };
+
+    match state.totp_secret_store.record_time_step(user_id, time_step).await {
+        Ok(()) => Ok(true),
+        Err(TotpSecretStoreError::CodeReused) => Ok(false),
+        Err(e) => Err(AuthAPIError::UnexpectedError(e.into())),
+    }
+}
+
+#[instrument(name = "Consume recovery code", skip_all)]
+async fn consume_recovery_code(state: &AppState, user_id: &Uuid, code: &RecoveryCode) -> Result<bool, AuthAPIError> {
+    match state.recovery_code_store.consume_code(user_id, code).await {
+        Ok(()) => Ok(true),
+        Err(RecoveryCodeStoreError::CodeNotFound) => Ok(false),
+        Err(e) => Err(AuthAPIError::UnexpectedError(e.into())),
+    }
 }
@@ -0,0 +1,109 @@
+use {
+    crate::{
+        app_state::AppState,
+        domain::{
+            data_stores::{EmailVerificationStoreError, EmailVerificationToken},
+            email::Email,
+            error::AuthAPIError,
+        },
+    },
+    axum::{Json, extract::State, http::StatusCode, response::IntoResponse},
+    chrono::Utc,
+    secrecy::{ExposeSecret, SecretBox},
+    serde::{Deserialize, Serialize},
+    tracing::instrument,
+};
+
+const RESEND_THROTTLE_SECONDS: i64 = 60;
+
+#[derive(Deserialize)]
+pub struct VerifyEmailRequest {
+    pub email: SecretBox<String>,
+    pub token: SecretBox<String>,
+}
+
+#[derive(Deserialize)]
+pub struct ResendVerificationEmailRequest {
+    pub email: SecretBox<String>,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Serialize)]
+pub struct VerifyEmailResponse {
+    pub message: String,
+}
+
+#[instrument(name = "Verify email", skip_all)]
+pub async fn verify_email(
+    state: State<AppState>,
+    Json(request): Json<VerifyEmailRequest>,
+) -> Result<impl IntoResponse, AuthAPIError> {
+    let Ok(email) = Email::parse(&request.email)
+    else {
+        return Err(AuthAPIError::InvalidCredentials);
+    };
+    let Ok(token) = EmailVerificationToken::parse(request.token.expose_secret())
+    else {
+        return Err(AuthAPIError::InvalidToken);
+    };
+    let stored_token = match state.email_verification_store.take_token(&email).await {
+        Ok(stored_token) => stored_token,
+        Err(EmailVerificationStoreError::TokenNotFound) => return Err(AuthAPIError::InvalidToken),
+        Err(e) => return Err(AuthAPIError::UnexpectedError(e.into())),
+    };
+
+    if token != stored_token {
+        return Err(AuthAPIError::InvalidToken);
+    }
+
+    state.user_store.verify_email(&email).await.map_err(|e| AuthAPIError::UnexpectedError(e.into()))?;
+
+    Ok((StatusCode::OK, Json(VerifyEmailResponse { message: "Email verified successfully!".to_string() })))
+}
+
+#[instrument(name = "Resend verification email", skip_all)]
+pub async fn resend_verification_email(
+    state: State<AppState>,
+    Json(request): Json<ResendVerificationEmailRequest>,
+) -> Result<impl IntoResponse, AuthAPIError> {
+    let Ok(email) = Email::parse(&request.email)
+    else {
+        return Err(AuthAPIError::InvalidCredentials);
+    };
+
+    if state.user_store.get_user(&email).await.is_ok_and(|user| !user.email_verified) {
+        // throttled silently, as refusing would tell unverified accounts apart from every other address
+        let sent_recently = state
+            .email_verification_store
+            .get_token(&email)
+            .await
+            .is_ok_and(|(_, sent_at)| Utc::now().timestamp() - sent_at < RESEND_THROTTLE_SECONDS);
+
+        if !sent_recently {
+            send_verification_email(&email, &state).await?;
+        }
+    }
+
+    Ok((
+        StatusCode::OK,
+        Json(VerifyEmailResponse {
+            message: "If the account exists and is unverified, a verification email has been sent".to_string(),
+        }),
+    ))
+}
+
+#[instrument(name = "Send verification email", skip_all)]
+pub(crate) async fn send_verification_email(email: &Email, state: &AppState) -> Result<(), AuthAPIError> {
+    let token = EmailVerificationToken::default();
+
+    state
+        .email_verification_store
+        .add_token(email.clone(), token.clone())
+        .await
+        .map_err(|e| AuthAPIError::UnexpectedError(e.into()))?;
+
+    state
+        .email_client
+        .send_email(email, "Verify your email", token.as_ref().expose_secret())
+        .await
+        .map_err(AuthAPIError::UnexpectedError)
+}
@@ -1,9 +1,20 @@
 use {
-    crate::{app_state::AppState, domain::error::AuthAPIError, utils::auth::validate_token},
+    crate::{
+        app_state::AppState,
+        domain::{
+            audit::{AuditEvent, AuditEventType},
+            error::AuthAPIError,
+        },
+        utils::{
+            audit::record_event,
+            auth::{ClientInfo, check_cached_account_status, validate_token},
+        },
+    },
     axum::{Json, extract::State, http::StatusCode, response::IntoResponse},
     secrecy::SecretBox,
     serde::{Deserialize, Serialize},
     tracing::instrument,
+    uuid::Uuid,
 };
 
 #[derive(Deserialize)]
@@ -19,17 +30,47 @@ struct VerifyTokenResponse {
 #[instrument(name = "Verify token", skip_all)]
 pub async fn verify_token(
     state: State<AppState>,
+    client: ClientInfo,
     Json(request): Json<VerifyTokenRequest>,
 ) -> Result<impl IntoResponse, AuthAPIError> {
     let parts = request.token.split('.').collect::<Vec<_>>();
 
     if parts.len() != 3 {
-        return Err(AuthAPIError::MalformedToken);
+        return Err(verification_failed(&state, &client, None, AuthAPIError::MalformedToken, "Malformed token").await);
     }
 
-    if validate_token(Some(state.banned_token_store.clone()), &SecretBox::new(Box::new(request.token))).await.is_err() {
-        return Err(AuthAPIError::InvalidToken);
+    let claims = match validate_token(Some(&state), &SecretBox::new(Box::new(request.token))).await {
+        Ok(claims) => claims,
+        Err(e) => {
+            return Err(verification_failed(&state, &client, None, AuthAPIError::InvalidToken, format!("{e:?}")).await);
+        }
+    };
+    let Ok(user_id) = Uuid::parse_str(&claims.sub)
+    else {
+        return Err(verification_failed(&state, &client, None, AuthAPIError::InvalidToken, "Invalid subject").await);
+    };
+
+    // tokens stay valid until they expire, so a suspension has to be checked for on every verification
+    if let Err(e) = check_cached_account_status(&state, &user_id).await {
+        let reason = e.to_string();
+
+        return Err(verification_failed(&state, &client, Some(user_id), e, reason).await);
     }
 
     Ok((StatusCode::OK, Json(VerifyTokenResponse { message: "Token verified!".to_string() })))
 }
+
+/// Records the failed verification and hands back the error it failed with.
+async fn verification_failed(
+    state: &AppState,
+    client: &ClientInfo,
+    user_id: Option<Uuid>,
+    error: AuthAPIError,
+    reason: impl Into<String>,
+) -> AuthAPIError {
+    let event = AuditEvent { user_id, ..client.event(AuditEventType::TokenVerificationFailed) };
+
+    record_event(state, event.details(reason)).await;
+
+    error
+}
@@ -0,0 +1,62 @@
+use {
+    chrono::Utc,
+    std::{collections::HashMap, hash::Hash},
+};
+
+/// Map whose entries disappear once their time to live has passed, the way Redis keys set with an expiry do.
+pub(crate) struct ExpiringMap<K, V> {
+    /// Values along with the unix timestamp in milliseconds they expire at.
+    entries: HashMap<K, (V, i64)>,
+}
+
+impl<K: Eq + Hash, V> ExpiringMap<K, V> {
+    /// Stores `value` under `key` for `ttl_seconds`, replacing any earlier value and expiry.
+    pub fn insert(&mut self, key: K, value: V, ttl_seconds: u64) {
+        let now = Utc::now().timestamp_millis();
+
+        // expired entries are only skipped on lookup, so they are dropped here to keep the map from growing
+        self.entries.retain(|_, (_, expires_at)| *expires_at > now);
+        self.entries.insert(key, (value, now + ttl_seconds as i64 * 1000));
+    }
+
+    pub fn get(&self, key: &K) -> Option<&V> {
+        match self.entries.get(key) {
+            Some((value, expires_at)) if *expires_at > Utc::now().timestamp_millis() => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+        match self.entries.get_mut(key) {
+            Some((value, expires_at)) if *expires_at > Utc::now().timestamp_millis() => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// Removes the value stored under `key`, returning it unless it had already expired.
+    pub fn remove(&mut self, key: &K) -> Option<V> {
+        match self.entries.remove(key) {
+            Some((value, expires_at)) if expires_at > Utc::now().timestamp_millis() => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Lets the value stored under `key` live for another `ttl_seconds`.
+    pub fn expire(&mut self, key: &K, ttl_seconds: u64) {
+        let now = Utc::now().timestamp_millis();
+
+        if let Some((_, expires_at)) = self.entries.get_mut(key).filter(|(_, expires_at)| *expires_at > now) {
+            *expires_at = now + ttl_seconds as i64 * 1000;
+        }
+    }
+}
+
+impl<K, V> Default for ExpiringMap<K, V> {
+    fn default() -> Self {
+        Self { entries: HashMap::new() }
+    }
+}
@@ -0,0 +1,42 @@
+use {
+    crate::{
+        domain::data_stores::{EmailChangeStore, EmailChangeStoreError, PendingEmailChange},
+        services::data_stores::expiring_map::ExpiringMap,
+    },
+    tokio::sync::RwLock,
+    tracing::instrument,
+    uuid::Uuid,
+};
+
+const ONE_DAY_IN_SECONDS: u64 = 86400;
+
+/// Keeps pending email changes in process memory, for local development and tests.
+#[derive(Default)]
+pub struct HashmapEmailChangeStore {
+    changes: RwLock<ExpiringMap<Uuid, PendingEmailChange>>,
+}
+
+#[async_trait::async_trait]
+impl EmailChangeStore for HashmapEmailChangeStore {
+    #[instrument(name = "Add email change in memory", skip_all)]
+    async fn add_change(&self, user_id: Uuid, change: PendingEmailChange) -> Result<(), EmailChangeStoreError> {
+        self.changes.write().await.insert(user_id, change, ONE_DAY_IN_SECONDS);
+
+        Ok(())
+    }
+
+    #[instrument(name = "Get email change in memory", skip_all)]
+    async fn get_change(&self, user_id: &Uuid) -> Result<PendingEmailChange, EmailChangeStoreError> {
+        match self.changes.read().await.get(user_id) {
+            Some(change) => Ok(change.clone()),
+            None => Err(EmailChangeStoreError::ChangeNotFound),
+        }
+    }
+
+    #[instrument(name = "Remove email change in memory", skip_all)]
+    async fn remove_change(&self, user_id: &Uuid) -> Result<(), EmailChangeStoreError> {
+        self.changes.write().await.remove(user_id);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,59 @@
+use {
+    crate::{
+        domain::{
+            data_stores::{EmailVerificationStore, EmailVerificationStoreError, EmailVerificationToken},
+            email::Email,
+        },
+        services::data_stores::expiring_map::ExpiringMap,
+    },
+    chrono::Utc,
+    secrecy::ExposeSecret,
+    tokio::sync::RwLock,
+    tracing::instrument,
+};
+
+const ONE_DAY_IN_SECONDS: u64 = 86400;
+
+/// Keeps email verification tokens in process memory, for local development and tests.
+#[derive(Default)]
+pub struct HashmapEmailVerificationStore {
+    /// Tokens along with the unix timestamp they were issued at.
+    tokens: RwLock<ExpiringMap<String, (EmailVerificationToken, i64)>>,
+}
+
+#[async_trait::async_trait]
+impl EmailVerificationStore for HashmapEmailVerificationStore {
+    #[instrument(name = "Add email verification token in memory", skip_all)]
+    async fn add_token(&self, email: Email, token: EmailVerificationToken) -> Result<(), EmailVerificationStoreError> {
+        self.tokens.write().await.insert(get_key(&email), (token, Utc::now().timestamp()), ONE_DAY_IN_SECONDS);
+
+        Ok(())
+    }
+
+    #[instrument(name = "Remove email verification token in memory", skip_all)]
+    async fn remove_token(&self, email: &Email) -> Result<(), EmailVerificationStoreError> {
+        self.tokens.write().await.remove(&get_key(email));
+
+        Ok(())
+    }
+
+    #[instrument(name = "Get email verification token in memory", skip_all)]
+    async fn get_token(&self, email: &Email) -> Result<(EmailVerificationToken, i64), EmailVerificationStoreError> {
+        match self.tokens.read().await.get(&get_key(email)) {
+            Some((token, issued_at)) => Ok((token.clone(), *issued_at)),
+            None => Err(EmailVerificationStoreError::TokenNotFound),
+        }
+    }
+
+    #[instrument(name = "Take email verification token in memory", skip_all)]
+    async fn take_token(&self, email: &Email) -> Result<EmailVerificationToken, EmailVerificationStoreError> {
+        match self.tokens.write().await.remove(&get_key(email)) {
+            Some((token, _)) => Ok(token),
+            None => Err(EmailVerificationStoreError::TokenNotFound),
+        }
+    }
+}
+
+fn get_key(email: &Email) -> String {
+    email.as_ref().expose_secret().to_lowercase()
+}
@@ -0,0 +1,59 @@
+use {
+    crate::{
+        domain::data_stores::{FailedAttempts, LoginThrottleStore, LoginThrottleStoreError, ThrottleKey},
+        services::data_stores::expiring_map::ExpiringMap,
+        utils::constants::LOGIN_LOCKOUT_SECONDS,
+    },
+    chrono::Utc,
+    secrecy::ExposeSecret,
+    tokio::sync::RwLock,
+    tracing::instrument,
+};
+
+/// Keeps failed login counters in process memory, for local development and tests.
+#[derive(Default)]
+pub struct HashmapLoginThrottleStore {
+    failures: RwLock<ExpiringMap<String, FailedAttempts>>,
+}
+
+#[async_trait::async_trait]
+impl LoginThrottleStore for HashmapLoginThrottleStore {
+    #[instrument(name = "Record login failure in memory", skip_all)]
+    async fn record_failure(&self, key: &ThrottleKey) -> Result<FailedAttempts, LoginThrottleStoreError> {
+        let mut failures = self.failures.write().await;
+        let now = Utc::now().timestamp();
+
+        // the counter expires a fixed time after the first failure it counts
+        if let Some(attempts) = failures.get_mut(&get_key(key)) {
+            *attempts = FailedAttempts { count: attempts.count + 1, last_failure_at: now };
+
+            return Ok(*attempts);
+        }
+
+        let attempts = FailedAttempts { count: 1, last_failure_at: now };
+
+        failures.insert(get_key(key), attempts, *LOGIN_LOCKOUT_SECONDS);
+
+        Ok(attempts)
+    }
+
+    #[instrument(name = "Get login failures in memory", skip_all)]
+    async fn get_failures(&self, key: &ThrottleKey) -> Result<FailedAttempts, LoginThrottleStoreError> {
+        Ok(self.failures.read().await.get(&get_key(key)).copied().unwrap_or_default())
+    }
+
+    #[instrument(name = "Reset login failures in memory", skip_all)]
+    async fn reset(&self, key: &ThrottleKey) -> Result<(), LoginThrottleStoreError> {
+        self.failures.write().await.remove(&get_key(key));
+
+        Ok(())
+    }
+}
+
+fn get_key(key: &ThrottleKey) -> String {
+    match key {
+        ThrottleKey::Account(email) => format!("account:{}", email.as_ref().expose_secret().to_lowercase()),
+        ThrottleKey::Ip(ip) => format!("ip:{ip}"),
+        ThrottleKey::TwoFactor(user_id) => format!("2fa:{user_id}"),
+    }
+}
@@ -0,0 +1,38 @@
+use {
+    crate::{
+        domain::{
+            data_stores::{MagicLinkStore, MagicLinkStoreError, MagicLinkToken},
+            email::Email,
+        },
+        services::data_stores::expiring_map::ExpiringMap,
+        utils::auth::MAGIC_LINK_TTL_SECONDS,
+    },
+    secrecy::ExposeSecret,
+    tokio::sync::RwLock,
+    tracing::instrument,
+};
+
+/// Keeps magic link tokens in process memory, for local development and tests.
+#[derive(Default)]
+pub struct HashmapMagicLinkStore {
+    tokens: RwLock<ExpiringMap<String, MagicLinkToken>>,
+}
+
+#[async_trait::async_trait]
+impl MagicLinkStore for HashmapMagicLinkStore {
+    #[instrument(name = "Add magic link token in memory", skip_all)]
+    async fn add_token(&self, email: Email, token: MagicLinkToken) -> Result<(), MagicLinkStoreError> {
+        self.tokens.write().await.insert(get_key(&email), token, MAGIC_LINK_TTL_SECONDS as u64);
+
+        Ok(())
+    }
+
+    #[instrument(name = "Take magic link token in memory", skip_all)]
+    async fn take_token(&self, email: &Email) -> Result<MagicLinkToken, MagicLinkStoreError> {
+        self.tokens.write().await.remove(&get_key(email)).ok_or(MagicLinkStoreError::TokenNotFound)
+    }
+}
+
+fn get_key(email: &Email) -> String {
+    email.as_ref().expose_secret().to_lowercase()
+}
@@ -0,0 +1,61 @@
+use {
+    crate::{
+        domain::{
+            data_stores::{PasskeyCeremonyStore, PasskeyCeremonyStoreError},
+            email::Email,
+        },
+        services::data_stores::expiring_map::ExpiringMap,
+    },
+    secrecy::ExposeSecret,
+    tokio::sync::RwLock,
+    tracing::instrument,
+    webauthn_rs::prelude::{PasskeyAuthentication, PasskeyRegistration},
+};
+
+const FIVE_MINUTES_IN_SECONDS: u64 = 300;
+
+/// Keeps pending passkey ceremonies in process memory, for local development and tests.
+#[derive(Default)]
+pub struct HashmapPasskeyCeremonyStore {
+    registrations: RwLock<ExpiringMap<String, PasskeyRegistration>>,
+    authentications: RwLock<ExpiringMap<String, PasskeyAuthentication>>,
+}
+
+#[async_trait::async_trait]
+impl PasskeyCeremonyStore for HashmapPasskeyCeremonyStore {
+    #[instrument(name = "Add passkey registration in memory", skip_all)]
+    async fn add_registration(
+        &self,
+        email: &Email,
+        state: PasskeyRegistration,
+    ) -> Result<(), PasskeyCeremonyStoreError> {
+        self.registrations.write().await.insert(get_key(email), state, FIVE_MINUTES_IN_SECONDS);
+
+        Ok(())
+    }
+
+    #[instrument(name = "Take passkey registration in memory", skip_all)]
+    async fn take_registration(&self, email: &Email) -> Result<PasskeyRegistration, PasskeyCeremonyStoreError> {
+        self.registrations.write().await.remove(&get_key(email)).ok_or(PasskeyCeremonyStoreError::CeremonyNotFound)
+    }
+
+    #[instrument(name = "Add passkey authentication in memory", skip_all)]
+    async fn add_authentication(
+        &self,
+        email: &Email,
+        state: PasskeyAuthentication,
+    ) -> Result<(), PasskeyCeremonyStoreError> {
+        self.authentications.write().await.insert(get_key(email), state, FIVE_MINUTES_IN_SECONDS);
+
+        Ok(())
+    }
+
+    #[instrument(name = "Take passkey authentication in memory", skip_all)]
+    async fn take_authentication(&self, email: &Email) -> Result<PasskeyAuthentication, PasskeyCeremonyStoreError> {
+        self.authentications.write().await.remove(&get_key(email)).ok_or(PasskeyCeremonyStoreError::CeremonyNotFound)
+    }
+}
+
+fn get_key(email: &Email) -> String {
+    email.as_ref().expose_secret().to_owned()
+}
@@ -0,0 +1,51 @@
+use {
+    crate::domain::data_stores::{PasskeyStore, PasskeyStoreError},
+    std::collections::HashMap,
+    tokio::sync::RwLock,
+    tracing::instrument,
+    uuid::Uuid,
+    webauthn_rs::prelude::Passkey,
+};
+
+/// Keeps passkeys in process memory, for local development and tests.
+#[derive(Default)]
+pub struct HashmapPasskeyStore {
+    /// Passkeys of each user in the order they were registered.
+    passkeys: RwLock<HashMap<Uuid, Vec<Passkey>>>,
+}
+
+#[async_trait::async_trait]
+impl PasskeyStore for HashmapPasskeyStore {
+    #[instrument(name = "Add passkey in memory", skip_all)]
+    async fn add_passkey(&self, user_id: &Uuid, passkey: Passkey) -> Result<(), PasskeyStoreError> {
+        let mut passkeys = self.passkeys.write().await;
+
+        // credential IDs are unique across users, like the primary key of the table
+        if passkeys.values().flatten().any(|existing| existing.cred_id() == passkey.cred_id()) {
+            return Err(PasskeyStoreError::CredentialAlreadyExists);
+        }
+
+        passkeys.entry(*user_id).or_default().push(passkey);
+
+        Ok(())
+    }
+
+    #[instrument(name = "Get passkeys in memory", skip_all)]
+    async fn get_passkeys(&self, user_id: &Uuid) -> Result<Vec<Passkey>, PasskeyStoreError> {
+        Ok(self.passkeys.read().await.get(user_id).cloned().unwrap_or_default())
+    }
+
+    #[instrument(name = "Update passkey in memory", skip_all)]
+    async fn update_passkey(&self, user_id: &Uuid, passkey: &Passkey) -> Result<(), PasskeyStoreError> {
+        let mut passkeys = self.passkeys.write().await;
+
+        if let Some(existing) = passkeys
+            .get_mut(user_id)
+            .and_then(|passkeys| passkeys.iter_mut().find(|existing| existing.cred_id() == passkey.cred_id()))
+        {
+            *existing = passkey.clone();
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,54 @@
+use {
+    crate::{
+        domain::{
+            data_stores::{PasswordResetStore, PasswordResetStoreError, PasswordResetToken},
+            email::Email,
+        },
+        services::data_stores::expiring_map::ExpiringMap,
+    },
+    secrecy::ExposeSecret,
+    tokio::sync::RwLock,
+    tracing::instrument,
+};
+
+const FIFTEEN_MINUTES_IN_SECONDS: u64 = 900;
+
+/// Keeps password reset tokens in process memory, for local development and tests.
+#[derive(Default)]
+pub struct HashmapPasswordResetStore {
+    tokens: RwLock<ExpiringMap<String, PasswordResetToken>>,
+}
+
+#[async_trait::async_trait]
+impl PasswordResetStore for HashmapPasswordResetStore {
+    #[instrument(name = "Add password reset token in memory", skip_all)]
+    async fn add_token(&self, email: Email, token: PasswordResetToken) -> Result<(), PasswordResetStoreError> {
+        self.tokens.write().await.insert(get_key(&email), token, FIFTEEN_MINUTES_IN_SECONDS);
+
+        Ok(())
+    }
+
+    #[instrument(name = "Remove password reset token in memory", skip_all)]
+    async fn remove_token(&self, email: &Email) -> Result<(), PasswordResetStoreError> {
+        self.tokens.write().await.remove(&get_key(email));
+
+        Ok(())
+    }
+
+    #[instrument(name = "Get password reset token in memory", skip_all)]
+    async fn get_token(&self, email: &Email) -> Result<PasswordResetToken, PasswordResetStoreError> {
+        match self.tokens.read().await.get(&get_key(email)) {
+            Some(token) => Ok(token.clone()),
+            None => Err(PasswordResetStoreError::TokenNotFound),
+        }
+    }
+
+    #[instrument(name = "Take password reset token in memory", skip_all)]
+    async fn take_token(&self, email: &Email) -> Result<PasswordResetToken, PasswordResetStoreError> {
+        self.tokens.write().await.remove(&get_key(email)).ok_or(PasswordResetStoreError::TokenNotFound)
+    }
+}
+
+fn get_key(email: &Email) -> String {
+    email.as_ref().expose_secret().to_lowercase()
+}
@@ -0,0 +1,76 @@
+use {
+    crate::domain::{
+        data_stores::{RecoveryCode, RecoveryCodeStore, RecoveryCodeStoreError},
+        password::hash_secret,
+    },
+    argon2::{Argon2, PasswordHash, PasswordVerifier},
+    color_eyre::Result,
+    secrecy::ExposeSecret,
+    std::collections::HashMap,
+    tokio::{sync::RwLock, task::spawn_blocking},
+    tracing::{Span, instrument},
+    uuid::Uuid,
+};
+
+/// Keeps hashed recovery codes in process memory, for local development and tests.
+#[derive(Default)]
+pub struct HashmapRecoveryCodeStore {
+    code_hashes: RwLock<HashMap<Uuid, Vec<String>>>,
+}
+
+#[async_trait::async_trait]
+impl RecoveryCodeStore for HashmapRecoveryCodeStore {
+    #[instrument(name = "Replace recovery codes in memory", skip_all)]
+    async fn replace_codes(&self, user_id: &Uuid, codes: Vec<RecoveryCode>) -> Result<(), RecoveryCodeStoreError> {
+        let current_span = Span::current();
+        let hashes = spawn_blocking(move || {
+            current_span.in_scope(|| {
+                codes.iter().map(|code| hash_secret(code.as_ref().expose_secret())).collect::<Result<Vec<_>>>()
+            })
+        })
+        .await
+        .map_err(|e| RecoveryCodeStoreError::UnexpectedError(e.into()))?
+        .map_err(RecoveryCodeStoreError::UnexpectedError)?;
+
+        self.code_hashes.write().await.insert(*user_id, hashes);
+
+        Ok(())
+    }
+
+    #[instrument(name = "Consume recovery code in memory", skip_all)]
+    async fn consume_code(&self, user_id: &Uuid, code: &RecoveryCode) -> Result<(), RecoveryCodeStoreError> {
+        let hashes = self.code_hashes.read().await.get(user_id).cloned().unwrap_or_default();
+        let current_span = Span::current();
+        let target = code.as_ref().expose_secret().to_owned();
+        let matched = spawn_blocking(move || {
+            current_span.in_scope(|| {
+                hashes.into_iter().find(|code_hash| {
+                    PasswordHash::new(code_hash)
+                        .is_ok_and(|hash| Argon2::default().verify_password(target.as_bytes(), &hash).is_ok())
+                })
+            })
+        })
+        .await
+        .map_err(|e| RecoveryCodeStoreError::UnexpectedError(e.into()))?;
+        let Some(matched) = matched
+        else {
+            return Err(RecoveryCodeStoreError::CodeNotFound);
+        };
+        let mut code_hashes = self.code_hashes.write().await;
+        let Some(position) =
+            code_hashes.get(user_id).and_then(|hashes| hashes.iter().position(|code_hash| *code_hash == matched))
+        else {
+            // a concurrent request may have consumed the same code first
+            return Err(RecoveryCodeStoreError::CodeNotFound);
+        };
+
+        code_hashes.entry(*user_id).or_default().remove(position);
+
+        Ok(())
+    }
+
+    #[instrument(name = "Count recovery codes in memory", skip_all)]
+    async fn count_codes(&self, user_id: &Uuid) -> Result<u64, RecoveryCodeStoreError> {
+        Ok(self.code_hashes.read().await.get(user_id).map_or(0, Vec::len) as u64)
+    }
+}
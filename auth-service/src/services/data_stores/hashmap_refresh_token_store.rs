@@ -0,0 +1,142 @@
+use {
+    crate::{
+        domain::data_stores::{RefreshToken, RefreshTokenStore, RefreshTokenStoreError},
+        services::data_stores::expiring_map::ExpiringMap,
+        utils::auth::REFRESH_TOKEN_TTL_SECONDS,
+    },
+    secrecy::ExposeSecret,
+    std::collections::HashSet,
+    tokio::sync::RwLock,
+    tracing::instrument,
+    uuid::Uuid,
+};
+
+#[derive(Clone, Copy)]
+struct RefreshTokenRecord {
+    user_id: Uuid,
+    family: Uuid,
+    used: bool,
+}
+
+#[derive(Default)]
+struct RefreshTokens {
+    records: ExpiringMap<String, RefreshTokenRecord>,
+    /// Families that can still be refreshed.
+    active_families: ExpiringMap<Uuid, ()>,
+    /// Families issued to each user.
+    user_families: ExpiringMap<Uuid, HashSet<Uuid>>,
+}
+
+/// Keeps refresh token families in process memory, for local development and tests.
+#[derive(Default)]
+pub struct HashmapRefreshTokenStore {
+    tokens: RwLock<RefreshTokens>,
+}
+
+#[async_trait::async_trait]
+impl RefreshTokenStore for HashmapRefreshTokenStore {
+    #[instrument(name = "Add refresh token in memory", skip_all)]
+    async fn add_token(
+        &self,
+        user_id: Uuid,
+        session_id: Uuid,
+        token: RefreshToken,
+    ) -> Result<(), RefreshTokenStoreError> {
+        let ttl_seconds = REFRESH_TOKEN_TTL_SECONDS as u64;
+        let mut tokens = self.tokens.write().await;
+        let mut families = tokens.user_families.remove(&user_id).unwrap_or_default();
+
+        families.insert(session_id);
+        tokens.records.insert(
+            get_key(&token),
+            RefreshTokenRecord { user_id, family: session_id, used: false },
+            ttl_seconds,
+        );
+        tokens.active_families.insert(session_id, (), ttl_seconds);
+        tokens.user_families.insert(user_id, families, ttl_seconds);
+
+        Ok(())
+    }
+
+    #[instrument(name = "Rotate refresh token in memory", skip_all)]
+    async fn rotate_token(
+        &self,
+        token: &RefreshToken,
+        new_token: RefreshToken,
+    ) -> Result<(Uuid, Uuid), RefreshTokenStoreError> {
+        let ttl_seconds = REFRESH_TOKEN_TTL_SECONDS as u64;
+        let mut tokens = self.tokens.write().await;
+        let Some(record) = tokens.records.get(&get_key(token)).copied()
+        else {
+            return Err(RefreshTokenStoreError::TokenNotFound);
+        };
+
+        if !tokens.active_families.contains_key(&record.family) {
+            return Err(RefreshTokenStoreError::TokenNotFound);
+        }
+
+        if record.used {
+            tokens.active_families.remove(&record.family);
+
+            return Err(RefreshTokenStoreError::TokenReused);
+        }
+
+        tokens.records.insert(get_key(token), RefreshTokenRecord { used: true, ..record }, ttl_seconds);
+        tokens.records.insert(get_key(&new_token), record, ttl_seconds);
+        tokens.active_families.expire(&record.family, ttl_seconds);
+
+        Ok((record.user_id, record.family))
+    }
+
+    #[instrument(name = "Revoke refresh token in memory", skip_all)]
+    async fn revoke_token(&self, token: &RefreshToken) -> Result<(), RefreshTokenStoreError> {
+        let mut tokens = self.tokens.write().await;
+
+        if let Some(record) = tokens.records.get(&get_key(token)).copied() {
+            tokens.active_families.remove(&record.family);
+        }
+
+        Ok(())
+    }
+
+    #[instrument(name = "Revoke user refresh tokens in memory", skip_all)]
+    async fn revoke_user(&self, user_id: &Uuid, except: Option<&RefreshToken>) -> Result<(), RefreshTokenStoreError> {
+        let mut tokens = self.tokens.write().await;
+        let except = except.and_then(|token| tokens.records.get(&get_key(token))).map(|record| record.family);
+        let Some(families) = tokens.user_families.get_mut(user_id)
+        else {
+            return Ok(());
+        };
+        let revoked = families.iter().filter(|family| Some(**family) != except).copied().collect::<Vec<_>>();
+
+        families.retain(|family| Some(*family) == except);
+
+        for family in revoked {
+            tokens.active_families.remove(&family);
+        }
+
+        Ok(())
+    }
+
+    #[instrument(name = "Revoke session refresh tokens in memory", skip_all)]
+    async fn revoke_session(&self, session_id: &Uuid) -> Result<(), RefreshTokenStoreError> {
+        self.tokens.write().await.active_families.remove(session_id);
+
+        Ok(())
+    }
+
+    #[instrument(name = "Count refresh token sessions in memory", skip_all)]
+    async fn count_sessions(&self, user_id: &Uuid) -> Result<u64, RefreshTokenStoreError> {
+        let tokens = self.tokens.read().await;
+        let Some(families) = tokens.user_families.get(user_id)
+        else {
+            return Ok(0);
+        };
+
+        Ok(families.iter().filter(|family| tokens.active_families.contains_key(family)).count() as u64)
+    }
+}
+
+fn get_key(token: &RefreshToken) -> String {
+    token.as_ref().expose_secret().to_owned()
+}
@@ -0,0 +1,86 @@
+use {
+    crate::{
+        app_state::UserStoreType,
+        domain::{
+            data_stores::{RoleStore, RoleStoreError, UserStoreError},
+            role::{ADMIN_ROLE, AuditRead, Permission, Role, RolesRead, RolesWrite, UsersRead, UsersWrite},
+        },
+    },
+    std::collections::{BTreeSet, HashMap},
+    tokio::sync::RwLock,
+    tracing::instrument,
+    uuid::Uuid,
+};
+
+/// Keeps role assignments in process memory, for local development and tests.
+pub struct HashmapRoleStore {
+    /// Roles seeded the way the migrations seed the database.
+    roles: Vec<Role>,
+    user_roles: RwLock<HashMap<Uuid, BTreeSet<String>>>,
+    /// Looked up when assigning a role, in place of the foreign key on `user_roles`.
+    user_store: UserStoreType,
+}
+
+impl HashmapRoleStore {
+    pub fn new(user_store: UserStoreType) -> Self {
+        let admin = Role {
+            name: ADMIN_ROLE.to_owned(),
+            description: "Manages users and their roles".to_owned(),
+            permissions: [AuditRead::NAME, RolesRead::NAME, RolesWrite::NAME, UsersRead::NAME, UsersWrite::NAME]
+                .map(str::to_owned)
+                .to_vec(),
+        };
+
+        Self { roles: vec![admin], user_roles: RwLock::default(), user_store }
+    }
+}
+
+#[async_trait::async_trait]
+impl RoleStore for HashmapRoleStore {
+    #[instrument(name = "Get roles in memory", skip_all)]
+    async fn get_roles(&self) -> Result<Vec<Role>, RoleStoreError> {
+        Ok(self.roles.clone())
+    }
+
+    #[instrument(name = "Get user roles in memory", skip_all)]
+    async fn get_user_roles(&self, user_id: &Uuid) -> Result<Vec<String>, RoleStoreError> {
+        Ok(self.user_roles.read().await.get(user_id).map(|roles| roles.iter().cloned().collect()).unwrap_or_default())
+    }
+
+    #[instrument(name = "Assign role in memory", skip_all)]
+    async fn assign_role(&self, user_id: &Uuid, role: &str) -> Result<(), RoleStoreError> {
+        if !self.roles.iter().any(|existing| existing.name == role) {
+            return Err(RoleStoreError::RoleNotFound);
+        }
+
+        self.user_store.get_user_by_id(user_id).await.map_err(|e| match e {
+            UserStoreError::UserNotFound => RoleStoreError::UserNotFound,
+            e => RoleStoreError::UnexpectedError(e.into()),
+        })?;
+
+        self.user_roles.write().await.entry(*user_id).or_default().insert(role.to_owned());
+
+        Ok(())
+    }
+
+    #[instrument(name = "Revoke role in memory", skip_all)]
+    async fn revoke_role(&self, user_id: &Uuid, role: &str) -> Result<(), RoleStoreError> {
+        if let Some(roles) = self.user_roles.write().await.get_mut(user_id) {
+            roles.remove(role);
+        }
+
+        Ok(())
+    }
+
+    #[instrument(name = "Get role permissions in memory", skip_all)]
+    async fn get_permissions(&self, roles: &[String]) -> Result<Vec<String>, RoleStoreError> {
+        let permissions: BTreeSet<_> = self
+            .roles
+            .iter()
+            .filter(|role| roles.contains(&role.name))
+            .flat_map(|role| role.permissions.iter().cloned())
+            .collect();
+
+        Ok(permissions.into_iter().collect())
+    }
+}
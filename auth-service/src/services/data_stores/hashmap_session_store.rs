@@ -0,0 +1,99 @@
+use {
+    crate::{
+        domain::data_stores::{Session, SessionStore, SessionStoreError},
+        services::data_stores::expiring_map::ExpiringMap,
+        utils::auth::REFRESH_TOKEN_TTL_SECONDS,
+    },
+    chrono::Utc,
+    std::collections::HashMap,
+    tokio::sync::RwLock,
+    tracing::instrument,
+    uuid::Uuid,
+};
+
+/// Keeps sessions in process memory, for local development and tests.
+#[derive(Default)]
+pub struct HashmapSessionStore {
+    /// Sessions of each user by session id, dropped together once none was touched for the refresh token lifetime.
+    sessions: RwLock<ExpiringMap<Uuid, HashMap<Uuid, Session>>>,
+}
+
+#[async_trait::async_trait]
+impl SessionStore for HashmapSessionStore {
+    #[instrument(name = "Add session in memory", skip_all)]
+    async fn add_session(&self, session: Session) -> Result<(), SessionStoreError> {
+        let mut sessions = self.sessions.write().await;
+        let user_id = session.user_id;
+
+        match sessions.get_mut(&user_id) {
+            Some(user_sessions) => {
+                user_sessions.insert(session.session_id, session);
+                sessions.expire(&user_id, REFRESH_TOKEN_TTL_SECONDS as u64);
+            }
+            None => sessions.insert(
+                user_id,
+                HashMap::from([(session.session_id, session)]),
+                REFRESH_TOKEN_TTL_SECONDS as u64,
+            ),
+        }
+
+        Ok(())
+    }
+
+    #[instrument(name = "Record session token in memory", skip_all)]
+    async fn record_token(
+        &self,
+        user_id: &Uuid,
+        session_id: &Uuid,
+        jti: String,
+        issued_at: i64,
+    ) -> Result<(), SessionStoreError> {
+        let mut sessions = self.sessions.write().await;
+        let Some(session) = sessions.get_mut(user_id).and_then(|user_sessions| user_sessions.get_mut(session_id))
+        else {
+            return Err(SessionStoreError::SessionNotFound);
+        };
+
+        session.jti = jti;
+        session.refreshed_at = issued_at;
+        sessions.expire(user_id, REFRESH_TOKEN_TTL_SECONDS as u64);
+
+        Ok(())
+    }
+
+    #[instrument(name = "Get sessions in memory", skip_all)]
+    async fn get_sessions(&self, user_id: &Uuid) -> Result<Vec<Session>, SessionStoreError> {
+        let mut sessions = self.sessions.write().await;
+        let Some(user_sessions) = sessions.get_mut(user_id)
+        else {
+            return Ok(Vec::new());
+        };
+        let expired_before = Utc::now().timestamp() - REFRESH_TOKEN_TTL_SECONDS;
+
+        // a session nobody refreshed within the refresh token lifetime cannot be continued
+        user_sessions.retain(|_, session| session.refreshed_at >= expired_before);
+
+        let mut user_sessions = user_sessions.values().cloned().collect::<Vec<_>>();
+
+        user_sessions.sort_by_key(|session| (session.created_at, session.session_id));
+
+        Ok(user_sessions)
+    }
+
+    #[instrument(name = "Remove session in memory", skip_all)]
+    async fn remove_session(&self, user_id: &Uuid, session_id: &Uuid) -> Result<(), SessionStoreError> {
+        match self.sessions.write().await.get_mut(user_id).and_then(|user_sessions| user_sessions.remove(session_id)) {
+            Some(_) => Ok(()),
+            None => Err(SessionStoreError::SessionNotFound),
+        }
+    }
+
+    #[instrument(name = "Remove sessions in memory", skip_all)]
+    async fn remove_sessions(&self, user_id: &Uuid, except: Option<&Uuid>) -> Result<(), SessionStoreError> {
+        if let Some(user_sessions) = self.sessions.write().await.get_mut(user_id) {
+            user_sessions.retain(|session_id, _| Some(session_id) == except);
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,31 @@
+use {
+    crate::{
+        domain::data_stores::{SigningKeyStore, SigningKeyStoreError},
+        utils::keys::SigningKey,
+    },
+    std::collections::HashMap,
+    tokio::sync::RwLock,
+    tracing::instrument,
+};
+
+/// Keeps signing keys in process memory, for local development and tests.
+#[derive(Default)]
+pub struct HashmapSigningKeyStore {
+    /// Keyed by `kid`.
+    keys: RwLock<HashMap<String, SigningKey>>,
+}
+
+#[async_trait::async_trait]
+impl SigningKeyStore for HashmapSigningKeyStore {
+    #[instrument(name = "Add signing key to memory", skip_all)]
+    async fn add_key(&self, key: &SigningKey) -> Result<(), SigningKeyStoreError> {
+        self.keys.write().await.entry(key.key.kid.clone()).or_insert_with(|| key.clone());
+
+        Ok(())
+    }
+
+    #[instrument(name = "Get signing keys from memory", skip_all)]
+    async fn get_keys(&self) -> Result<Vec<SigningKey>, SigningKeyStoreError> {
+        Ok(self.keys.read().await.values().cloned().collect())
+    }
+}
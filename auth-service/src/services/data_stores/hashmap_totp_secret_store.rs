@@ -0,0 +1,80 @@
+use {
+    crate::domain::{
+        data_stores::{TotpSecretStore, TotpSecretStoreError},
+        totp::TotpSecret,
+    },
+    std::collections::HashMap,
+    tokio::sync::RwLock,
+    tracing::instrument,
+    uuid::Uuid,
+};
+
+#[derive(Default)]
+struct TotpSecrets {
+    active: Option<TotpSecret>,
+    pending: Option<TotpSecret>,
+    /// Latest time step a code of the active secret was accepted for.
+    last_time_step: Option<u64>,
+}
+
+/// Keeps TOTP secrets in process memory, for local development and tests. Unlike the database, nothing is
+/// encrypted, since the secrets never leave the process.
+#[derive(Default)]
+pub struct HashmapTotpSecretStore {
+    secrets: RwLock<HashMap<Uuid, TotpSecrets>>,
+}
+
+#[async_trait::async_trait]
+impl TotpSecretStore for HashmapTotpSecretStore {
+    #[instrument(name = "Add pending TOTP secret in memory", skip_all)]
+    async fn add_secret(&self, user_id: &Uuid, secret: TotpSecret) -> Result<(), TotpSecretStoreError> {
+        self.secrets.write().await.entry(*user_id).or_default().pending = Some(secret);
+
+        Ok(())
+    }
+
+    #[instrument(name = "Get pending TOTP secret in memory", skip_all)]
+    async fn get_pending_secret(&self, user_id: &Uuid) -> Result<TotpSecret, TotpSecretStoreError> {
+        match self.secrets.read().await.get(user_id).and_then(|secrets| secrets.pending.as_ref()) {
+            Some(secret) => Ok(secret.clone()),
+            None => Err(TotpSecretStoreError::SecretNotFound),
+        }
+    }
+
+    #[instrument(name = "Confirm TOTP secret in memory", skip_all)]
+    async fn confirm_secret(&self, user_id: &Uuid) -> Result<(), TotpSecretStoreError> {
+        let mut secrets = self.secrets.write().await;
+        let Some(secrets) = secrets.get_mut(user_id).filter(|secrets| secrets.pending.is_some())
+        else {
+            return Err(TotpSecretStoreError::SecretNotFound);
+        };
+
+        secrets.active = secrets.pending.take();
+        secrets.last_time_step = None;
+
+        Ok(())
+    }
+
+    #[instrument(name = "Get TOTP secret in memory", skip_all)]
+    async fn get_secret(&self, user_id: &Uuid) -> Result<TotpSecret, TotpSecretStoreError> {
+        match self.secrets.read().await.get(user_id).and_then(|secrets| secrets.active.as_ref()) {
+            Some(secret) => Ok(secret.clone()),
+            None => Err(TotpSecretStoreError::SecretNotFound),
+        }
+    }
+
+    #[instrument(name = "Record TOTP time step in memory", skip_all)]
+    async fn record_time_step(&self, user_id: &Uuid, time_step: u64) -> Result<(), TotpSecretStoreError> {
+        let mut secrets = self.secrets.write().await;
+        let Some(secrets) = secrets
+            .get_mut(user_id)
+            .filter(|secrets| secrets.active.is_some() && secrets.last_time_step.is_none_or(|last| last < time_step))
+        else {
+            return Err(TotpSecretStoreError::CodeReused);
+        };
+
+        secrets.last_time_step = Some(time_step);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,47 @@
+use {
+    crate::{
+        domain::data_stores::{LoginAttemptId, TwoFactorCode, TwoFactorStore, TwoFactorStoreError},
+        services::data_stores::expiring_map::ExpiringMap,
+    },
+    tokio::sync::RwLock,
+    tracing::instrument,
+    uuid::Uuid,
+};
+
+const TEN_MINUTES_IN_SECONDS: u64 = 600;
+
+/// Keeps pending 2FA codes in process memory, for local development and tests.
+#[derive(Default)]
+pub struct HashmapTwoFactorStore {
+    codes: RwLock<ExpiringMap<Uuid, (LoginAttemptId, TwoFactorCode)>>,
+}
+
+#[async_trait::async_trait]
+impl TwoFactorStore for HashmapTwoFactorStore {
+    #[instrument(name = "Add code in memory", skip_all)]
+    async fn add_code(
+        &self,
+        user_id: Uuid,
+        attempt_id: LoginAttemptId,
+        code: TwoFactorCode,
+    ) -> Result<(), TwoFactorStoreError> {
+        self.codes.write().await.insert(user_id, (attempt_id, code), TEN_MINUTES_IN_SECONDS);
+
+        Ok(())
+    }
+
+    #[instrument(name = "Remove code in memory", skip_all)]
+    async fn remove_code(&self, user_id: &Uuid) -> Result<(), TwoFactorStoreError> {
+        self.codes.write().await.remove(user_id);
+
+        Ok(())
+    }
+
+    #[instrument(name = "Get code in memory", skip_all)]
+    async fn get_code(&self, user_id: &Uuid) -> Result<(LoginAttemptId, TwoFactorCode), TwoFactorStoreError> {
+        match self.codes.read().await.get(user_id) {
+            Some(entry) => Ok(entry.clone()),
+            None => Err(TwoFactorStoreError::LoginAttemptIdNotFound),
+        }
+    }
+}
@@ -0,0 +1,271 @@
+use {
+    crate::domain::{
+        data_stores::{UserStore, UserStoreError},
+        email::Email,
+        password::Password,
+        user::{AccountStatus, TwoFactorMethod, User, UserPage, UserRow},
+    },
+    chrono::Utc,
+    secrecy::ExposeSecret,
+    std::collections::HashMap,
+    tokio::{sync::RwLock, task::spawn_blocking},
+    tracing::{Span, instrument},
+    uuid::Uuid,
+};
+
+/// Keeps users in process memory, for local development and tests.
+#[derive(Default)]
+pub struct HashmapUserStore {
+    /// Users along with the unix timestamp their scheduled deletion is due at.
+    users: RwLock<HashMap<Uuid, (UserRow, Option<i64>)>>,
+    /// Token versions of users that had theirs bumped; the rest are at 0.
+    token_versions: RwLock<HashMap<Uuid, u64>>,
+}
+
+impl HashmapUserStore {
+    /// Applies `update` to the user registered under `email`, whether or not its deletion is scheduled.
+    async fn update(&self, email: &Email, update: impl FnOnce(&mut UserRow)) -> Result<(), UserStoreError> {
+        let mut users = self.users.write().await;
+        let user_id = find_user_id(&users, email.as_ref().expose_secret()).ok_or(UserStoreError::UserNotFound)?;
+
+        if let Some((user, _)) = users.get_mut(&user_id) {
+            update(user);
+        }
+
+        Ok(())
+    }
+}
+
+#[async_trait::async_trait]
+impl UserStore for HashmapUserStore {
+    #[instrument(name = "Add user in memory", skip_all)]
+    async fn add_user(&self, user: User) -> Result<(), UserStoreError> {
+        let user = user.into_row().await.map_err(UserStoreError::UnexpectedError)?;
+        let mut users = self.users.write().await;
+
+        if find_user_id(&users, &user.email).is_some() {
+            return Err(UserStoreError::UserAlreadyExists);
+        }
+
+        users.insert(user.user_id, (user, None));
+
+        Ok(())
+    }
+
+    #[instrument(name = "Get user in memory", skip_all)]
+    async fn get_user(&self, email: &Email) -> Result<UserRow, UserStoreError> {
+        let users = self.users.read().await;
+
+        match find_user_id(&users, email.as_ref().expose_secret()).and_then(|user_id| users.get(&user_id)) {
+            Some((user, None)) => Ok(user.clone()),
+            _ => Err(UserStoreError::UserNotFound),
+        }
+    }
+
+    #[instrument(name = "Get user by id in memory", skip_all)]
+    async fn get_user_by_id(&self, user_id: &Uuid) -> Result<UserRow, UserStoreError> {
+        match self.users.read().await.get(user_id) {
+            Some((user, None)) => Ok(user.clone()),
+            _ => Err(UserStoreError::UserNotFound),
+        }
+    }
+
+    #[instrument(name = "Validate user credentials in memory", skip_all)]
+    async fn validate_user(&self, email: &Email, password: &Password) -> Result<(), UserStoreError> {
+        let user = self.get_user(email).await?;
+
+        user.verify_password_hash(password.as_ref()).await.map_err(UserStoreError::UnexpectedError)?;
+
+        Ok(())
+    }
+
+    #[instrument(name = "Update user password in memory", skip_all)]
+    async fn update_password(&self, email: &Email, password: Password) -> Result<(), UserStoreError> {
+        let current_span = Span::current();
+        let password_hash = spawn_blocking(move || current_span.in_scope(|| password.hash()))
+            .await
+            .map_err(|e| UserStoreError::UnexpectedError(e.into()))?
+            .map_err(UserStoreError::UnexpectedError)?;
+
+        self.update(email, |user| {
+            user.password_hash = password_hash;
+            user.password_reset_required = false;
+        })
+        .await
+    }
+
+    #[instrument(name = "Mark user email as verified in memory", skip_all)]
+    async fn verify_email(&self, email: &Email) -> Result<(), UserStoreError> {
+        self.update(email, |user| user.email_verified = true).await
+    }
+
+    #[instrument(name = "Update user 2FA method in memory", skip_all)]
+    async fn update_two_factor_method(&self, email: &Email, method: TwoFactorMethod) -> Result<(), UserStoreError> {
+        self.update(email, |user| user.two_factor_method = method).await
+    }
+
+    #[instrument(name = "Update email in memory", skip_all)]
+    async fn update_email(&self, user_id: &Uuid, email: &Email) -> Result<(), UserStoreError> {
+        let mut users = self.users.write().await;
+        let email = email.as_ref().expose_secret();
+
+        if find_user_id(&users, email).is_some_and(|other_id| other_id != *user_id) {
+            return Err(UserStoreError::UserAlreadyExists);
+        }
+
+        let Some((user, _)) = users.get_mut(user_id)
+        else {
+            return Err(UserStoreError::UserNotFound);
+        };
+
+        user.email = email.to_owned();
+        user.email_verified = true;
+
+        Ok(())
+    }
+
+    #[instrument(name = "List users in memory", skip_all)]
+    async fn list_users(&self, search: Option<&str>, offset: u64, limit: u64) -> Result<UserPage, UserStoreError> {
+        let search = search.map(str::to_lowercase);
+        let users = self.users.read().await;
+        let mut matching: Vec<_> = users
+            .values()
+            .filter(|(user, delete_after)| {
+                delete_after.is_none() &&
+                    search.as_ref().is_none_or(|search| user.email.to_lowercase().contains(search.as_str()))
+            })
+            .map(|(user, _)| user.clone())
+            .collect();
+
+        matching.sort_by_key(|user| user.email.to_lowercase());
+
+        Ok(UserPage {
+            total: matching.len() as u64,
+            users: matching.into_iter().skip(offset as usize).take(limit as usize).collect(),
+        })
+    }
+
+    #[instrument(name = "Update user status in memory", skip_all)]
+    async fn update_status(
+        &self,
+        user_id: &Uuid,
+        status: AccountStatus,
+        reason: Option<String>,
+        suspended_until: Option<i64>,
+    ) -> Result<(), UserStoreError> {
+        match self.users.write().await.get_mut(user_id) {
+            Some((user, None)) => {
+                user.status = status;
+                user.status_reason = reason;
+                user.suspended_until = suspended_until;
+
+                Ok(())
+            }
+            _ => Err(UserStoreError::UserNotFound),
+        }
+    }
+
+    #[instrument(name = "Require user password reset in memory", skip_all)]
+    async fn require_password_reset(&self, user_id: &Uuid) -> Result<(), UserStoreError> {
+        match self.users.write().await.get_mut(user_id) {
+            Some((user, None)) => {
+                user.password_reset_required = true;
+
+                Ok(())
+            }
+            _ => Err(UserStoreError::UserNotFound),
+        }
+    }
+
+    #[instrument(name = "Schedule user deletion in memory", skip_all)]
+    async fn schedule_deletion(&self, user_id: &Uuid, grace_seconds: u64) -> Result<(), UserStoreError> {
+        match self.users.write().await.get_mut(user_id) {
+            Some((_, delete_after @ None)) => {
+                *delete_after = Some(Utc::now().timestamp() + grace_seconds as i64);
+
+                Ok(())
+            }
+            _ => Err(UserStoreError::UserNotFound),
+        }
+    }
+
+    #[instrument(name = "Get deleted user in memory", skip_all)]
+    async fn get_deleted_user(&self, email: &Email) -> Result<UserRow, UserStoreError> {
+        let users = self.users.read().await;
+        let now = Utc::now().timestamp();
+
+        match find_user_id(&users, email.as_ref().expose_secret()).and_then(|user_id| users.get(&user_id)) {
+            Some((user, Some(delete_after))) if *delete_after > now => Ok(user.clone()),
+            _ => Err(UserStoreError::UserNotFound),
+        }
+    }
+
+    #[instrument(name = "Restore user in memory", skip_all)]
+    async fn restore_user(&self, user_id: &Uuid) -> Result<(), UserStoreError> {
+        let now = Utc::now().timestamp();
+
+        match self.users.write().await.get_mut(user_id) {
+            Some((_, delete_after)) if delete_after.is_some_and(|delete_after| delete_after > now) => {
+                *delete_after = None;
+
+                Ok(())
+            }
+            _ => Err(UserStoreError::UserNotFound),
+        }
+    }
+
+    #[instrument(name = "Get token version in memory", skip_all)]
+    async fn get_token_version(&self, user_id: &Uuid) -> Result<u64, UserStoreError> {
+        if !self.users.read().await.contains_key(user_id) {
+            return Err(UserStoreError::UserNotFound);
+        }
+
+        Ok(self.token_versions.read().await.get(user_id).copied().unwrap_or_default())
+    }
+
+    #[instrument(name = "Bump token version in memory", skip_all)]
+    async fn bump_token_version(&self, user_id: &Uuid, known: u64) -> Result<u64, UserStoreError> {
+        if !self.users.read().await.contains_key(user_id) {
+            return Err(UserStoreError::UserNotFound);
+        }
+
+        let mut token_versions = self.token_versions.write().await;
+        let version = token_versions.entry(*user_id).or_default();
+
+        *version = (*version).max(known) + 1;
+
+        Ok(*version)
+    }
+
+    #[instrument(name = "Delete user in memory", skip_all)]
+    async fn delete_user(&self, user_id: &Uuid) -> Result<(), UserStoreError> {
+        match self.users.write().await.remove(user_id) {
+            Some(_) => Ok(()),
+            None => Err(UserStoreError::UserNotFound),
+        }
+    }
+
+    #[instrument(name = "Purge deleted users in memory", skip_all)]
+    async fn purge_deleted_users(&self) -> Result<Vec<Uuid>, UserStoreError> {
+        let now = Utc::now().timestamp();
+        let mut users = self.users.write().await;
+        let purged: Vec<_> = users
+            .iter()
+            .filter(|(_, (_, delete_after))| delete_after.is_some_and(|delete_after| delete_after <= now))
+            .map(|(user_id, _)| *user_id)
+            .collect();
+
+        for user_id in &purged {
+            users.remove(user_id);
+        }
+
+        Ok(purged)
+    }
+}
+
+/// Looks the user up the way the unique index on `lower(email)` does.
+fn find_user_id(users: &HashMap<Uuid, (UserRow, Option<i64>)>, email: &str) -> Option<Uuid> {
+    let email = email.to_lowercase();
+
+    users.values().find(|(user, _)| user.email.to_lowercase() == email).map(|(user, _)| user.user_id)
+}
@@ -0,0 +1,73 @@
+use {
+    crate::{
+        domain::data_stores::{BannedTokenStore, BannedTokenStoreError},
+        services::data_stores::expiring_map::ExpiringMap,
+        utils::auth::TOKEN_TTL_SECONDS,
+    },
+    chrono::Utc,
+    secrecy::SecretBox,
+    std::collections::HashMap,
+    tokio::sync::RwLock,
+    tracing::instrument,
+    uuid::Uuid,
+};
+
+/// Keeps bans in process memory, for local development and tests.
+#[derive(Default)]
+pub struct HashsetBannedTokenStore {
+    /// `jti`s of banned tokens, each kept until the token expires.
+    jtis: RwLock<ExpiringMap<String, ()>>,
+    /// Copies of the token versions the user store keeps.
+    token_versions: RwLock<HashMap<Uuid, u64>>,
+    revoked_sessions: RwLock<ExpiringMap<Uuid, ()>>,
+}
+
+#[async_trait::async_trait]
+impl BannedTokenStore for HashsetBannedTokenStore {
+    #[instrument(name = "Check token in memory", skip_all)]
+    async fn check(&self, jti: &str, _token: &SecretBox<String>) -> Result<bool, BannedTokenStoreError> {
+        // bans were always keyed by jti here, so there are no raw tokens to match
+        Ok(self.jtis.read().await.contains_key(&jti.to_owned()))
+    }
+
+    #[instrument(name = "Add token in memory", skip_all)]
+    async fn register(&self, jti: &str, expires_at: i64) -> Result<(), BannedTokenStoreError> {
+        let remaining_seconds = expires_at - Utc::now().timestamp();
+
+        // an expired token is rejected anyway
+        if remaining_seconds <= 0 {
+            return Ok(());
+        }
+
+        self.jtis.write().await.insert(jti.to_owned(), (), remaining_seconds as u64);
+
+        Ok(())
+    }
+
+    #[instrument(name = "Get token version in memory", skip_all)]
+    async fn get_token_version(&self, user_id: &Uuid) -> Result<Option<u64>, BannedTokenStoreError> {
+        Ok(self.token_versions.read().await.get(user_id).copied())
+    }
+
+    #[instrument(name = "Cache token version in memory", skip_all)]
+    async fn cache_token_version(&self, user_id: &Uuid, version: u64) -> Result<(), BannedTokenStoreError> {
+        let mut token_versions = self.token_versions.write().await;
+        let cached = token_versions.entry(*user_id).or_default();
+
+        *cached = (*cached).max(version);
+
+        Ok(())
+    }
+
+    #[instrument(name = "Revoke session tokens in memory", skip_all)]
+    async fn revoke_session(&self, session_id: &Uuid) -> Result<(), BannedTokenStoreError> {
+        self.revoked_sessions.write().await.insert(*session_id, (), TOKEN_TTL_SECONDS as u64);
+
+        Ok(())
+    }
+
+    #[instrument(name = "Check session tokens in memory", skip_all)]
+    async fn check_session(&self, session_id: &Uuid) -> Result<bool, BannedTokenStoreError> {
+        Ok(self.revoked_sessions.read().await.contains_key(session_id))
+    }
+}
@@ -0,0 +1,72 @@
+use {
+    crate::domain::{
+        audit::{AuditEvent, AuditEventFilter, AuditEventPage, AuditEventType},
+        data_stores::{AuditLog, AuditLogError},
+    },
+    std::net::{IpAddr, Ipv4Addr},
+    tokio::sync::RwLock,
+    tracing::instrument,
+    uuid::Uuid,
+};
+
+/// Keeps audit events in process memory, for local development and tests.
+#[derive(Default)]
+pub struct InMemoryAuditLog {
+    /// Events in the order they were recorded.
+    events: RwLock<Vec<AuditEvent>>,
+}
+
+#[async_trait::async_trait]
+impl AuditLog for InMemoryAuditLog {
+    #[instrument(name = "Record audit event in memory", skip_all)]
+    async fn record(&self, event: AuditEvent) -> Result<(), AuditLogError> {
+        self.events.write().await.push(event);
+
+        Ok(())
+    }
+
+    #[instrument(name = "Query audit events in memory", skip_all)]
+    async fn query(&self, filter: &AuditEventFilter, offset: u64, limit: u64) -> Result<AuditEventPage, AuditLogError> {
+        let events = self.events.read().await;
+        let matching: Vec<_> = events
+            .iter()
+            .rev()
+            .filter(|event| {
+                filter.user_id.is_none_or(|user_id| event.user_id == Some(user_id)) &&
+                    filter.event_type.is_none_or(|event_type| event.event_type == event_type)
+            })
+            .collect();
+
+        Ok(AuditEventPage {
+            total: matching.len() as u64,
+            events: matching.into_iter().skip(offset as usize).take(limit as usize).cloned().collect(),
+        })
+    }
+
+    #[instrument(name = "Anonymise user in audit log in memory", skip_all)]
+    async fn anonymise_user(&self, user_id: &Uuid) -> Result<(), AuditLogError> {
+        let user_id = Some(*user_id);
+
+        for event in self.events.write().await.iter_mut() {
+            if event.user_id != user_id && event.actor_id != user_id {
+                continue;
+            }
+
+            // the address and user agent are those of whoever made the request: the admin for admin actions
+            if event.actor_id == user_id || event.event_type != AuditEventType::AdminAction {
+                event.ip = IpAddr::V4(Ipv4Addr::UNSPECIFIED);
+                event.user_agent = None;
+            }
+
+            if event.user_id == user_id {
+                event.user_id = None;
+            }
+
+            if event.actor_id == user_id {
+                event.actor_id = None;
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,43 @@
+use {
+    crate::domain::{
+        data_stores::{RateLimitStore, RateLimitStoreError},
+        rate_limit::{RateLimitDecision, RateLimitPolicy, TokenBucket},
+    },
+    chrono::Utc,
+    std::collections::HashMap,
+    tokio::sync::RwLock,
+    tracing::instrument,
+};
+
+/// Buckets kept before full ones are evicted.
+const MAX_BUCKETS: usize = 10_000;
+
+/// Keeps buckets in process memory, so limits are not shared between instances.
+#[derive(Default)]
+pub struct InMemoryRateLimitStore {
+    /// Buckets along with the unix timestamp in milliseconds at which they are full again.
+    buckets: RwLock<HashMap<String, (TokenBucket, i64)>>,
+}
+
+#[async_trait::async_trait]
+impl RateLimitStore for InMemoryRateLimitStore {
+    #[instrument(name = "Acquire rate limit token in memory", skip_all)]
+    async fn acquire(&self, key: &str, policy: &RateLimitPolicy) -> Result<RateLimitDecision, RateLimitStoreError> {
+        let now = Utc::now().timestamp_millis();
+        let mut buckets = self.buckets.write().await;
+
+        if buckets.len() >= MAX_BUCKETS {
+            buckets.retain(|_, (_, full_at)| *full_at > now);
+        }
+
+        let bucket = match buckets.get(key) {
+            Some((bucket, _)) => *bucket,
+            None => TokenBucket::new(policy, now),
+        };
+        let (bucket, decision) = bucket.take(policy, now);
+
+        buckets.insert(key.to_owned(), (bucket, now + decision.reset_after as i64 * 1000));
+
+        Ok(decision)
+    }
+}
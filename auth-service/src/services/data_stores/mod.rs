@@ -1,5 +1,53 @@
+mod expiring_map;
+mod hashmap_email_change_store;
+mod hashmap_email_verification_store;
+mod hashmap_login_throttle_store;
+mod hashmap_magic_link_store;
+mod hashmap_passkey_ceremony_store;
+mod hashmap_passkey_store;
+mod hashmap_password_reset_store;
+mod hashmap_recovery_code_store;
+mod hashmap_refresh_token_store;
+mod hashmap_role_store;
+mod hashmap_session_store;
+mod hashmap_signing_key_store;
+mod hashmap_totp_secret_store;
+mod hashmap_two_factor_store;
+mod hashmap_user_store;
+mod hashset_banned_token_store;
+mod in_memory_audit_log;
+mod in_memory_rate_limit_store;
+mod postgres_audit_log;
+mod postgres_passkey_store;
+mod postgres_recovery_code_store;
+mod postgres_refresh_token_store;
+mod postgres_role_store;
+mod postgres_signing_key_store;
+mod postgres_totp_secret_store;
 mod postgres_user_store;
 mod redis_banned_token_store;
+mod redis_email_change_store;
+mod redis_email_verification_store;
+mod redis_login_throttle_store;
+mod redis_magic_link_store;
+mod redis_passkey_ceremony_store;
+mod redis_password_reset_store;
+mod redis_rate_limit_store;
+mod redis_refresh_token_store;
+mod redis_session_store;
 mod redis_two_factor_store;
+mod user_status_cache;
 
-pub use {postgres_user_store::*, redis_banned_token_store::*, redis_two_factor_store::*};
+pub use {
+    hashmap_email_change_store::*, hashmap_email_verification_store::*, hashmap_login_throttle_store::*,
+    hashmap_magic_link_store::*, hashmap_passkey_ceremony_store::*, hashmap_passkey_store::*,
+    hashmap_password_reset_store::*, hashmap_recovery_code_store::*, hashmap_refresh_token_store::*,
+    hashmap_role_store::*, hashmap_session_store::*, hashmap_signing_key_store::*, hashmap_totp_secret_store::*,
+    hashmap_two_factor_store::*, hashmap_user_store::*, hashset_banned_token_store::*, in_memory_audit_log::*,
+    in_memory_rate_limit_store::*, postgres_audit_log::*, postgres_passkey_store::*, postgres_recovery_code_store::*,
+    postgres_refresh_token_store::*, postgres_role_store::*, postgres_signing_key_store::*,
+    postgres_totp_secret_store::*, postgres_user_store::*, redis_banned_token_store::*, redis_email_change_store::*,
+    redis_email_verification_store::*, redis_login_throttle_store::*, redis_magic_link_store::*,
+    redis_passkey_ceremony_store::*, redis_password_reset_store::*, redis_rate_limit_store::*,
+    redis_refresh_token_store::*, redis_session_store::*, redis_two_factor_store::*, user_status_cache::*,
+};
@@ -0,0 +1,123 @@
+use {
+    crate::domain::{
+        audit::{AuditEvent, AuditEventFilter, AuditEventPage, AuditEventType},
+        data_stores::{AuditLog, AuditLogError},
+    },
+    sqlx::{PgPool, query, query_as},
+    std::net::AddrParseError,
+    tracing::instrument,
+    uuid::Uuid,
+};
+
+pub struct PostgresAuditLog {
+    pool: PgPool,
+}
+
+impl PostgresAuditLog {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+/// Audit event as read from the database, which keeps addresses as `inet`.
+struct AuditEventRow {
+    event_type: AuditEventType,
+    occurred_at: i64,
+    user_id: Option<Uuid>,
+    actor_id: Option<Uuid>,
+    ip: String,
+    user_agent: Option<String>,
+    request_id: Uuid,
+    details: Option<String>,
+}
+
+#[async_trait::async_trait]
+impl AuditLog for PostgresAuditLog {
+    #[instrument(name = "Record audit event in database", skip_all)]
+    async fn record(&self, event: AuditEvent) -> Result<(), AuditLogError> {
+        query!(
+            r#"insert into audit_events
+            (event_type, occurred_at, user_id, actor_id, ip, user_agent, request_id, details)
+            values ($1, to_timestamp($2::bigint), $3, $4, $5::text::inet, $6, $7, $8);"#,
+            event.event_type as AuditEventType,
+            event.occurred_at,
+            event.user_id,
+            event.actor_id,
+            event.ip.to_string(),
+            event.user_agent,
+            event.request_id,
+            event.details,
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|e| AuditLogError::UnexpectedError(e.into()))?;
+
+        Ok(())
+    }
+
+    #[instrument(name = "Query audit events from database", skip_all)]
+    async fn query(&self, filter: &AuditEventFilter, offset: u64, limit: u64) -> Result<AuditEventPage, AuditLogError> {
+        let rows = query_as!(
+            AuditEventRow,
+            r#"select event_type as "event_type: AuditEventType",
+            extract(epoch from occurred_at)::bigint as "occurred_at!", user_id, actor_id, host(ip) as "ip!",
+            user_agent, request_id, details
+            from audit_events
+            where ($1::uuid is null or user_id = $1) and ($2::audit_event_type is null or event_type = $2)
+            order by id desc limit $3 offset $4;"#,
+            filter.user_id,
+            filter.event_type as Option<AuditEventType>,
+            limit as i64,
+            offset as i64,
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| AuditLogError::UnexpectedError(e.into()))?;
+        let total = query!(
+            r#"select count(*) as "count!" from audit_events
+            where ($1::uuid is null or user_id = $1) and ($2::audit_event_type is null or event_type = $2);"#,
+            filter.user_id,
+            filter.event_type as Option<AuditEventType>,
+        )
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| AuditLogError::UnexpectedError(e.into()))?
+        .count;
+        let events = rows
+            .into_iter()
+            .map(|row| {
+                Ok(AuditEvent {
+                    event_type: row.event_type,
+                    occurred_at: row.occurred_at,
+                    user_id: row.user_id,
+                    actor_id: row.actor_id,
+                    ip: row.ip.parse().map_err(|e: AddrParseError| AuditLogError::UnexpectedError(e.into()))?,
+                    user_agent: row.user_agent,
+                    request_id: row.request_id,
+                    details: row.details,
+                })
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(AuditEventPage { events, total: total as u64 })
+    }
+
+    #[instrument(name = "Anonymise user in audit log in database", skip_all)]
+    async fn anonymise_user(&self, user_id: &Uuid) -> Result<(), AuditLogError> {
+        // the address and user agent are those of whoever made the request: the admin for admin actions
+        query!(
+            r#"update audit_events set
+            user_id = case when user_id = $1 then null else user_id end,
+            actor_id = case when actor_id = $1 then null else actor_id end,
+            ip = case when actor_id = $1 or event_type <> 'admin_action' then '0.0.0.0'::inet else ip end,
+            user_agent = case when actor_id = $1 or event_type <> 'admin_action' then null else user_agent end
+            where user_id = $1 or actor_id = $1;"#,
+            user_id,
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|e| AuditLogError::UnexpectedError(e.into()))?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,71 @@
+use {
+    crate::domain::data_stores::{PasskeyStore, PasskeyStoreError},
+    serde_json::{from_value, to_value},
+    sqlx::{PgPool, query},
+    tracing::instrument,
+    uuid::Uuid,
+    webauthn_rs::prelude::Passkey,
+};
+
+pub struct PostgresPasskeyStore {
+    pool: PgPool,
+}
+
+impl PostgresPasskeyStore {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait::async_trait]
+impl PasskeyStore for PostgresPasskeyStore {
+    #[instrument(name = "Add passkey to database", skip_all)]
+    async fn add_passkey(&self, user_id: &Uuid, passkey: Passkey) -> Result<(), PasskeyStoreError> {
+        let value = to_value(&passkey).map_err(|e| PasskeyStoreError::UnexpectedError(e.into()))?;
+        let result = query!(
+            r#"insert into passkeys (credential_id, user_id, passkey) values ($1, $2, $3)
+            on conflict (credential_id) do nothing;"#,
+            passkey.cred_id().as_ref(),
+            user_id,
+            value,
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|e| PasskeyStoreError::UnexpectedError(e.into()))?;
+
+        if result.rows_affected() == 0 {
+            return Err(PasskeyStoreError::CredentialAlreadyExists);
+        }
+
+        Ok(())
+    }
+
+    #[instrument(name = "Get passkeys from database", skip_all)]
+    async fn get_passkeys(&self, user_id: &Uuid) -> Result<Vec<Passkey>, PasskeyStoreError> {
+        let rows = query!(r#"select passkey from passkeys where user_id = $1 order by created_at;"#, user_id)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|e| PasskeyStoreError::UnexpectedError(e.into()))?;
+
+        rows.into_iter()
+            .map(|row| from_value(row.passkey).map_err(|e| PasskeyStoreError::UnexpectedError(e.into())))
+            .collect()
+    }
+
+    #[instrument(name = "Update passkey in database", skip_all)]
+    async fn update_passkey(&self, user_id: &Uuid, passkey: &Passkey) -> Result<(), PasskeyStoreError> {
+        let value = to_value(passkey).map_err(|e| PasskeyStoreError::UnexpectedError(e.into()))?;
+
+        query!(
+            r#"update passkeys set passkey = $3 where credential_id = $1 and user_id = $2;"#,
+            passkey.cred_id().as_ref(),
+            user_id,
+            value,
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|e| PasskeyStoreError::UnexpectedError(e.into()))?;
+
+        Ok(())
+    }
+}
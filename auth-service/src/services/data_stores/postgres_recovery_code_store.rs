@@ -0,0 +1,101 @@
+use {
+    crate::domain::{
+        data_stores::{RecoveryCode, RecoveryCodeStore, RecoveryCodeStoreError},
+        password::hash_secret,
+    },
+    argon2::{Argon2, PasswordHash, PasswordVerifier},
+    color_eyre::Result,
+    secrecy::ExposeSecret,
+    sqlx::{PgPool, query, query_scalar},
+    tokio::task::spawn_blocking,
+    tracing::{Span, instrument},
+    uuid::Uuid,
+};
+
+pub struct PostgresRecoveryCodeStore {
+    pool: PgPool,
+}
+
+impl PostgresRecoveryCodeStore {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait::async_trait]
+impl RecoveryCodeStore for PostgresRecoveryCodeStore {
+    #[instrument(name = "Replace recovery codes in database", skip_all)]
+    async fn replace_codes(&self, user_id: &Uuid, codes: Vec<RecoveryCode>) -> Result<(), RecoveryCodeStoreError> {
+        let current_span = Span::current();
+        let hashes = spawn_blocking(move || {
+            current_span.in_scope(|| {
+                codes.iter().map(|code| hash_secret(code.as_ref().expose_secret())).collect::<Result<Vec<_>>>()
+            })
+        })
+        .await
+        .map_err(|e| RecoveryCodeStoreError::UnexpectedError(e.into()))?
+        .map_err(RecoveryCodeStoreError::UnexpectedError)?;
+        let mut transaction = self.pool.begin().await.map_err(|e| RecoveryCodeStoreError::UnexpectedError(e.into()))?;
+
+        query!(r#"delete from recovery_codes where user_id = $1;"#, user_id)
+            .execute(&mut *transaction)
+            .await
+            .map_err(|e| RecoveryCodeStoreError::UnexpectedError(e.into()))?;
+
+        query!(r#"insert into recovery_codes (user_id, code_hash) select $1, unnest($2::text[]);"#, user_id, &hashes,)
+            .execute(&mut *transaction)
+            .await
+            .map_err(|e| RecoveryCodeStoreError::UnexpectedError(e.into()))?;
+
+        transaction.commit().await.map_err(|e| RecoveryCodeStoreError::UnexpectedError(e.into()))?;
+
+        Ok(())
+    }
+
+    #[instrument(name = "Consume recovery code in database", skip_all)]
+    async fn consume_code(&self, user_id: &Uuid, code: &RecoveryCode) -> Result<(), RecoveryCodeStoreError> {
+        let rows = query!(r#"select id, code_hash from recovery_codes where user_id = $1;"#, user_id)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|e| RecoveryCodeStoreError::UnexpectedError(e.into()))?;
+        let current_span = Span::current();
+        let target = code.as_ref().expose_secret().to_owned();
+        let matched = spawn_blocking(move || {
+            current_span.in_scope(|| {
+                rows.into_iter()
+                    .find(|row| {
+                        PasswordHash::new(&row.code_hash)
+                            .is_ok_and(|hash| Argon2::default().verify_password(target.as_bytes(), &hash).is_ok())
+                    })
+                    .map(|row| row.id)
+            })
+        })
+        .await
+        .map_err(|e| RecoveryCodeStoreError::UnexpectedError(e.into()))?;
+        let Some(id) = matched
+        else {
+            return Err(RecoveryCodeStoreError::CodeNotFound);
+        };
+        let result = query!(r#"delete from recovery_codes where id = $1;"#, id)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| RecoveryCodeStoreError::UnexpectedError(e.into()))?;
+
+        // a concurrent request may have consumed the same code first
+        if result.rows_affected() == 0 {
+            return Err(RecoveryCodeStoreError::CodeNotFound);
+        }
+
+        Ok(())
+    }
+
+    #[instrument(name = "Count recovery codes in database", skip_all)]
+    async fn count_codes(&self, user_id: &Uuid) -> Result<u64, RecoveryCodeStoreError> {
+        let count = query_scalar!(r#"select count(*) as "count!" from recovery_codes where user_id = $1;"#, user_id)
+            .fetch_one(&self.pool)
+            .await
+            .map_err(|e| RecoveryCodeStoreError::UnexpectedError(e.into()))?;
+
+        Ok(count as u64)
+    }
+}
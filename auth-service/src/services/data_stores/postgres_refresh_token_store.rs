@@ -0,0 +1,153 @@
+use {
+    crate::{
+        domain::data_stores::{RefreshToken, RefreshTokenStore, RefreshTokenStoreError},
+        utils::auth::REFRESH_TOKEN_TTL_SECONDS,
+    },
+    sqlx::{PgPool, query, query_scalar},
+    tracing::instrument,
+    uuid::Uuid,
+};
+
+pub struct PostgresRefreshTokenStore {
+    pool: PgPool,
+}
+
+impl PostgresRefreshTokenStore {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait::async_trait]
+impl RefreshTokenStore for PostgresRefreshTokenStore {
+    #[instrument(name = "Add refresh token to database", skip_all)]
+    async fn add_token(
+        &self,
+        user_id: Uuid,
+        session_id: Uuid,
+        token: RefreshToken,
+    ) -> Result<(), RefreshTokenStoreError> {
+        query!(
+            r#"insert into refresh_tokens (token_hash, family_id, user_id, expires_at)
+            values ($1, $2, $3, now() + make_interval(secs => $4));"#,
+            token.hash(),
+            session_id,
+            user_id,
+            REFRESH_TOKEN_TTL_SECONDS as f64,
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|e| RefreshTokenStoreError::UnexpectedError(e.into()))?;
+
+        Ok(())
+    }
+
+    #[instrument(name = "Rotate refresh token in database", skip_all)]
+    async fn rotate_token(
+        &self,
+        token: &RefreshToken,
+        new_token: RefreshToken,
+    ) -> Result<(Uuid, Uuid), RefreshTokenStoreError> {
+        let mut transaction = self.pool.begin().await.map_err(|e| RefreshTokenStoreError::UnexpectedError(e.into()))?;
+        let Some(row) = query!(
+            r#"select user_id, family_id, used, revoked, expires_at > now() as "active!" from refresh_tokens
+            where token_hash = $1 for update;"#,
+            token.hash(),
+        )
+        .fetch_optional(&mut *transaction)
+        .await
+        .map_err(|e| RefreshTokenStoreError::UnexpectedError(e.into()))?
+        else {
+            return Err(RefreshTokenStoreError::TokenNotFound);
+        };
+
+        if row.revoked || !row.active {
+            return Err(RefreshTokenStoreError::TokenNotFound);
+        }
+
+        if row.used {
+            query!(
+                r#"update refresh_tokens set revoked = true
+                where family_id = (select family_id from refresh_tokens where token_hash = $1);"#,
+                token.hash(),
+            )
+            .execute(&mut *transaction)
+            .await
+            .map_err(|e| RefreshTokenStoreError::UnexpectedError(e.into()))?;
+            transaction.commit().await.map_err(|e| RefreshTokenStoreError::UnexpectedError(e.into()))?;
+
+            return Err(RefreshTokenStoreError::TokenReused);
+        }
+
+        query!(r#"update refresh_tokens set used = true where token_hash = $1;"#, token.hash())
+            .execute(&mut *transaction)
+            .await
+            .map_err(|e| RefreshTokenStoreError::UnexpectedError(e.into()))?;
+        query!(
+            r#"insert into refresh_tokens (token_hash, family_id, user_id, expires_at)
+            select $2, family_id, user_id, now() + make_interval(secs => $3) from refresh_tokens where token_hash = $1;"#,
+            token.hash(),
+            new_token.hash(),
+            REFRESH_TOKEN_TTL_SECONDS as f64,
+        )
+        .execute(&mut *transaction)
+        .await
+        .map_err(|e| RefreshTokenStoreError::UnexpectedError(e.into()))?;
+        transaction.commit().await.map_err(|e| RefreshTokenStoreError::UnexpectedError(e.into()))?;
+
+        Ok((row.user_id, row.family_id))
+    }
+
+    #[instrument(name = "Revoke refresh token in database", skip_all)]
+    async fn revoke_token(&self, token: &RefreshToken) -> Result<(), RefreshTokenStoreError> {
+        query!(
+            r#"update refresh_tokens set revoked = true
+            where family_id = (select family_id from refresh_tokens where token_hash = $1);"#,
+            token.hash(),
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|e| RefreshTokenStoreError::UnexpectedError(e.into()))?;
+
+        Ok(())
+    }
+
+    #[instrument(name = "Revoke user refresh tokens in database", skip_all)]
+    async fn revoke_user(&self, user_id: &Uuid, except: Option<&RefreshToken>) -> Result<(), RefreshTokenStoreError> {
+        query!(
+            r#"update refresh_tokens set revoked = true
+            where user_id = $1 and family_id not in (select family_id from refresh_tokens where token_hash = $2);"#,
+            user_id,
+            except.map(RefreshToken::hash),
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|e| RefreshTokenStoreError::UnexpectedError(e.into()))?;
+
+        Ok(())
+    }
+
+    #[instrument(name = "Revoke session refresh tokens in database", skip_all)]
+    async fn revoke_session(&self, session_id: &Uuid) -> Result<(), RefreshTokenStoreError> {
+        query!(r#"update refresh_tokens set revoked = true where family_id = $1;"#, session_id)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| RefreshTokenStoreError::UnexpectedError(e.into()))?;
+
+        Ok(())
+    }
+
+    #[instrument(name = "Count refresh token sessions in database", skip_all)]
+    async fn count_sessions(&self, user_id: &Uuid) -> Result<u64, RefreshTokenStoreError> {
+        let count = query_scalar!(
+            r#"select count(distinct family_id) as "count!" from refresh_tokens
+            where user_id = $1 and not used and not revoked and expires_at > now();"#,
+            user_id
+        )
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| RefreshTokenStoreError::UnexpectedError(e.into()))?;
+
+        Ok(count as u64)
+    }
+}
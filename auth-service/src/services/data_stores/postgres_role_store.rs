@@ -0,0 +1,88 @@
+use {
+    crate::domain::{
+        data_stores::{RoleStore, RoleStoreError},
+        role::Role,
+    },
+    sqlx::{Error as SqlxError, PgPool, query, query_as},
+    tracing::instrument,
+    uuid::Uuid,
+};
+
+pub struct PostgresRoleStore {
+    pool: PgPool,
+}
+
+impl PostgresRoleStore {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait::async_trait]
+impl RoleStore for PostgresRoleStore {
+    #[instrument(name = "Get roles from database", skip_all)]
+    async fn get_roles(&self) -> Result<Vec<Role>, RoleStoreError> {
+        query_as!(
+            Role,
+            r#"select roles.name, roles.description,
+            array_remove(array_agg(role_permissions.permission order by role_permissions.permission), null)
+            as "permissions!"
+            from roles left join role_permissions on role_permissions.role = roles.name
+            group by roles.name order by roles.name;"#
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| RoleStoreError::UnexpectedError(e.into()))
+    }
+
+    #[instrument(name = "Get user roles from database", skip_all)]
+    async fn get_user_roles(&self, user_id: &Uuid) -> Result<Vec<String>, RoleStoreError> {
+        let rows = query!(r#"select role from user_roles where user_id = $1 order by role;"#, user_id)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|e| RoleStoreError::UnexpectedError(e.into()))?;
+
+        Ok(rows.into_iter().map(|row| row.role).collect())
+    }
+
+    #[instrument(name = "Assign role in database", skip_all)]
+    async fn assign_role(&self, user_id: &Uuid, role: &str) -> Result<(), RoleStoreError> {
+        query!(r#"insert into user_roles (user_id, role) values ($1, $2) on conflict do nothing;"#, user_id, role,)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| match e {
+                SqlxError::Database(e) if e.constraint() == Some("user_roles_role_fkey") => {
+                    RoleStoreError::RoleNotFound
+                }
+                SqlxError::Database(e) if e.constraint() == Some("user_roles_user_id_fkey") => {
+                    RoleStoreError::UserNotFound
+                }
+                e => RoleStoreError::UnexpectedError(e.into()),
+            })?;
+
+        Ok(())
+    }
+
+    #[instrument(name = "Revoke role in database", skip_all)]
+    async fn revoke_role(&self, user_id: &Uuid, role: &str) -> Result<(), RoleStoreError> {
+        query!(r#"delete from user_roles where user_id = $1 and role = $2;"#, user_id, role)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| RoleStoreError::UnexpectedError(e.into()))?;
+
+        Ok(())
+    }
+
+    #[instrument(name = "Get role permissions from database", skip_all)]
+    async fn get_permissions(&self, roles: &[String]) -> Result<Vec<String>, RoleStoreError> {
+        let rows = query!(
+            r#"select distinct permission from role_permissions where role = any($1) order by permission;"#,
+            roles,
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| RoleStoreError::UnexpectedError(e.into()))?;
+
+        Ok(rows.into_iter().map(|row| row.permission).collect())
+    }
+}
@@ -0,0 +1,75 @@
+use {
+    crate::{
+        domain::data_stores::{SigningKeyStore, SigningKeyStoreError},
+        utils::{
+            encryption::{decrypt, encrypt},
+            keys::{JwtKey, SigningKey},
+        },
+    },
+    color_eyre::eyre::WrapErr,
+    secrecy::{ExposeSecret, SecretBox},
+    sqlx::{PgPool, query},
+    std::sync::Arc,
+    tracing::instrument,
+};
+
+pub struct PostgresSigningKeyStore {
+    pool: PgPool,
+    encryption_key: SecretBox<String>,
+}
+
+impl PostgresSigningKeyStore {
+    pub fn new(pool: PgPool, encryption_key: &SecretBox<String>) -> Self {
+        Self { pool, encryption_key: SecretBox::new(Box::new(encryption_key.expose_secret().to_owned())) }
+    }
+}
+
+#[async_trait::async_trait]
+impl SigningKeyStore for PostgresSigningKeyStore {
+    #[instrument(name = "Add signing key to database", skip_all)]
+    async fn add_key(&self, key: &SigningKey) -> Result<(), SigningKeyStoreError> {
+        let encrypted = encrypt(key.key.pkcs8(), &self.encryption_key)
+            .wrap_err("Failed to encrypt signing key")
+            .map_err(SigningKeyStoreError::UnexpectedError)?;
+
+        query!(
+            r#"insert into jwt_signing_keys (kid, private_key, activates_at, retired_at)
+            values ($1, $2, to_timestamp($3::bigint), to_timestamp($4::bigint))
+            on conflict (kid) do nothing;"#,
+            key.key.kid,
+            encrypted,
+            key.activates_at,
+            key.retired_at,
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|e| SigningKeyStoreError::UnexpectedError(e.into()))?;
+
+        Ok(())
+    }
+
+    #[instrument(name = "Get signing keys from database", skip_all)]
+    async fn get_keys(&self) -> Result<Vec<SigningKey>, SigningKeyStoreError> {
+        let rows = query!(
+            r#"select private_key, extract(epoch from activates_at)::bigint as "activates_at!",
+            extract(epoch from retired_at)::bigint as retired_at from jwt_signing_keys;"#
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| SigningKeyStoreError::UnexpectedError(e.into()))?;
+
+        rows.into_iter()
+            .map(|row| {
+                let pkcs8 =
+                    decrypt(&row.private_key, &self.encryption_key).wrap_err("Failed to decrypt signing key")?;
+
+                Ok(SigningKey {
+                    key: Arc::new(JwtKey::from_pkcs8(&pkcs8)?),
+                    activates_at: row.activates_at,
+                    retired_at: row.retired_at,
+                })
+            })
+            .collect::<color_eyre::Result<_>>()
+            .map_err(SigningKeyStoreError::UnexpectedError)
+    }
+}
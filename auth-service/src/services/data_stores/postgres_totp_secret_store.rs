@@ -0,0 +1,106 @@
+use {
+    crate::domain::{
+        data_stores::{TotpSecretStore, TotpSecretStoreError},
+        totp::TotpSecret,
+    },
+    secrecy::{ExposeSecret, SecretBox},
+    sqlx::{PgPool, query},
+    tracing::instrument,
+    uuid::Uuid,
+};
+
+pub struct PostgresTotpSecretStore {
+    pool: PgPool,
+    encryption_key: SecretBox<String>,
+}
+
+impl PostgresTotpSecretStore {
+    pub fn new(pool: PgPool, encryption_key: &SecretBox<String>) -> Self {
+        Self { pool, encryption_key: SecretBox::new(Box::new(encryption_key.expose_secret().to_owned())) }
+    }
+}
+
+#[async_trait::async_trait]
+impl TotpSecretStore for PostgresTotpSecretStore {
+    #[instrument(name = "Add pending TOTP secret to database", skip_all)]
+    async fn add_secret(&self, user_id: &Uuid, secret: TotpSecret) -> Result<(), TotpSecretStoreError> {
+        let encrypted = secret.encrypt(&self.encryption_key).map_err(TotpSecretStoreError::UnexpectedError)?;
+
+        query!(
+            r#"insert into totp_secrets (user_id, pending_secret) values ($1, $2)
+            on conflict (user_id) do update set pending_secret = excluded.pending_secret;"#,
+            user_id,
+            encrypted,
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|e| TotpSecretStoreError::UnexpectedError(e.into()))?;
+
+        Ok(())
+    }
+
+    #[instrument(name = "Get pending TOTP secret from database", skip_all)]
+    async fn get_pending_secret(&self, user_id: &Uuid) -> Result<TotpSecret, TotpSecretStoreError> {
+        let row = query!(r#"select pending_secret from totp_secrets where user_id = $1;"#, user_id)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(|e| TotpSecretStoreError::UnexpectedError(e.into()))?;
+        let Some(encrypted) = row.and_then(|row| row.pending_secret)
+        else {
+            return Err(TotpSecretStoreError::SecretNotFound);
+        };
+
+        TotpSecret::decrypt(&encrypted, &self.encryption_key).map_err(TotpSecretStoreError::UnexpectedError)
+    }
+
+    #[instrument(name = "Confirm TOTP secret in database", skip_all)]
+    async fn confirm_secret(&self, user_id: &Uuid) -> Result<(), TotpSecretStoreError> {
+        let result = query!(
+            r#"update totp_secrets set secret = pending_secret, pending_secret = null, last_time_step = null
+            where user_id = $1 and pending_secret is not null;"#,
+            user_id,
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|e| TotpSecretStoreError::UnexpectedError(e.into()))?;
+
+        if result.rows_affected() == 0 {
+            return Err(TotpSecretStoreError::SecretNotFound);
+        }
+
+        Ok(())
+    }
+
+    #[instrument(name = "Get TOTP secret from database", skip_all)]
+    async fn get_secret(&self, user_id: &Uuid) -> Result<TotpSecret, TotpSecretStoreError> {
+        let row = query!(r#"select secret from totp_secrets where user_id = $1;"#, user_id)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(|e| TotpSecretStoreError::UnexpectedError(e.into()))?;
+        let Some(encrypted) = row.and_then(|row| row.secret)
+        else {
+            return Err(TotpSecretStoreError::SecretNotFound);
+        };
+
+        TotpSecret::decrypt(&encrypted, &self.encryption_key).map_err(TotpSecretStoreError::UnexpectedError)
+    }
+
+    #[instrument(name = "Record TOTP time step in database", skip_all)]
+    async fn record_time_step(&self, user_id: &Uuid, time_step: u64) -> Result<(), TotpSecretStoreError> {
+        let result = query!(
+            r#"update totp_secrets set last_time_step = $2
+            where user_id = $1 and secret is not null and (last_time_step is null or last_time_step < $2);"#,
+            user_id,
+            time_step as i64,
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|e| TotpSecretStoreError::UnexpectedError(e.into()))?;
+
+        if result.rows_affected() == 0 {
+            return Err(TotpSecretStoreError::CodeReused);
+        }
+
+        Ok(())
+    }
+}
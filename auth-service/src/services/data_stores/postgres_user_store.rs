@@ -3,11 +3,13 @@ use {
         data_stores::{UserStore, UserStoreError},
         email::Email,
         password::Password,
-        user::{User, UserRow},
+        user::{AccountStatus, TwoFactorMethod, User, UserPage, UserRow},
     },
     secrecy::ExposeSecret,
-    sqlx::{PgPool, query_as},
-    tracing::instrument,
+    sqlx::{Error as SqlxError, PgPool, query, query_as, query_scalar},
+    tokio::task::spawn_blocking,
+    tracing::{Span, instrument},
+    uuid::Uuid,
 };
 
 pub struct PostgresUserStore {
@@ -24,38 +26,326 @@ impl PostgresUserStore {
 impl UserStore for PostgresUserStore {
     #[instrument(name = "Add user to database", skip_all)]
     async fn add_user(&self, user: User) -> Result<(), UserStoreError> {
-        let user = user.into_row().await.map_err(|e| UserStoreError::UnexpectedError(e.into()))?;
+        let user = user.into_row().await.map_err(UserStoreError::UnexpectedError)?;
 
         query_as!(
             UserRow,
-            r#"insert into users values ($1, $2, $3) returning *;"#,
+            r#"insert into users (user_id, email, password_hash, two_factor_method, email_verified, status)
+            values ($1, $2, $3, $4, $5, $6)
+            returning user_id, email, password_hash, two_factor_method as "two_factor_method: TwoFactorMethod",
+            email_verified, status as "status: AccountStatus", status_reason,
+            extract(epoch from suspended_until)::bigint as suspended_until, password_reset_required;"#,
+            user.user_id,
             user.email,
             user.password_hash,
-            user.requires_2fa,
+            user.two_factor_method as TwoFactorMethod,
+            user.email_verified,
+            user.status as AccountStatus,
         )
         .fetch_one(&self.pool)
         .await
-        .map_err(|e| UserStoreError::UnexpectedError(e.into()))?;
+        .map_err(|e| match e {
+            SqlxError::Database(e) if e.is_unique_violation() => UserStoreError::UserAlreadyExists,
+            e => UserStoreError::UnexpectedError(e.into()),
+        })?;
 
         Ok(())
     }
 
     #[instrument(name = "Get user from database", skip_all)]
     async fn get_user(&self, email: &Email) -> Result<UserRow, UserStoreError> {
-        let user_row = query_as!(UserRow, r#"select * from users where email = $1;"#, email.as_ref().expose_secret())
-            .fetch_one(&self.pool)
-            .await
-            .map_err(|e| UserStoreError::UnexpectedError(e.into()))?;
+        query_as!(
+            UserRow,
+            r#"select user_id, email, password_hash, two_factor_method as "two_factor_method: TwoFactorMethod",
+            email_verified, status as "status: AccountStatus", status_reason,
+            extract(epoch from suspended_until)::bigint as suspended_until, password_reset_required
+            from users where lower(email) = lower($1) and delete_after is null;"#,
+            email.as_ref().expose_secret()
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| UserStoreError::UnexpectedError(e.into()))?
+        .ok_or(UserStoreError::UserNotFound)
+    }
 
-        Ok(user_row)
+    #[instrument(name = "Get user by id from database", skip_all)]
+    async fn get_user_by_id(&self, user_id: &Uuid) -> Result<UserRow, UserStoreError> {
+        query_as!(
+            UserRow,
+            r#"select user_id, email, password_hash, two_factor_method as "two_factor_method: TwoFactorMethod",
+            email_verified, status as "status: AccountStatus", status_reason,
+            extract(epoch from suspended_until)::bigint as suspended_until, password_reset_required
+            from users where user_id = $1 and delete_after is null;"#,
+            user_id
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| UserStoreError::UnexpectedError(e.into()))?
+        .ok_or(UserStoreError::UserNotFound)
     }
 
     #[instrument(name = "Validate user credentials in database", skip_all)]
     async fn validate_user(&self, email: &Email, password: &Password) -> Result<(), UserStoreError> {
         let user = self.get_user(email).await?;
 
-        user.verify_password_hash(password.as_ref()).await.map_err(|e| UserStoreError::UnexpectedError(e.into()))?;
+        user.verify_password_hash(password.as_ref()).await.map_err(UserStoreError::UnexpectedError)?;
+
+        Ok(())
+    }
+
+    #[instrument(name = "Update user password in database", skip_all)]
+    async fn update_password(&self, email: &Email, password: Password) -> Result<(), UserStoreError> {
+        let current_span = Span::current();
+        let password_hash = spawn_blocking(move || current_span.in_scope(|| password.hash()))
+            .await
+            .map_err(|e| UserStoreError::UnexpectedError(e.into()))?
+            .map_err(UserStoreError::UnexpectedError)?;
+        let result = query!(
+            r#"update users set password_hash = $2, password_reset_required = false where lower(email) = lower($1);"#,
+            email.as_ref().expose_secret(),
+            password_hash,
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|e| UserStoreError::UnexpectedError(e.into()))?;
+
+        if result.rows_affected() == 0 {
+            return Err(UserStoreError::UserNotFound);
+        }
 
         Ok(())
     }
+
+    #[instrument(name = "Mark user email as verified in database", skip_all)]
+    async fn verify_email(&self, email: &Email) -> Result<(), UserStoreError> {
+        let result = query!(
+            r#"update users set email_verified = true where lower(email) = lower($1);"#,
+            email.as_ref().expose_secret()
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|e| UserStoreError::UnexpectedError(e.into()))?;
+
+        if result.rows_affected() == 0 {
+            return Err(UserStoreError::UserNotFound);
+        }
+
+        Ok(())
+    }
+
+    #[instrument(name = "Update user 2FA method in database", skip_all)]
+    async fn update_two_factor_method(&self, email: &Email, method: TwoFactorMethod) -> Result<(), UserStoreError> {
+        let result = query!(
+            r#"update users set two_factor_method = $2 where lower(email) = lower($1);"#,
+            email.as_ref().expose_secret(),
+            method as TwoFactorMethod,
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|e| UserStoreError::UnexpectedError(e.into()))?;
+
+        if result.rows_affected() == 0 {
+            return Err(UserStoreError::UserNotFound);
+        }
+
+        Ok(())
+    }
+    #[instrument(name = "Update email in database", skip_all)]
+    async fn update_email(&self, user_id: &Uuid, email: &Email) -> Result<(), UserStoreError> {
+        let result = query!(
+            r#"update users set email = $2, email_verified = true where user_id = $1;"#,
+            user_id,
+            email.as_ref().expose_secret(),
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|e| match e {
+            SqlxError::Database(e) if e.is_unique_violation() => UserStoreError::UserAlreadyExists,
+            e => UserStoreError::UnexpectedError(e.into()),
+        })?;
+
+        if result.rows_affected() == 0 {
+            return Err(UserStoreError::UserNotFound);
+        }
+
+        Ok(())
+    }
+
+    #[instrument(name = "List users from database", skip_all)]
+    async fn list_users(&self, search: Option<&str>, offset: u64, limit: u64) -> Result<UserPage, UserStoreError> {
+        let pattern = search.map(|search| format!("%{}%", escape_like(search)));
+        let users = query_as!(
+            UserRow,
+            r#"select user_id, email, password_hash, two_factor_method as "two_factor_method: TwoFactorMethod",
+            email_verified, status as "status: AccountStatus", status_reason,
+            extract(epoch from suspended_until)::bigint as suspended_until, password_reset_required
+            from users where delete_after is null and ($1::text is null or email ilike $1)
+            order by lower(email) limit $2 offset $3;"#,
+            pattern,
+            limit as i64,
+            offset as i64,
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| UserStoreError::UnexpectedError(e.into()))?;
+        let total = query!(
+            r#"select count(*) as "count!" from users
+            where delete_after is null and ($1::text is null or email ilike $1);"#,
+            pattern,
+        )
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| UserStoreError::UnexpectedError(e.into()))?
+        .count;
+
+        Ok(UserPage { users, total: total as u64 })
+    }
+
+    #[instrument(name = "Update user status in database", skip_all)]
+    async fn update_status(
+        &self,
+        user_id: &Uuid,
+        status: AccountStatus,
+        reason: Option<String>,
+        suspended_until: Option<i64>,
+    ) -> Result<(), UserStoreError> {
+        let result = query!(
+            r#"update users set status = $2, status_reason = $3, suspended_until = to_timestamp($4::bigint)
+            where user_id = $1 and delete_after is null;"#,
+            user_id,
+            status as AccountStatus,
+            reason,
+            suspended_until,
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|e| UserStoreError::UnexpectedError(e.into()))?;
+
+        if result.rows_affected() == 0 {
+            return Err(UserStoreError::UserNotFound);
+        }
+
+        Ok(())
+    }
+
+    #[instrument(name = "Require user password reset in database", skip_all)]
+    async fn require_password_reset(&self, user_id: &Uuid) -> Result<(), UserStoreError> {
+        let result = query!(
+            r#"update users set password_reset_required = true where user_id = $1 and delete_after is null;"#,
+            user_id,
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|e| UserStoreError::UnexpectedError(e.into()))?;
+
+        if result.rows_affected() == 0 {
+            return Err(UserStoreError::UserNotFound);
+        }
+
+        Ok(())
+    }
+
+    #[instrument(name = "Schedule user deletion in database", skip_all)]
+    async fn schedule_deletion(&self, user_id: &Uuid, grace_seconds: u64) -> Result<(), UserStoreError> {
+        let result = query!(
+            r#"update users set delete_after = now() + make_interval(secs => $2)
+            where user_id = $1 and delete_after is null;"#,
+            user_id,
+            grace_seconds as f64,
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|e| UserStoreError::UnexpectedError(e.into()))?;
+
+        if result.rows_affected() == 0 {
+            return Err(UserStoreError::UserNotFound);
+        }
+
+        Ok(())
+    }
+
+    #[instrument(name = "Get deleted user from database", skip_all)]
+    async fn get_deleted_user(&self, email: &Email) -> Result<UserRow, UserStoreError> {
+        query_as!(
+            UserRow,
+            r#"select user_id, email, password_hash, two_factor_method as "two_factor_method: TwoFactorMethod",
+            email_verified, status as "status: AccountStatus", status_reason,
+            extract(epoch from suspended_until)::bigint as suspended_until, password_reset_required
+            from users where lower(email) = lower($1) and delete_after > now();"#,
+            email.as_ref().expose_secret()
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| UserStoreError::UnexpectedError(e.into()))?
+        .ok_or(UserStoreError::UserNotFound)
+    }
+
+    #[instrument(name = "Restore user in database", skip_all)]
+    async fn restore_user(&self, user_id: &Uuid) -> Result<(), UserStoreError> {
+        let result =
+            query!(r#"update users set delete_after = null where user_id = $1 and delete_after > now();"#, user_id)
+                .execute(&self.pool)
+                .await
+                .map_err(|e| UserStoreError::UnexpectedError(e.into()))?;
+
+        if result.rows_affected() == 0 {
+            return Err(UserStoreError::UserNotFound);
+        }
+
+        Ok(())
+    }
+
+    #[instrument(name = "Get token version from database", skip_all)]
+    async fn get_token_version(&self, user_id: &Uuid) -> Result<u64, UserStoreError> {
+        let version = query_scalar!(r#"select token_version from users where user_id = $1;"#, user_id)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(|e| UserStoreError::UnexpectedError(e.into()))?
+            .ok_or(UserStoreError::UserNotFound)?;
+
+        Ok(version as u64)
+    }
+
+    #[instrument(name = "Bump token version in database", skip_all)]
+    async fn bump_token_version(&self, user_id: &Uuid, known: u64) -> Result<u64, UserStoreError> {
+        let version = query_scalar!(
+            r#"update users set token_version = greatest(token_version, $2) + 1 where user_id = $1
+            returning token_version;"#,
+            user_id,
+            known as i64,
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| UserStoreError::UnexpectedError(e.into()))?
+        .ok_or(UserStoreError::UserNotFound)?;
+
+        Ok(version as u64)
+    }
+
+    #[instrument(name = "Delete user from database", skip_all)]
+    async fn delete_user(&self, user_id: &Uuid) -> Result<(), UserStoreError> {
+        let result = query!(r#"delete from users where user_id = $1;"#, user_id)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| UserStoreError::UnexpectedError(e.into()))?;
+
+        if result.rows_affected() == 0 {
+            return Err(UserStoreError::UserNotFound);
+        }
+
+        Ok(())
+    }
+
+    #[instrument(name = "Purge deleted users from database", skip_all)]
+    async fn purge_deleted_users(&self) -> Result<Vec<Uuid>, UserStoreError> {
+        query_scalar!(r#"delete from users where delete_after <= now() returning user_id;"#)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|e| UserStoreError::UnexpectedError(e.into()))
+    }
+}
+
+/// Escapes the wildcards of `like` patterns, so searches match them literally.
+fn escape_like(search: &str) -> String {
+    search.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_")
 }
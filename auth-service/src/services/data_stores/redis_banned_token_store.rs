@@ -3,59 +3,141 @@ use {
         domain::data_stores::{BannedTokenStore, BannedTokenStoreError},
         utils::auth::TOKEN_TTL_SECONDS,
     },
-    redis::{Connection, TypedCommands},
+    chrono::Utc,
+    redis::{AsyncTypedCommands, Script, aio::ConnectionManager},
     secrecy::{ExposeSecret, SecretBox},
-    tokio::sync::RwLock,
+    std::num::ParseIntError,
     tracing::instrument,
+    uuid::Uuid,
 };
 
-const BANNED_TOKEN_KEY_PREFIX: &str = "banned_token:";
+const BANNED_JTI_KEY_PREFIX: &str = "banned_jti:";
+/// Bans used to be keyed by the raw token. They are still checked until the last of them expires, at most
+/// `TOKEN_TTL_SECONDS` after the upgrade, and can be dropped afterwards.
+const LEGACY_BANNED_TOKEN_KEY_PREFIX: &str = "banned_token:";
+const TOKEN_VERSION_KEY_PREFIX: &str = "token_version:";
+const REVOKED_SESSION_KEY_PREFIX: &str = "revoked_session:";
+/// Token versions are kept by the user store, so a cached one can expire and be read again on the next request.
+const TOKEN_VERSION_CACHE_SECONDS: u64 = 3600;
+
+/// Caches the version `ARGV[1]` under `KEYS[1]` for `ARGV[2]` seconds unless a higher one is cached, so a read that
+/// raced a bump cannot cache the version the bump replaced. Versions cached before the user store kept them have no
+/// expiry and are carried over by the next bump.
+const CACHE_VERSION_SCRIPT: &str = r#"
+local cached = tonumber(redis.call('GET', KEYS[1]))
+
+if cached and cached >= tonumber(ARGV[1]) then
+    return 0
+end
+
+redis.call('SET', KEYS[1], ARGV[1], 'EX', ARGV[2])
+
+return 1
+"#;
 
 pub struct RedisBannedTokenStore {
-    connection: RwLock<Connection>,
+    connection: ConnectionManager,
+    cache_version_script: Script,
 }
 
 impl RedisBannedTokenStore {
-    pub fn new(connection: Connection) -> Self {
-        Self { connection: RwLock::new(connection) }
+    pub fn new(connection: ConnectionManager) -> Self {
+        Self { connection, cache_version_script: Script::new(CACHE_VERSION_SCRIPT) }
     }
 }
 
 #[async_trait::async_trait]
 impl BannedTokenStore for RedisBannedTokenStore {
     #[instrument(name = "Check token in redis", skip_all)]
-    async fn check(&self, token: &SecretBox<String>) -> Result<bool, BannedTokenStoreError> {
-        let mut connection = self.connection.write().await;
+    async fn check(&self, jti: &str, token: &SecretBox<String>) -> Result<bool, BannedTokenStoreError> {
+        let mut connection = self.connection.clone();
 
-        match connection.exists(get_key(token)) {
+        match connection.exists(vec![get_key(jti), get_legacy_key(token)]).await {
             Ok(exists) => Ok(exists),
             Err(e) => Err(BannedTokenStoreError::UnexpectedError(e.into())),
         }
     }
 
     #[instrument(name = "Add token to redis", skip_all)]
-    async fn register(&self, tokens: Vec<&SecretBox<String>>) -> Result<(), BannedTokenStoreError> {
-        let mut connection = self.connection.write().await;
-
-        for token in tokens {
-            let exists = match connection.exists(get_key(token)) {
-                Ok(v) => v,
-                Err(e) => return Err(BannedTokenStoreError::UnexpectedError(e.into())),
-            };
-
-            if exists {
-                continue;
-            }
-
-            if let Err(e) = connection.set_ex(get_key(token), true, TOKEN_TTL_SECONDS as u64) {
-                return Err(BannedTokenStoreError::UnexpectedError(e.into()));
-            }
+    async fn register(&self, jti: &str, expires_at: i64) -> Result<(), BannedTokenStoreError> {
+        let remaining_seconds = expires_at - Utc::now().timestamp();
+
+        // an expired token is rejected anyway
+        if remaining_seconds <= 0 {
+            return Ok(());
+        }
+
+        let mut connection = self.connection.clone();
+
+        if let Err(e) = connection.set_ex(get_key(jti), true, remaining_seconds as u64).await {
+            return Err(BannedTokenStoreError::UnexpectedError(e.into()));
+        }
+
+        Ok(())
+    }
+
+    #[instrument(name = "Get token version from redis", skip_all)]
+    async fn get_token_version(&self, user_id: &Uuid) -> Result<Option<u64>, BannedTokenStoreError> {
+        let mut connection = self.connection.clone();
+        let version = match connection.get(get_version_key(user_id)).await {
+            Ok(Some(v)) => v,
+            Ok(None) => return Ok(None),
+            Err(e) => return Err(BannedTokenStoreError::UnexpectedError(e.into())),
+        };
+
+        version.parse().map(Some).map_err(|e: ParseIntError| BannedTokenStoreError::UnexpectedError(e.into()))
+    }
+
+    #[instrument(name = "Cache token version in redis", skip_all)]
+    async fn cache_token_version(&self, user_id: &Uuid, version: u64) -> Result<(), BannedTokenStoreError> {
+        let mut connection = self.connection.clone();
+
+        self.cache_version_script
+            .key(get_version_key(user_id))
+            .arg(version)
+            .arg(TOKEN_VERSION_CACHE_SECONDS)
+            .invoke_async::<bool>(&mut connection)
+            .await
+            .map_err(|e| BannedTokenStoreError::UnexpectedError(e.into()))?;
+
+        Ok(())
+    }
+
+    #[instrument(name = "Revoke session tokens in redis", skip_all)]
+    async fn revoke_session(&self, session_id: &Uuid) -> Result<(), BannedTokenStoreError> {
+        let mut connection = self.connection.clone();
+
+        // the session's refresh tokens are revoked alongside, so no token issued later belongs to it
+        if let Err(e) = connection.set_ex(get_session_key(session_id), true, TOKEN_TTL_SECONDS as u64).await {
+            return Err(BannedTokenStoreError::UnexpectedError(e.into()));
         }
 
         Ok(())
     }
+
+    #[instrument(name = "Check session tokens in redis", skip_all)]
+    async fn check_session(&self, session_id: &Uuid) -> Result<bool, BannedTokenStoreError> {
+        let mut connection = self.connection.clone();
+
+        match connection.exists(get_session_key(session_id)).await {
+            Ok(exists) => Ok(exists),
+            Err(e) => Err(BannedTokenStoreError::UnexpectedError(e.into())),
+        }
+    }
+}
+
+fn get_key(jti: &str) -> String {
+    format!("{BANNED_JTI_KEY_PREFIX}{jti}")
+}
+
+fn get_legacy_key(token: &SecretBox<String>) -> String {
+    format!("{LEGACY_BANNED_TOKEN_KEY_PREFIX}{}", token.expose_secret())
+}
+
+fn get_version_key(user_id: &Uuid) -> String {
+    format!("{TOKEN_VERSION_KEY_PREFIX}{user_id}")
 }
 
-fn get_key(token: &SecretBox<String>) -> String {
-    format!("{BANNED_TOKEN_KEY_PREFIX}{}", token.expose_secret())
+fn get_session_key(session_id: &Uuid) -> String {
+    format!("{REVOKED_SESSION_KEY_PREFIX}{session_id}")
 }
@@ -0,0 +1,90 @@
+use {
+    crate::domain::{
+        data_stores::{EmailChangeStore, EmailChangeStoreError, EmailChangeToken, PendingEmailChange},
+        email::Email,
+    },
+    color_eyre::eyre::eyre,
+    redis::{AsyncTypedCommands, aio::ConnectionManager},
+    secrecy::{ExposeSecret, SecretBox},
+    serde::{Deserialize, Serialize},
+    serde_json::{from_str, to_string},
+    tracing::instrument,
+    uuid::Uuid,
+};
+
+const ONE_DAY_IN_SECONDS: u64 = 86400;
+const EMAIL_CHANGE_PREFIX: &str = "email_change:";
+
+#[derive(Serialize, Deserialize)]
+struct EmailChangeRecord {
+    new_email: String,
+    confirm_token: String,
+    cancel_token: String,
+}
+
+pub struct RedisEmailChangeStore {
+    connection: ConnectionManager,
+}
+
+impl RedisEmailChangeStore {
+    pub fn new(connection: ConnectionManager) -> Self {
+        Self { connection }
+    }
+}
+
+#[async_trait::async_trait]
+impl EmailChangeStore for RedisEmailChangeStore {
+    #[instrument(name = "Add email change to redis", skip_all)]
+    async fn add_change(&self, user_id: Uuid, change: PendingEmailChange) -> Result<(), EmailChangeStoreError> {
+        let record = EmailChangeRecord {
+            new_email: change.new_email.as_ref().expose_secret().to_owned(),
+            confirm_token: change.confirm_token.as_ref().expose_secret().to_owned(),
+            cancel_token: change.cancel_token.as_ref().expose_secret().to_owned(),
+        };
+        let value = to_string(&record).map_err(|e| EmailChangeStoreError::UnexpectedError(e.into()))?;
+        let mut connection = self.connection.clone();
+
+        if let Err(e) = connection.set_ex(get_key(&user_id), value, ONE_DAY_IN_SECONDS).await {
+            return Err(EmailChangeStoreError::UnexpectedError(e.into()));
+        }
+
+        Ok(())
+    }
+
+    #[instrument(name = "Get email change from redis", skip_all)]
+    async fn get_change(&self, user_id: &Uuid) -> Result<PendingEmailChange, EmailChangeStoreError> {
+        let mut connection = self.connection.clone();
+        let value = match connection.get(get_key(user_id)).await {
+            Ok(Some(v)) => v,
+            Ok(None) => return Err(EmailChangeStoreError::ChangeNotFound),
+            Err(e) => return Err(EmailChangeStoreError::UnexpectedError(e.into())),
+        };
+        let record =
+            from_str::<EmailChangeRecord>(&value).map_err(|e| EmailChangeStoreError::UnexpectedError(e.into()))?;
+        let (Ok(new_email), Ok(confirm_token), Ok(cancel_token)) = (
+            Email::parse(&SecretBox::new(Box::new(record.new_email))),
+            EmailChangeToken::parse(&record.confirm_token),
+            EmailChangeToken::parse(&record.cancel_token),
+        )
+        else {
+            return Err(EmailChangeStoreError::UnexpectedError(eyre!("Malformed email change record")));
+        };
+
+        Ok(PendingEmailChange { new_email, confirm_token, cancel_token })
+    }
+
+    #[instrument(name = "Remove email change from redis", skip_all)]
+    async fn remove_change(&self, user_id: &Uuid) -> Result<(), EmailChangeStoreError> {
+        let mut connection = self.connection.clone();
+
+        if let Err(e) = connection.del(get_key(user_id)).await {
+            return Err(EmailChangeStoreError::UnexpectedError(e.into()));
+        }
+
+        Ok(())
+    }
+}
+
+fn get_key(user_id: &Uuid) -> String {
+    format!("{EMAIL_CHANGE_PREFIX}{user_id}")
+}
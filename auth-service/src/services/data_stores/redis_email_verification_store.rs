@@ -0,0 +1,102 @@
+use {
+    crate::domain::{
+        data_stores::{EmailVerificationStore, EmailVerificationStoreError, EmailVerificationToken},
+        email::Email,
+    },
+    chrono::Utc,
+    redis::{AsyncTypedCommands, aio::ConnectionManager},
+    secrecy::ExposeSecret,
+    serde::{Deserialize, Serialize},
+    serde_json::{from_str, to_string},
+    tracing::instrument,
+};
+
+const ONE_DAY_IN_SECONDS: u64 = 86400;
+const EMAIL_VERIFICATION_PREFIX: &str = "email_verification:";
+
+#[derive(Serialize, Deserialize)]
+struct EmailVerificationTuple(pub String, pub i64);
+
+pub struct RedisEmailVerificationStore {
+    connection: ConnectionManager,
+}
+
+impl RedisEmailVerificationStore {
+    pub fn new(connection: ConnectionManager) -> Self {
+        Self { connection }
+    }
+}
+
+#[async_trait::async_trait]
+impl EmailVerificationStore for RedisEmailVerificationStore {
+    #[instrument(name = "Add email verification token to redis", skip_all)]
+    async fn add_token(&self, email: Email, token: EmailVerificationToken) -> Result<(), EmailVerificationStoreError> {
+        let tuple_string = match to_string(&EmailVerificationTuple(
+            token.as_ref().expose_secret().to_string(),
+            Utc::now().timestamp(),
+        )) {
+            Ok(string) => string,
+            Err(e) => return Err(EmailVerificationStoreError::UnexpectedError(e.into())),
+        };
+        let mut connection = self.connection.clone();
+
+        if let Err(e) = connection.set_ex(get_key(&email), tuple_string, ONE_DAY_IN_SECONDS).await {
+            return Err(EmailVerificationStoreError::UnexpectedError(e.into()));
+        }
+
+        Ok(())
+    }
+
+    #[instrument(name = "Remove email verification token from redis", skip_all)]
+    async fn remove_token(&self, email: &Email) -> Result<(), EmailVerificationStoreError> {
+        let mut connection = self.connection.clone();
+
+        if let Err(e) = connection.del(get_key(email)).await {
+            return Err(EmailVerificationStoreError::UnexpectedError(e.into()));
+        }
+
+        Ok(())
+    }
+
+    #[instrument(name = "Get email verification token from redis", skip_all)]
+    async fn get_token(&self, email: &Email) -> Result<(EmailVerificationToken, i64), EmailVerificationStoreError> {
+        let mut connection = self.connection.clone();
+
+        let tuple_string = match connection.get(get_key(email)).await {
+            Ok(Some(v)) => v,
+            Ok(None) => return Err(EmailVerificationStoreError::TokenNotFound),
+            Err(e) => return Err(EmailVerificationStoreError::UnexpectedError(e.into())),
+        };
+
+        parse_tuple(&tuple_string)
+    }
+
+    #[instrument(name = "Take email verification token from redis", skip_all)]
+    async fn take_token(&self, email: &Email) -> Result<EmailVerificationToken, EmailVerificationStoreError> {
+        let mut connection = self.connection.clone();
+        // GETDEL lets only one of several concurrent requests consume the token
+        let tuple_string = match connection.get_del(get_key(email)).await {
+            Ok(Some(v)) => v,
+            Ok(None) => return Err(EmailVerificationStoreError::TokenNotFound),
+            Err(e) => return Err(EmailVerificationStoreError::UnexpectedError(e.into())),
+        };
+
+        parse_tuple(&tuple_string).map(|(token, _)| token)
+    }
+}
+
+fn parse_tuple(tuple_string: &str) -> Result<(EmailVerificationToken, i64), EmailVerificationStoreError> {
+    let tuple: EmailVerificationTuple = match from_str(tuple_string) {
+        Ok(v) => v,
+        Err(e) => return Err(EmailVerificationStoreError::UnexpectedError(e.into())),
+    };
+
+    match EmailVerificationToken::parse(&tuple.0) {
+        Ok(token) => Ok((token, tuple.1)),
+        Err(e) => Err(EmailVerificationStoreError::UnexpectedError(color_eyre::eyre::eyre!(e))),
+    }
+}
+
+fn get_key(email: &Email) -> String {
+    format!("{EMAIL_VERIFICATION_PREFIX}{}", email.as_ref().expose_secret().to_lowercase())
+}
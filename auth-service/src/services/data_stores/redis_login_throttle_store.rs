@@ -0,0 +1,96 @@
+use {
+    crate::{
+        domain::data_stores::{FailedAttempts, LoginThrottleStore, LoginThrottleStoreError, ThrottleKey},
+        utils::constants::LOGIN_LOCKOUT_SECONDS,
+    },
+    chrono::Utc,
+    redis::{AsyncTypedCommands, aio::ConnectionManager, cmd, pipe},
+    secrecy::ExposeSecret,
+    tracing::instrument,
+};
+
+/// Failures used to be kept as JSON under `login_failures:`, which cannot be counted up in place. Those counters are
+/// no longer read and expire within `LOGIN_LOCKOUT_SECONDS` of the upgrade.
+const LOGIN_FAILURES_PREFIX: &str = "login_failure_counts:";
+const COUNT_FIELD: &str = "count";
+const LAST_FAILURE_AT_FIELD: &str = "last_failure_at";
+
+/// Keeps the failures under each key in a hash, counted up in Redis so that concurrent failures are all counted.
+pub struct RedisLoginThrottleStore {
+    connection: ConnectionManager,
+}
+
+impl RedisLoginThrottleStore {
+    pub fn new(connection: ConnectionManager) -> Self {
+        Self { connection }
+    }
+}
+
+#[async_trait::async_trait]
+impl LoginThrottleStore for RedisLoginThrottleStore {
+    #[instrument(name = "Record login failure in redis", skip_all)]
+    async fn record_failure(&self, key: &ThrottleKey) -> Result<FailedAttempts, LoginThrottleStoreError> {
+        let mut connection = self.connection.clone();
+        let key = get_key(key);
+        let now = Utc::now().timestamp();
+        // the counter expires a fixed time after the first failure it counts
+        let (count,): (u32,) = pipe()
+            .atomic()
+            .hincr(&key, COUNT_FIELD, 1)
+            .hset(&key, LAST_FAILURE_AT_FIELD, now)
+            .ignore()
+            .cmd("EXPIRE")
+            .arg(&key)
+            .arg(*LOGIN_LOCKOUT_SECONDS)
+            .arg("NX")
+            .ignore()
+            .query_async(&mut connection)
+            .await
+            .map_err(|e| LoginThrottleStoreError::UnexpectedError(e.into()))?;
+
+        Ok(FailedAttempts { count, last_failure_at: now })
+    }
+
+    #[instrument(name = "Get login failures from redis", skip_all)]
+    async fn get_failures(&self, key: &ThrottleKey) -> Result<FailedAttempts, LoginThrottleStoreError> {
+        let mut connection = self.connection.clone();
+
+        get_attempts(&mut connection, key).await
+    }
+
+    #[instrument(name = "Reset login failures in redis", skip_all)]
+    async fn reset(&self, key: &ThrottleKey) -> Result<(), LoginThrottleStoreError> {
+        let mut connection = self.connection.clone();
+
+        if let Err(e) = connection.del(get_key(key)).await {
+            return Err(LoginThrottleStoreError::UnexpectedError(e.into()));
+        }
+
+        Ok(())
+    }
+}
+
+async fn get_attempts(
+    connection: &mut ConnectionManager,
+    key: &ThrottleKey,
+) -> Result<FailedAttempts, LoginThrottleStoreError> {
+    let (count, last_failure_at): (Option<u32>, Option<i64>) = cmd("HMGET")
+        .arg(get_key(key))
+        .arg(COUNT_FIELD)
+        .arg(LAST_FAILURE_AT_FIELD)
+        .query_async(connection)
+        .await
+        .map_err(|e| LoginThrottleStoreError::UnexpectedError(e.into()))?;
+
+    Ok(FailedAttempts { count: count.unwrap_or_default(), last_failure_at: last_failure_at.unwrap_or_default() })
+}
+
+fn get_key(key: &ThrottleKey) -> String {
+    match key {
+        ThrottleKey::Account(email) => {
+            format!("{LOGIN_FAILURES_PREFIX}account:{}", email.as_ref().expose_secret().to_lowercase())
+        }
+        ThrottleKey::Ip(ip) => format!("{LOGIN_FAILURES_PREFIX}ip:{ip}"),
+        ThrottleKey::TwoFactor(user_id) => format!("{LOGIN_FAILURES_PREFIX}2fa:{user_id}"),
+    }
+}
@@ -0,0 +1,61 @@
+use {
+    crate::{
+        domain::{
+            data_stores::{MagicLinkStore, MagicLinkStoreError, MagicLinkToken},
+            email::Email,
+        },
+        utils::auth::MAGIC_LINK_TTL_SECONDS,
+    },
+    redis::{AsyncTypedCommands, aio::ConnectionManager},
+    secrecy::ExposeSecret,
+    tracing::instrument,
+};
+
+const MAGIC_LINK_PREFIX: &str = "magic_link:";
+
+pub struct RedisMagicLinkStore {
+    connection: ConnectionManager,
+}
+
+impl RedisMagicLinkStore {
+    pub fn new(connection: ConnectionManager) -> Self {
+        Self { connection }
+    }
+}
+
+#[async_trait::async_trait]
+impl MagicLinkStore for RedisMagicLinkStore {
+    #[instrument(name = "Add magic link token to redis", skip_all)]
+    async fn add_token(&self, email: Email, token: MagicLinkToken) -> Result<(), MagicLinkStoreError> {
+        let mut connection = self.connection.clone();
+
+        if let Err(e) = connection
+            .set_ex(get_key(&email), token.as_ref().expose_secret().to_owned(), MAGIC_LINK_TTL_SECONDS as u64)
+            .await
+        {
+            return Err(MagicLinkStoreError::UnexpectedError(e.into()));
+        }
+
+        Ok(())
+    }
+
+    #[instrument(name = "Take magic link token from redis", skip_all)]
+    async fn take_token(&self, email: &Email) -> Result<MagicLinkToken, MagicLinkStoreError> {
+        let mut connection = self.connection.clone();
+        // GETDEL lets only one of several concurrent requests consume the link
+        let token = match connection.get_del(get_key(email)).await {
+            Ok(Some(v)) => v,
+            Ok(None) => return Err(MagicLinkStoreError::TokenNotFound),
+            Err(e) => return Err(MagicLinkStoreError::UnexpectedError(e.into())),
+        };
+
+        match MagicLinkToken::parse(&token) {
+            Ok(v) => Ok(v),
+            Err(e) => Err(MagicLinkStoreError::UnexpectedError(color_eyre::eyre::eyre!(e))),
+        }
+    }
+}
+
+fn get_key(email: &Email) -> String {
+    format!("{MAGIC_LINK_PREFIX}{}", email.as_ref().expose_secret().to_lowercase())
+}
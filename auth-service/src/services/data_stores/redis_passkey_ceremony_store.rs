@@ -0,0 +1,83 @@
+use {
+    crate::domain::{
+        data_stores::{PasskeyCeremonyStore, PasskeyCeremonyStoreError},
+        email::Email,
+    },
+    redis::{AsyncTypedCommands, aio::ConnectionManager},
+    secrecy::ExposeSecret,
+    serde::{Serialize, de::DeserializeOwned},
+    serde_json::{from_str, to_string},
+    tracing::instrument,
+    webauthn_rs::prelude::{PasskeyAuthentication, PasskeyRegistration},
+};
+
+const FIVE_MINUTES_IN_SECONDS: u64 = 300;
+const PASSKEY_REGISTRATION_PREFIX: &str = "passkey_registration:";
+const PASSKEY_AUTHENTICATION_PREFIX: &str = "passkey_authentication:";
+
+pub struct RedisPasskeyCeremonyStore {
+    connection: ConnectionManager,
+}
+
+impl RedisPasskeyCeremonyStore {
+    pub fn new(connection: ConnectionManager) -> Self {
+        Self { connection }
+    }
+
+    async fn add<T: Serialize>(&self, key: String, state: &T) -> Result<(), PasskeyCeremonyStoreError> {
+        let state = to_string(state).map_err(|e| PasskeyCeremonyStoreError::UnexpectedError(e.into()))?;
+        let mut connection = self.connection.clone();
+
+        if let Err(e) = connection.set_ex(key, state, FIVE_MINUTES_IN_SECONDS).await {
+            return Err(PasskeyCeremonyStoreError::UnexpectedError(e.into()));
+        }
+
+        Ok(())
+    }
+
+    async fn take<T: DeserializeOwned>(&self, key: String) -> Result<T, PasskeyCeremonyStoreError> {
+        let mut connection = self.connection.clone();
+        let state = match connection.get_del(&key).await {
+            Ok(Some(v)) => v,
+            Ok(None) => return Err(PasskeyCeremonyStoreError::CeremonyNotFound),
+            Err(e) => return Err(PasskeyCeremonyStoreError::UnexpectedError(e.into())),
+        };
+
+        from_str(&state).map_err(|e| PasskeyCeremonyStoreError::UnexpectedError(e.into()))
+    }
+}
+
+#[async_trait::async_trait]
+impl PasskeyCeremonyStore for RedisPasskeyCeremonyStore {
+    #[instrument(name = "Add passkey registration to redis", skip_all)]
+    async fn add_registration(
+        &self,
+        email: &Email,
+        state: PasskeyRegistration,
+    ) -> Result<(), PasskeyCeremonyStoreError> {
+        self.add(get_key(PASSKEY_REGISTRATION_PREFIX, email), &state).await
+    }
+
+    #[instrument(name = "Take passkey registration from redis", skip_all)]
+    async fn take_registration(&self, email: &Email) -> Result<PasskeyRegistration, PasskeyCeremonyStoreError> {
+        self.take(get_key(PASSKEY_REGISTRATION_PREFIX, email)).await
+    }
+
+    #[instrument(name = "Add passkey authentication to redis", skip_all)]
+    async fn add_authentication(
+        &self,
+        email: &Email,
+        state: PasskeyAuthentication,
+    ) -> Result<(), PasskeyCeremonyStoreError> {
+        self.add(get_key(PASSKEY_AUTHENTICATION_PREFIX, email), &state).await
+    }
+
+    #[instrument(name = "Take passkey authentication from redis", skip_all)]
+    async fn take_authentication(&self, email: &Email) -> Result<PasskeyAuthentication, PasskeyCeremonyStoreError> {
+        self.take(get_key(PASSKEY_AUTHENTICATION_PREFIX, email)).await
+    }
+}
+
+fn get_key(prefix: &str, email: &Email) -> String {
+    format!("{prefix}{}", email.as_ref().expose_secret())
+}
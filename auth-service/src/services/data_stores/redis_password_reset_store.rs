@@ -0,0 +1,86 @@
+use {
+    crate::domain::{
+        data_stores::{PasswordResetStore, PasswordResetStoreError, PasswordResetToken},
+        email::Email,
+    },
+    redis::{AsyncTypedCommands, aio::ConnectionManager},
+    secrecy::ExposeSecret,
+    tracing::instrument,
+};
+
+const FIFTEEN_MINUTES_IN_SECONDS: u64 = 900;
+const PASSWORD_RESET_PREFIX: &str = "password_reset:";
+
+pub struct RedisPasswordResetStore {
+    connection: ConnectionManager,
+}
+
+impl RedisPasswordResetStore {
+    pub fn new(connection: ConnectionManager) -> Self {
+        Self { connection }
+    }
+}
+
+#[async_trait::async_trait]
+impl PasswordResetStore for RedisPasswordResetStore {
+    #[instrument(name = "Add password reset token to redis", skip_all)]
+    async fn add_token(&self, email: Email, token: PasswordResetToken) -> Result<(), PasswordResetStoreError> {
+        let mut connection = self.connection.clone();
+
+        if let Err(e) = connection
+            .set_ex(get_key(&email), token.as_ref().expose_secret().to_owned(), FIFTEEN_MINUTES_IN_SECONDS)
+            .await
+        {
+            return Err(PasswordResetStoreError::UnexpectedError(e.into()));
+        }
+
+        Ok(())
+    }
+
+    #[instrument(name = "Remove password reset token from redis", skip_all)]
+    async fn remove_token(&self, email: &Email) -> Result<(), PasswordResetStoreError> {
+        let mut connection = self.connection.clone();
+
+        if let Err(e) = connection.del(get_key(email)).await {
+            return Err(PasswordResetStoreError::UnexpectedError(e.into()));
+        }
+
+        Ok(())
+    }
+
+    #[instrument(name = "Get password reset token from redis", skip_all)]
+    async fn get_token(&self, email: &Email) -> Result<PasswordResetToken, PasswordResetStoreError> {
+        let mut connection = self.connection.clone();
+
+        let token = match connection.get(get_key(email)).await {
+            Ok(Some(v)) => v,
+            Ok(None) => return Err(PasswordResetStoreError::TokenNotFound),
+            Err(e) => return Err(PasswordResetStoreError::UnexpectedError(e.into())),
+        };
+
+        match PasswordResetToken::parse(&token) {
+            Ok(v) => Ok(v),
+            Err(e) => Err(PasswordResetStoreError::UnexpectedError(color_eyre::eyre::eyre!(e))),
+        }
+    }
+
+    #[instrument(name = "Take password reset token from redis", skip_all)]
+    async fn take_token(&self, email: &Email) -> Result<PasswordResetToken, PasswordResetStoreError> {
+        let mut connection = self.connection.clone();
+        // GETDEL lets only one of several concurrent requests consume the token
+        let token = match connection.get_del(get_key(email)).await {
+            Ok(Some(v)) => v,
+            Ok(None) => return Err(PasswordResetStoreError::TokenNotFound),
+            Err(e) => return Err(PasswordResetStoreError::UnexpectedError(e.into())),
+        };
+
+        match PasswordResetToken::parse(&token) {
+            Ok(v) => Ok(v),
+            Err(e) => Err(PasswordResetStoreError::UnexpectedError(color_eyre::eyre::eyre!(e))),
+        }
+    }
+}
+
+fn get_key(email: &Email) -> String {
+    format!("{PASSWORD_RESET_PREFIX}{}", email.as_ref().expose_secret().to_lowercase())
+}
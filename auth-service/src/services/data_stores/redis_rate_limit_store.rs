@@ -0,0 +1,95 @@
+use {
+    crate::{
+        domain::{
+            data_stores::{RateLimitStore, RateLimitStoreError},
+            rate_limit::{RateLimitDecision, RateLimitPolicy},
+        },
+        services::data_stores::InMemoryRateLimitStore,
+    },
+    chrono::Utc,
+    redis::{Script, aio::ConnectionManager},
+    tracing::{instrument, warn},
+};
+
+const RATE_LIMIT_PREFIX: &str = "rate_limit:";
+
+/// Refills the bucket stored under `KEYS[1]` and takes a token from it in one step, so instances sharing the bucket
+/// never spend the same token twice. Takes the capacity, refill interval and current time in milliseconds, and
+/// returns whether a token was taken along with how many are left.
+const ACQUIRE_SCRIPT: &str = r#"
+local capacity = tonumber(ARGV[1])
+local interval = tonumber(ARGV[2])
+local now = tonumber(ARGV[3])
+local tokens = capacity
+local updated_at = now
+local stored = redis.call('GET', KEYS[1])
+
+if stored then
+    local bucket = cjson.decode(stored)
+    tokens = bucket.tokens
+    updated_at = bucket.updated_at
+end
+
+tokens = math.min(tokens + math.max(now - updated_at, 0) / interval, capacity)
+
+local allowed = 0
+
+if tokens >= 1 then
+    tokens = tokens - 1
+    allowed = 1
+end
+
+-- a bucket that expired is indistinguishable from a full one
+local full_after = math.max(math.ceil((capacity - tokens) * interval), 1)
+
+redis.call('SET', KEYS[1], cjson.encode({ tokens = tokens, updated_at = now }), 'PX', full_after)
+
+return { allowed, tostring(tokens) }
+"#;
+
+/// Shares buckets between instances through Redis and falls back to in-process buckets while Redis fails.
+pub struct RedisRateLimitStore {
+    connection: ConnectionManager,
+    acquire_script: Script,
+    fallback: InMemoryRateLimitStore,
+}
+
+impl RedisRateLimitStore {
+    pub fn new(connection: ConnectionManager) -> Self {
+        Self { connection, acquire_script: Script::new(ACQUIRE_SCRIPT), fallback: InMemoryRateLimitStore::default() }
+    }
+
+    async fn acquire_in_redis(
+        &self,
+        key: &str,
+        policy: &RateLimitPolicy,
+    ) -> Result<RateLimitDecision, RateLimitStoreError> {
+        let mut connection = self.connection.clone();
+        let (allowed, tokens): (bool, f64) = self
+            .acquire_script
+            .key(format!("{RATE_LIMIT_PREFIX}{key}"))
+            .arg(policy.capacity)
+            .arg(policy.refill_interval_ms.max(1))
+            .arg(Utc::now().timestamp_millis())
+            .invoke_async(&mut connection)
+            .await
+            .map_err(|e| RateLimitStoreError::UnexpectedError(e.into()))?;
+
+        Ok(RateLimitDecision::new(policy, allowed, tokens))
+    }
+}
+
+#[async_trait::async_trait]
+impl RateLimitStore for RedisRateLimitStore {
+    #[instrument(name = "Acquire rate limit token in redis", skip_all)]
+    async fn acquire(&self, key: &str, policy: &RateLimitPolicy) -> Result<RateLimitDecision, RateLimitStoreError> {
+        match self.acquire_in_redis(key, policy).await {
+            Ok(decision) => Ok(decision),
+            Err(e) => {
+                warn!("Falling back to in-process rate limiting: {e:?}");
+
+                self.fallback.acquire(key, policy).await
+            }
+        }
+    }
+}
@@ -0,0 +1,227 @@
+use {
+    crate::{
+        domain::data_stores::{RefreshToken, RefreshTokenStore, RefreshTokenStoreError},
+        utils::auth::REFRESH_TOKEN_TTL_SECONDS,
+    },
+    color_eyre::eyre::eyre,
+    redis::{AsyncTypedCommands, Script, aio::ConnectionManager, pipe},
+    serde::{Deserialize, Serialize},
+    serde_json::{from_str, to_string},
+    tracing::instrument,
+    uuid::Uuid,
+};
+
+const REFRESH_TOKEN_PREFIX: &str = "refresh_token:";
+const REFRESH_TOKEN_FAMILY_PREFIX: &str = "refresh_token_family:";
+const REFRESH_TOKEN_USER_PREFIX: &str = "refresh_token_user:";
+
+/// Marks the token stored under `KEYS[1]` used and stores `KEYS[2]` as its successor in one step, so two instances
+/// rotating the same token cannot both see it unused. Takes the family key prefix and the token lifetime, and returns
+/// the outcome along with the record of the rotated token.
+const ROTATE_SCRIPT: &str = r#"
+local stored = redis.call('GET', KEYS[1])
+
+if not stored then
+    return { 'not_found', '' }
+end
+
+local record = cjson.decode(stored)
+local family_key = ARGV[1] .. record.family
+
+if redis.call('EXISTS', family_key) == 0 then
+    return { 'not_found', '' }
+end
+
+if record.used then
+    redis.call('DEL', family_key)
+
+    return { 'reused', '' }
+end
+
+record.used = true
+redis.call('SET', KEYS[1], cjson.encode(record), 'EX', ARGV[2])
+record.used = false
+redis.call('SET', KEYS[2], cjson.encode(record), 'EX', ARGV[2])
+redis.call('EXPIRE', family_key, ARGV[2])
+
+return { 'rotated', stored }
+"#;
+
+#[derive(Serialize, Deserialize)]
+struct RefreshTokenRecord {
+    user_id: Uuid,
+    family: String,
+    used: bool,
+}
+
+pub struct RedisRefreshTokenStore {
+    connection: ConnectionManager,
+    rotate_script: Script,
+}
+
+impl RedisRefreshTokenStore {
+    pub fn new(connection: ConnectionManager) -> Self {
+        Self { connection, rotate_script: Script::new(ROTATE_SCRIPT) }
+    }
+}
+
+#[async_trait::async_trait]
+impl RefreshTokenStore for RedisRefreshTokenStore {
+    #[instrument(name = "Add refresh token to redis", skip_all)]
+    async fn add_token(
+        &self,
+        user_id: Uuid,
+        session_id: Uuid,
+        token: RefreshToken,
+    ) -> Result<(), RefreshTokenStoreError> {
+        let record = RefreshTokenRecord { user_id, family: session_id.to_string(), used: false };
+        let record_string = to_string(&record).map_err(|e| RefreshTokenStoreError::UnexpectedError(e.into()))?;
+        let mut connection = self.connection.clone();
+
+        pipe()
+            .atomic()
+            .set_ex(get_key(&token), record_string, REFRESH_TOKEN_TTL_SECONDS as u64)
+            .set_ex(get_family_key(&record.family), true, REFRESH_TOKEN_TTL_SECONDS as u64)
+            .sadd(get_user_key(&user_id), &record.family)
+            .expire(get_user_key(&user_id), REFRESH_TOKEN_TTL_SECONDS)
+            .exec_async(&mut connection)
+            .await
+            .map_err(|e| RefreshTokenStoreError::UnexpectedError(e.into()))
+    }
+
+    #[instrument(name = "Rotate refresh token in redis", skip_all)]
+    async fn rotate_token(
+        &self,
+        token: &RefreshToken,
+        new_token: RefreshToken,
+    ) -> Result<(Uuid, Uuid), RefreshTokenStoreError> {
+        let mut connection = self.connection.clone();
+        let (outcome, record_string): (String, String) = self
+            .rotate_script
+            .key(get_key(token))
+            .key(get_key(&new_token))
+            .arg(REFRESH_TOKEN_FAMILY_PREFIX)
+            .arg(REFRESH_TOKEN_TTL_SECONDS)
+            .invoke_async(&mut connection)
+            .await
+            .map_err(|e| RefreshTokenStoreError::UnexpectedError(e.into()))?;
+
+        match outcome.as_str() {
+            "rotated" => {}
+            "reused" => return Err(RefreshTokenStoreError::TokenReused),
+            _ => return Err(RefreshTokenStoreError::TokenNotFound),
+        }
+
+        let record = from_str::<RefreshTokenRecord>(&record_string)
+            .map_err(|e| RefreshTokenStoreError::UnexpectedError(e.into()))?;
+        let session_id =
+            Uuid::parse_str(&record.family).map_err(|e| RefreshTokenStoreError::UnexpectedError(e.into()))?;
+
+        Ok((record.user_id, session_id))
+    }
+
+    #[instrument(name = "Revoke refresh token in redis", skip_all)]
+    async fn revoke_token(&self, token: &RefreshToken) -> Result<(), RefreshTokenStoreError> {
+        let mut connection = self.connection.clone();
+        let Some(record) = get_record(&mut connection, token).await?
+        else {
+            return Ok(());
+        };
+
+        connection
+            .del(get_family_key(&record.family))
+            .await
+            .map_err(|e| RefreshTokenStoreError::UnexpectedError(e.into()))?;
+
+        Ok(())
+    }
+
+    #[instrument(name = "Revoke user refresh tokens in redis", skip_all)]
+    async fn revoke_user(&self, user_id: &Uuid, except: Option<&RefreshToken>) -> Result<(), RefreshTokenStoreError> {
+        let mut connection = self.connection.clone();
+        let except = match except {
+            Some(token) => get_record(&mut connection, token).await?.map(|record| record.family),
+            None => None,
+        };
+        let families = connection
+            .smembers(get_user_key(user_id))
+            .await
+            .map_err(|e| RefreshTokenStoreError::UnexpectedError(e.into()))?;
+
+        let families = families.iter().filter(|family| Some(*family) != except.as_ref()).collect::<Vec<_>>();
+
+        if families.is_empty() {
+            return Ok(());
+        }
+
+        pipe()
+            .atomic()
+            .del(families.iter().map(|family| get_family_key(family)).collect::<Vec<_>>())
+            .srem(get_user_key(user_id), families)
+            .exec_async(&mut connection)
+            .await
+            .map_err(|e| RefreshTokenStoreError::UnexpectedError(e.into()))
+    }
+
+    #[instrument(name = "Revoke session refresh tokens in redis", skip_all)]
+    async fn revoke_session(&self, session_id: &Uuid) -> Result<(), RefreshTokenStoreError> {
+        let mut connection = self.connection.clone();
+
+        connection
+            .del(get_family_key(&session_id.to_string()))
+            .await
+            .map_err(|e| RefreshTokenStoreError::UnexpectedError(e.into()))?;
+
+        Ok(())
+    }
+
+    #[instrument(name = "Count refresh token sessions in redis", skip_all)]
+    async fn count_sessions(&self, user_id: &Uuid) -> Result<u64, RefreshTokenStoreError> {
+        let mut connection = self.connection.clone();
+        let families = connection
+            .smembers(get_user_key(user_id))
+            .await
+            .map_err(|e| RefreshTokenStoreError::UnexpectedError(e.into()))?;
+        let mut count = 0;
+
+        for family in families.iter() {
+            if connection
+                .exists(get_family_key(family))
+                .await
+                .map_err(|e| RefreshTokenStoreError::UnexpectedError(e.into()))?
+            {
+                count += 1;
+            }
+        }
+
+        Ok(count)
+    }
+}
+
+async fn get_record(
+    connection: &mut ConnectionManager,
+    token: &RefreshToken,
+) -> Result<Option<RefreshTokenRecord>, RefreshTokenStoreError> {
+    let record_string = match connection.get(get_key(token)).await {
+        Ok(Some(v)) => v,
+        Ok(None) => return Ok(None),
+        Err(e) => return Err(RefreshTokenStoreError::UnexpectedError(e.into())),
+    };
+
+    match from_str(&record_string) {
+        Ok(record) => Ok(Some(record)),
+        Err(e) => Err(RefreshTokenStoreError::UnexpectedError(eyre!(e))),
+    }
+}
+
+fn get_key(token: &RefreshToken) -> String {
+    format!("{REFRESH_TOKEN_PREFIX}{}", token.hash())
+}
+
+fn get_family_key(family: &str) -> String {
+    format!("{REFRESH_TOKEN_FAMILY_PREFIX}{family}")
+}
+
+fn get_user_key(user_id: &Uuid) -> String {
+    format!("{REFRESH_TOKEN_USER_PREFIX}{user_id}")
+}
@@ -0,0 +1,181 @@
+use {
+    crate::{
+        domain::data_stores::{Session, SessionStore, SessionStoreError},
+        utils::auth::REFRESH_TOKEN_TTL_SECONDS,
+    },
+    chrono::Utc,
+    redis::{AsyncTypedCommands, Script, aio::ConnectionManager, pipe},
+    serde_json::{from_str, to_string},
+    tracing::instrument,
+    uuid::Uuid,
+};
+
+const SESSIONS_PREFIX: &str = "sessions:";
+
+/// Records the token `ARGV[2]` issued at `ARGV[3]` on the session `ARGV[1]` of the hash `KEYS[1]` in one step, so a
+/// session removed meanwhile is not written back. Takes the hash lifetime last and returns whether the session exists.
+const RECORD_TOKEN_SCRIPT: &str = r#"
+local stored = redis.call('HGET', KEYS[1], ARGV[1])
+
+if not stored then
+    return 0
+end
+
+local session = cjson.decode(stored)
+
+session.jti = ARGV[2]
+session.refreshed_at = tonumber(ARGV[3])
+redis.call('HSET', KEYS[1], ARGV[1], cjson.encode(session))
+redis.call('EXPIRE', KEYS[1], ARGV[4])
+
+return 1
+"#;
+
+/// Removes each session id in `ARGV` from the hash `KEYS[1]` if it still holds the value following the id, so a
+/// session refreshed since it was read is kept.
+const PRUNE_SCRIPT: &str = r#"
+for i = 1, #ARGV, 2 do
+    if redis.call('HGET', KEYS[1], ARGV[i]) == ARGV[i + 1] then
+        redis.call('HDEL', KEYS[1], ARGV[i])
+    end
+end
+
+return 0
+"#;
+
+/// Keeps the sessions of each user in one hash, field per session id.
+pub struct RedisSessionStore {
+    connection: ConnectionManager,
+    record_token_script: Script,
+    prune_script: Script,
+}
+
+impl RedisSessionStore {
+    pub fn new(connection: ConnectionManager) -> Self {
+        Self {
+            connection,
+            record_token_script: Script::new(RECORD_TOKEN_SCRIPT),
+            prune_script: Script::new(PRUNE_SCRIPT),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl SessionStore for RedisSessionStore {
+    #[instrument(name = "Add session to redis", skip_all)]
+    async fn add_session(&self, session: Session) -> Result<(), SessionStoreError> {
+        let mut connection = self.connection.clone();
+
+        set_session(&mut connection, &session).await
+    }
+
+    #[instrument(name = "Record session token in redis", skip_all)]
+    async fn record_token(
+        &self,
+        user_id: &Uuid,
+        session_id: &Uuid,
+        jti: String,
+        issued_at: i64,
+    ) -> Result<(), SessionStoreError> {
+        let mut connection = self.connection.clone();
+        let recorded: bool = self
+            .record_token_script
+            .key(get_key(user_id))
+            .arg(session_id.to_string())
+            .arg(jti)
+            .arg(issued_at)
+            .arg(REFRESH_TOKEN_TTL_SECONDS)
+            .invoke_async(&mut connection)
+            .await
+            .map_err(|e| SessionStoreError::UnexpectedError(e.into()))?;
+
+        match recorded {
+            true => Ok(()),
+            false => Err(SessionStoreError::SessionNotFound),
+        }
+    }
+
+    #[instrument(name = "Get sessions from redis", skip_all)]
+    async fn get_sessions(&self, user_id: &Uuid) -> Result<Vec<Session>, SessionStoreError> {
+        let mut connection = self.connection.clone();
+        let values =
+            connection.hgetall(get_key(user_id)).await.map_err(|e| SessionStoreError::UnexpectedError(e.into()))?;
+        let expired_before = Utc::now().timestamp() - REFRESH_TOKEN_TTL_SECONDS;
+        let mut sessions = Vec::new();
+        let mut prune = self.prune_script.key(get_key(user_id));
+        let mut expired = false;
+
+        for (session_id, value) in values {
+            let session = from_str::<Session>(&value).map_err(|e| SessionStoreError::UnexpectedError(e.into()))?;
+
+            // a session nobody refreshed within the refresh token lifetime cannot be continued
+            if session.refreshed_at < expired_before {
+                prune.arg(session_id).arg(value);
+                expired = true;
+            }
+            else {
+                sessions.push(session);
+            }
+        }
+
+        if expired {
+            prune
+                .invoke_async::<()>(&mut connection)
+                .await
+                .map_err(|e| SessionStoreError::UnexpectedError(e.into()))?;
+        }
+
+        sessions.sort_by_key(|session| (session.created_at, session.session_id));
+
+        Ok(sessions)
+    }
+
+    #[instrument(name = "Remove session from redis", skip_all)]
+    async fn remove_session(&self, user_id: &Uuid, session_id: &Uuid) -> Result<(), SessionStoreError> {
+        let mut connection = self.connection.clone();
+
+        match connection.hdel(get_key(user_id), session_id.to_string()).await {
+            Ok(0) => Err(SessionStoreError::SessionNotFound),
+            Ok(_) => Ok(()),
+            Err(e) => Err(SessionStoreError::UnexpectedError(e.into())),
+        }
+    }
+
+    #[instrument(name = "Remove sessions from redis", skip_all)]
+    async fn remove_sessions(&self, user_id: &Uuid, except: Option<&Uuid>) -> Result<(), SessionStoreError> {
+        let mut connection = self.connection.clone();
+        let except = except.map(Uuid::to_string);
+        let session_ids =
+            connection.hkeys(get_key(user_id)).await.map_err(|e| SessionStoreError::UnexpectedError(e.into()))?;
+        let session_ids =
+            session_ids.into_iter().filter(|session_id| Some(session_id) != except.as_ref()).collect::<Vec<_>>();
+
+        if session_ids.is_empty() {
+            return Ok(());
+        }
+
+        // sessions added since they were listed are kept, as if they were added afterwards
+        connection
+            .hdel(get_key(user_id), session_ids)
+            .await
+            .map_err(|e| SessionStoreError::UnexpectedError(e.into()))?;
+
+        Ok(())
+    }
+}
+
+async fn set_session(connection: &mut ConnectionManager, session: &Session) -> Result<(), SessionStoreError> {
+    let value = to_string(session).map_err(|e| SessionStoreError::UnexpectedError(e.into()))?;
+
+    pipe()
+        .atomic()
+        .hset(get_key(&session.user_id), session.session_id.to_string(), value)
+        .expire(get_key(&session.user_id), REFRESH_TOKEN_TTL_SECONDS)
+        .exec_async(connection)
+        .await
+        .map_err(|e| SessionStoreError::UnexpectedError(e.into()))
+}
+
+fn get_key(user_id: &Uuid) -> String {
+    format!("{SESSIONS_PREFIX}{user_id}")
+}
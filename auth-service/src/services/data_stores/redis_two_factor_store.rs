@@ -1,14 +1,11 @@
 use {
-    crate::domain::{
-        data_stores::{LoginAttemptId, TwoFactorCode, TwoFactorStore, TwoFactorStoreError},
-        email::Email,
-    },
-    redis::{Connection, TypedCommands},
+    crate::domain::data_stores::{LoginAttemptId, TwoFactorCode, TwoFactorStore, TwoFactorStoreError},
+    redis::{AsyncTypedCommands, aio::ConnectionManager},
     secrecy::ExposeSecret,
     serde::{Deserialize, Serialize},
     serde_json::{from_str, to_string},
-    tokio::sync::RwLock,
     tracing::instrument,
+    uuid::Uuid,
 };
 
 const TEN_MINUTES_IN_SECONDS: u64 = 600;
@@ -18,12 +15,12 @@ const TWO_FACTOR_PREFIX: &str = "two_factor:";
 struct TwoFactorTuple(pub String, pub String);
 
 pub struct RedisTwoFactorStore {
-    connection: RwLock<Connection>,
+    connection: ConnectionManager,
 }
 
 impl RedisTwoFactorStore {
-    pub fn new(connection: Connection) -> Self {
-        Self { connection: RwLock::new(connection) }
+    pub fn new(connection: ConnectionManager) -> Self {
+        Self { connection }
     }
 }
 
@@ -32,7 +29,7 @@ impl TwoFactorStore for RedisTwoFactorStore {
     #[instrument(name = "Add code to redis", skip_all)]
     async fn add_code(
         &self,
-        email: Email,
+        user_id: Uuid,
         attempt_id: LoginAttemptId,
         code: TwoFactorCode,
     ) -> Result<(), TwoFactorStoreError> {
@@ -43,9 +40,9 @@ impl TwoFactorStore for RedisTwoFactorStore {
             Ok(string) => string,
             Err(e) => return Err(TwoFactorStoreError::UnexpectedError(e.into())),
         };
-        let mut connection = self.connection.write().await;
+        let mut connection = self.connection.clone();
 
-        if let Err(e) = connection.set_ex(get_key(&email), tuple_string, TEN_MINUTES_IN_SECONDS) {
+        if let Err(e) = connection.set_ex(get_key(&user_id), tuple_string, TEN_MINUTES_IN_SECONDS).await {
             return Err(TwoFactorStoreError::UnexpectedError(e.into()));
         }
 
@@ -53,10 +50,10 @@ impl TwoFactorStore for RedisTwoFactorStore {
     }
 
     #[instrument(name = "Remove code from redis", skip_all)]
-    async fn remove_code(&self, email: &Email) -> Result<(), TwoFactorStoreError> {
-        let mut connection = self.connection.write().await;
+    async fn remove_code(&self, user_id: &Uuid) -> Result<(), TwoFactorStoreError> {
+        let mut connection = self.connection.clone();
 
-        if let Err(e) = connection.del(get_key(email)) {
+        if let Err(e) = connection.del(get_key(user_id)).await {
             return Err(TwoFactorStoreError::UnexpectedError(e.into()));
         }
 
@@ -64,10 +61,10 @@ impl TwoFactorStore for RedisTwoFactorStore {
     }
 
     #[instrument(name = "Get code from redis", skip_all)]
-    async fn get_code(&self, email: &Email) -> Result<(LoginAttemptId, TwoFactorCode), TwoFactorStoreError> {
-        let mut connection = self.connection.write().await;
+    async fn get_code(&self, user_id: &Uuid) -> Result<(LoginAttemptId, TwoFactorCode), TwoFactorStoreError> {
+        let mut connection = self.connection.clone();
 
-        let maybe_tuple_string: Option<String> = match connection.get(get_key(email)) {
+        let maybe_tuple_string: Option<String> = match connection.get(get_key(user_id)).await {
             Ok(v) => v,
             Err(e) => return Err(TwoFactorStoreError::UnexpectedError(e.into())),
         };
@@ -96,6 +93,6 @@ impl TwoFactorStore for RedisTwoFactorStore {
     }
 }
 
-fn get_key(email: &Email) -> String {
-    format!("{TWO_FACTOR_PREFIX}{}", email.as_ref().expose_secret())
+fn get_key(user_id: &Uuid) -> String {
+    format!("{TWO_FACTOR_PREFIX}{user_id}")
 }
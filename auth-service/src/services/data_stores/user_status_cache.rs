@@ -0,0 +1,52 @@
+use {
+    super::expiring_map::ExpiringMap,
+    crate::domain::{
+        data_stores::{UserStore, UserStoreError},
+        user::AccountStatus,
+    },
+    tokio::sync::RwLock,
+    uuid::Uuid,
+};
+
+/// How long a looked up status is trusted before it is read from the user store again.
+const TTL_SECONDS: u64 = 30;
+
+/// Status of a user along with the unix timestamp their suspension lifts at and whether their email is verified.
+type StoredStatus = (AccountStatus, Option<i64>, bool);
+
+/// Keeps account statuses for a short while, so checking the status of a token's user does not take a user store
+/// lookup every time. Changes made through this instance drop the entry right away; changes made through other
+/// instances show up once it expires, which is why blocking a user also bumps their token version.
+#[derive(Default)]
+pub struct UserStatusCache {
+    /// Stored status of each user, or `None` for users that do not exist.
+    statuses: RwLock<ExpiringMap<Uuid, Option<StoredStatus>>>,
+}
+
+impl UserStatusCache {
+    /// Status in effect for the user, or `None` if there is no such user.
+    pub async fn get(
+        &self,
+        user_store: &dyn UserStore,
+        user_id: &Uuid,
+    ) -> Result<Option<AccountStatus>, UserStoreError> {
+        if let Some(cached) = self.statuses.read().await.get(user_id).copied() {
+            return Ok(cached
+                .map(|(status, suspended_until, email_verified)| status.in_effect(suspended_until, email_verified)));
+        }
+
+        let cached = match user_store.get_user_by_id(user_id).await {
+            Ok(user) => Some((user.status, user.suspended_until, user.email_verified)),
+            Err(UserStoreError::UserNotFound) => None,
+            Err(e) => return Err(e),
+        };
+
+        self.statuses.write().await.insert(*user_id, cached, TTL_SECONDS);
+
+        Ok(cached.map(|(status, suspended_until, email_verified)| status.in_effect(suspended_until, email_verified)))
+    }
+
+    pub async fn invalidate(&self, user_id: &Uuid) {
+        self.statuses.write().await.remove(user_id);
+    }
+}
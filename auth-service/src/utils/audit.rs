@@ -0,0 +1,36 @@
+use {
+    crate::{
+        app_state::AppState,
+        domain::{
+            audit::{AuditEvent, AuditEventType},
+            role::Permission,
+        },
+        utils::auth::{ClientInfo, RequirePermission},
+    },
+    tracing::error,
+    uuid::Uuid,
+};
+
+/// Appends `event` to the audit log. A failure to do so is logged rather than failing the request it happened in.
+pub async fn record_event(state: &AppState, event: AuditEvent) {
+    if let Err(e) = state.audit_log.record(event).await {
+        error!("Failed to record audit event: {e:?}");
+    }
+}
+
+/// Records `action` taken by an admin on `user_id`, crediting the admin's account unless they used the API key.
+pub async fn record_admin_action<P: Permission>(
+    state: &AppState,
+    client: &ClientInfo,
+    admin: &RequirePermission<P>,
+    user_id: Uuid,
+    action: &str,
+) {
+    let event = client
+        .event(AuditEventType::AdminAction)
+        .user(user_id)
+        .actor(admin.user.as_ref().map(|admin| admin.user_id))
+        .details(action);
+
+    record_event(state, event).await;
+}
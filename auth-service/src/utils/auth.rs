@@ -1,23 +1,59 @@
 use {
     crate::{
-        app_state::BannedTokenStoreType,
-        domain::email::Email,
-        utils::constants::{JWT_COOKIE_NAME, JWT_SECRET},
+        app_state::{AppState, RefreshTokenStoreType},
+        domain::{
+            audit::{AuditEvent, AuditEventType},
+            data_stores::{MagicLinkNonce, MagicLinkToken, RefreshToken, Session, SessionStoreError, UserStoreError},
+            email::Email,
+            error::AuthAPIError,
+            role::{DEFAULT_ROLE, Permission},
+            user::AccountStatus,
+        },
+        utils::{
+            audit::record_event,
+            constants::{
+                ADMIN_API_KEY, JWT_AUDIENCE, JWT_COOKIE_NAME, JWT_ISSUER, JWT_KEY_RING, MAGIC_LINK_NONCE_COOKIE_NAME,
+                REFRESH_COOKIE_NAME, TRUSTED_PROXIES,
+            },
+            keys::JWT_ALGORITHM,
+            tracing::RequestId,
+        },
+    },
+    axum::{
+        extract::{ConnectInfo, FromRequestParts},
+        http::{
+            HeaderMap,
+            header::{AUTHORIZATION, USER_AGENT},
+            request::Parts,
+        },
+    },
+    axum_extra::extract::{
+        CookieJar,
+        cookie::{Cookie, SameSite},
     },
-    axum_extra::extract::cookie::{Cookie, SameSite},
     chrono::{Duration, Utc},
     color_eyre::{
         Report,
         eyre::{Context, ContextCompat as _, Result, eyre},
     },
-    jsonwebtoken::{DecodingKey, EncodingKey, Header, Validation, decode, encode, errors::Error as JwtError},
+    jsonwebtoken::{Validation, decode, decode_header, encode, errors::Error as JwtError},
     secrecy::{ExposeSecret, SecretBox},
-    serde::{Deserialize, Serialize},
+    serde::{Deserialize, Serialize, de::DeserializeOwned},
+    sha2::{Digest, Sha256},
+    std::{
+        marker::PhantomData,
+        net::{IpAddr, SocketAddr},
+    },
     thiserror::Error,
-    tracing::instrument,
+    time::Duration as CookieDuration,
+    tracing::{instrument, warn},
+    uuid::Uuid,
 };
 
 pub const TOKEN_TTL_SECONDS: i64 = 600;
+pub const REFRESH_TOKEN_TTL_SECONDS: i64 = 60 * 60 * 24 * 14;
+pub const MAGIC_LINK_TTL_SECONDS: i64 = 600;
+const MAGIC_LINK_AUDIENCE: &str = "magic-link";
 
 #[derive(Debug, Error)]
 pub enum GenerateTokenError {
@@ -31,48 +67,442 @@ pub enum GenerateTokenError {
 pub enum ValidateTokenError {
     TokenError(JwtError),
     BannedToken,
+    InvalidNonce,
+    /// The token names no `kid` or one whose key has been fully retired.
+    UnknownKey,
     UnexpectedError,
 }
 
+/// Claims of an auth token. `sub` is the user id, so tokens do not carry the email address.
 #[derive(Debug, Deserialize, Serialize)]
 pub struct Claims {
+    pub aud: String,
     pub exp: usize,
+    pub iat: usize,
+    pub iss: String,
+    pub jti: String,
+    pub nbf: usize,
+    /// Roles of the user at issuance, always including the default role.
+    pub roles: Vec<String>,
+    /// Session the token was issued to; stays the same across refreshes.
+    pub sid: String,
     pub sub: String,
+    /// Token version of the user at issuance. Tokens issued before versions existed count as version 0.
+    #[serde(default)]
+    pub ver: u64,
+}
+
+/// Claims of a magic link token. The audience keeps it from being accepted as an auth token.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct MagicLinkClaims {
+    pub aud: String,
+    pub exp: usize,
+    pub nonce: String,
+    pub sub: String,
+}
+
+/// Resolves the caller from a valid, unrevoked JWT cookie.
+pub struct AuthenticatedUser {
+    pub user_id: Uuid,
+    pub session_id: Uuid,
+    pub email: Email,
+    pub token: SecretBox<String>,
+    pub roles: Vec<String>,
+}
+
+impl FromRequestParts<AppState> for AuthenticatedUser {
+    type Rejection = AuthAPIError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &AppState) -> Result<Self, Self::Rejection> {
+        let jar = CookieJar::from_headers(&parts.headers);
+        let Some(cookie) = jar.get(JWT_COOKIE_NAME)
+        else {
+            return Err(AuthAPIError::MissingToken);
+        };
+        let token = SecretBox::new(Box::new(cookie.value().to_owned()));
+        let Ok(claims) = validate_token(Some(state), &token).await
+        else {
+            return Err(AuthAPIError::InvalidToken);
+        };
+        let (Ok(user_id), Ok(session_id)) = (Uuid::parse_str(&claims.sub), Uuid::parse_str(&claims.sid))
+        else {
+            return Err(AuthAPIError::InvalidToken);
+        };
+        let user = match state.user_store.get_user_by_id(&user_id).await {
+            Ok(user) => user,
+            Err(UserStoreError::UserNotFound) => return Err(AuthAPIError::InvalidToken),
+            Err(e) => return Err(AuthAPIError::UnexpectedError(e.into())),
+        };
+
+        check_account_status(user.current_status())?;
+
+        let Ok(email) = Email::parse(&SecretBox::new(Box::new(user.email)))
+        else {
+            return Err(AuthAPIError::InvalidToken);
+        };
+
+        Ok(Self { user_id, session_id, email, token, roles: claims.roles })
+    }
+}
+
+/// Address and user agent of the client a session is started for, along with the id of its request.
+pub struct ClientInfo {
+    pub ip: IpAddr,
+    pub user_agent: Option<String>,
+    pub request_id: Uuid,
+}
+
+impl ClientInfo {
+    /// Audit event of `event_type` happening now, in this client's request.
+    pub fn event(&self, event_type: AuditEventType) -> AuditEvent {
+        AuditEvent {
+            event_type,
+            occurred_at: Utc::now().timestamp(),
+            user_id: None,
+            actor_id: None,
+            ip: self.ip,
+            user_agent: self.user_agent.clone(),
+            request_id: self.request_id,
+            details: None,
+        }
+    }
+}
+
+impl<S: Send + Sync> FromRequestParts<S> for ClientInfo {
+    type Rejection = AuthAPIError;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let Some(ConnectInfo(address)) = parts.extensions.get::<ConnectInfo<SocketAddr>>()
+        else {
+            return Err(AuthAPIError::UnexpectedError(eyre!("Missing connection info")));
+        };
+        let Some(RequestId(request_id)) = parts.extensions.get::<RequestId>()
+        else {
+            return Err(AuthAPIError::UnexpectedError(eyre!("Missing request id")));
+        };
+        let user_agent = parts.headers.get(USER_AGENT).and_then(|value| value.to_str().ok()).map(str::to_owned);
+
+        Ok(Self { ip: client_ip(&parts.headers, address.ip()), user_agent, request_id: *request_id })
+    }
+}
+
+/// Address of the client a request connecting from `peer` was made by. `X-Forwarded-For` is only believed when
+/// `peer` is one of the `TRUSTED_PROXIES`, and only as far back as trusted proxies forwarded the request.
+pub fn client_ip(headers: &HeaderMap, peer: IpAddr) -> IpAddr {
+    forwarded_client_ip(headers, peer, &TRUSTED_PROXIES)
+}
+
+fn forwarded_client_ip(headers: &HeaderMap, peer: IpAddr, trusted_proxies: &[IpAddr]) -> IpAddr {
+    let forwarded_for = headers
+        .get_all("x-forwarded-for")
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .collect::<Vec<_>>();
+    let mut client = peer;
+
+    // every trusted proxy appends the address it was connected from, so the nearest untrusted one is the client
+    for hop in forwarded_for.iter().rev() {
+        if !trusted_proxies.contains(&client) {
+            break;
+        }
+
+        let Ok(ip) = hop.trim().parse()
+        else {
+            break;
+        };
+
+        client = ip;
+    }
+
+    client
+}
+
+/// Admin caller authenticated by `Authorization: Bearer <ADMIN_API_KEY>`.
+pub struct AdminCredential;
+
+impl<S: Send + Sync> FromRequestParts<S> for AdminCredential {
+    type Rejection = AuthAPIError;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let Some(admin_api_key) = ADMIN_API_KEY.as_ref()
+        else {
+            return Err(AuthAPIError::IncorrectCredentials);
+        };
+        let Some(presented) = parts
+            .headers
+            .get(AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "))
+        else {
+            return Err(AuthAPIError::IncorrectCredentials);
+        };
+
+        // Compare digests so the check does not leak the key through timing
+        if Sha256::digest(presented.as_bytes()) != Sha256::digest(admin_api_key.expose_secret().as_bytes()) {
+            return Err(AuthAPIError::IncorrectCredentials);
+        }
+
+        Ok(Self)
+    }
+}
+
+/// Caller allowed to do what `P` permits: either the admin API key, or a user whose roles grant `P`.
+pub struct RequirePermission<P: Permission> {
+    /// `None` when the caller authenticated with the admin API key.
+    pub user: Option<AuthenticatedUser>,
+    permission: PhantomData<P>,
+}
+
+impl<P: Permission> FromRequestParts<AppState> for RequirePermission<P> {
+    type Rejection = AuthAPIError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &AppState) -> Result<Self, Self::Rejection> {
+        if parts.headers.contains_key(AUTHORIZATION) {
+            AdminCredential::from_request_parts(parts, state).await?;
+
+            return Ok(Self { user: None, permission: PhantomData });
+        }
+
+        let user = AuthenticatedUser::from_request_parts(parts, state).await?;
+        let permissions =
+            state.role_store.get_permissions(&user.roles).await.map_err(|e| AuthAPIError::UnexpectedError(e.into()))?;
+
+        if !permissions.iter().any(|permission| permission == P::NAME) {
+            return Err(AuthAPIError::Forbidden);
+        }
+
+        Ok(Self { user: Some(user), permission: PhantomData })
+    }
+}
+
+/// Fails with the error an account in `status` is turned away with, unless it is active.
+pub fn check_account_status(status: AccountStatus) -> Result<(), AuthAPIError> {
+    match status {
+        AccountStatus::Active => Ok(()),
+        AccountStatus::Suspended => Err(AuthAPIError::AccountSuspended),
+        AccountStatus::Disabled => Err(AuthAPIError::AccountDisabled),
+        AccountStatus::PendingVerification => Err(AuthAPIError::EmailNotVerified),
+    }
+}
+
+/// Checks the user's account status through the status cache, for requests that carry a token rather than load the
+/// user. Users that no longer exist pass; their tokens are turned away elsewhere.
+pub async fn check_cached_account_status(state: &AppState, user_id: &Uuid) -> Result<(), AuthAPIError> {
+    match state.user_status_cache.get(state.user_store.as_ref(), user_id).await {
+        Ok(Some(status)) => check_account_status(status),
+        Ok(None) => Ok(()),
+        Err(e) => Err(AuthAPIError::UnexpectedError(e.into())),
+    }
+}
+
+/// Registers a new session for the user and returns its auth and refresh cookies. Every way of logging in ends
+/// here, so this is where successful logins are audited.
+#[instrument(name = "Start session", skip_all)]
+pub async fn start_session(
+    state: &AppState,
+    user_id: &Uuid,
+    client: ClientInfo,
+) -> Result<(Cookie<'static>, Cookie<'static>)> {
+    let session_id = Uuid::new_v4();
+    let token_version = token_version(state, user_id).await.wrap_err("Failed to get token version")?;
+    let roles = user_roles(state, user_id).await?;
+    let claims = auth_claims(user_id, &session_id, token_version, roles)?;
+    let auth_cookie = generate_auth_cookie(&claims)?;
+    let refresh_cookie = generate_refresh_cookie(state.refresh_token_store.clone(), user_id, &session_id).await?;
+    let session = Session {
+        session_id,
+        user_id: *user_id,
+        jti: claims.jti,
+        created_at: claims.iat as i64,
+        refreshed_at: claims.iat as i64,
+        ip: client.ip,
+        user_agent: client.user_agent.clone(),
+    };
+
+    state.session_store.add_session(session).await.wrap_err("Failed to store session")?;
+
+    record_event(state, client.event(AuditEventType::LoginSucceeded).user(*user_id)).await;
+
+    Ok((auth_cookie, refresh_cookie))
+}
+
+/// Issues the next auth cookie of an existing session, carrying the user's current token version.
+#[instrument(name = "Continue session", skip_all)]
+pub async fn continue_session(state: &AppState, user_id: &Uuid, session_id: &Uuid) -> Result<Cookie<'static>> {
+    let token_version = token_version(state, user_id).await.wrap_err("Failed to get token version")?;
+    let roles = user_roles(state, user_id).await?;
+    let claims = auth_claims(user_id, session_id, token_version, roles)?;
+    let auth_cookie = generate_auth_cookie(&claims)?;
+
+    match state.session_store.record_token(user_id, session_id, claims.jti, claims.iat as i64).await {
+        // sessions started before the registry existed are not listed, but keep working
+        Ok(()) | Err(SessionStoreError::SessionNotFound) => Ok(auth_cookie),
+        Err(e) => Err(e).wrap_err("Failed to record session token"),
+    }
+}
+
+/// Version the user's auth tokens have to carry. The user store keeps it and the banned token store caches it; an
+/// unreachable cache only costs the database read.
+pub async fn token_version(state: &AppState, user_id: &Uuid) -> Result<u64, UserStoreError> {
+    match state.banned_token_store.get_token_version(user_id).await {
+        Ok(Some(version)) => return Ok(version),
+        Ok(None) => {}
+        Err(e) => warn!("Failed to read cached token version: {e:?}"),
+    }
+
+    let version = state.user_store.get_token_version(user_id).await?;
+
+    if let Err(e) = state.banned_token_store.cache_token_version(user_id, version).await {
+        warn!("Failed to cache token version: {e:?}");
+    }
+
+    Ok(version)
+}
+
+/// Rejects every auth token issued to the user so far. The cached version is read first since it may be the only
+/// record of a bump made before the user store kept versions, and the new one has to be cached for the bump to take
+/// effect, so both steps fail the bump.
+pub async fn bump_token_version(state: &AppState, user_id: &Uuid) -> Result<u64> {
+    let known =
+        state.banned_token_store.get_token_version(user_id).await.wrap_err("Failed to read cached token version")?;
+    let version = state
+        .user_store
+        .bump_token_version(user_id, known.unwrap_or_default())
+        .await
+        .wrap_err("Failed to bump token version")?;
+
+    state.banned_token_store.cache_token_version(user_id, version).await.wrap_err("Failed to cache token version")?;
+
+    Ok(version)
+}
+
+/// Roles to put in the user's next auth token: the default role followed by those assigned to the user.
+async fn user_roles(state: &AppState, user_id: &Uuid) -> Result<Vec<String>> {
+    let assigned = state.role_store.get_user_roles(user_id).await.wrap_err("Failed to get user roles")?;
+
+    Ok([DEFAULT_ROLE.to_owned()].into_iter().chain(assigned).collect())
 }
 
 #[instrument(name = "Generate auth cookie", skip_all)]
-pub fn generate_auth_cookie(email: &Email) -> Result<Cookie<'static>> {
-    let token = generate_auth_token(email)?;
+fn generate_auth_cookie(claims: &Claims) -> Result<Cookie<'static>> {
+    let token = create_token(claims)?;
 
     Ok(create_auth_cookie(token))
 }
 
+#[instrument(name = "Generate refresh cookie", skip_all)]
+pub async fn generate_refresh_cookie(
+    store: RefreshTokenStoreType,
+    user_id: &Uuid,
+    session_id: &Uuid,
+) -> Result<Cookie<'static>> {
+    let token = RefreshToken::default();
+
+    store.add_token(*user_id, *session_id, token.clone()).await.wrap_err("Failed to store refresh token")?;
+
+    Ok(create_refresh_cookie(&token))
+}
+
 #[instrument(name = "Validate token", skip_all)]
-pub async fn validate_token(
-    store: Option<BannedTokenStoreType>,
-    token: &SecretBox<String>,
-) -> Result<Claims, ValidateTokenError> {
-    if let Some(store) = store {
-        let Ok(exists) = store.check(token).await
+pub async fn validate_token(state: Option<&AppState>, token: &SecretBox<String>) -> Result<Claims, ValidateTokenError> {
+    let claims = decode_with_key_ring::<Claims>(token.expose_secret(), &auth_token_validation())?;
+
+    if let Some(state) = state {
+        let store = &state.banned_token_store;
+        let (Ok(user_id), Ok(session_id)) = (Uuid::parse_str(&claims.sub), Uuid::parse_str(&claims.sid))
+        else {
+            return Err(ValidateTokenError::UnexpectedError);
+        };
+        let token_version = match token_version(state, &user_id).await {
+            Ok(version) => version,
+            Err(UserStoreError::UserNotFound) => return Err(ValidateTokenError::BannedToken),
+            Err(_) => return Err(ValidateTokenError::UnexpectedError),
+        };
+        let (Ok(banned), Ok(session_revoked)) =
+            (store.check(&claims.jti, token).await, store.check_session(&session_id).await)
         else {
             return Err(ValidateTokenError::UnexpectedError);
         };
 
-        if exists {
+        if banned || claims.ver != token_version || session_revoked {
             return Err(ValidateTokenError::BannedToken);
         }
     }
 
-    match decode::<Claims>(
-        token.expose_secret(),
-        &DecodingKey::from_secret(JWT_SECRET.expose_secret().as_bytes()),
-        &Validation::default(),
-    )
-    .map(|data| data.claims)
-    {
-        Ok(claims) => Ok(claims),
-        Err(error) => Err(ValidateTokenError::TokenError(error)),
+    Ok(claims)
+}
+
+/// Checks the signature, lifetime, issuer and audience of an auth token.
+pub fn auth_token_validation() -> Validation {
+    let mut validation = Validation::new(JWT_ALGORITHM);
+
+    validation.set_issuer(&[JWT_ISSUER.as_str()]);
+    validation.set_audience(&[JWT_AUDIENCE.as_str()]);
+    validation.set_required_spec_claims(&["exp", "nbf", "iat", "iss", "aud", "sub"]);
+    validation.validate_nbf = true;
+
+    validation
+}
+
+/// Verifies `token` with the key ring entry named by its `kid` header.
+fn decode_with_key_ring<T: DeserializeOwned>(token: &str, validation: &Validation) -> Result<T, ValidateTokenError> {
+    let header = decode_header(token).map_err(ValidateTokenError::TokenError)?;
+    let Some(key) = header.kid.and_then(|kid| JWT_KEY_RING.find(&kid, Utc::now().timestamp()))
+    else {
+        return Err(ValidateTokenError::UnknownKey);
+    };
+
+    decode::<T>(token, key.decoding_key(), validation).map(|data| data.claims).map_err(ValidateTokenError::TokenError)
+}
+
+#[instrument(name = "Generate magic link token", skip_all)]
+pub fn generate_magic_link_token(email: &Email, nonce: &MagicLinkNonce) -> Result<MagicLinkToken> {
+    let delta = Duration::try_seconds(MAGIC_LINK_TTL_SECONDS).wrap_err("Failed to create magic link time delta")?;
+    let exp =
+        Utc::now().checked_add_signed(delta).ok_or(eyre!("Failed to add magic link TTL to current time"))?.timestamp();
+    let exp: usize = exp.try_into().wrap_err(format!("Failed to cast exp time to usize. exp time: {exp}"))?;
+    let claims = MagicLinkClaims {
+        aud: MAGIC_LINK_AUDIENCE.to_owned(),
+        exp,
+        nonce: hash_nonce(nonce),
+        sub: email.as_ref().expose_secret().to_owned(),
+    };
+    let token = create_token(&claims)?;
+
+    MagicLinkToken::parse(token.expose_secret()).map_err(|e| eyre!(e))
+}
+
+/// Checks the signature and expiry of `token` and that it was requested by the browser holding `nonce`.
+#[instrument(name = "Validate magic link token", skip_all)]
+pub fn validate_magic_link_token(
+    token: &MagicLinkToken,
+    nonce: &MagicLinkNonce,
+) -> Result<MagicLinkClaims, ValidateTokenError> {
+    let mut validation = Validation::new(JWT_ALGORITHM);
+
+    validation.set_audience(&[MAGIC_LINK_AUDIENCE]);
+    validation.set_required_spec_claims(&["exp", "aud", "sub"]);
+
+    let claims = decode_with_key_ring::<MagicLinkClaims>(token.as_ref().expose_secret(), &validation)?;
+
+    if claims.nonce != hash_nonce(nonce) {
+        return Err(ValidateTokenError::InvalidNonce);
     }
+
+    Ok(claims)
+}
+
+#[instrument(name = "Create magic link nonce cookie", skip_all)]
+pub fn create_magic_link_nonce_cookie(nonce: &MagicLinkNonce) -> Cookie<'static> {
+    let cookie = Cookie::build((MAGIC_LINK_NONCE_COOKIE_NAME, nonce.as_ref().expose_secret().to_owned()))
+        .path("/")
+        .http_only(true)
+        .same_site(SameSite::Lax)
+        .max_age(CookieDuration::seconds(MAGIC_LINK_TTL_SECONDS))
+        .build();
+
+    cookie
 }
 
 #[instrument(name = "Create auth cookie", skip_all)]
@@ -86,36 +516,111 @@ fn create_auth_cookie(token: SecretBox<String>) -> Cookie<'static> {
     cookie
 }
 
+#[instrument(name = "Create refresh cookie", skip_all)]
+pub fn create_refresh_cookie(token: &RefreshToken) -> Cookie<'static> {
+    let cookie = Cookie::build((REFRESH_COOKIE_NAME, token.as_ref().expose_secret().to_owned()))
+        .path("/")
+        .http_only(true)
+        .same_site(SameSite::Lax)
+        .max_age(CookieDuration::seconds(REFRESH_TOKEN_TTL_SECONDS))
+        .build();
+
+    cookie
+}
+
 #[instrument(name = "Generate auth token", skip_all)]
-pub fn generate_auth_token(email: &Email) -> Result<SecretBox<String>> {
+pub fn generate_auth_token(
+    user_id: &Uuid,
+    session_id: &Uuid,
+    token_version: u64,
+    roles: Vec<String>,
+) -> Result<SecretBox<String>> {
+    create_token(&auth_claims(user_id, session_id, token_version, roles)?)
+}
+
+fn auth_claims(user_id: &Uuid, session_id: &Uuid, token_version: u64, roles: Vec<String>) -> Result<Claims> {
+    let now = Utc::now();
     let delta = Duration::try_seconds(TOKEN_TTL_SECONDS).wrap_err("Failed to create 10 minutes time delta")?;
-    let exp =
-        Utc::now().checked_add_signed(delta).ok_or(eyre!("Failed to add 10 minutes to current time"))?.timestamp();
+    let exp = now.checked_add_signed(delta).ok_or(eyre!("Failed to add 10 minutes to current time"))?.timestamp();
     let exp: usize = exp.try_into().wrap_err(format!("Failed to cast exp time to usize. exp time: {exp}"))?;
-    let sub = email.as_ref().expose_secret().to_owned();
-    let claims = Claims { sub, exp };
+    let iat: usize = now.timestamp().try_into().wrap_err("Failed to cast current time to usize")?;
+    let claims = Claims {
+        aud: JWT_AUDIENCE.to_owned(),
+        exp,
+        iat,
+        iss: JWT_ISSUER.to_owned(),
+        jti: Uuid::new_v4().to_string(),
+        nbf: iat,
+        roles,
+        sid: session_id.to_string(),
+        sub: user_id.to_string(),
+        ver: token_version,
+    };
 
-    create_token(&claims)
+    Ok(claims)
 }
 
 #[instrument(name = "Create token", skip_all)]
-fn create_token(claims: &Claims) -> Result<SecretBox<String>> {
-    match encode(&Header::default(), &claims, &EncodingKey::from_secret(JWT_SECRET.expose_secret().as_bytes()))
-        .wrap_err("Failed to create token")
-    {
+fn create_token<T: Serialize>(claims: &T) -> Result<SecretBox<String>> {
+    let key = JWT_KEY_RING.active(Utc::now().timestamp()).ok_or_else(|| eyre!("No JWT signing key loaded"))?;
+
+    match encode(&key.header(), &claims, key.encoding_key()).wrap_err("Failed to create token") {
         Ok(token) => Ok(SecretBox::new(Box::new(token))),
         Err(e) => Err(e),
     }
 }
 
+fn hash_nonce(nonce: &MagicLinkNonce) -> String {
+    format!("{:x}", Sha256::digest(nonce.as_ref().expose_secret().as_bytes()))
+}
+
 #[cfg(test)]
 mod tests {
-    use {super::*, secrecy::SecretBox};
+    use {
+        super::*,
+        crate::{
+            domain::role::ADMIN_ROLE,
+            utils::keys::{JwtKey, SigningKey},
+        },
+        secrecy::SecretBox,
+        std::sync::Once,
+    };
+
+    /// Outside of tests `main` loads the key ring from the signing key store.
+    fn load_signing_key() {
+        static LOADED: Once = Once::new();
+
+        LOADED.call_once(|| JWT_KEY_RING.load(vec![SigningKey::new(JwtKey::generate().unwrap(), 0)]));
+    }
+
+    fn forwarded_for(value: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+
+        headers.insert("x-forwarded-for", value.parse().unwrap());
+        headers
+    }
+
+    #[test]
+    fn test_client_ip_ignores_forwarded_for_from_untrusted_peer() {
+        let peer = "203.0.113.7".parse().unwrap();
+
+        assert_eq!(forwarded_client_ip(&forwarded_for("198.51.100.1"), peer, &[]), peer);
+    }
+
+    #[test]
+    fn test_client_ip_stops_at_first_untrusted_hop() {
+        let proxies = ["10.0.0.1".parse().unwrap(), "10.0.0.2".parse().unwrap()];
+        let headers = forwarded_for("192.0.2.9, 198.51.100.1, 10.0.0.2");
+
+        assert_eq!(forwarded_client_ip(&headers, proxies[0], &proxies), "198.51.100.1".parse::<IpAddr>().unwrap());
+        assert_eq!(forwarded_client_ip(&HeaderMap::new(), proxies[0], &proxies), proxies[0]);
+    }
 
     #[tokio::test]
     async fn test_generate_auth_cookie() {
-        let email = Email::parse(&SecretBox::new(Box::new("test@example.com".to_string()))).unwrap();
-        let cookie = generate_auth_cookie(&email).unwrap();
+        load_signing_key();
+        let cookie =
+            generate_auth_cookie(&auth_claims(&Uuid::new_v4(), &Uuid::new_v4(), 0, Vec::new()).unwrap()).unwrap();
 
         assert_eq!(cookie.name(), JWT_COOKIE_NAME);
         assert_eq!(cookie.value().split('.').count(), 3);
@@ -136,28 +641,104 @@ mod tests {
         assert_eq!(cookie.same_site(), Some(SameSite::Lax));
     }
 
+    #[tokio::test]
+    async fn test_create_refresh_cookie() {
+        let token = RefreshToken::default();
+        let cookie = create_refresh_cookie(&token);
+
+        assert_eq!(cookie.name(), REFRESH_COOKIE_NAME);
+        assert_eq!(cookie.value(), token.as_ref().expose_secret());
+        assert_eq!(cookie.path(), Some("/"));
+        assert_eq!(cookie.http_only(), Some(true));
+        assert_eq!(cookie.same_site(), Some(SameSite::Lax));
+        assert_eq!(cookie.max_age(), Some(CookieDuration::seconds(REFRESH_TOKEN_TTL_SECONDS)));
+    }
+
     #[tokio::test]
     async fn test_generate_auth_token() {
-        let email = Email::parse(&SecretBox::new(Box::new("test@example.com".to_string()))).unwrap();
-        let result = generate_auth_token(&email).unwrap();
+        load_signing_key();
+        let result = generate_auth_token(&Uuid::new_v4(), &Uuid::new_v4(), 0, Vec::new()).unwrap();
 
         assert_eq!(result.expose_secret().split('.').count(), 3);
     }
 
     #[tokio::test]
     async fn test_validate_token_with_valid_token() {
-        let email = Email::parse(&SecretBox::new(Box::new("test@example.com".to_string()))).unwrap();
-        let token = generate_auth_token(&email).unwrap();
+        load_signing_key();
+        let user_id = Uuid::new_v4();
+        let session_id = Uuid::new_v4();
+        let roles = vec![DEFAULT_ROLE.to_owned(), ADMIN_ROLE.to_owned()];
+        let token = generate_auth_token(&user_id, &session_id, 3, roles.clone()).unwrap();
         let result = validate_token(None, &token).await.unwrap();
+        let now = Utc::now().timestamp() as usize;
 
-        assert_eq!(result.sub, "test@example.com");
+        assert_eq!(result.sub, user_id.to_string());
+        assert_eq!(result.sid, session_id.to_string());
+        assert_eq!(result.ver, 3);
+        assert_eq!(result.iss, *JWT_ISSUER);
+        assert_eq!(result.aud, *JWT_AUDIENCE);
+        assert_eq!(result.roles, roles);
+        assert!(result.iat <= now && result.iat + 5 > now);
+        assert_eq!(result.nbf, result.iat);
+        assert_eq!(result.exp, result.iat + TOKEN_TTL_SECONDS as usize);
+        assert!(Uuid::parse_str(&result.jti).is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_generate_auth_token_uses_unique_jti() {
+        load_signing_key();
+        let user_id = Uuid::new_v4();
+        let session_id = Uuid::new_v4();
+        let first =
+            validate_token(None, &generate_auth_token(&user_id, &session_id, 0, Vec::new()).unwrap()).await.unwrap();
+        let second =
+            validate_token(None, &generate_auth_token(&user_id, &session_id, 0, Vec::new()).unwrap()).await.unwrap();
+
+        assert_ne!(first.jti, second.jti);
+    }
+
+    #[tokio::test]
+    async fn test_validate_token_rejects_wrong_issuer_or_audience() {
+        load_signing_key();
+        let iat = Utc::now().timestamp() as usize;
+        let claims = Claims {
+            aud: JWT_AUDIENCE.to_owned(),
+            exp: iat + 60,
+            iat,
+            iss: "someone-else".to_owned(),
+            jti: Uuid::new_v4().to_string(),
+            nbf: iat,
+            roles: Vec::new(),
+            sid: Uuid::new_v4().to_string(),
+            sub: Uuid::new_v4().to_string(),
+            ver: 0,
+        };
 
-        let exp = Utc::now()
-            .checked_add_signed(chrono::Duration::try_minutes(9).expect("valid duration"))
-            .expect("valid timestamp")
-            .timestamp();
+        assert!(validate_token(None, &create_token(&claims).unwrap()).await.is_err());
 
-        assert!(result.exp > exp as usize);
+        let claims = Claims { aud: "another-service".to_owned(), iss: JWT_ISSUER.to_owned(), ..claims };
+
+        assert!(validate_token(None, &create_token(&claims).unwrap()).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_validate_token_rejects_token_before_nbf() {
+        load_signing_key();
+        let iat = Utc::now().timestamp() as usize;
+        let claims = Claims {
+            aud: JWT_AUDIENCE.to_owned(),
+            exp: iat + 600,
+            iat,
+            iss: JWT_ISSUER.to_owned(),
+            jti: Uuid::new_v4().to_string(),
+            nbf: iat + 300,
+            roles: Vec::new(),
+            sid: Uuid::new_v4().to_string(),
+            sub: Uuid::new_v4().to_string(),
+            ver: 0,
+        };
+
+        assert!(validate_token(None, &create_token(&claims).unwrap()).await.is_err());
     }
 
     #[tokio::test]
@@ -167,4 +748,40 @@ mod tests {
 
         assert!(result.is_err());
     }
+
+    #[tokio::test]
+    async fn test_validate_magic_link_token_with_matching_nonce() {
+        load_signing_key();
+        let email = Email::parse(&SecretBox::new(Box::new("test@example.com".to_string()))).unwrap();
+        let nonce = MagicLinkNonce::default();
+        let token = generate_magic_link_token(&email, &nonce).unwrap();
+        let result = validate_magic_link_token(&token, &nonce).unwrap();
+
+        assert_eq!(result.sub, "test@example.com");
+        assert_ne!(result.nonce, nonce.as_ref().expose_secret().to_owned());
+    }
+
+    #[tokio::test]
+    async fn test_validate_magic_link_token_with_other_nonce() {
+        load_signing_key();
+        let email = Email::parse(&SecretBox::new(Box::new("test@example.com".to_string()))).unwrap();
+        let token = generate_magic_link_token(&email, &MagicLinkNonce::default()).unwrap();
+        let result = validate_magic_link_token(&token, &MagicLinkNonce::default());
+
+        assert!(matches!(result, Err(ValidateTokenError::InvalidNonce)));
+    }
+
+    #[tokio::test]
+    async fn test_magic_link_token_and_auth_token_are_not_interchangeable() {
+        load_signing_key();
+        let email = Email::parse(&SecretBox::new(Box::new("test@example.com".to_string()))).unwrap();
+        let nonce = MagicLinkNonce::default();
+        let magic_link_token = generate_magic_link_token(&email, &nonce).unwrap();
+        let auth_token = generate_auth_token(&Uuid::new_v4(), &Uuid::new_v4(), 0, Vec::new()).unwrap();
+
+        assert!(validate_token(None, magic_link_token.as_ref()).await.is_err());
+        assert!(
+            validate_magic_link_token(&MagicLinkToken::parse(auth_token.expose_secret()).unwrap(), &nonce).is_err()
+        );
+    }
 }
@@ -1,8 +1,28 @@
 pub mod env {
-    pub const JWT_SECRET_ENV_VAR: &str = "JWT_SECRET";
+    pub const JWT_PRIVATE_KEY_PATH_ENV_VAR: &str = "JWT_PRIVATE_KEY_PATH";
+    pub const JWT_RETIRED_KEY_PATHS_ENV_VAR: &str = "JWT_RETIRED_KEY_PATHS";
+    pub const JWT_KEY_ROTATION_SECONDS_ENV_VAR: &str = "JWT_KEY_ROTATION_SECONDS";
+    pub const JWT_KEY_OVERLAP_SECONDS_ENV_VAR: &str = "JWT_KEY_OVERLAP_SECONDS";
+    pub const ADMIN_API_KEY_ENV_VAR: &str = "ADMIN_API_KEY";
+    pub const JWT_ISSUER_ENV_VAR: &str = "JWT_ISSUER";
+    pub const JWT_AUDIENCE_ENV_VAR: &str = "JWT_AUDIENCE";
     pub const DATABASE_URL_ENV_VAR: &str = "DATABASE_URL";
     pub const REDIS_HOST_NAME_ENV_VAR: &str = "REDIS_HOST_NAME";
     pub const RESEND_SENDER_API_KEY_ENV_VAR: &str = "RESEND_SENDER_API_KEY";
+    pub const TOTP_ENCRYPTION_KEY_ENV_VAR: &str = "TOTP_ENCRYPTION_KEY";
+    pub const TOTP_SKEW_ENV_VAR: &str = "TOTP_SKEW";
+    pub const WEBAUTHN_RP_ID_ENV_VAR: &str = "WEBAUTHN_RP_ID";
+    pub const WEBAUTHN_RP_ORIGIN_ENV_VAR: &str = "WEBAUTHN_RP_ORIGIN";
+    pub const MAGIC_LINK_URL_ENV_VAR: &str = "MAGIC_LINK_URL";
+    pub const CHANGE_EMAIL_URL_ENV_VAR: &str = "CHANGE_EMAIL_URL";
+    pub const LOGIN_BACKOFF_THRESHOLD_ENV_VAR: &str = "LOGIN_BACKOFF_THRESHOLD";
+    pub const LOGIN_LOCKOUT_THRESHOLD_ENV_VAR: &str = "LOGIN_LOCKOUT_THRESHOLD";
+    pub const LOGIN_LOCKOUT_SECONDS_ENV_VAR: &str = "LOGIN_LOCKOUT_SECONDS";
+    pub const IP_BACKOFF_THRESHOLD_ENV_VAR: &str = "IP_BACKOFF_THRESHOLD";
+    pub const IP_LOCKOUT_THRESHOLD_ENV_VAR: &str = "IP_LOCKOUT_THRESHOLD";
+    pub const MAX_2FA_ATTEMPTS_ENV_VAR: &str = "MAX_2FA_ATTEMPTS";
+    pub const ACCOUNT_DELETION_GRACE_SECONDS_ENV_VAR: &str = "ACCOUNT_DELETION_GRACE_SECONDS";
+    pub const TRUSTED_PROXIES_ENV_VAR: &str = "TRUSTED_PROXIES";
 }
 
 pub mod prod {
@@ -18,6 +38,7 @@ pub mod prod {
 
 pub mod test {
     pub const APP_ADDRESS: &str = "127.0.0.1:0";
+    pub const ADMIN_API_KEY: &str = "test-admin-api-key";
 
     pub mod email_client {
         use std::time::Duration;
@@ -27,28 +48,126 @@ pub mod test {
     }
 }
 
-use {dotenvy::dotenv, lazy_static::lazy_static, secrecy::SecretBox, std::env::var};
+use {
+    super::keys::{JwtKey, KeyRing},
+    dotenvy::dotenv,
+    lazy_static::lazy_static,
+    secrecy::SecretBox,
+    std::{env::var, fs::read_to_string, net::IpAddr, str::FromStr, sync::Arc, time::Duration},
+};
 
 pub const JWT_COOKIE_NAME: &str = "jwt";
+pub const REFRESH_COOKIE_NAME: &str = "refresh_token";
+pub const MAGIC_LINK_NONCE_COOKIE_NAME: &str = "magic_link_nonce";
 pub const DEFAULT_REDIS_HOSTNAME: &str = "127.0.0.1";
+pub const DEFAULT_TOTP_SKEW: u8 = 1;
+pub const TOTP_ISSUER: &str = "LGR Auth Service";
+pub const DEFAULT_WEBAUTHN_RP_ID: &str = "localhost";
+pub const DEFAULT_WEBAUTHN_RP_ORIGIN: &str = "http://localhost:3000";
+pub const WEBAUTHN_RP_NAME: &str = "LGR Auth Service";
+pub const DEFAULT_MAGIC_LINK_URL: &str = "http://localhost:8000/magic-link";
+pub const DEFAULT_CHANGE_EMAIL_URL: &str = "http://localhost:8000/change-email";
+pub const DEFAULT_LOGIN_BACKOFF_THRESHOLD: u32 = 3;
+pub const DEFAULT_LOGIN_LOCKOUT_THRESHOLD: u32 = 10;
+pub const DEFAULT_LOGIN_LOCKOUT_SECONDS: u64 = 900;
+pub const DEFAULT_IP_BACKOFF_THRESHOLD: u32 = 20;
+pub const DEFAULT_IP_LOCKOUT_THRESHOLD: u32 = 100;
+pub const DEFAULT_MAX_2FA_ATTEMPTS: u32 = 5;
+pub const DEFAULT_JWT_ISSUER: &str = "auth-service";
+pub const DEFAULT_JWT_AUDIENCE: &str = "app-service";
+pub const DEFAULT_JWT_KEY_ROTATION_SECONDS: u64 = 0;
+pub const DEFAULT_JWT_KEY_OVERLAP_SECONDS: i64 = 60 * 60;
+/// How often every instance reloads the signing keys from the signing key store.
+pub const KEY_RING_SYNC_INTERVAL: Duration = Duration::from_secs(30);
+/// How long a new signing key is published before it signs. Exceeds `KEY_RING_SYNC_INTERVAL` so every instance
+/// verifies tokens signed with the key by the time they show up.
+pub const KEY_ACTIVATION_DELAY_SECONDS: i64 = 60;
+pub const DEFAULT_ACCOUNT_DELETION_GRACE_SECONDS: u64 = 30 * 24 * 60 * 60;
+pub const ACCOUNT_PURGE_INTERVAL: Duration = Duration::from_secs(60 * 60);
+/// Page size of the admin listings when none is asked for, and the largest one allowed.
+pub const DEFAULT_PAGE_SIZE: u64 = 20;
+pub const MAX_PAGE_SIZE: u64 = 100;
 
 lazy_static! {
-    pub static ref JWT_SECRET: SecretBox<String> = set_token();
+    /// Signs and verifies all issued JWTs; its public keys are published at `/.well-known/jwks.json`. Empty until
+    /// loaded from the signing key store with `load_key_ring`.
+    pub static ref JWT_KEY_RING: KeyRing = KeyRing::new(*JWT_KEY_OVERLAP_SECONDS);
+    /// Signing key to add to the signing key store if it does not hold it yet.
+    pub static ref JWT_PRIVATE_KEY: Option<Arc<JwtKey>> = set_jwt_private_key();
+    /// Keys to add to the signing key store as retired, so they verify tokens for the overlap window after first
+    /// being loaded.
+    pub static ref JWT_RETIRED_KEYS: Vec<Arc<JwtKey>> = set_jwt_retired_keys();
+    /// Interval of scheduled signing key rotation, disabled when 0.
+    pub static ref JWT_KEY_ROTATION_SECONDS: u64 =
+        set_number(env::JWT_KEY_ROTATION_SECONDS_ENV_VAR, DEFAULT_JWT_KEY_ROTATION_SECONDS);
+    /// How long a retired key keeps verifying tokens. Must outlive the tokens it signed.
+    pub static ref JWT_KEY_OVERLAP_SECONDS: i64 =
+        set_number(env::JWT_KEY_OVERLAP_SECONDS_ENV_VAR, DEFAULT_JWT_KEY_OVERLAP_SECONDS);
+    pub static ref JWT_ISSUER: String = set_string(env::JWT_ISSUER_ENV_VAR, DEFAULT_JWT_ISSUER);
+    /// Service that auth tokens are meant for; tokens for any other audience are rejected.
+    pub static ref JWT_AUDIENCE: String = set_string(env::JWT_AUDIENCE_ENV_VAR, DEFAULT_JWT_AUDIENCE);
+    /// Bearer credential for admin endpoints, which are disabled when unset.
+    pub static ref ADMIN_API_KEY: Option<SecretBox<String>> = set_admin_api_key();
     pub static ref DATABASE_URL: SecretBox<String> = set_database_url();
     pub static ref REDIS_HOST_NAME: String = set_redis_host();
     pub static ref RESEND_SENDER_API_KEY: SecretBox<String> = set_resend_token();
+    pub static ref TOTP_ENCRYPTION_KEY: SecretBox<String> = set_totp_encryption_key();
+    pub static ref TOTP_SKEW: u8 = set_totp_skew();
+    pub static ref WEBAUTHN_RP_ID: String = set_webauthn_rp_id();
+    pub static ref WEBAUTHN_RP_ORIGIN: String = set_webauthn_rp_origin();
+    pub static ref MAGIC_LINK_URL: String = set_magic_link_url();
+    /// Page the confirmation and cancel links of an email change point to.
+    pub static ref CHANGE_EMAIL_URL: String = set_string(env::CHANGE_EMAIL_URL_ENV_VAR, DEFAULT_CHANGE_EMAIL_URL);
+    /// Failures after which every further attempt on an account has to wait exponentially longer.
+    pub static ref LOGIN_BACKOFF_THRESHOLD: u32 =
+        set_number(env::LOGIN_BACKOFF_THRESHOLD_ENV_VAR, DEFAULT_LOGIN_BACKOFF_THRESHOLD);
+    /// Failures after which an account is locked until its counter expires.
+    pub static ref LOGIN_LOCKOUT_THRESHOLD: u32 =
+        set_number(env::LOGIN_LOCKOUT_THRESHOLD_ENV_VAR, DEFAULT_LOGIN_LOCKOUT_THRESHOLD);
+    /// How long failure counters live after the first failure they count.
+    pub static ref LOGIN_LOCKOUT_SECONDS: u64 =
+        set_number(env::LOGIN_LOCKOUT_SECONDS_ENV_VAR, DEFAULT_LOGIN_LOCKOUT_SECONDS);
+    pub static ref IP_BACKOFF_THRESHOLD: u32 =
+        set_number(env::IP_BACKOFF_THRESHOLD_ENV_VAR, DEFAULT_IP_BACKOFF_THRESHOLD);
+    pub static ref IP_LOCKOUT_THRESHOLD: u32 =
+        set_number(env::IP_LOCKOUT_THRESHOLD_ENV_VAR, DEFAULT_IP_LOCKOUT_THRESHOLD);
+    /// Wrong codes after which the pending 2FA attempt is discarded.
+    pub static ref MAX_2FA_ATTEMPTS: u32 = set_number(env::MAX_2FA_ATTEMPTS_ENV_VAR, DEFAULT_MAX_2FA_ATTEMPTS);
+    /// How long a deleted account is kept before it is purged, 0 deletes it right away.
+    pub static ref ACCOUNT_DELETION_GRACE_SECONDS: u64 =
+        set_number(env::ACCOUNT_DELETION_GRACE_SECONDS_ENV_VAR, DEFAULT_ACCOUNT_DELETION_GRACE_SECONDS);
+    /// Reverse proxies whose `X-Forwarded-For` header is believed. Without any, every client behind a proxy shares
+    /// the proxy's address and with it the per-IP rate limits and login throttling.
+    pub static ref TRUSTED_PROXIES: Vec<IpAddr> = set_trusted_proxies();
 }
 
-fn set_token() -> SecretBox<String> {
+fn set_jwt_private_key() -> Option<Arc<JwtKey>> {
     dotenv().ok();
 
-    let secret = var(env::JWT_SECRET_ENV_VAR).expect("JWT_SECRET must be set.");
+    var(env::JWT_PRIVATE_KEY_PATH_ENV_VAR).ok().filter(|path| !path.is_empty()).map(|path| read_jwt_key(&path))
+}
 
-    if secret.is_empty() {
-        panic!("JWT_SECRET must not be empty.");
-    }
+fn set_jwt_retired_keys() -> Vec<Arc<JwtKey>> {
+    dotenv().ok();
 
-    SecretBox::new(Box::new(secret))
+    var(env::JWT_RETIRED_KEY_PATHS_ENV_VAR)
+        .unwrap_or_default()
+        .split(',')
+        .filter(|p| !p.is_empty())
+        .map(read_jwt_key)
+        .collect()
+}
+
+fn read_jwt_key(path: &str) -> Arc<JwtKey> {
+    let pem = read_to_string(path).unwrap_or_else(|e| panic!("Failed to read JWT key {path}: {e}"));
+
+    Arc::new(JwtKey::from_pem(&pem).unwrap_or_else(|e| panic!("JWT key {path} must be an Ed25519 key: {e}")))
+}
+
+fn set_admin_api_key() -> Option<SecretBox<String>> {
+    dotenv().ok();
+
+    var(env::ADMIN_API_KEY_ENV_VAR).ok().filter(|key| !key.is_empty()).map(|key| SecretBox::new(Box::new(key)))
 }
 
 fn set_database_url() -> SecretBox<String> {
@@ -80,3 +199,69 @@ fn set_resend_token() -> SecretBox<String> {
 
     SecretBox::new(Box::new(secret))
 }
+
+fn set_totp_encryption_key() -> SecretBox<String> {
+    dotenv().ok();
+
+    let secret = var(env::TOTP_ENCRYPTION_KEY_ENV_VAR).expect("TOTP_ENCRYPTION_KEY must be set.");
+
+    if secret.is_empty() {
+        panic!("TOTP_ENCRYPTION_KEY must not be empty.");
+    }
+
+    SecretBox::new(Box::new(secret))
+}
+
+fn set_totp_skew() -> u8 {
+    dotenv().ok();
+
+    match var(env::TOTP_SKEW_ENV_VAR) {
+        Ok(skew) => skew.parse().expect("TOTP_SKEW must be a number of 30 second steps."),
+        Err(_) => DEFAULT_TOTP_SKEW,
+    }
+}
+
+fn set_webauthn_rp_id() -> String {
+    dotenv().ok();
+
+    var(env::WEBAUTHN_RP_ID_ENV_VAR).unwrap_or(DEFAULT_WEBAUTHN_RP_ID.to_owned())
+}
+
+fn set_webauthn_rp_origin() -> String {
+    dotenv().ok();
+
+    var(env::WEBAUTHN_RP_ORIGIN_ENV_VAR).unwrap_or(DEFAULT_WEBAUTHN_RP_ORIGIN.to_owned())
+}
+
+fn set_magic_link_url() -> String {
+    dotenv().ok();
+
+    var(env::MAGIC_LINK_URL_ENV_VAR).unwrap_or(DEFAULT_MAGIC_LINK_URL.to_owned())
+}
+
+fn set_trusted_proxies() -> Vec<IpAddr> {
+    dotenv().ok();
+
+    var(env::TRUSTED_PROXIES_ENV_VAR)
+        .unwrap_or_default()
+        .split(',')
+        .map(str::trim)
+        .filter(|ip| !ip.is_empty())
+        .map(|ip| ip.parse().unwrap_or_else(|_| panic!("TRUSTED_PROXIES must be comma separated IP addresses.")))
+        .collect()
+}
+
+fn set_string(name: &str, default: &str) -> String {
+    dotenv().ok();
+
+    var(name).ok().filter(|value| !value.is_empty()).unwrap_or(default.to_owned())
+}
+
+fn set_number<T: FromStr>(name: &str, default: T) -> T {
+    dotenv().ok();
+
+    match var(name) {
+        Ok(value) => value.parse().unwrap_or_else(|_| panic!("{name} must be a positive number.")),
+        Err(_) => default,
+    }
+}
@@ -0,0 +1,33 @@
+use {
+    aes_gcm::{
+        Aes256Gcm, KeyInit, Nonce,
+        aead::{Aead, AeadCore, OsRng},
+    },
+    color_eyre::{Result, eyre::eyre},
+    secrecy::{ExposeSecret, SecretBox},
+    sha2::{Digest, Sha256},
+};
+
+const NONCE_LENGTH: usize = 12;
+
+/// Encrypts `plaintext` with AES-256-GCM under the SHA-256 digest of `key`, prefixing the random nonce.
+pub fn encrypt(plaintext: &[u8], key: &SecretBox<String>) -> Result<Vec<u8>> {
+    let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+    let ciphertext = cipher(key).encrypt(&nonce, plaintext).map_err(|_| eyre!("Failed to encrypt"))?;
+
+    Ok([nonce.as_slice(), &ciphertext].concat())
+}
+
+pub fn decrypt(encrypted: &[u8], key: &SecretBox<String>) -> Result<Vec<u8>> {
+    if encrypted.len() <= NONCE_LENGTH {
+        return Err(eyre!("Ciphertext is too short"));
+    }
+
+    let (nonce, ciphertext) = encrypted.split_at(NONCE_LENGTH);
+
+    cipher(key).decrypt(Nonce::from_slice(nonce), ciphertext).map_err(|_| eyre!("Failed to decrypt"))
+}
+
+fn cipher(key: &SecretBox<String>) -> Aes256Gcm {
+    Aes256Gcm::new(&Sha256::digest(key.expose_secret().as_bytes()))
+}
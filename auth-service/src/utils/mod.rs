@@ -1,3 +1,8 @@
+pub mod audit;
 pub mod auth;
 pub mod constants;
+pub mod encryption;
+pub mod keys;
+pub mod rate_limit;
+pub mod throttle;
 pub mod tracing;
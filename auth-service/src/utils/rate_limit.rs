@@ -0,0 +1,206 @@
+use {
+    crate::{
+        app_state::AppState,
+        domain::{
+            email::Email,
+            error::AuthAPIError,
+            rate_limit::{RateLimitDecision, RateLimitPolicy},
+        },
+        utils::auth::client_ip,
+    },
+    axum::{
+        body::{Body, to_bytes},
+        extract::{ConnectInfo, Request, State},
+        http::{HeaderMap, HeaderValue, StatusCode},
+        middleware::Next,
+        response::{IntoResponse, Response},
+    },
+    secrecy::{ExposeSecret, SecretBox},
+    serde::Deserialize,
+    std::net::SocketAddr,
+    tracing::{error, instrument},
+};
+
+/// Largest body buffered to read the email a route is limited by.
+const MAX_BODY_BYTES: usize = 64 * 1024;
+
+/// Routes other services call on behalf of each of their own requests, from the handful of addresses they run on.
+/// Limiting those per IP would throttle every user of such a service at once.
+const EXEMPT_PATHS: &[&str] = &["/verify-token", "/.well-known/jwks.json"];
+
+/// Limits every route that has no policy of its own.
+const DEFAULT_POLICY: RoutePolicy = RoutePolicy {
+    path: "*",
+    key: RateLimitKey::Ip,
+    policy: RateLimitPolicy { capacity: 100, refill_interval_ms: 600 },
+};
+
+/// Routes that send emails or check passwords get tighter buckets, per client IP and per targeted account.
+const ROUTE_POLICIES: &[RoutePolicy] = &[
+    RoutePolicy {
+        path: "/signup",
+        key: RateLimitKey::Ip,
+        policy: RateLimitPolicy { capacity: 10, refill_interval_ms: 60_000 },
+    },
+    RoutePolicy {
+        path: "/login",
+        key: RateLimitKey::Ip,
+        policy: RateLimitPolicy { capacity: 20, refill_interval_ms: 3_000 },
+    },
+    RoutePolicy {
+        path: "/login",
+        key: RateLimitKey::Email,
+        policy: RateLimitPolicy { capacity: 10, refill_interval_ms: 60_000 },
+    },
+    RoutePolicy {
+        path: "/account/restore",
+        key: RateLimitKey::Ip,
+        policy: RateLimitPolicy { capacity: 20, refill_interval_ms: 3_000 },
+    },
+    RoutePolicy {
+        path: "/account/restore",
+        key: RateLimitKey::Email,
+        policy: RateLimitPolicy { capacity: 10, refill_interval_ms: 60_000 },
+    },
+    RoutePolicy {
+        path: "/login/magic-link",
+        key: RateLimitKey::Ip,
+        policy: RateLimitPolicy { capacity: 10, refill_interval_ms: 30_000 },
+    },
+    RoutePolicy {
+        path: "/login/magic-link",
+        key: RateLimitKey::Email,
+        policy: RateLimitPolicy { capacity: 3, refill_interval_ms: 300_000 },
+    },
+    RoutePolicy {
+        path: "/password-reset/request",
+        key: RateLimitKey::Ip,
+        policy: RateLimitPolicy { capacity: 10, refill_interval_ms: 30_000 },
+    },
+    RoutePolicy {
+        path: "/password-reset/request",
+        key: RateLimitKey::Email,
+        policy: RateLimitPolicy { capacity: 3, refill_interval_ms: 300_000 },
+    },
+    RoutePolicy {
+        path: "/verify-email/resend",
+        key: RateLimitKey::Ip,
+        policy: RateLimitPolicy { capacity: 10, refill_interval_ms: 30_000 },
+    },
+    RoutePolicy {
+        path: "/verify-email/resend",
+        key: RateLimitKey::Email,
+        policy: RateLimitPolicy { capacity: 3, refill_interval_ms: 300_000 },
+    },
+];
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum RateLimitKey {
+    Ip,
+    /// The `email` field of the JSON body.
+    Email,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct RoutePolicy {
+    pub path: &'static str,
+    pub key: RateLimitKey,
+    pub policy: RateLimitPolicy,
+}
+
+#[derive(Deserialize)]
+struct EmailBody {
+    email: SecretBox<String>,
+}
+
+/// Takes a token from every bucket the request falls into and rejects it with 429 once any of them is empty.
+#[instrument(name = "Rate limit", skip_all)]
+pub async fn rate_limit(
+    State(state): State<AppState>,
+    ConnectInfo(address): ConnectInfo<SocketAddr>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let path = request.uri().path().to_owned();
+
+    if EXEMPT_PATHS.contains(&path.as_str()) {
+        return next.run(request).await;
+    }
+
+    let ip = client_ip(request.headers(), address.ip());
+    let policies = match ROUTE_POLICIES.iter().filter(|policy| policy.path == path).collect::<Vec<_>>() {
+        policies if policies.is_empty() => vec![&DEFAULT_POLICY],
+        policies => policies,
+    };
+    let (request, email) = match policies.iter().any(|policy| policy.key == RateLimitKey::Email) {
+        true => match read_email(request).await {
+            Ok(result) => result,
+            Err(response) => return response,
+        },
+        false => (request, None),
+    };
+    let mut decisions = Vec::with_capacity(policies.len());
+
+    for policy in policies {
+        let subject = match (policy.key, &email) {
+            (RateLimitKey::Ip, _) => format!("ip:{ip}"),
+            (RateLimitKey::Email, Some(email)) => format!("email:{}", email.as_ref().expose_secret().to_lowercase()),
+            // requests without a valid email are rejected by the handler and still count against the IP
+            (RateLimitKey::Email, None) => continue,
+        };
+
+        match state.rate_limit_store.acquire(&format!("{}:{subject}", policy.path), &policy.policy).await {
+            Ok(decision) => decisions.push(decision),
+            // fail open, an outage of the limiter must not take the service down with it
+            Err(e) => error!("Failed to acquire rate limit token: {e:?}"),
+        }
+    }
+
+    let Some(decision) = most_restrictive(&decisions)
+    else {
+        return next.run(request).await;
+    };
+
+    if !decision.allowed {
+        let mut response = AuthAPIError::TooManyRequests.into_response();
+
+        add_headers(response.headers_mut(), &decision);
+
+        return response;
+    }
+
+    let mut response = next.run(request).await;
+
+    add_headers(response.headers_mut(), &decision);
+
+    response
+}
+
+/// Buffers the body to read its `email` field and hands back an equivalent request.
+async fn read_email(request: Request) -> Result<(Request, Option<Email>), Response> {
+    let (parts, body) = request.into_parts();
+    let Ok(bytes) = to_bytes(body, MAX_BODY_BYTES).await
+    else {
+        return Err(StatusCode::PAYLOAD_TOO_LARGE.into_response());
+    };
+    let email = serde_json::from_slice::<EmailBody>(&bytes).ok().and_then(|body| Email::parse(&body.email).ok());
+
+    Ok((Request::from_parts(parts, Body::from(bytes)), email))
+}
+
+fn most_restrictive(decisions: &[RateLimitDecision]) -> Option<RateLimitDecision> {
+    match decisions.iter().filter(|decision| !decision.allowed).max_by_key(|decision| decision.retry_after) {
+        Some(rejected) => Some(*rejected),
+        None => decisions.iter().min_by_key(|decision| decision.remaining).copied(),
+    }
+}
+
+fn add_headers(headers: &mut HeaderMap, decision: &RateLimitDecision) {
+    headers.insert("RateLimit-Limit", HeaderValue::from(decision.limit));
+    headers.insert("RateLimit-Remaining", HeaderValue::from(decision.remaining));
+    headers.insert("RateLimit-Reset", HeaderValue::from(decision.reset_after));
+
+    if !decision.allowed {
+        headers.insert("Retry-After", HeaderValue::from(decision.retry_after));
+    }
+}
@@ -0,0 +1,123 @@
+use {
+    crate::{
+        app_state::AppState,
+        domain::{
+            data_stores::{FailedAttempts, ThrottleKey},
+            email::Email,
+            error::AuthAPIError,
+        },
+        utils::constants::{
+            IP_BACKOFF_THRESHOLD, IP_LOCKOUT_THRESHOLD, LOGIN_BACKOFF_THRESHOLD, LOGIN_LOCKOUT_THRESHOLD,
+            MAX_2FA_ATTEMPTS,
+        },
+    },
+    chrono::Utc,
+    std::net::IpAddr,
+    tracing::instrument,
+    uuid::Uuid,
+};
+
+/// Longest backoff between two attempts, in seconds.
+const MAX_BACKOFF_SECONDS: i64 = 300;
+
+/// Rejects a login attempt while the account or the client IP is locked out or inside its backoff window.
+#[instrument(name = "Check login throttle", skip_all)]
+pub async fn check_login_throttle(state: &AppState, email: &Email, ip: IpAddr) -> Result<(), AuthAPIError> {
+    let store = &state.login_throttle_store;
+    let account = store
+        .get_failures(&ThrottleKey::Account(email.clone()))
+        .await
+        .map_err(|e| AuthAPIError::UnexpectedError(e.into()))?;
+    let now = Utc::now().timestamp();
+
+    if let Some(error) = throttle(account, *LOGIN_BACKOFF_THRESHOLD, *LOGIN_LOCKOUT_THRESHOLD, now) {
+        return Err(error);
+    }
+
+    let ip = store.get_failures(&ThrottleKey::Ip(ip)).await.map_err(|e| AuthAPIError::UnexpectedError(e.into()))?;
+
+    // an IP spans many accounts, so it is only ever slowed down rather than reported as a locked account
+    match throttle(ip, *IP_BACKOFF_THRESHOLD, *IP_LOCKOUT_THRESHOLD, now) {
+        Some(_) => Err(AuthAPIError::TooManyRequests),
+        None => Ok(()),
+    }
+}
+
+#[instrument(name = "Record login failure", skip_all)]
+pub async fn record_login_failure(state: &AppState, email: &Email, ip: IpAddr) -> Result<(), AuthAPIError> {
+    for key in [ThrottleKey::Account(email.clone()), ThrottleKey::Ip(ip)] {
+        state.login_throttle_store.record_failure(&key).await.map_err(|e| AuthAPIError::UnexpectedError(e.into()))?;
+    }
+
+    Ok(())
+}
+
+/// Counts a wrong code for the pending 2FA attempt and discards the attempt once too many were entered.
+#[instrument(name = "Record 2FA failure", skip_all)]
+pub async fn record_two_factor_failure(state: &AppState, user_id: &Uuid, ip: IpAddr) -> Result<(), AuthAPIError> {
+    state
+        .login_throttle_store
+        .record_failure(&ThrottleKey::Ip(ip))
+        .await
+        .map_err(|e| AuthAPIError::UnexpectedError(e.into()))?;
+
+    let key = ThrottleKey::TwoFactor(*user_id);
+    let attempts =
+        state.login_throttle_store.record_failure(&key).await.map_err(|e| AuthAPIError::UnexpectedError(e.into()))?;
+
+    if attempts.count >= *MAX_2FA_ATTEMPTS {
+        state.two_factor_store.remove_code(user_id).await.map_err(|e| AuthAPIError::UnexpectedError(e.into()))?;
+        state.login_throttle_store.reset(&key).await.map_err(|e| AuthAPIError::UnexpectedError(e.into()))?;
+    }
+
+    Ok(())
+}
+
+fn throttle(
+    attempts: FailedAttempts,
+    backoff_threshold: u32,
+    lockout_threshold: u32,
+    now: i64,
+) -> Option<AuthAPIError> {
+    if attempts.count >= lockout_threshold {
+        return Some(AuthAPIError::AccountLocked);
+    }
+
+    if attempts.count < backoff_threshold {
+        return None;
+    }
+
+    let delay = 2_i64.saturating_pow(attempts.count - backoff_threshold).min(MAX_BACKOFF_SECONDS);
+
+    match now < attempts.last_failure_at + delay {
+        true => Some(AuthAPIError::TooManyRequests),
+        false => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_throttle_below_backoff_threshold() {
+        let attempts = FailedAttempts { count: 2, last_failure_at: 100 };
+
+        assert!(throttle(attempts, 3, 10, 100).is_none());
+    }
+
+    #[test]
+    fn test_throttle_backoff_doubles_per_failure() {
+        let attempts = FailedAttempts { count: 5, last_failure_at: 100 };
+
+        assert!(matches!(throttle(attempts, 3, 10, 103), Some(AuthAPIError::TooManyRequests)));
+        assert!(throttle(attempts, 3, 10, 104).is_none());
+    }
+
+    #[test]
+    fn test_throttle_locks_at_lockout_threshold() {
+        let attempts = FailedAttempts { count: 10, last_failure_at: 0 };
+
+        assert!(matches!(throttle(attempts, 3, 10, 1_000_000), Some(AuthAPIError::AccountLocked)));
+    }
+}
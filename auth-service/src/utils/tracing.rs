@@ -1,5 +1,5 @@
 use {
-    axum::{body::Body, extract::Request, response::Response},
+    axum::{body::Body, extract::Request, middleware::Next, response::Response},
     color_eyre::eyre::Result,
     std::time::Duration,
     tracing::{Level, Span, event, field, span},
@@ -17,8 +17,19 @@ pub fn init_tracing() -> Result<()> {
     Ok(())
 }
 
+/// Id given to each request by `set_request_id`, so what handlers record can be matched with the request's span.
+#[derive(Clone, Copy, Debug)]
+pub struct RequestId(pub Uuid);
+
+/// Gives the request its id; runs ahead of the trace layer, whose span picks the id up.
+pub async fn set_request_id(mut request: Request, next: Next) -> Response {
+    request.extensions_mut().insert(RequestId(Uuid::new_v4()));
+
+    next.run(request).await
+}
+
 pub fn make_span_with_request_id(request: &Request<Body>) -> Span {
-    let request_id = Uuid::new_v4();
+    let request_id = request.extensions().get::<RequestId>().map_or_else(Uuid::new_v4, |RequestId(id)| *id);
 
     span!(
         Level::INFO,
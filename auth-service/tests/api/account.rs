@@ -0,0 +1,237 @@
+use {
+    crate::helpers::{TestApp, get_random_email},
+    auth_service::{
+        domain::{audit::AuditEventType, data_stores::ThrottleKey, email::Email, user::TwoFactorMethod},
+        routes::{AccountExport, DeleteAccountResponse, ProfileExport, RestoreAccountResponse, TwoFactorExport},
+        utils::constants::JWT_COOKIE_NAME,
+    },
+    secrecy::SecretBox,
+    serde_json::json,
+};
+
+async fn signup_and_login(app: &TestApp, email: &str) -> String {
+    let _ = app
+        .post_signup(&json!({
+            "email": email,
+            "password": "abcd1234",
+            "requires2FA": false
+        }))
+        .await;
+
+    app.verify_email(email).await;
+
+    let response = app
+        .post_login(&json!({
+            "email": email,
+            "password": "abcd1234",
+        }))
+        .await;
+    let cookie = response.cookies().find(|cookie| cookie.name() == JWT_COOKIE_NAME).expect("No auth cookie found");
+
+    cookie.value().to_owned()
+}
+
+#[tokio::test]
+async fn should_export_account_data() {
+    let mut app = TestApp::new().await;
+    let email = get_random_email();
+    let _ = signup_and_login(&app, &email).await;
+    let response = app.get_account_export().await;
+
+    assert_eq!(response.status().as_u16(), 200);
+
+    let export = response.json::<AccountExport>().await.expect("Could not deserialize response body to AccountExport");
+
+    assert_eq!(export.profile, ProfileExport { user_id: app.get_user_id(&email).await, email, email_verified: true });
+    assert_eq!(
+        export.two_factor,
+        TwoFactorExport {
+            method: TwoFactorMethod::None,
+            totp_enabled: false,
+            recovery_codes_remaining: 0,
+            passkeys: vec![],
+        }
+    );
+    assert_eq!(export.sessions.len(), 1);
+    assert!(export.sessions[0].current);
+    assert_eq!(export.sessions[0].ip, app.ip);
+    assert_eq!(
+        export.audit_events.iter().map(|event| event.event_type).collect::<Vec<_>>(),
+        vec![AuditEventType::LoginSucceeded, AuditEventType::Signup]
+    );
+    assert!(export.audit_events.iter().all(|event| event.user_id == Some(export.profile.user_id)));
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_delete_account_and_revoke_sessions() {
+    let mut app = TestApp::new().await;
+    let email = get_random_email();
+    let token = signup_and_login(&app, &email).await;
+    let response = app.post_account_delete(&json!({ "password": "abcd1234" })).await;
+
+    assert_eq!(response.status().as_u16(), 200);
+    assert_eq!(
+        response
+            .json::<DeleteAccountResponse>()
+            .await
+            .expect("Could not deserialize response body to DeleteAccountResponse"),
+        DeleteAccountResponse { message: "Account deleted".to_owned() }
+    );
+
+    let response = app.post_verify_token(&json!({ "token": token })).await;
+
+    assert_eq!(response.status().as_u16(), 401);
+
+    let response = app.post_refresh().await;
+
+    assert_eq!(response.status().as_u16(), 401);
+
+    let response = app.post_login(&json!({ "email": email, "password": "abcd1234" })).await;
+
+    assert_eq!(response.status().as_u16(), 400);
+
+    // the address stays taken until the grace period ends and the account is purged
+    let response = app
+        .post_signup(&json!({
+            "email": email,
+            "password": "abcd1234",
+            "requires2FA": false
+        }))
+        .await;
+
+    assert_eq!(response.status().as_u16(), 409);
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_remove_account_right_away_without_grace_period() {
+    let mut app = TestApp::with_state(|state| state.account_deletion_grace_seconds = 0).await;
+    let email = get_random_email();
+    let token = signup_and_login(&app, &email).await;
+    let response = app.post_account_delete(&json!({ "password": "abcd1234" })).await;
+
+    assert_eq!(response.status().as_u16(), 200);
+
+    let response = app.post_verify_token(&json!({ "token": token })).await;
+
+    assert_eq!(response.status().as_u16(), 401);
+
+    let response = app.post_refresh().await;
+
+    assert_eq!(response.status().as_u16(), 401);
+
+    // nothing is kept to restore, and the address is free again
+    let response = app.post_account_restore(&json!({ "email": email, "password": "abcd1234" })).await;
+
+    assert_eq!(response.status().as_u16(), 401);
+
+    let response = app
+        .post_signup(&json!({
+            "email": email,
+            "password": "abcd1234",
+            "requires2FA": false
+        }))
+        .await;
+
+    assert_eq!(response.status().as_u16(), 201);
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_restore_account_during_grace_period() {
+    let mut app = TestApp::new().await;
+    let email = get_random_email();
+    let _ = signup_and_login(&app, &email).await;
+    let response = app.post_account_delete(&json!({ "password": "abcd1234" })).await;
+
+    assert_eq!(response.status().as_u16(), 200);
+
+    let response = app.post_account_restore(&json!({ "email": email, "password": "wrong-password" })).await;
+
+    assert_eq!(response.status().as_u16(), 401);
+
+    let response = app.post_account_restore(&json!({ "email": get_random_email(), "password": "abcd1234" })).await;
+
+    assert_eq!(response.status().as_u16(), 401);
+
+    let response = app.post_account_restore(&json!({ "email": email.to_uppercase(), "password": "abcd1234" })).await;
+
+    assert_eq!(response.status().as_u16(), 200);
+    assert_eq!(
+        response
+            .json::<RestoreAccountResponse>()
+            .await
+            .expect("Could not deserialize response body to RestoreAccountResponse"),
+        RestoreAccountResponse { message: "Account restored".to_owned() }
+    );
+
+    let response = app.post_login(&json!({ "email": email, "password": "abcd1234" })).await;
+
+    assert_eq!(response.status().as_u16(), 200);
+
+    // audit events are kept until the account is purged
+    let export = app.get_account_export().await.json::<AccountExport>().await.expect("Could not deserialize export");
+
+    assert!(export.audit_events.iter().any(|event| event.event_type == AuditEventType::Signup));
+
+    // only accounts awaiting deletion can be restored
+    let response = app.post_account_restore(&json!({ "email": email, "password": "abcd1234" })).await;
+
+    assert_eq!(response.status().as_u16(), 401);
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_remove_email_keyed_data_on_delete() {
+    let mut app = TestApp::new().await;
+    let email = get_random_email();
+    let _ = signup_and_login(&app, &email).await;
+    let parsed_email = Email::parse(&SecretBox::new(Box::new(email.clone()))).unwrap();
+
+    // requested with another casing of the address, which is keyed the same
+    let response = app.post_password_reset_request(&json!({ "email": email.to_uppercase() })).await;
+
+    assert_eq!(response.status().as_u16(), 200);
+
+    let response = app.post_magic_link(&json!({ "email": email })).await;
+
+    assert_eq!(response.status().as_u16(), 200);
+
+    app.login_throttle_store
+        .record_failure(&ThrottleKey::Account(parsed_email.clone()))
+        .await
+        .expect("Failed to record login failure");
+
+    let response = app.post_account_delete(&json!({ "password": "abcd1234" })).await;
+
+    assert_eq!(response.status().as_u16(), 200);
+    assert!(app.password_reset_store.get_token(&parsed_email).await.is_err());
+    assert!(app.magic_link_store.take_token(&parsed_email).await.is_err());
+
+    let failures = app.login_throttle_store.get_failures(&ThrottleKey::Account(parsed_email)).await.unwrap();
+
+    assert_eq!(failures.count, 0);
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_return_401_if_password_is_incorrect() {
+    let mut app = TestApp::new().await;
+    let email = get_random_email();
+    let token = signup_and_login(&app, &email).await;
+    let response = app.post_account_delete(&json!({ "password": "wrong-password" })).await;
+
+    assert_eq!(response.status().as_u16(), 401);
+
+    let response = app.post_verify_token(&json!({ "token": token })).await;
+
+    assert_eq!(response.status().as_u16(), 200);
+
+    app.clean_up().await;
+}
@@ -0,0 +1,367 @@
+use {
+    crate::helpers::{TestApp, get_random_email},
+    auth_service::{
+        ErrorResponse,
+        domain::{
+            data_stores::ThrottleKey,
+            email::Email,
+            role::ADMIN_ROLE,
+            user::{AccountStatus, TwoFactorMethod},
+        },
+        routes::{AdminUserDetailsResponse, AdminUsersResponse},
+        utils::{auth::generate_auth_token, constants::LOGIN_LOCKOUT_THRESHOLD},
+    },
+    chrono::Utc,
+    reqwest::Response,
+    secrecy::{ExposeSecret, SecretBox},
+    serde_json::json,
+    uuid::Uuid,
+};
+
+async fn signup(app: &TestApp) -> (String, Uuid) {
+    let email = get_random_email();
+    let response = app
+        .post_signup(&json!({
+            "email": email,
+            "password": "abcd1234",
+            "requires2FA": false
+        }))
+        .await;
+
+    assert_eq!(response.status().as_u16(), 201);
+
+    app.verify_email(&email).await;
+
+    let user_id = app.get_user_id(&email).await;
+
+    (email, user_id)
+}
+
+async fn login(app: &TestApp, email: &str, password: &str) -> Response {
+    app.post_login(&json!({ "email": email, "password": password })).await
+}
+
+async fn error_message(response: Response) -> String {
+    response.json::<ErrorResponse>().await.expect("Could not deserialize response body to ErrorResponse").error
+}
+
+async fn get_user(app: &TestApp, user_id: &Uuid) -> AdminUserDetailsResponse {
+    let response = app.get_admin_user(user_id, Some(&app.admin_api_key())).await;
+
+    assert_eq!(response.status().as_u16(), 200);
+
+    response.json().await.expect("Could not deserialize response body to AdminUserDetailsResponse")
+}
+
+#[tokio::test]
+async fn should_list_users_with_search_and_pagination() {
+    let mut app = TestApp::new().await;
+    let (email, user_id) = signup(&app).await;
+    let _ = signup(&app).await;
+    let _ = signup(&app).await;
+    let response =
+        app.get_admin_users(&format!("search={}", &email[..8].to_uppercase()), Some(&app.admin_api_key())).await;
+
+    assert_eq!(response.status().as_u16(), 200);
+
+    let users = response.json::<AdminUsersResponse>().await.expect("Could not deserialize to AdminUsersResponse");
+
+    assert_eq!(users.total, 1);
+    assert_eq!(users.users[0].user_id, user_id);
+    assert_eq!(users.users[0].email, email);
+
+    let response = app.get_admin_users("page=2&perPage=2", Some(&app.admin_api_key())).await;
+    let users = response.json::<AdminUsersResponse>().await.expect("Could not deserialize to AdminUsersResponse");
+
+    assert_eq!((users.total, users.page, users.per_page, users.users.len()), (3, 2, 2, 1));
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_require_users_permission() {
+    let mut app = TestApp::new().await;
+    let (email, user_id) = signup(&app).await;
+    let _ = login(&app, &email, "abcd1234").await;
+
+    assert_eq!(app.get_admin_users("", None).await.status().as_u16(), 403);
+    assert_eq!(app.post_admin_user_action(&user_id, "disable", None).await.status().as_u16(), 403);
+    assert_eq!(app.get_admin_users("", Some("not-the-admin-key")).await.status().as_u16(), 401);
+
+    app.role_store.assign_role(&user_id, ADMIN_ROLE).await.expect("Failed to assign role");
+
+    assert_eq!(app.post_refresh().await.status().as_u16(), 200);
+    assert_eq!(app.get_admin_users("", None).await.status().as_u16(), 200);
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_return_user_with_roles_and_sessions() {
+    let mut app = TestApp::new().await;
+    let (email, user_id) = signup(&app).await;
+    let _ = login(&app, &email, "abcd1234").await;
+
+    app.role_store.assign_role(&user_id, ADMIN_ROLE).await.expect("Failed to assign role");
+
+    let user = get_user(&app, &user_id).await;
+
+    assert_eq!(user.user.email, email);
+    assert!(user.user.email_verified);
+    assert_eq!(user.user.status, AccountStatus::Active);
+    assert_eq!(user.roles, vec![ADMIN_ROLE.to_owned()]);
+    assert_eq!(user.sessions, 1);
+
+    let response = app.get_admin_user(&Uuid::new_v4(), Some(&app.admin_api_key())).await;
+
+    assert_eq!(response.status().as_u16(), 404);
+    assert_eq!(error_message(response).await, "User not found");
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_disable_and_enable_user() {
+    let mut app = TestApp::new().await;
+    let (email, user_id) = signup(&app).await;
+    let _ = login(&app, &email, "abcd1234").await;
+    let response = app.post_admin_user_action(&user_id, "disable", Some(&app.admin_api_key())).await;
+
+    assert_eq!(response.status().as_u16(), 200);
+    assert_eq!(get_user(&app, &user_id).await.user.status, AccountStatus::Disabled);
+
+    // disabling signs the user out everywhere
+    assert_eq!(app.get_sessions().await.status().as_u16(), 401);
+    assert_eq!(app.post_refresh().await.status().as_u16(), 401);
+
+    let response = login(&app, &email, "abcd1234").await;
+
+    assert_eq!(response.status().as_u16(), 403);
+    assert_eq!(error_message(response).await, "Account disabled");
+
+    let response = app.post_admin_user_action(&user_id, "enable", Some(&app.admin_api_key())).await;
+
+    assert_eq!(response.status().as_u16(), 200);
+    assert_eq!(login(&app, &email, "abcd1234").await.status().as_u16(), 200);
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_suspend_user() {
+    let mut app = TestApp::new().await;
+    let (email, user_id) = signup(&app).await;
+    let _ = login(&app, &email, "abcd1234").await;
+    let token = generate_auth_token(&user_id, &Uuid::new_v4(), 0, Vec::new()).unwrap();
+
+    assert_eq!(app.post_verify_token(&json!({ "token": token.expose_secret() })).await.status().as_u16(), 200);
+
+    let response =
+        app.post_admin_user_suspend(&user_id, &json!({ "reason": "spam" }), Some(&app.admin_api_key())).await;
+
+    assert_eq!(response.status().as_u16(), 200);
+
+    let user = get_user(&app, &user_id).await;
+
+    assert_eq!(user.user.status, AccountStatus::Suspended);
+    assert_eq!(user.user.status_reason.as_deref(), Some("spam"));
+    assert_eq!(user.sessions, 1);
+
+    // the sessions are kept, but the auth tokens issued before are rejected and they cannot be refreshed while
+    // suspended
+    assert_eq!(app.post_verify_token(&json!({ "token": token.expose_secret() })).await.status().as_u16(), 401);
+    assert_eq!(app.get_sessions().await.status().as_u16(), 401);
+
+    let response = app.post_refresh().await;
+
+    assert_eq!(response.status().as_u16(), 403);
+    assert_eq!(error_message(response).await, "Account suspended");
+
+    let response = login(&app, &email, "abcd1234").await;
+
+    assert_eq!(response.status().as_u16(), 403);
+    assert_eq!(error_message(response).await, "Account suspended");
+
+    let response = app.post_admin_user_action(&user_id, "enable", Some(&app.admin_api_key())).await;
+
+    assert_eq!(response.status().as_u16(), 200);
+    assert_eq!(app.post_verify_token(&json!({ "token": token.expose_secret() })).await.status().as_u16(), 401);
+    assert_eq!(app.post_refresh().await.status().as_u16(), 200);
+    assert_eq!(app.get_sessions().await.status().as_u16(), 200);
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_lift_suspension_once_expired() {
+    let mut app = TestApp::new().await;
+    let (email, user_id) = signup(&app).await;
+    let response = app
+        .post_admin_user_suspend(
+            &user_id,
+            &json!({ "reason": "cooling off", "until": Utc::now().timestamp() - 1 }),
+            Some(&app.admin_api_key()),
+        )
+        .await;
+
+    assert_eq!(response.status().as_u16(), 200);
+    assert_eq!(login(&app, &email, "abcd1234").await.status().as_u16(), 200);
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_keep_unverified_user_pending_after_suspension() {
+    let mut app = TestApp::new().await;
+    let email = get_random_email();
+    let _ = app
+        .post_signup(&json!({
+            "email": email,
+            "password": "abcd1234",
+            "requires2FA": false
+        }))
+        .await;
+    let user_id = app.get_user_id(&email).await;
+    let response = app
+        .post_admin_user_suspend(
+            &user_id,
+            &json!({ "reason": "cooling off", "until": Utc::now().timestamp() - 1 }),
+            Some(&app.admin_api_key()),
+        )
+        .await;
+
+    assert_eq!(response.status().as_u16(), 200);
+    assert_eq!(get_user(&app, &user_id).await.user.status, AccountStatus::PendingVerification);
+
+    let response = login(&app, &email, "abcd1234").await;
+
+    assert_eq!(response.status().as_u16(), 403);
+    assert_eq!(error_message(response).await, "Email not verified");
+
+    let response = app.post_admin_user_action(&user_id, "disable", Some(&app.admin_api_key())).await;
+
+    assert_eq!(response.status().as_u16(), 200);
+
+    app.verify_email(&email).await;
+
+    // verifying the email does not lift what an admin set
+    assert_eq!(get_user(&app, &user_id).await.user.status, AccountStatus::Disabled);
+
+    let response = app.post_admin_user_action(&user_id, "enable", Some(&app.admin_api_key())).await;
+
+    assert_eq!(response.status().as_u16(), 200);
+    assert_eq!(get_user(&app, &user_id).await.user.status, AccountStatus::Active);
+    assert_eq!(login(&app, &email, "abcd1234").await.status().as_u16(), 200);
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_force_password_reset() {
+    let mut app = TestApp::new().await;
+    let (email, user_id) = signup(&app).await;
+    let _ = login(&app, &email, "abcd1234").await;
+    let response = app.post_admin_user_action(&user_id, "password-reset", Some(&app.admin_api_key())).await;
+
+    assert_eq!(response.status().as_u16(), 200);
+    assert_eq!(app.get_sessions().await.status().as_u16(), 401);
+
+    let response = login(&app, &email, "abcd1234").await;
+
+    assert_eq!(response.status().as_u16(), 403);
+    assert_eq!(error_message(response).await, "Password reset required");
+
+    let token = app
+        .password_reset_store
+        .get_token(&Email::parse(&SecretBox::new(Box::new(email.clone()))).unwrap())
+        .await
+        .expect("No password reset token was issued");
+    let response = app
+        .post_password_reset_confirm(&json!({
+            "email": email,
+            "token": token.as_ref().expose_secret(),
+            "newPassword": "efgh5678"
+        }))
+        .await;
+
+    assert_eq!(response.status().as_u16(), 200);
+    assert_eq!(login(&app, &email, "efgh5678").await.status().as_u16(), 200);
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_toggle_2fa() {
+    let mut app = TestApp::new().await;
+    let (email, user_id) = signup(&app).await;
+    let response = app.post_admin_user_2fa(&user_id, &json!({ "requires2FA": true }), Some(&app.admin_api_key())).await;
+
+    assert_eq!(response.status().as_u16(), 200);
+    assert_eq!(get_user(&app, &user_id).await.user.two_factor_method, TwoFactorMethod::Email);
+    assert_eq!(login(&app, &email, "abcd1234").await.status().as_u16(), 206);
+
+    let response =
+        app.post_admin_user_2fa(&user_id, &json!({ "requires2FA": false }), Some(&app.admin_api_key())).await;
+
+    assert_eq!(response.status().as_u16(), 200);
+    assert_eq!(get_user(&app, &user_id).await.user.two_factor_method, TwoFactorMethod::None);
+    assert_eq!(login(&app, &email, "abcd1234").await.status().as_u16(), 200);
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_unlock_locked_account() {
+    let mut app = TestApp::new().await;
+    let (email, user_id) = signup(&app).await;
+    let key = ThrottleKey::Account(Email::parse(&SecretBox::new(Box::new(email.clone()))).unwrap());
+
+    for _ in 0..*LOGIN_LOCKOUT_THRESHOLD {
+        app.login_throttle_store.record_failure(&key).await.expect("Failed to record login failure");
+    }
+
+    assert_eq!(login(&app, &email, "abcd1234").await.status().as_u16(), 423);
+
+    let response = app.post_admin_user_action(&user_id, "unlock", Some(&app.admin_api_key())).await;
+
+    assert_eq!(response.status().as_u16(), 200);
+    assert_eq!(login(&app, &email, "abcd1234").await.status().as_u16(), 200);
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_revoke_all_sessions() {
+    let mut app = TestApp::new().await;
+    let (email, user_id) = signup(&app).await;
+    let _ = login(&app, &email, "abcd1234").await;
+    let response = app.post_admin_user_action(&user_id, "sessions/revoke", Some(&app.admin_api_key())).await;
+
+    assert_eq!(response.status().as_u16(), 200);
+    assert_eq!(app.get_sessions().await.status().as_u16(), 401);
+    assert_eq!(app.post_refresh().await.status().as_u16(), 401);
+    assert_eq!(get_user(&app, &user_id).await.sessions, 0);
+
+    // the account itself keeps working
+    assert_eq!(login(&app, &email, "abcd1234").await.status().as_u16(), 200);
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_return_404_if_user_not_found() {
+    let mut app = TestApp::new().await;
+
+    for action in ["disable", "enable", "password-reset", "unlock", "sessions/revoke"] {
+        let response = app.post_admin_user_action(&Uuid::new_v4(), action, Some(&app.admin_api_key())).await;
+
+        assert_eq!(response.status().as_u16(), 404, "{action}");
+    }
+
+    let response =
+        app.post_admin_user_suspend(&Uuid::new_v4(), &json!({ "reason": "spam" }), Some(&app.admin_api_key())).await;
+
+    assert_eq!(response.status().as_u16(), 404);
+
+    app.clean_up().await;
+}
@@ -0,0 +1,187 @@
+use {
+    crate::helpers::{TestApp, get_random_email},
+    auth_service::{
+        domain::{audit::AuditEventType, role::ADMIN_ROLE},
+        routes::{AuditEventResponse, AuditEventsResponse},
+    },
+    secrecy::ExposeSecret,
+    serde_json::json,
+    std::collections::HashSet,
+    uuid::Uuid,
+};
+
+async fn signup(app: &TestApp, requires_2fa: bool) -> (String, Uuid) {
+    let email = get_random_email();
+    let response = app
+        .post_signup(&json!({
+            "email": email,
+            "password": "abcd1234",
+            "requires2FA": requires_2fa
+        }))
+        .await;
+
+    assert_eq!(response.status().as_u16(), 201);
+
+    app.verify_email(&email).await;
+
+    let user_id = app.get_user_id(&email).await;
+
+    (email, user_id)
+}
+
+async fn get_events(app: &TestApp, query: &str) -> AuditEventsResponse {
+    let response = app.get_audit_events(query, Some(&app.admin_api_key())).await;
+
+    assert_eq!(response.status().as_u16(), 200);
+
+    response.json().await.expect("Could not deserialize response body to AuditEventsResponse")
+}
+
+fn event_types(events: &[AuditEventResponse]) -> Vec<AuditEventType> {
+    events.iter().map(|event| event.event_type).collect()
+}
+
+#[tokio::test]
+async fn should_record_login_and_logout() {
+    let mut app = TestApp::new().await;
+    let (email, user_id) = signup(&app, false).await;
+
+    let _ = app.post_login(&json!({ "email": email, "password": "wrong password" })).await;
+    let _ = app.post_login(&json!({ "email": email, "password": "abcd1234" })).await;
+    let _ = app.post_logout().await;
+
+    let events = get_events(&app, &format!("userId={user_id}")).await.events;
+
+    assert_eq!(
+        event_types(&events),
+        vec![
+            AuditEventType::Logout,
+            AuditEventType::LoginSucceeded,
+            AuditEventType::LoginFailed,
+            AuditEventType::Signup
+        ]
+    );
+    assert!(events.iter().all(|event| event.user_id == Some(user_id) && event.ip == app.ip));
+    assert_eq!(events[2].details.as_deref(), Some("Incorrect credentials"));
+
+    // every event was recorded in a request of its own
+    let request_ids: HashSet<_> = events.iter().map(|event| event.request_id).collect();
+
+    assert_eq!(request_ids.len(), events.len());
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_record_failed_logins_of_unknown_users() {
+    let mut app = TestApp::new().await;
+    let _ = app.post_login(&json!({ "email": get_random_email(), "password": "abcd1234" })).await;
+    let events = get_events(&app, "eventType=login_failed").await.events;
+
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].user_id, None);
+    assert_eq!(events[0].details.as_deref(), Some("Invalid credentials"));
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_record_2fa() {
+    let mut app = TestApp::new().await;
+    let (email, user_id) = signup(&app, true).await;
+    let _ = app.post_login(&json!({ "email": email, "password": "abcd1234" })).await;
+    let (attempt_id, code) =
+        app.two_factor_store.get_code(&user_id).await.expect("Failed to get code from two factor store");
+    let wrong_code = if code.as_ref().expose_secret() == "000000" { "111111" } else { "000000" };
+
+    for code in [wrong_code, code.as_ref().expose_secret()] {
+        let _ = app
+            .post_verify_2fa(&json!({
+                "email": email,
+                "loginAttemptId": attempt_id.as_ref().expose_secret(),
+                "2FACode": code
+            }))
+            .await;
+    }
+
+    let events = get_events(&app, &format!("userId={user_id}")).await.events;
+
+    assert_eq!(
+        event_types(&events),
+        vec![
+            AuditEventType::LoginSucceeded,
+            AuditEventType::TwoFactorVerified,
+            AuditEventType::TwoFactorFailed,
+            AuditEventType::TwoFactorSent,
+            AuditEventType::Signup,
+        ]
+    );
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_record_token_verification_failures() {
+    let mut app = TestApp::new().await;
+    let _ = app.post_verify_token(&json!({ "token": "abcd.efgh.ijkl" })).await;
+    let _ = app.post_verify_token(&json!({ "token": "abcd" })).await;
+    let events = get_events(&app, "eventType=token_verification_failed").await;
+
+    assert_eq!(events.total, 2);
+    assert_eq!(events.events[0].details.as_deref(), Some("Malformed token"));
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_record_admin_actions() {
+    let mut app = TestApp::new().await;
+    let (_, user_id) = signup(&app, false).await;
+    let (admin_email, admin_id) = signup(&app, false).await;
+
+    let _ = app.post_admin_user_action(&user_id, "disable", Some(&app.admin_api_key())).await;
+
+    app.role_store.assign_role(&admin_id, ADMIN_ROLE).await.expect("Failed to assign role");
+
+    let _ = app.post_login(&json!({ "email": admin_email, "password": "abcd1234" })).await;
+    let _ = app.post_admin_user_action(&user_id, "enable", None).await;
+    let events = get_events(&app, &format!("userId={user_id}&eventType=admin_action")).await.events;
+    let actions: Vec<_> = events.iter().map(|event| (event.actor_id, event.details.as_deref())).collect();
+
+    // actions taken with the admin API key are not credited to anyone
+    assert_eq!(actions, vec![(Some(admin_id), Some("enable")), (None, Some("disable"))]);
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_paginate_events() {
+    let mut app = TestApp::new().await;
+
+    for _ in 0..3 {
+        let _ = signup(&app, false).await;
+    }
+
+    let events = get_events(&app, "eventType=signup&page=2&perPage=2").await;
+
+    assert_eq!((events.total, events.page, events.per_page, events.events.len()), (3, 2, 2, 1));
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_require_audit_permission() {
+    let mut app = TestApp::new().await;
+    let (email, user_id) = signup(&app, false).await;
+    let _ = app.post_login(&json!({ "email": email, "password": "abcd1234" })).await;
+
+    assert_eq!(app.get_audit_events("", None).await.status().as_u16(), 403);
+    assert_eq!(app.get_audit_events("", Some("not-the-admin-key")).await.status().as_u16(), 401);
+
+    app.role_store.assign_role(&user_id, ADMIN_ROLE).await.expect("Failed to assign role");
+
+    assert_eq!(app.post_refresh().await.status().as_u16(), 200);
+    assert_eq!(app.get_audit_events("", None).await.status().as_u16(), 200);
+
+    app.clean_up().await;
+}
@@ -0,0 +1,159 @@
+use {
+    crate::helpers::{TestApp, get_random_email},
+    auth_service::{ErrorResponse, routes::ChangeEmailResponse, utils::constants::JWT_COOKIE_NAME},
+    secrecy::ExposeSecret,
+    serde_json::json,
+};
+
+async fn signup_and_login(app: &TestApp, email: &str) -> String {
+    let _ = app
+        .post_signup(&json!({
+            "email": email,
+            "password": "abcd1234",
+            "requires2FA": false
+        }))
+        .await;
+
+    app.verify_email(email).await;
+
+    let response = app
+        .post_login(&json!({
+            "email": email,
+            "password": "abcd1234",
+        }))
+        .await;
+    let cookie = response.cookies().find(|cookie| cookie.name() == JWT_COOKIE_NAME).expect("No auth cookie found");
+
+    cookie.value().to_owned()
+}
+
+#[tokio::test]
+async fn should_change_email_once_confirmed_and_revoke_sessions() {
+    let mut app = TestApp::new().await;
+    let email = get_random_email();
+    let new_email = get_random_email();
+    let token = signup_and_login(&app, &email).await;
+    let response = app.post_change_email(&json!({ "newEmail": new_email, "password": "abcd1234" })).await;
+
+    assert_eq!(response.status().as_u16(), 200);
+    assert_eq!(
+        response
+            .json::<ChangeEmailResponse>()
+            .await
+            .expect("Could not deserialize response body to ChangeEmailResponse"),
+        ChangeEmailResponse { message: "Confirmation email sent".to_owned() }
+    );
+
+    // nothing changes until the new address is confirmed
+    let response = app.post_login(&json!({ "email": email, "password": "abcd1234" })).await;
+
+    assert_eq!(response.status().as_u16(), 200);
+
+    let user_id = app.get_user_id(&email).await;
+    let change = app.email_change_store.get_change(&user_id).await.expect("Failed to get pending email change");
+    let response = app
+        .post_change_email_confirm(&json!({
+            "userId": user_id.to_string(),
+            "token": change.confirm_token.as_ref().expose_secret()
+        }))
+        .await;
+
+    assert_eq!(response.status().as_u16(), 200);
+
+    let response = app.post_verify_token(&json!({ "token": token })).await;
+
+    assert_eq!(response.status().as_u16(), 401);
+
+    let response = app.post_login(&json!({ "email": email, "password": "abcd1234" })).await;
+
+    assert_eq!(response.status().as_u16(), 400);
+
+    let response = app.post_login(&json!({ "email": new_email, "password": "abcd1234" })).await;
+
+    assert_eq!(response.status().as_u16(), 200);
+    assert_eq!(app.get_user_id(&new_email).await, user_id);
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_discard_change_when_cancelled() {
+    let mut app = TestApp::new().await;
+    let email = get_random_email();
+    let _ = signup_and_login(&app, &email).await;
+    let _ = app.post_change_email(&json!({ "newEmail": get_random_email(), "password": "abcd1234" })).await;
+    let user_id = app.get_user_id(&email).await;
+    let change = app.email_change_store.get_change(&user_id).await.expect("Failed to get pending email change");
+    let response = app
+        .post_change_email_cancel(&json!({
+            "userId": user_id.to_string(),
+            "token": change.cancel_token.as_ref().expose_secret()
+        }))
+        .await;
+
+    assert_eq!(response.status().as_u16(), 200);
+
+    let response = app
+        .post_change_email_confirm(&json!({
+            "userId": user_id.to_string(),
+            "token": change.confirm_token.as_ref().expose_secret()
+        }))
+        .await;
+
+    assert_eq!(response.status().as_u16(), 401);
+
+    let response = app.post_login(&json!({ "email": email, "password": "abcd1234" })).await;
+
+    assert_eq!(response.status().as_u16(), 200);
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_return_401_if_confirmed_with_cancel_token() {
+    let mut app = TestApp::new().await;
+    let email = get_random_email();
+    let _ = signup_and_login(&app, &email).await;
+    let _ = app.post_change_email(&json!({ "newEmail": get_random_email(), "password": "abcd1234" })).await;
+    let user_id = app.get_user_id(&email).await;
+    let change = app.email_change_store.get_change(&user_id).await.expect("Failed to get pending email change");
+    let response = app
+        .post_change_email_confirm(&json!({
+            "userId": user_id.to_string(),
+            "token": change.cancel_token.as_ref().expose_secret()
+        }))
+        .await;
+
+    assert_eq!(response.status().as_u16(), 401);
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_return_401_if_password_is_incorrect() {
+    let mut app = TestApp::new().await;
+    let _ = signup_and_login(&app, &get_random_email()).await;
+    let response =
+        app.post_change_email(&json!({ "newEmail": get_random_email(), "password": "wrong-password" })).await;
+
+    assert_eq!(response.status().as_u16(), 401);
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_return_409_if_new_email_is_taken() {
+    let mut app = TestApp::new().await;
+    let taken_email = get_random_email();
+    let _ = signup_and_login(&app, &taken_email).await;
+    let _ = signup_and_login(&app, &get_random_email()).await;
+    let response = app.post_change_email(&json!({ "newEmail": taken_email, "password": "abcd1234" })).await;
+
+    assert_eq!(response.status().as_u16(), 409);
+    assert_eq!(
+        response.json::<ErrorResponse>().await.expect("Could not deserialize response body to ErrorResponse").error,
+        "User already exists".to_owned()
+    );
+
+    app.clean_up().await;
+}
@@ -0,0 +1,196 @@
+use {
+    crate::helpers::{TestApp, get_random_email},
+    auth_service::{ErrorResponse, routes::ChangePasswordResponse, utils::constants::JWT_COOKIE_NAME},
+    reqwest::Url,
+    serde_json::json,
+    std::time::Duration,
+};
+
+async fn signup_and_login(app: &TestApp, email: &str, password: &str) -> String {
+    let _ = app
+        .post_signup(&json!({
+            "email": email,
+            "password": password,
+            "requires2FA": false
+        }))
+        .await;
+
+    app.verify_email(email).await;
+
+    let response = app
+        .post_login(&json!({
+            "email": email,
+            "password": password,
+        }))
+        .await;
+    let cookie = response.cookies().find(|cookie| cookie.name() == JWT_COOKIE_NAME).expect("No auth cookie found");
+
+    cookie.value().to_owned()
+}
+
+#[tokio::test]
+async fn should_return_200_and_keep_current_session() {
+    let mut app = TestApp::new().await;
+    let email = get_random_email();
+    let token = signup_and_login(&app, &email, "abcd1234").await;
+    let response = app
+        .post_change_password(&json!({
+            "currentPassword": "abcd1234",
+            "newPassword": "efgh5678"
+        }))
+        .await;
+
+    assert_eq!(response.status().as_u16(), 200);
+
+    let new_token = response
+        .cookies()
+        .find(|cookie| cookie.name() == JWT_COOKIE_NAME)
+        .expect("No auth cookie found")
+        .value()
+        .to_owned();
+
+    assert_eq!(
+        response
+            .json::<ChangePasswordResponse>()
+            .await
+            .expect("Could not deserialize response body to ChangePasswordResponse"),
+        ChangePasswordResponse { message: "Password changed successfully!".to_owned() }
+    );
+
+    // the token the request was made with is replaced rather than kept
+    let response = app.post_verify_token(&json!({ "token": token })).await;
+
+    assert_eq!(response.status().as_u16(), 401);
+
+    let response = app.post_verify_token(&json!({ "token": new_token })).await;
+
+    assert_eq!(response.status().as_u16(), 200);
+
+    let response = app.post_refresh().await;
+
+    assert_eq!(response.status().as_u16(), 200);
+
+    let response = app
+        .post_login(&json!({
+            "email": email,
+            "password": "efgh5678",
+        }))
+        .await;
+
+    assert_eq!(response.status().as_u16(), 200);
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_revoke_other_sessions() {
+    let mut app = TestApp::new().await;
+    let email = get_random_email();
+    let other_token = signup_and_login(&app, &email, "abcd1234").await;
+
+    // tokens issued within the same second are identical, so wait for a distinct second session
+    tokio::time::sleep(Duration::from_secs(1)).await;
+
+    let _ = app
+        .post_login(&json!({
+            "email": email,
+            "password": "abcd1234",
+        }))
+        .await;
+    let response = app
+        .post_change_password(&json!({
+            "currentPassword": "abcd1234",
+            "newPassword": "efgh5678"
+        }))
+        .await;
+
+    assert_eq!(response.status().as_u16(), 200);
+
+    let response = app.post_verify_token(&json!({ "token": other_token })).await;
+
+    assert_eq!(response.status().as_u16(), 401);
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_return_400_if_jwt_cookie_missing() {
+    let mut app = TestApp::new().await;
+    let response = app
+        .post_change_password(&json!({
+            "currentPassword": "abcd1234",
+            "newPassword": "efgh5678"
+        }))
+        .await;
+
+    assert_eq!(response.status().as_u16(), 400);
+    assert_eq!(
+        response.json::<ErrorResponse>().await.expect("Could not deserialize response body to ErrorResponse").error,
+        "Missing token".to_owned()
+    );
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_return_400_if_invalid_new_password() {
+    let mut app = TestApp::new().await;
+    let email = get_random_email();
+    let _ = signup_and_login(&app, &email, "abcd1234").await;
+    let response = app
+        .post_change_password(&json!({
+            "currentPassword": "abcd1234",
+            "newPassword": "efgh567"
+        }))
+        .await;
+
+    assert_eq!(response.status().as_u16(), 400);
+    assert_eq!(
+        response.json::<ErrorResponse>().await.expect("Could not deserialize response body to ErrorResponse").error,
+        "Invalid credentials".to_owned()
+    );
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_return_401_if_incorrect_current_password() {
+    let mut app = TestApp::new().await;
+    let email = get_random_email();
+    let _ = signup_and_login(&app, &email, "abcd1234").await;
+    let response = app
+        .post_change_password(&json!({
+            "currentPassword": "abcd7890",
+            "newPassword": "efgh5678"
+        }))
+        .await;
+
+    assert_eq!(response.status().as_u16(), 401);
+    assert_eq!(
+        response.json::<ErrorResponse>().await.expect("Could not deserialize response body to ErrorResponse").error,
+        "Incorrect credentials".to_owned()
+    );
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_return_401_if_invalid_token() {
+    let mut app = TestApp::new().await;
+
+    app.cookie_jar.add_cookie_str(
+        &format!("{JWT_COOKIE_NAME}=invalid; HttpOnly; SameSite=Lax; Secure; Path=/"),
+        &Url::parse("http://127.0.0.1").expect("Failed to parse URL"),
+    );
+
+    let response = app
+        .post_change_password(&json!({
+            "currentPassword": "abcd1234",
+            "newPassword": "efgh5678"
+        }))
+        .await;
+
+    assert_eq!(response.status().as_u16(), 401);
+
+    app.clean_up().await;
+}
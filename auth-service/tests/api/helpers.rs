@@ -1,14 +1,32 @@
 use {
     auth_service::{
         Application,
-        app_state::{AppState, BannedTokenStoreType, TwoFactorStoreType},
-        get_postgres_pool, get_redis_client,
-        services::{MockEmailClient, PostgresUserStore, RedisBannedTokenStore, RedisTwoFactorStore},
-        utils::constants::{DATABASE_URL, REDIS_HOST_NAME, test},
+        app_state::{
+            AppState, BannedTokenStoreType, EmailChangeStoreType, EmailVerificationStoreType, LoginThrottleStoreType,
+            MagicLinkStoreType, PasswordResetStoreType, RoleStoreType, SigningKeyStoreType, TwoFactorStoreType,
+            UserStoreType,
+        },
+        domain::email::Email,
+        get_postgres_pool, get_redis_client, get_webauthn,
+        services::{
+            MockEmailClient, PostgresAuditLog, PostgresPasskeyStore, PostgresRecoveryCodeStore,
+            PostgresRefreshTokenStore, PostgresRoleStore, PostgresSigningKeyStore, PostgresTotpSecretStore,
+            PostgresUserStore, RedisBannedTokenStore, RedisEmailChangeStore, RedisEmailVerificationStore,
+            RedisLoginThrottleStore, RedisMagicLinkStore, RedisPasskeyCeremonyStore, RedisPasswordResetStore,
+            RedisRateLimitStore, RedisSessionStore, RedisTwoFactorStore,
+        },
+        utils::{
+            constants::{
+                ADMIN_API_KEY, DATABASE_URL, JWT_KEY_RING, REDIS_HOST_NAME, TOTP_ENCRYPTION_KEY, WEBAUTHN_RP_ID,
+                WEBAUTHN_RP_ORIGIN, env::ADMIN_API_KEY_ENV_VAR, test,
+            },
+            keys::load_key_ring,
+        },
     },
-    redis::Connection as RedisConnection,
+    rand::{Rng, rng},
+    redis::aio::ConnectionManager,
     reqwest::{
-        Client, ClientBuilder, Response, Url,
+        Client, ClientBuilder, RequestBuilder, Response, Url,
         cookie::{CookieStore, Jar},
         header::COOKIE,
     },
@@ -18,7 +36,12 @@ use {
         Connection as _, Executor, PgConnection, PgPool, migrate,
         postgres::{PgConnectOptions, PgPoolOptions},
     },
-    std::{str::FromStr as _, sync::Arc},
+    std::{
+        env::{set_var, var},
+        net::{IpAddr, Ipv4Addr},
+        str::FromStr as _,
+        sync::Arc,
+    },
     uuid::Uuid,
 };
 
@@ -28,8 +51,18 @@ pub struct TestApp {
     cleaned_up: bool,
     pub cookie_jar: Arc<Jar>,
     pub database_name: String,
+    pub email_change_store: EmailChangeStoreType,
+    pub email_verification_store: EmailVerificationStoreType,
     pub http_client: Client,
+    /// Loopback address the client connects from, so per-IP counters are not shared between tests.
+    pub ip: IpAddr,
+    pub login_throttle_store: LoginThrottleStoreType,
+    pub magic_link_store: MagicLinkStoreType,
+    pub password_reset_store: PasswordResetStoreType,
+    pub role_store: RoleStoreType,
     pub two_factor_store: TwoFactorStoreType,
+    pub user_store: UserStoreType,
+    pub signing_key_store: SigningKeyStoreType,
 }
 
 impl Drop for TestApp {
@@ -42,19 +75,75 @@ impl Drop for TestApp {
 
 impl TestApp {
     pub async fn new() -> Self {
+        Self::with_state(|_| ()).await
+    }
+
+    /// Starts the app with its state adjusted by `configure`, for settings that are read from the environment once.
+    pub async fn with_state(configure: impl FnOnce(&mut AppState)) -> Self {
+        // Enable the admin endpoints unless the environment already provides a key
+        if var(ADMIN_API_KEY_ENV_VAR).is_err() {
+            set_var(ADMIN_API_KEY_ENV_VAR, test::ADMIN_API_KEY);
+        }
+
         let (pool, database_name) = configure_postgresql().await;
-        let user_store = Arc::new(PostgresUserStore::new(pool));
-        let banned_token_store = Arc::new(RedisBannedTokenStore::new(configure_redis()));
-        let two_factor_store = Arc::new(RedisTwoFactorStore::new(configure_redis()));
+        let user_store = Arc::new(PostgresUserStore::new(pool.clone()));
+        let refresh_token_store = Arc::new(PostgresRefreshTokenStore::new(pool.clone()));
+        let totp_secret_store = Arc::new(PostgresTotpSecretStore::new(pool.clone(), &TOTP_ENCRYPTION_KEY));
+        let recovery_code_store = Arc::new(PostgresRecoveryCodeStore::new(pool.clone()));
+        let passkey_store = Arc::new(PostgresPasskeyStore::new(pool.clone()));
+        let role_store = Arc::new(PostgresRoleStore::new(pool.clone()));
+        let audit_log = Arc::new(PostgresAuditLog::new(pool.clone()));
+        let signing_key_store: SigningKeyStoreType = Arc::new(PostgresSigningKeyStore::new(pool, &TOTP_ENCRYPTION_KEY));
+        let redis = configure_redis().await;
+        let banned_token_store = Arc::new(RedisBannedTokenStore::new(redis.clone()));
+        let two_factor_store = Arc::new(RedisTwoFactorStore::new(redis.clone()));
+        let password_reset_store = Arc::new(RedisPasswordResetStore::new(redis.clone()));
+        let email_verification_store = Arc::new(RedisEmailVerificationStore::new(redis.clone()));
+        let session_store = Arc::new(RedisSessionStore::new(redis.clone()));
+        let email_change_store = Arc::new(RedisEmailChangeStore::new(redis.clone()));
+        let passkey_ceremony_store = Arc::new(RedisPasskeyCeremonyStore::new(redis.clone()));
+        let magic_link_store = Arc::new(RedisMagicLinkStore::new(redis.clone()));
+        let login_throttle_store = Arc::new(RedisLoginThrottleStore::new(redis.clone()));
+        let rate_limit_store = Arc::new(RedisRateLimitStore::new(redis.clone()));
         let email_client = Arc::new(MockEmailClient);
-        let app_state = AppState::new(banned_token_store.clone(), user_store, two_factor_store.clone(), email_client);
+        let webauthn = Arc::new(
+            get_webauthn(&WEBAUTHN_RP_ID, &Url::parse(&WEBAUTHN_RP_ORIGIN).expect("Failed to parse URL"))
+                .expect("Failed to configure WebAuthn"),
+        );
+        let mut app_state = AppState::new(
+            banned_token_store.clone(),
+            user_store.clone(),
+            two_factor_store.clone(),
+            password_reset_store.clone(),
+            email_verification_store.clone(),
+            email_change_store.clone(),
+            refresh_token_store,
+            session_store,
+            totp_secret_store,
+            recovery_code_store,
+            passkey_store,
+            passkey_ceremony_store,
+            magic_link_store.clone(),
+            role_store.clone(),
+            audit_log,
+            signing_key_store.clone(),
+            login_throttle_store.clone(),
+            rate_limit_store,
+            email_client,
+            webauthn,
+        );
+
+        configure(&mut app_state);
+        load_key_ring(&JWT_KEY_RING, signing_key_store.as_ref()).await.expect("Failed to load JWT signing keys");
+
         let app = Application::build(app_state, test::APP_ADDRESS).await.expect("Failed to build app");
         let address = format!("http://{}", app.address.clone());
 
         #[allow(clippy::let_underscore_future)]
         let _ = tokio::spawn(app.run());
         let cookie_jar = Arc::new(Jar::default());
-        let Ok(http_client) = ClientBuilder::new().cookie_provider(Arc::clone(&cookie_jar)).build()
+        let ip = IpAddr::V4(Ipv4Addr::new(127, rng().random(), rng().random(), rng().random_range(2..255)));
+        let Ok(http_client) = ClientBuilder::new().cookie_provider(Arc::clone(&cookie_jar)).local_address(ip).build()
         else {
             panic!("Failed to build reqwest client.")
         };
@@ -65,8 +154,17 @@ impl TestApp {
             cleaned_up: false,
             cookie_jar,
             database_name,
+            email_change_store,
+            email_verification_store,
             http_client,
+            ip,
+            login_throttle_store,
+            magic_link_store,
+            password_reset_store,
+            role_store,
+            signing_key_store,
             two_factor_store,
+            user_store,
         }
     }
 
@@ -74,6 +172,115 @@ impl TestApp {
         self.http_client.get(format!("{}/", &self.address)).send().await.expect("Failed to execute request.")
     }
 
+    pub async fn get_jwks(&self) -> Response {
+        self.http_client
+            .get(format!("{}/.well-known/jwks.json", &self.address))
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn post_rotate_signing_key(&self, admin_api_key: &str) -> Response {
+        self.http_client
+            .post(format!("{}/admin/keys/rotate", &self.address))
+            .bearer_auth(admin_api_key)
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    /// Lists roles as the logged-in user, or as the admin when `admin_api_key` is given.
+    pub async fn get_roles(&self, admin_api_key: Option<&str>) -> Response {
+        self.as_admin(self.http_client.get(format!("{}/admin/roles", &self.address)), admin_api_key)
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn post_assign_role<Body>(&self, body: &Body, admin_api_key: Option<&str>) -> Response
+    where
+        Body: Serialize,
+    {
+        self.as_admin(self.http_client.post(format!("{}/admin/roles/assign", &self.address)), admin_api_key)
+            .json(body)
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn post_revoke_role<Body>(&self, body: &Body, admin_api_key: Option<&str>) -> Response
+    where
+        Body: Serialize,
+    {
+        self.as_admin(self.http_client.post(format!("{}/admin/roles/revoke", &self.address)), admin_api_key)
+            .json(body)
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    /// Lists users matching `query`, a query string such as `search=abc&page=2`.
+    pub async fn get_admin_users(&self, query: &str, admin_api_key: Option<&str>) -> Response {
+        self.as_admin(self.http_client.get(format!("{}/admin/users?{query}", &self.address)), admin_api_key)
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn get_audit_events(&self, query: &str, admin_api_key: Option<&str>) -> Response {
+        self.as_admin(self.http_client.get(format!("{}/admin/audit-events?{query}", &self.address)), admin_api_key)
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn get_admin_user(&self, user_id: &Uuid, admin_api_key: Option<&str>) -> Response {
+        self.as_admin(self.http_client.get(format!("{}/admin/users/{user_id}", &self.address)), admin_api_key)
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    /// Posts to one of the bodiless admin user actions, such as `disable` or `sessions/revoke`.
+    pub async fn post_admin_user_action(&self, user_id: &Uuid, action: &str, admin_api_key: Option<&str>) -> Response {
+        self.as_admin(self.http_client.post(format!("{}/admin/users/{user_id}/{action}", &self.address)), admin_api_key)
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn post_admin_user_2fa<Body>(&self, user_id: &Uuid, body: &Body, admin_api_key: Option<&str>) -> Response
+    where
+        Body: Serialize,
+    {
+        self.as_admin(self.http_client.post(format!("{}/admin/users/{user_id}/2fa", &self.address)), admin_api_key)
+            .json(body)
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn post_admin_user_suspend<Body>(
+        &self,
+        user_id: &Uuid,
+        body: &Body,
+        admin_api_key: Option<&str>,
+    ) -> Response
+    where
+        Body: Serialize,
+    {
+        self.as_admin(self.http_client.post(format!("{}/admin/users/{user_id}/suspend", &self.address)), admin_api_key)
+            .json(body)
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    /// Key the admin endpoints accept in this test run.
+    pub fn admin_api_key(&self) -> String {
+        ADMIN_API_KEY.as_ref().expect("ADMIN_API_KEY must be set for tests").expose_secret().to_owned()
+    }
+
     pub async fn post_signup<Body>(&self, body: &Body) -> Response
     where
         Body: Serialize,
@@ -99,20 +306,18 @@ impl TestApp {
     }
 
     pub async fn post_logout(&self) -> Response {
-        let cookies = match Url::parse(&self.address) {
-            Ok(url) => match self.cookie_jar.cookies(&url) {
-                Some(cookies) => match cookies.to_str() {
-                    Ok(cookies) => cookies.to_string(),
-                    _ => String::new(),
-                },
-                _ => String::new(),
-            },
-            _ => String::new(),
-        };
-
         self.http_client
             .post(format!("{}/logout", &self.address))
-            .header(COOKIE, cookies)
+            .header(COOKIE, self.cookies())
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn post_logout_all(&self) -> Response {
+        self.http_client
+            .post(format!("{}/logout-all", &self.address))
+            .header(COOKIE, self.cookies())
             .send()
             .await
             .expect("Failed to execute request.")
@@ -142,10 +347,352 @@ impl TestApp {
             .expect("Failed to execute request.")
     }
 
+    pub async fn post_password_reset_request<Body>(&self, body: &Body) -> Response
+    where
+        Body: Serialize,
+    {
+        self.http_client
+            .post(format!("{}/password-reset/request", &self.address))
+            .json(body)
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn post_password_reset_confirm<Body>(&self, body: &Body) -> Response
+    where
+        Body: Serialize,
+    {
+        self.http_client
+            .post(format!("{}/password-reset/confirm", &self.address))
+            .json(body)
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn post_verify_email<Body>(&self, body: &Body) -> Response
+    where
+        Body: Serialize,
+    {
+        self.http_client
+            .post(format!("{}/verify-email", &self.address))
+            .json(body)
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn post_resend_verification_email<Body>(&self, body: &Body) -> Response
+    where
+        Body: Serialize,
+    {
+        self.http_client
+            .post(format!("{}/verify-email/resend", &self.address))
+            .json(body)
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn verify_email(&self, email: &str) {
+        let (token, _) = self
+            .email_verification_store
+            .get_token(&Email::parse(&SecretBox::new(Box::new(email.to_owned()))).unwrap())
+            .await
+            .expect("Failed to get token from email verification store");
+        let response = self
+            .post_verify_email(&serde_json::json!({
+                "email": email,
+                "token": token.as_ref().expose_secret()
+            }))
+            .await;
+
+        assert_eq!(response.status().as_u16(), 200, "Failed to verify email.");
+    }
+
+    pub async fn get_user_id(&self, email: &str) -> Uuid {
+        self.user_store
+            .get_user(&Email::parse(&SecretBox::new(Box::new(email.to_owned()))).unwrap())
+            .await
+            .expect("Failed to get user from user store")
+            .user_id
+    }
+
+    pub async fn post_change_password<Body>(&self, body: &Body) -> Response
+    where
+        Body: Serialize,
+    {
+        self.http_client
+            .post(format!("{}/change-password", &self.address))
+            .header(COOKIE, self.cookies())
+            .json(body)
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn post_change_email<Body>(&self, body: &Body) -> Response
+    where
+        Body: Serialize,
+    {
+        self.http_client
+            .post(format!("{}/change-email", &self.address))
+            .header(COOKIE, self.cookies())
+            .json(body)
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn post_change_email_confirm<Body>(&self, body: &Body) -> Response
+    where
+        Body: Serialize,
+    {
+        self.http_client
+            .post(format!("{}/change-email/confirm", &self.address))
+            .json(body)
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn post_change_email_cancel<Body>(&self, body: &Body) -> Response
+    where
+        Body: Serialize,
+    {
+        self.http_client
+            .post(format!("{}/change-email/cancel", &self.address))
+            .json(body)
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn get_account_export(&self) -> Response {
+        self.http_client
+            .get(format!("{}/account/export", &self.address))
+            .header(COOKIE, self.cookies())
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn post_account_delete<Body>(&self, body: &Body) -> Response
+    where
+        Body: Serialize,
+    {
+        self.http_client
+            .post(format!("{}/account/delete", &self.address))
+            .header(COOKIE, self.cookies())
+            .json(body)
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn post_account_restore<Body>(&self, body: &Body) -> Response
+    where
+        Body: Serialize,
+    {
+        self.http_client
+            .post(format!("{}/account/restore", &self.address))
+            .json(body)
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn get_sessions(&self) -> Response {
+        self.http_client
+            .get(format!("{}/sessions", &self.address))
+            .header(COOKIE, self.cookies())
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn post_revoke_session<Body>(&self, body: &Body) -> Response
+    where
+        Body: Serialize,
+    {
+        self.http_client
+            .post(format!("{}/sessions/revoke", &self.address))
+            .header(COOKIE, self.cookies())
+            .json(body)
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn post_revoke_other_sessions(&self) -> Response {
+        self.http_client
+            .post(format!("{}/sessions/revoke-others", &self.address))
+            .header(COOKIE, self.cookies())
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn post_refresh(&self) -> Response {
+        self.http_client
+            .post(format!("{}/refresh", &self.address))
+            .header(COOKIE, self.cookies())
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn post_totp_enroll(&self) -> Response {
+        self.http_client
+            .post(format!("{}/2fa/totp/enroll", &self.address))
+            .header(COOKIE, self.cookies())
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn post_totp_confirm<Body>(&self, body: &Body) -> Response
+    where
+        Body: Serialize,
+    {
+        self.http_client
+            .post(format!("{}/2fa/totp/confirm", &self.address))
+            .header(COOKIE, self.cookies())
+            .json(body)
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn post_two_factor_method<Body>(&self, body: &Body) -> Response
+    where
+        Body: Serialize,
+    {
+        self.http_client
+            .post(format!("{}/2fa/method", &self.address))
+            .header(COOKIE, self.cookies())
+            .json(body)
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn post_recovery_codes(&self) -> Response {
+        self.http_client
+            .post(format!("{}/2fa/recovery-codes", &self.address))
+            .header(COOKIE, self.cookies())
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn post_passkey_register_start(&self) -> Response {
+        self.http_client
+            .post(format!("{}/passkey/register/start", &self.address))
+            .header(COOKIE, self.cookies())
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn post_passkey_register_finish<Body>(&self, body: &Body) -> Response
+    where
+        Body: Serialize,
+    {
+        self.http_client
+            .post(format!("{}/passkey/register/finish", &self.address))
+            .header(COOKIE, self.cookies())
+            .json(body)
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn post_passkey_login_start<Body>(&self, body: &Body) -> Response
+    where
+        Body: Serialize,
+    {
+        self.http_client
+            .post(format!("{}/passkey/login/start", &self.address))
+            .json(body)
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn post_passkey_login_finish<Body>(&self, body: &Body) -> Response
+    where
+        Body: Serialize,
+    {
+        self.http_client
+            .post(format!("{}/passkey/login/finish", &self.address))
+            .json(body)
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn post_magic_link<Body>(&self, body: &Body) -> Response
+    where
+        Body: Serialize,
+    {
+        self.http_client
+            .post(format!("{}/login/magic-link", &self.address))
+            .json(body)
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn post_magic_link_consume<Body>(&self, body: &Body) -> Response
+    where
+        Body: Serialize,
+    {
+        self.http_client
+            .post(format!("{}/login/magic-link/consume", &self.address))
+            .header(COOKIE, self.cookies())
+            .json(body)
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    /// Reads the pending magic link token for `email` without consuming it.
+    pub async fn get_magic_link_token(&self, email: &str) -> String {
+        let email = Email::parse(&SecretBox::new(Box::new(email.to_owned()))).unwrap();
+        let token = self.magic_link_store.take_token(&email).await.expect("Failed to get magic link token");
+
+        self.magic_link_store.add_token(email, token.clone()).await.expect("Failed to restore magic link token");
+
+        token.as_ref().expose_secret().to_owned()
+    }
+
     pub async fn clean_up(&mut self) {
         delete_database(&self.database_name).await;
         self.cleaned_up = true;
     }
+
+    /// Authenticates `request` with `admin_api_key` if given, and with the auth cookie otherwise.
+    fn as_admin(&self, request: RequestBuilder, admin_api_key: Option<&str>) -> RequestBuilder {
+        match admin_api_key {
+            Some(admin_api_key) => request.bearer_auth(admin_api_key),
+            None => request.header(COOKIE, self.cookies()),
+        }
+    }
+
+    fn cookies(&self) -> String {
+        match Url::parse(&self.address) {
+            Ok(url) => match self.cookie_jar.cookies(&url) {
+                Some(cookies) => match cookies.to_str() {
+                    Ok(cookies) => cookies.to_string(),
+                    _ => String::new(),
+                },
+                _ => String::new(),
+            },
+            _ => String::new(),
+        }
+    }
 }
 
 pub fn get_random_email() -> String {
@@ -188,7 +735,7 @@ async fn configure_database(url: &str, suffix: &str) -> (String, String) {
     (url.to_string(), name)
 }
 
-async fn delete_database(name: &str) {
+pub async fn delete_database(name: &str) {
     let (url, _) = DATABASE_URL.expose_secret().rsplit_once('/').unwrap();
     let options =
         PgConnectOptions::from_str(&format!("{url}/postgres")).expect("Failed to parse PostgreSQL connection string");
@@ -210,9 +757,10 @@ async fn delete_database(name: &str) {
     connection.execute(format!(r#"drop database "{name}";"#).as_str()).await.expect("Failed to drop the database");
 }
 
-fn configure_redis() -> RedisConnection {
+pub async fn configure_redis() -> ConnectionManager {
     get_redis_client(REDIS_HOST_NAME.as_str())
         .expect("Failed to get Redis client")
-        .get_connection()
+        .get_connection_manager()
+        .await
         .expect("Failed to get Redis connection")
 }
@@ -0,0 +1,156 @@
+use {
+    crate::helpers::{TestApp, get_random_email},
+    auth_service::{
+        routes::RotateSigningKeyResponse,
+        utils::{
+            constants::{JWT_AUDIENCE, JWT_COOKIE_NAME, JWT_KEY_OVERLAP_SECONDS, KEY_ACTIVATION_DELAY_SECONDS},
+            keys::{KeyRing, load_key_ring},
+        },
+    },
+    chrono::Utc,
+    jsonwebtoken::{
+        Algorithm, DecodingKey, Validation, decode, decode_header,
+        jwk::{AlgorithmParameters, JwkSet},
+    },
+    serde_json::{Value, json},
+    uuid::Uuid,
+};
+
+#[tokio::test]
+async fn should_return_200_with_public_signing_key() {
+    let mut app = TestApp::new().await;
+    let response = app.get_jwks().await;
+
+    assert_eq!(response.status().as_u16(), 200);
+
+    let jwks = response.json::<JwkSet>().await.expect("Could not deserialize response body to JwkSet");
+
+    assert!(!jwks.keys.is_empty());
+
+    for jwk in &jwks.keys {
+        assert!(jwk.common.key_id.is_some());
+        assert!(matches!(jwk.algorithm, AlgorithmParameters::OctetKeyPair(_)));
+    }
+
+    app.clean_up().await;
+}
+
+async fn login(app: &TestApp, email: &str) -> String {
+    let password = "abcd1234";
+    let response = app
+        .post_signup(&json!({
+            "email": email,
+            "password": password,
+            "requires2FA": false
+        }))
+        .await;
+
+    assert_eq!(response.status().as_u16(), 201);
+
+    app.verify_email(email).await;
+
+    let response = app.post_login(&json!({ "email": email, "password": password })).await;
+
+    assert_eq!(response.status().as_u16(), 200);
+
+    let cookie = response.cookies().find(|cookie| cookie.name() == JWT_COOKIE_NAME).expect("No auth cookie found");
+
+    cookie.value().to_owned()
+}
+
+#[tokio::test]
+async fn should_verify_issued_token_with_published_key() {
+    let mut app = TestApp::new().await;
+    let email = get_random_email();
+    let token = login(&app, &email).await;
+    let jwks = app.get_jwks().await.json::<JwkSet>().await.expect("Could not deserialize response body to JwkSet");
+    let header = decode_header(&token).expect("Could not decode token header");
+
+    assert_eq!(header.alg, Algorithm::EdDSA);
+
+    let jwk = jwks.find(&header.kid.expect("Token has no kid")).expect("Token kid is not published");
+    let key = DecodingKey::from_jwk(jwk).expect("Could not build decoding key from JWK");
+    let mut validation = Validation::new(Algorithm::EdDSA);
+
+    validation.set_audience(&[JWT_AUDIENCE.as_str()]);
+
+    let claims = decode::<Value>(&token, &key, &validation).expect("Token did not verify").claims;
+
+    assert_ne!(claims["sub"], email);
+    assert!(Uuid::parse_str(claims["sub"].as_str().expect("Token has no sub")).is_ok());
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_keep_verifying_tokens_signed_before_rotation() {
+    let mut app = TestApp::new().await;
+    let token = login(&app, &get_random_email()).await;
+    let old_kid = decode_header(&token).expect("Could not decode token header").kid.expect("Token has no kid");
+    let response = app.post_rotate_signing_key(&app.admin_api_key()).await;
+
+    assert_eq!(response.status().as_u16(), 200);
+
+    let new_kid = response
+        .json::<RotateSigningKeyResponse>()
+        .await
+        .expect("Could not deserialize response body to RotateSigningKeyResponse")
+        .kid;
+
+    assert_ne!(new_kid, old_kid);
+
+    let jwks = app.get_jwks().await.json::<JwkSet>().await.expect("Could not deserialize response body to JwkSet");
+
+    assert!(jwks.find(&old_kid).is_some());
+    assert!(jwks.find(&new_kid).is_some());
+
+    let response = app.post_verify_token(&json!({ "token": token })).await;
+
+    assert_eq!(response.status().as_u16(), 200);
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_load_rotated_key_after_restart() {
+    let mut app = TestApp::new().await;
+    let response = app.post_rotate_signing_key(&app.admin_api_key()).await;
+
+    assert_eq!(response.status().as_u16(), 200);
+
+    let new_kid = response
+        .json::<RotateSigningKeyResponse>()
+        .await
+        .expect("Could not deserialize response body to RotateSigningKeyResponse")
+        .kid;
+    let ring = KeyRing::new(*JWT_KEY_OVERLAP_SECONDS);
+
+    load_key_ring(&ring, app.signing_key_store.as_ref()).await.expect("Failed to load JWT signing keys");
+
+    let now = Utc::now().timestamp();
+
+    assert!(ring.find(&new_kid, now).is_some());
+    assert_ne!(ring.active(now).expect("No signing key loaded").kid, new_kid);
+    assert_eq!(ring.active(now + KEY_ACTIVATION_DELAY_SECONDS).expect("No signing key loaded").kid, new_kid);
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_return_401_rotating_without_admin_credential() {
+    let mut app = TestApp::new().await;
+    let response = app.post_rotate_signing_key("wrong-key").await;
+
+    assert_eq!(response.status().as_u16(), 401);
+
+    let response = app
+        .http_client
+        .post(format!("{}/admin/keys/rotate", &app.address))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    assert_eq!(response.status().as_u16(), 401);
+
+    app.clean_up().await;
+}
@@ -2,14 +2,37 @@ use {
     crate::helpers::{TestApp, get_random_email},
     auth_service::{
         ErrorResponse,
-        domain::email::Email,
+        domain::{data_stores::ThrottleKey, email::Email},
         routes::{RegularAuthResponse, TwoFactorAuthResponse},
-        utils::constants::JWT_COOKIE_NAME,
+        utils::constants::{IP_LOCKOUT_THRESHOLD, JWT_COOKIE_NAME, LOGIN_BACKOFF_THRESHOLD, LOGIN_LOCKOUT_THRESHOLD},
     },
     secrecy::SecretBox,
     serde_json::json,
+    std::time::Duration,
 };
 
+async fn signup(app: &TestApp, email: &str) {
+    let _ = app
+        .post_signup(&json!({
+            "email": email,
+            "password": "abcd1234",
+            "requires2FA": false
+        }))
+        .await;
+
+    app.verify_email(email).await;
+}
+
+async fn record_failures(app: &TestApp, key: ThrottleKey, count: u32) {
+    for _ in 0..count {
+        app.login_throttle_store.record_failure(&key).await.expect("Failed to record login failure");
+    }
+}
+
+fn account_key(email: &str) -> ThrottleKey {
+    ThrottleKey::Account(Email::parse(&SecretBox::new(Box::new(email.to_owned()))).unwrap())
+}
+
 #[tokio::test]
 async fn should_return_200_if_valid_credentials_and_2fa_disabled() {
     let mut app = TestApp::new().await;
@@ -25,6 +48,8 @@ async fn should_return_200_if_valid_credentials_and_2fa_disabled() {
 
     assert_eq!(response.status().as_u16(), 201);
 
+    app.verify_email(&email).await;
+
     let response = app
         .post_login(&json!({
             "email": email,
@@ -41,6 +66,25 @@ async fn should_return_200_if_valid_credentials_and_2fa_disabled() {
     app.clean_up().await;
 }
 
+#[tokio::test]
+async fn should_return_200_if_email_differs_in_case() {
+    let mut app = TestApp::new().await;
+    let email = get_random_email();
+
+    signup(&app, &email).await;
+
+    let response = app
+        .post_login(&json!({
+            "email": email.to_uppercase(),
+            "password": "abcd1234",
+        }))
+        .await;
+
+    assert_eq!(response.status().as_u16(), 200);
+
+    app.clean_up().await;
+}
+
 #[tokio::test]
 async fn should_return_206_if_valid_credentials_and_2fa_enabled() {
     let mut app = TestApp::new().await;
@@ -56,6 +100,8 @@ async fn should_return_206_if_valid_credentials_and_2fa_enabled() {
 
     assert_eq!(response.status().as_u16(), 201);
 
+    app.verify_email(&email).await;
+
     let response = app
         .post_login(&json!({
             "email": email,
@@ -72,8 +118,7 @@ async fn should_return_206_if_valid_credentials_and_2fa_enabled() {
 
     assert_eq!(body.message, "2FA required".to_owned());
 
-    let email = Email::parse(&SecretBox::new(Box::new(email))).unwrap();
-    let maybe_value = app.two_factor_store.get_code(&email).await;
+    let maybe_value = app.two_factor_store.get_code(&app.get_user_id(&email).await).await;
 
     assert!(maybe_value.is_ok());
 
@@ -131,6 +176,14 @@ async fn should_return_401_if_incorrect_credentials() {
 
     assert_eq!(response.status().as_u16(), 401);
 
+    let failures = app.login_throttle_store.get_failures(&account_key(&email)).await.unwrap();
+
+    assert_eq!(failures.count, 1);
+
+    let failures = app.login_throttle_store.get_failures(&ThrottleKey::Ip(app.ip)).await.unwrap();
+
+    assert_eq!(failures.count, 1);
+
     app.clean_up().await;
 }
 
@@ -163,3 +216,108 @@ async fn should_return_422_if_malformed_credentials() {
 
     app.clean_up().await;
 }
+
+#[tokio::test]
+async fn should_return_423_if_account_locked() {
+    let mut app = TestApp::new().await;
+    let email = get_random_email();
+
+    signup(&app, &email).await;
+    record_failures(&app, account_key(&email), *LOGIN_LOCKOUT_THRESHOLD).await;
+
+    let response = app
+        .post_login(&json!({
+            "email": email,
+            "password": "abcd1234",
+        }))
+        .await;
+
+    assert_eq!(response.status().as_u16(), 423);
+    assert_eq!(
+        response.json::<ErrorResponse>().await.expect("Could not deserialize response body to ErrorResponse").error,
+        "Account locked".to_owned()
+    );
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_lock_account_on_failures_with_different_casings() {
+    let mut app = TestApp::new().await;
+    let email = get_random_email();
+
+    signup(&app, &email).await;
+
+    for i in 0..*LOGIN_LOCKOUT_THRESHOLD {
+        let casing = match i % 2 {
+            0 => email.to_uppercase(),
+            _ => email.clone(),
+        };
+
+        record_failures(&app, account_key(&casing), 1).await;
+    }
+
+    let response = app
+        .post_login(&json!({
+            "email": email.replace("example", "Example"),
+            "password": "abcd1234",
+        }))
+        .await;
+
+    assert_eq!(response.status().as_u16(), 423);
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_return_429_during_backoff_and_reset_after_success() {
+    let mut app = TestApp::new().await;
+    let email = get_random_email();
+
+    signup(&app, &email).await;
+    record_failures(&app, account_key(&email), *LOGIN_BACKOFF_THRESHOLD).await;
+
+    let body = json!({
+        "email": email.to_uppercase(),
+        "password": "abcd1234",
+    });
+    let response = app.post_login(&body).await;
+
+    assert_eq!(response.status().as_u16(), 429);
+
+    tokio::time::sleep(Duration::from_millis(1100)).await;
+
+    let response = app.post_login(&body).await;
+
+    assert_eq!(response.status().as_u16(), 200);
+
+    let failures = app.login_throttle_store.get_failures(&account_key(&email)).await.unwrap();
+
+    assert_eq!(failures.count, 0);
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_return_429_if_ip_locked_out() {
+    let mut app = TestApp::new().await;
+    let email = get_random_email();
+
+    signup(&app, &email).await;
+    record_failures(&app, ThrottleKey::Ip(app.ip), *IP_LOCKOUT_THRESHOLD).await;
+
+    let response = app
+        .post_login(&json!({
+            "email": email,
+            "password": "abcd1234",
+        }))
+        .await;
+
+    assert_eq!(response.status().as_u16(), 429);
+    assert_eq!(
+        response.json::<ErrorResponse>().await.expect("Could not deserialize response body to ErrorResponse").error,
+        "Too many requests".to_owned()
+    );
+
+    app.clean_up().await;
+}
@@ -1,6 +1,10 @@
 use {
-    crate::helpers::{TestApp, get_random_email},
-    auth_service::{ErrorResponse, utils::constants::JWT_COOKIE_NAME},
+    crate::helpers::{TestApp, configure_redis, get_random_email},
+    auth_service::{
+        ErrorResponse,
+        utils::{auth::validate_token, constants::JWT_COOKIE_NAME},
+    },
+    redis::AsyncTypedCommands,
     reqwest::Url,
     secrecy::SecretBox,
     serde_json::json,
@@ -19,6 +23,8 @@ async fn should_return_200_if_valid_jwt_cookie() {
     }))
     .await;
 
+    app.verify_email(&email).await;
+
     let response = app
         .post_login(&json!({
             "email": email,
@@ -31,7 +37,9 @@ async fn should_return_200_if_valid_jwt_cookie() {
 
     assert_eq!(response.status().as_u16(), 200);
 
-    let exists = app.banned_token_store.check(&SecretBox::new(Box::new(token.value().to_string()))).await;
+    let token = SecretBox::new(Box::new(token.value().to_string()));
+    let claims = validate_token(None, &token).await.expect("Failed to decode auth token");
+    let exists = app.banned_token_store.check(&claims.jti, &token).await;
 
     assert!(exists.is_ok());
     assert!(exists.unwrap());
@@ -52,6 +60,8 @@ async fn should_return_400_if_logout_called_twice_in_a_row() {
     }))
     .await;
 
+    app.verify_email(&email).await;
+
     app.post_login(&json!({
         "email": email,
         "password": password,
@@ -104,3 +114,84 @@ async fn should_return_401_if_invalid_token() {
 
     app.clean_up().await;
 }
+
+#[tokio::test]
+async fn should_invalidate_every_session_on_logout_all() {
+    let mut app = TestApp::new().await;
+    let email = get_random_email();
+    let password = "abcd1234";
+
+    app.post_signup(&json!({
+        "email": email,
+        "password": password,
+        "requires2FA": false
+    }))
+    .await;
+
+    app.verify_email(&email).await;
+
+    let mut tokens = Vec::new();
+
+    for _ in 0..2 {
+        let response = app.post_login(&json!({ "email": email, "password": password })).await;
+        let token = response.cookies().find(|cookie| cookie.name() == JWT_COOKIE_NAME).expect("No auth cookie found");
+
+        tokens.push(token.value().to_owned());
+    }
+
+    let response = app.post_logout_all().await;
+
+    assert_eq!(response.status().as_u16(), 200);
+
+    for token in tokens {
+        let response = app.post_verify_token(&json!({ "token": token })).await;
+
+        assert_eq!(response.status().as_u16(), 401);
+    }
+
+    let response = app.post_refresh().await;
+
+    assert_eq!(response.status().as_u16(), 400);
+
+    // logging in afterwards starts a fresh, valid session
+    let response = app.post_login(&json!({ "email": email, "password": password })).await;
+    let token = response.cookies().find(|cookie| cookie.name() == JWT_COOKIE_NAME).expect("No auth cookie found");
+    let response = app.post_verify_token(&json!({ "token": token.value() })).await;
+
+    assert_eq!(response.status().as_u16(), 200);
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_keep_rejecting_tokens_after_losing_cached_token_version() {
+    let mut app = TestApp::new().await;
+    let email = get_random_email();
+    let password = "abcd1234";
+
+    app.post_signup(&json!({
+        "email": email,
+        "password": password,
+        "requires2FA": false
+    }))
+    .await;
+
+    app.verify_email(&email).await;
+
+    let response = app.post_login(&json!({ "email": email, "password": password })).await;
+    let token = response.cookies().find(|cookie| cookie.name() == JWT_COOKIE_NAME).expect("No auth cookie found");
+    let token = token.value().to_owned();
+    let claims = validate_token(None, &SecretBox::new(Box::new(token.clone()))).await.expect("Invalid auth token");
+    let response = app.post_logout_all().await;
+
+    assert_eq!(response.status().as_u16(), 200);
+
+    // redis only caches the version, so flushing it must not revive the token
+    configure_redis().await.del(format!("token_version:{}", claims.sub)).await.expect("Failed to drop cached version");
+
+    let response = app.post_verify_token(&json!({ "token": token })).await;
+
+    assert_eq!(response.status().as_u16(), 401);
+
+    app.clean_up().await;
+}
@@ -0,0 +1,137 @@
+use {
+    crate::helpers::{TestApp, get_random_email},
+    auth_service::{
+        ErrorResponse,
+        domain::{data_stores::MagicLinkStoreError, email::Email},
+        routes::{MagicLinkResponse, TwoFactorAuthResponse},
+        utils::constants::{JWT_COOKIE_NAME, MAGIC_LINK_NONCE_COOKIE_NAME},
+    },
+    secrecy::SecretBox,
+    serde_json::json,
+};
+
+async fn signup(app: &TestApp, email: &str, requires_2fa: bool) {
+    let _ = app
+        .post_signup(&json!({
+            "email": email,
+            "password": "abcd1234",
+            "requires2FA": requires_2fa
+        }))
+        .await;
+
+    app.verify_email(email).await;
+}
+
+async fn request_magic_link(app: &TestApp, email: &str) -> String {
+    let response = app.post_magic_link(&json!({ "email": email })).await;
+
+    assert_eq!(response.status().as_u16(), 200);
+    assert!(response.cookies().any(|cookie| cookie.name() == MAGIC_LINK_NONCE_COOKIE_NAME));
+
+    app.get_magic_link_token(email).await
+}
+
+#[tokio::test]
+async fn should_login_with_magic_link_only_once() {
+    let mut app = TestApp::new().await;
+    let email = get_random_email();
+
+    signup(&app, &email, false).await;
+
+    let token = request_magic_link(&app, &email).await;
+    let response = app.post_magic_link_consume(&json!({ "token": token })).await;
+
+    assert_eq!(response.status().as_u16(), 200);
+
+    let auth_cookie = response.cookies().find(|cookie| cookie.name() == JWT_COOKIE_NAME).expect("No auth cookie found");
+
+    assert!(!auth_cookie.value().is_empty());
+
+    let response = app.post_magic_link_consume(&json!({ "token": token })).await;
+
+    assert_eq!(response.status().as_u16(), 401);
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_return_401_if_consumed_from_another_browser() {
+    let mut app = TestApp::new().await;
+    let email = get_random_email();
+
+    signup(&app, &email, false).await;
+
+    let token = request_magic_link(&app, &email).await;
+    let response = reqwest::Client::new()
+        .post(format!("{}/login/magic-link/consume", &app.address))
+        .json(&json!({ "token": token }))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    assert_eq!(response.status().as_u16(), 401);
+    assert_eq!(
+        response.json::<ErrorResponse>().await.expect("Could not deserialize response body to ErrorResponse").error,
+        "Invalid token".to_owned()
+    );
+
+    let response = app.post_magic_link_consume(&json!({ "token": token })).await;
+
+    assert_eq!(response.status().as_u16(), 200);
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_return_401_if_token_tampered() {
+    let mut app = TestApp::new().await;
+    let email = get_random_email();
+
+    signup(&app, &email, false).await;
+
+    let token = request_magic_link(&app, &email).await;
+    let response = app.post_magic_link_consume(&json!({ "token": format!("{token}x") })).await;
+
+    assert_eq!(response.status().as_u16(), 401);
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_still_require_2fa_after_magic_link() {
+    let mut app = TestApp::new().await;
+    let email = get_random_email();
+
+    signup(&app, &email, true).await;
+
+    let token = request_magic_link(&app, &email).await;
+    let response = app.post_magic_link_consume(&json!({ "token": token })).await;
+
+    assert_eq!(response.status().as_u16(), 206);
+    assert!(response.cookies().all(|cookie| cookie.name() != JWT_COOKIE_NAME));
+
+    let body = response.json::<TwoFactorAuthResponse>().await.expect("Could not deserialize response body");
+
+    assert_eq!(body.message, "2FA required".to_owned());
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_return_200_without_sending_link_if_user_unknown() {
+    let mut app = TestApp::new().await;
+    let email = get_random_email();
+    let response = app.post_magic_link(&json!({ "email": email })).await;
+
+    assert_eq!(response.status().as_u16(), 200);
+    assert_eq!(
+        response.json::<MagicLinkResponse>().await.expect("Could not deserialize response body"),
+        MagicLinkResponse { message: "If the account exists, a login link has been sent".to_owned() }
+    );
+
+    let email = Email::parse(&SecretBox::new(Box::new(email))).unwrap();
+
+    assert_eq!(app.magic_link_store.take_token(&email).await.err(), Some(MagicLinkStoreError::TokenNotFound));
+
+    app.clean_up().await;
+}
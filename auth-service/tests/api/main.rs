@@ -1,7 +1,24 @@
+mod account;
+mod admin_users;
+mod audit;
+mod change_email;
+mod change_password;
 mod helpers;
+mod jwks;
 mod login;
 mod logout;
+mod magic_link;
+mod passkey;
+mod password_reset;
+mod rate_limit;
+mod recovery_codes;
+mod refresh;
+mod roles;
 mod root;
+mod sessions;
 mod signup;
+mod store_conformance;
+mod totp;
 mod verify_2fa;
+mod verify_email;
 mod verify_token;
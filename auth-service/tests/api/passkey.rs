@@ -0,0 +1,192 @@
+use {
+    crate::helpers::{TestApp, get_random_email},
+    auth_service::{
+        ErrorResponse,
+        domain::user::TwoFactorMethod,
+        routes::{PasskeyResponse, TwoFactorAuthResponse},
+        utils::constants::{JWT_COOKIE_NAME, WEBAUTHN_RP_ORIGIN},
+    },
+    reqwest::Url,
+    serde_json::json,
+    webauthn_authenticator_rs::{WebauthnAuthenticator, softpasskey::SoftPasskey},
+    webauthn_rs::prelude::{CreationChallengeResponse, RequestChallengeResponse},
+};
+
+async fn signup_and_login(app: &TestApp, email: &str) {
+    let _ = app
+        .post_signup(&json!({
+            "email": email,
+            "password": "abcd1234",
+            "requires2FA": false
+        }))
+        .await;
+
+    app.verify_email(email).await;
+
+    let _ = app
+        .post_login(&json!({
+            "email": email,
+            "password": "abcd1234",
+        }))
+        .await;
+}
+
+fn origin() -> Url {
+    Url::parse(&WEBAUTHN_RP_ORIGIN).expect("Failed to parse URL")
+}
+
+async fn register(app: &TestApp, authenticator: &mut WebauthnAuthenticator<SoftPasskey>) {
+    let response = app.post_passkey_register_start().await;
+
+    assert_eq!(response.status().as_u16(), 200);
+
+    let challenge = response.json::<CreationChallengeResponse>().await.expect("Could not deserialize response body");
+    let credential = authenticator.do_registration(origin(), challenge).expect("Failed to register passkey");
+    let response = app.post_passkey_register_finish(&credential).await;
+
+    assert_eq!(response.status().as_u16(), 201);
+    assert_eq!(
+        response.json::<PasskeyResponse>().await.expect("Could not deserialize response body").message,
+        "Passkey registered successfully!".to_owned()
+    );
+}
+
+async fn login_challenge(app: &TestApp, email: &str) -> RequestChallengeResponse {
+    let response = app.post_passkey_login_start(&json!({ "email": email })).await;
+
+    assert_eq!(response.status().as_u16(), 200);
+
+    response.json::<RequestChallengeResponse>().await.expect("Could not deserialize response body")
+}
+
+#[tokio::test]
+async fn should_register_passkey_and_login_without_password() {
+    let mut app = TestApp::new().await;
+    let email = get_random_email();
+    let mut authenticator = WebauthnAuthenticator::new(SoftPasskey::new(true));
+
+    signup_and_login(&app, &email).await;
+    register(&app, &mut authenticator).await;
+
+    let challenge = login_challenge(&app, &email).await;
+    let credential = authenticator.do_authentication(origin(), challenge).expect("Failed to authenticate");
+    let response = app.post_passkey_login_finish(&json!({ "email": email, "credential": credential })).await;
+
+    assert_eq!(response.status().as_u16(), 200);
+
+    let auth_cookie = response.cookies().find(|cookie| cookie.name() == JWT_COOKIE_NAME).expect("No auth cookie found");
+
+    assert!(!auth_cookie.value().is_empty());
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_accept_passkey_as_second_factor() {
+    let mut app = TestApp::new().await;
+    let email = get_random_email();
+    let mut authenticator = WebauthnAuthenticator::new(SoftPasskey::new(true));
+
+    signup_and_login(&app, &email).await;
+    register(&app, &mut authenticator).await;
+
+    let response = app.post_two_factor_method(&json!({ "method": "passkey" })).await;
+
+    assert_eq!(response.status().as_u16(), 200);
+
+    let response = app
+        .post_login(&json!({
+            "email": email,
+            "password": "abcd1234",
+        }))
+        .await;
+
+    assert_eq!(response.status().as_u16(), 206);
+
+    let body = response.json::<TwoFactorAuthResponse>().await.expect("Could not deserialize response body");
+
+    assert_eq!(body.two_factor_method, TwoFactorMethod::Passkey);
+
+    let challenge = login_challenge(&app, &email).await;
+    let credential = authenticator.do_authentication(origin(), challenge).expect("Failed to authenticate");
+    let response = app
+        .post_passkey_login_finish(&json!({
+            "email": email,
+            "credential": credential,
+            "loginAttemptId": body.login_attempt_id
+        }))
+        .await;
+
+    assert_eq!(response.status().as_u16(), 200);
+
+    let response = app
+        .post_verify_2fa(&json!({
+            "email": email,
+            "loginAttemptId": body.login_attempt_id,
+            "2FACode": "123456"
+        }))
+        .await;
+
+    assert_eq!(response.status().as_u16(), 401);
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_return_401_if_challenge_replayed() {
+    let mut app = TestApp::new().await;
+    let email = get_random_email();
+    let mut authenticator = WebauthnAuthenticator::new(SoftPasskey::new(true));
+
+    signup_and_login(&app, &email).await;
+    register(&app, &mut authenticator).await;
+
+    let challenge = login_challenge(&app, &email).await;
+    let credential = authenticator.do_authentication(origin(), challenge).expect("Failed to authenticate");
+    let body = json!({ "email": email, "credential": credential });
+    let response = app.post_passkey_login_finish(&body).await;
+
+    assert_eq!(response.status().as_u16(), 200);
+
+    let response = app.post_passkey_login_finish(&body).await;
+
+    assert_eq!(response.status().as_u16(), 401);
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_return_400_if_no_passkeys_registered() {
+    let mut app = TestApp::new().await;
+    let email = get_random_email();
+
+    signup_and_login(&app, &email).await;
+
+    let response = app.post_passkey_login_start(&json!({ "email": email })).await;
+
+    assert_eq!(response.status().as_u16(), 400);
+    assert_eq!(
+        response.json::<ErrorResponse>().await.expect("Could not deserialize response body to ErrorResponse").error,
+        "Invalid credentials".to_owned()
+    );
+
+    let response = app.post_two_factor_method(&json!({ "method": "passkey" })).await;
+
+    assert_eq!(response.status().as_u16(), 400);
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_return_400_if_registering_without_auth_cookie() {
+    let mut app = TestApp::new().await;
+    let response = app.post_passkey_register_start().await;
+
+    assert_eq!(response.status().as_u16(), 400);
+    assert_eq!(
+        response.json::<ErrorResponse>().await.expect("Could not deserialize response body to ErrorResponse").error,
+        "Missing token".to_owned()
+    );
+
+    app.clean_up().await;
+}
@@ -0,0 +1,213 @@
+use {
+    crate::helpers::{TestApp, get_random_email},
+    auth_service::{
+        ErrorResponse, domain::email::Email, routes::PasswordResetResponse, utils::constants::JWT_COOKIE_NAME,
+    },
+    secrecy::{ExposeSecret, SecretBox},
+    serde_json::json,
+};
+
+#[tokio::test]
+async fn should_return_200_and_store_token_if_user_exists() {
+    let mut app = TestApp::new().await;
+    let email = get_random_email();
+    let _ = app
+        .post_signup(&json!({
+            "email": email,
+            "password": "abcd1234",
+            "requires2FA": false
+        }))
+        .await;
+    let response = app.post_password_reset_request(&json!({ "email": email })).await;
+
+    assert_eq!(response.status().as_u16(), 200);
+    assert!(
+        response
+            .json::<PasswordResetResponse>()
+            .await
+            .expect("Could not deserialize response body to PasswordResetResponse")
+            .message
+            .starts_with("If the account exists"),
+    );
+
+    let email = Email::parse(&SecretBox::new(Box::new(email))).unwrap();
+
+    assert!(app.password_reset_store.get_token(&email).await.is_ok());
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_return_200_without_token_if_user_does_not_exist() {
+    let mut app = TestApp::new().await;
+    let email = get_random_email();
+    let response = app.post_password_reset_request(&json!({ "email": email })).await;
+
+    assert_eq!(response.status().as_u16(), 200);
+
+    let email = Email::parse(&SecretBox::new(Box::new(email))).unwrap();
+
+    assert!(app.password_reset_store.get_token(&email).await.is_err());
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_reset_password_and_revoke_sessions_if_valid_token() {
+    let mut app = TestApp::new().await;
+    let email = get_random_email();
+    let _ = app
+        .post_signup(&json!({
+            "email": email,
+            "password": "abcd1234",
+            "requires2FA": false
+        }))
+        .await;
+
+    app.verify_email(&email).await;
+
+    let response = app
+        .post_login(&json!({
+            "email": email,
+            "password": "abcd1234",
+        }))
+        .await;
+    let cookie = response.cookies().find(|cookie| cookie.name() == JWT_COOKIE_NAME).expect("No auth cookie found");
+    let _ = app.post_password_reset_request(&json!({ "email": email })).await;
+    let token = app
+        .password_reset_store
+        .get_token(&Email::parse(&SecretBox::new(Box::new(email.clone()))).unwrap())
+        .await
+        .expect("Failed to get token from password reset store");
+    let response = app
+        .post_password_reset_confirm(&json!({
+            "email": email,
+            "token": token.as_ref().expose_secret(),
+            "newPassword": "efgh5678"
+        }))
+        .await;
+
+    assert_eq!(response.status().as_u16(), 200);
+
+    let response = app.post_verify_token(&json!({ "token": cookie.value() })).await;
+
+    assert_eq!(response.status().as_u16(), 401);
+
+    let response = app
+        .post_login(&json!({
+            "email": email,
+            "password": "abcd1234",
+        }))
+        .await;
+
+    assert_eq!(response.status().as_u16(), 401);
+
+    let response = app
+        .post_login(&json!({
+            "email": email,
+            "password": "efgh5678",
+        }))
+        .await;
+
+    assert_eq!(response.status().as_u16(), 200);
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_return_400_if_invalid_input() {
+    let mut app = TestApp::new().await;
+    let response = app.post_password_reset_request(&json!({ "email": "me" })).await;
+
+    assert_eq!(response.status().as_u16(), 400);
+
+    let response = app
+        .post_password_reset_confirm(&json!({
+            "email": get_random_email(),
+            "token": "550e8400-e29b-41d4-a716-446655440000",
+            "newPassword": "abcd123"
+        }))
+        .await;
+
+    assert_eq!(response.status().as_u16(), 400);
+    assert_eq!(
+        response.json::<ErrorResponse>().await.expect("Could not deserialize response body to ErrorResponse").error,
+        "Invalid credentials".to_owned()
+    );
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_return_401_if_token_used_twice() {
+    let mut app = TestApp::new().await;
+    let email = get_random_email();
+    let _ = app
+        .post_signup(&json!({
+            "email": email,
+            "password": "abcd1234",
+            "requires2FA": false
+        }))
+        .await;
+    let _ = app.post_password_reset_request(&json!({ "email": email })).await;
+    let token = app
+        .password_reset_store
+        .get_token(&Email::parse(&SecretBox::new(Box::new(email.clone()))).unwrap())
+        .await
+        .expect("Failed to get token from password reset store");
+    let body = json!({
+        "email": email,
+        "token": token.as_ref().expose_secret(),
+        "newPassword": "efgh5678"
+    });
+    let _ = app.post_password_reset_confirm(&body).await;
+    let response = app.post_password_reset_confirm(&body).await;
+
+    assert_eq!(response.status().as_u16(), 401);
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_return_401_if_incorrect_token() {
+    let mut app = TestApp::new().await;
+    let email = get_random_email();
+    let _ = app
+        .post_signup(&json!({
+            "email": email,
+            "password": "abcd1234",
+            "requires2FA": false
+        }))
+        .await;
+    let _ = app.post_password_reset_request(&json!({ "email": email })).await;
+    let response = app
+        .post_password_reset_confirm(&json!({
+            "email": email,
+            "token": "550e8400-e29b-41d4-a716-446655440000",
+            "newPassword": "efgh5678"
+        }))
+        .await;
+
+    assert_eq!(response.status().as_u16(), 401);
+    assert_eq!(
+        response.json::<ErrorResponse>().await.expect("Could not deserialize response body to ErrorResponse").error,
+        "Invalid token".to_owned()
+    );
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_return_422_if_malformed_input() {
+    let mut app = TestApp::new().await;
+    let response = app
+        .post_password_reset_confirm(&json!({
+            "email": get_random_email(),
+            "newPassword": "efgh5678"
+        }))
+        .await;
+
+    assert_eq!(response.status().as_u16(), 422);
+
+    app.clean_up().await;
+}
@@ -0,0 +1,96 @@
+use {
+    crate::helpers::{TestApp, get_random_email},
+    auth_service::{
+        ErrorResponse,
+        domain::{data_stores::RateLimitStore, rate_limit::RateLimitPolicy},
+        services::InMemoryRateLimitStore,
+    },
+    reqwest::Response,
+    serde_json::json,
+};
+
+fn header(response: &Response, name: &str) -> u64 {
+    response.headers().get(name).unwrap_or_else(|| panic!("No {name} header found")).to_str().unwrap().parse().unwrap()
+}
+
+#[tokio::test]
+async fn should_add_rate_limit_headers() {
+    let mut app = TestApp::new().await;
+    let response = app.get_root().await;
+
+    assert_eq!(response.status().as_u16(), 200);
+    assert_eq!(header(&response, "RateLimit-Limit"), 100);
+    assert_eq!(header(&response, "RateLimit-Remaining"), 99);
+    assert!(response.headers().get("Retry-After").is_none());
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_not_limit_token_verification() {
+    let mut app = TestApp::new().await;
+    let response = app.post_verify_token(&json!({ "token": "abcd" })).await;
+
+    assert!(response.headers().get("RateLimit-Limit").is_none());
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_return_429_with_retry_after_once_email_bucket_is_empty() {
+    let mut app = TestApp::new().await;
+    let body = json!({ "email": get_random_email() });
+
+    for remaining in (0..3).rev() {
+        let response = app.post_password_reset_request(&body).await;
+
+        assert_eq!(response.status().as_u16(), 200);
+        assert_eq!(header(&response, "RateLimit-Remaining"), remaining);
+    }
+
+    let response = app.post_password_reset_request(&body).await;
+
+    assert_eq!(response.status().as_u16(), 429);
+    assert!(header(&response, "Retry-After") > 0);
+    assert_eq!(
+        response.json::<ErrorResponse>().await.expect("Could not deserialize response body to ErrorResponse").error,
+        "Too many requests".to_owned()
+    );
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_share_email_limits_between_instances() {
+    let mut app = TestApp::new().await;
+    let mut other_app = TestApp::new().await;
+    let body = json!({ "email": get_random_email() });
+
+    for _ in 0..3 {
+        let response = app.post_password_reset_request(&body).await;
+
+        assert_eq!(response.status().as_u16(), 200);
+    }
+
+    let response = other_app.post_password_reset_request(&body).await;
+
+    assert_eq!(response.status().as_u16(), 429);
+
+    app.clean_up().await;
+    other_app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_limit_in_process() {
+    let store = InMemoryRateLimitStore::default();
+    let policy = RateLimitPolicy { capacity: 2, refill_interval_ms: 60_000 };
+
+    assert!(store.acquire("key", &policy).await.unwrap().allowed);
+    assert!(store.acquire("key", &policy).await.unwrap().allowed);
+
+    let decision = store.acquire("key", &policy).await.unwrap();
+
+    assert!(!decision.allowed);
+    assert_eq!(decision.retry_after, 60);
+    assert!(store.acquire("other key", &policy).await.unwrap().allowed);
+}
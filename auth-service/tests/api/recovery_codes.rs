@@ -0,0 +1,161 @@
+use {
+    crate::helpers::{TestApp, get_random_email},
+    auth_service::{
+        ErrorResponse,
+        routes::{RecoveryCodesResponse, TwoFactorAuthResponse, TwoFactorMethodResponse},
+    },
+    serde_json::json,
+};
+
+async fn signup_and_login(app: &TestApp, email: &str) {
+    let _ = app
+        .post_signup(&json!({
+            "email": email,
+            "password": "abcd1234",
+            "requires2FA": false
+        }))
+        .await;
+
+    app.verify_email(email).await;
+
+    let _ = app
+        .post_login(&json!({
+            "email": email,
+            "password": "abcd1234",
+        }))
+        .await;
+}
+
+async fn login_with_recovery_code(app: &TestApp, email: &str, recovery_code: &str) -> u16 {
+    let response = app
+        .post_login(&json!({
+            "email": email,
+            "password": "abcd1234",
+        }))
+        .await;
+
+    assert_eq!(response.status().as_u16(), 206);
+
+    let body = response.json::<TwoFactorAuthResponse>().await.expect("Could not deserialize response body");
+    let response = app
+        .post_verify_2fa(&json!({
+            "email": email,
+            "loginAttemptId": body.login_attempt_id,
+            "2FACode": recovery_code
+        }))
+        .await;
+
+    response.status().as_u16()
+}
+
+#[tokio::test]
+async fn should_issue_recovery_codes_when_2fa_enabled() {
+    let mut app = TestApp::new().await;
+    let email = get_random_email();
+
+    signup_and_login(&app, &email).await;
+
+    let response = app.post_two_factor_method(&json!({ "method": "email" })).await;
+
+    assert_eq!(response.status().as_u16(), 200);
+
+    let recovery_codes = response
+        .json::<TwoFactorMethodResponse>()
+        .await
+        .expect("Could not deserialize response body")
+        .recovery_codes
+        .expect("No recovery codes issued");
+
+    assert_eq!(recovery_codes.len(), 10);
+
+    let response = app.post_two_factor_method(&json!({ "method": "email" })).await;
+
+    assert_eq!(
+        response.json::<TwoFactorMethodResponse>().await.expect("Could not deserialize response body").recovery_codes,
+        None
+    );
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_accept_recovery_code_only_once() {
+    let mut app = TestApp::new().await;
+    let email = get_random_email();
+
+    signup_and_login(&app, &email).await;
+
+    let response = app.post_two_factor_method(&json!({ "method": "email" })).await;
+    let recovery_codes = response
+        .json::<TwoFactorMethodResponse>()
+        .await
+        .expect("Could not deserialize response body")
+        .recovery_codes
+        .expect("No recovery codes issued");
+
+    assert_eq!(login_with_recovery_code(&app, &email, &recovery_codes[0]).await, 200);
+    assert_eq!(login_with_recovery_code(&app, &email, &recovery_codes[0]).await, 401);
+    assert_eq!(login_with_recovery_code(&app, &email, &recovery_codes[1].to_uppercase()).await, 200);
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_invalidate_old_codes_on_regeneration() {
+    let mut app = TestApp::new().await;
+    let email = get_random_email();
+
+    signup_and_login(&app, &email).await;
+
+    let response = app.post_two_factor_method(&json!({ "method": "email" })).await;
+    let old_codes = response
+        .json::<TwoFactorMethodResponse>()
+        .await
+        .expect("Could not deserialize response body")
+        .recovery_codes
+        .expect("No recovery codes issued");
+    let response = app.post_recovery_codes().await;
+
+    assert_eq!(response.status().as_u16(), 200);
+
+    let new_codes =
+        response.json::<RecoveryCodesResponse>().await.expect("Could not deserialize response body").recovery_codes;
+
+    assert_eq!(new_codes.len(), 10);
+    assert_eq!(login_with_recovery_code(&app, &email, &old_codes[0]).await, 401);
+    assert_eq!(login_with_recovery_code(&app, &email, &new_codes[0]).await, 200);
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_return_400_if_2fa_disabled() {
+    let mut app = TestApp::new().await;
+    let email = get_random_email();
+
+    signup_and_login(&app, &email).await;
+
+    let response = app.post_recovery_codes().await;
+
+    assert_eq!(response.status().as_u16(), 400);
+    assert_eq!(
+        response.json::<ErrorResponse>().await.expect("Could not deserialize response body to ErrorResponse").error,
+        "Invalid credentials".to_owned()
+    );
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_return_400_if_jwt_cookie_missing() {
+    let mut app = TestApp::new().await;
+    let response = app.post_recovery_codes().await;
+
+    assert_eq!(response.status().as_u16(), 400);
+    assert_eq!(
+        response.json::<ErrorResponse>().await.expect("Could not deserialize response body to ErrorResponse").error,
+        "Missing token".to_owned()
+    );
+
+    app.clean_up().await;
+}
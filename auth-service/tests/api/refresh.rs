@@ -0,0 +1,142 @@
+use {
+    crate::helpers::{TestApp, get_random_email},
+    auth_service::{
+        ErrorResponse,
+        utils::constants::{JWT_COOKIE_NAME, REFRESH_COOKIE_NAME},
+    },
+    reqwest::Url,
+    serde_json::json,
+};
+
+async fn signup_and_login(app: &TestApp) -> String {
+    let email = get_random_email();
+    let _ = app
+        .post_signup(&json!({
+            "email": email,
+            "password": "abcd1234",
+            "requires2FA": false
+        }))
+        .await;
+
+    app.verify_email(&email).await;
+
+    let response = app
+        .post_login(&json!({
+            "email": email,
+            "password": "abcd1234",
+        }))
+        .await;
+    let cookie =
+        response.cookies().find(|cookie| cookie.name() == REFRESH_COOKIE_NAME).expect("No refresh cookie found");
+
+    cookie.value().to_owned()
+}
+
+fn set_refresh_cookie(app: &TestApp, token: &str) {
+    app.cookie_jar.add_cookie_str(
+        &format!("{REFRESH_COOKIE_NAME}={token}; HttpOnly; SameSite=Lax; Path=/"),
+        &Url::parse("http://127.0.0.1").expect("Failed to parse URL"),
+    );
+}
+
+#[tokio::test]
+async fn should_return_200_and_rotate_refresh_token() {
+    let mut app = TestApp::new().await;
+    let refresh_token = signup_and_login(&app).await;
+    let response = app.post_refresh().await;
+
+    assert_eq!(response.status().as_u16(), 200);
+
+    let auth_cookie = response.cookies().find(|cookie| cookie.name() == JWT_COOKIE_NAME).expect("No auth cookie found");
+
+    assert!(!auth_cookie.value().is_empty());
+
+    let refresh_cookie =
+        response.cookies().find(|cookie| cookie.name() == REFRESH_COOKIE_NAME).expect("No refresh cookie found");
+
+    assert_ne!(refresh_cookie.value(), refresh_token);
+
+    let response = app.post_refresh().await;
+
+    assert_eq!(response.status().as_u16(), 200);
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_revoke_token_family_if_refresh_token_reused() {
+    let mut app = TestApp::new().await;
+    let refresh_token = signup_and_login(&app).await;
+    let response = app.post_refresh().await;
+
+    assert_eq!(response.status().as_u16(), 200);
+
+    let rotated_token = response
+        .cookies()
+        .find(|cookie| cookie.name() == REFRESH_COOKIE_NAME)
+        .expect("No refresh cookie found")
+        .value()
+        .to_owned();
+
+    set_refresh_cookie(&app, &refresh_token);
+
+    let response = app.post_refresh().await;
+
+    assert_eq!(response.status().as_u16(), 401);
+
+    set_refresh_cookie(&app, &rotated_token);
+
+    let response = app.post_refresh().await;
+
+    assert_eq!(response.status().as_u16(), 401);
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_return_401_after_logout() {
+    let mut app = TestApp::new().await;
+    let refresh_token = signup_and_login(&app).await;
+    let response = app.post_logout().await;
+
+    assert_eq!(response.status().as_u16(), 200);
+
+    set_refresh_cookie(&app, &refresh_token);
+
+    let response = app.post_refresh().await;
+
+    assert_eq!(response.status().as_u16(), 401);
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_return_400_if_refresh_cookie_missing() {
+    let mut app = TestApp::new().await;
+    let response = app.post_refresh().await;
+
+    assert_eq!(response.status().as_u16(), 400);
+    assert_eq!(
+        response.json::<ErrorResponse>().await.expect("Could not deserialize response body to ErrorResponse").error,
+        "Missing token".to_owned()
+    );
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_return_401_if_invalid_refresh_token() {
+    let mut app = TestApp::new().await;
+
+    set_refresh_cookie(&app, "550e8400-e29b-41d4-a716-446655440000");
+
+    let response = app.post_refresh().await;
+
+    assert_eq!(response.status().as_u16(), 401);
+    assert_eq!(
+        response.json::<ErrorResponse>().await.expect("Could not deserialize response body to ErrorResponse").error,
+        "Invalid token".to_owned()
+    );
+
+    app.clean_up().await;
+}
@@ -0,0 +1,174 @@
+use {
+    crate::helpers::{TestApp, get_random_email},
+    auth_service::{
+        ErrorResponse,
+        domain::role::{ADMIN_ROLE, DEFAULT_ROLE, Permission, RolesRead},
+        routes::RolesResponse,
+        utils::{auth::validate_token, constants::JWT_COOKIE_NAME},
+    },
+    reqwest::Response,
+    secrecy::SecretBox,
+    serde_json::json,
+    uuid::Uuid,
+};
+
+/// Signs a new user up and in, returning their user id.
+async fn login(app: &TestApp) -> Uuid {
+    let email = get_random_email();
+    let response = app
+        .post_signup(&json!({
+            "email": email,
+            "password": "abcd1234",
+            "requires2FA": false
+        }))
+        .await;
+
+    assert_eq!(response.status().as_u16(), 201);
+
+    app.verify_email(&email).await;
+
+    let response = app.post_login(&json!({ "email": email, "password": "abcd1234" })).await;
+
+    assert_eq!(response.status().as_u16(), 200);
+
+    app.get_user_id(&email).await
+}
+
+/// Refreshes the session and returns the roles carried by the new auth token.
+async fn refresh(app: &TestApp) -> Vec<String> {
+    let response = app.post_refresh().await;
+
+    assert_eq!(response.status().as_u16(), 200);
+
+    let cookie = response.cookies().find(|cookie| cookie.name() == JWT_COOKIE_NAME).expect("No auth cookie found");
+    let claims =
+        validate_token(None, &SecretBox::new(Box::new(cookie.value().to_owned()))).await.expect("Invalid auth token");
+
+    claims.roles
+}
+
+async fn error_message(response: Response) -> String {
+    response.json::<ErrorResponse>().await.expect("Could not deserialize response body to ErrorResponse").error
+}
+
+#[tokio::test]
+async fn should_return_403_if_user_lacks_permission() {
+    let mut app = TestApp::new().await;
+    let user_id = login(&app).await;
+    let response = app.get_roles(None).await;
+
+    assert_eq!(response.status().as_u16(), 403);
+    assert_eq!(error_message(response).await, "Forbidden");
+
+    let response = app.post_assign_role(&json!({ "userId": user_id, "role": ADMIN_ROLE }), None).await;
+
+    assert_eq!(response.status().as_u16(), 403);
+    assert!(app.role_store.get_user_roles(&user_id).await.unwrap().is_empty());
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_return_401_if_incorrect_admin_api_key() {
+    let mut app = TestApp::new().await;
+    let response = app.get_roles(Some("not-the-admin-key")).await;
+
+    assert_eq!(response.status().as_u16(), 401);
+
+    let response = app.get_roles(None).await;
+
+    assert_eq!(response.status().as_u16(), 400);
+    assert_eq!(error_message(response).await, "Missing token");
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_list_roles_with_admin_api_key() {
+    let mut app = TestApp::new().await;
+    let response = app.get_roles(Some(&app.admin_api_key())).await;
+
+    assert_eq!(response.status().as_u16(), 200);
+
+    let roles = response.json::<RolesResponse>().await.expect("Could not deserialize response body to RolesResponse");
+    let admin = roles.roles.iter().find(|role| role.name == ADMIN_ROLE).expect("No admin role found");
+
+    assert!(admin.permissions.iter().any(|permission| permission == RolesRead::NAME));
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_grant_permissions_of_assigned_role_after_refresh() {
+    let mut app = TestApp::new().await;
+    let user_id = login(&app).await;
+    let response =
+        app.post_assign_role(&json!({ "userId": user_id, "role": ADMIN_ROLE }), Some(&app.admin_api_key())).await;
+
+    assert_eq!(response.status().as_u16(), 200);
+
+    // assigning again changes nothing
+    let response =
+        app.post_assign_role(&json!({ "userId": user_id, "role": ADMIN_ROLE }), Some(&app.admin_api_key())).await;
+
+    assert_eq!(response.status().as_u16(), 200);
+
+    // the current token was issued before the assignment
+    assert_eq!(app.get_roles(None).await.status().as_u16(), 403);
+    assert_eq!(refresh(&app).await, vec![DEFAULT_ROLE.to_owned(), ADMIN_ROLE.to_owned()]);
+    assert_eq!(app.get_roles(None).await.status().as_u16(), 200);
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_return_404_if_role_or_user_not_found() {
+    let mut app = TestApp::new().await;
+    let user_id = login(&app).await;
+    let response =
+        app.post_assign_role(&json!({ "userId": user_id, "role": "superuser" }), Some(&app.admin_api_key())).await;
+
+    assert_eq!(response.status().as_u16(), 404);
+    assert_eq!(error_message(response).await, "Role not found");
+
+    let response = app
+        .post_assign_role(&json!({ "userId": Uuid::new_v4(), "role": ADMIN_ROLE }), Some(&app.admin_api_key()))
+        .await;
+
+    assert_eq!(response.status().as_u16(), 404);
+    assert_eq!(error_message(response).await, "User not found");
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_return_422_if_malformed_input() {
+    let mut app = TestApp::new().await;
+    let response = app.post_assign_role(&json!({ "role": ADMIN_ROLE }), Some(&app.admin_api_key())).await;
+
+    assert_eq!(response.status().as_u16(), 422);
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_withdraw_permissions_when_role_revoked() {
+    let mut app = TestApp::new().await;
+    let user_id = login(&app).await;
+    let _ = app.post_assign_role(&json!({ "userId": user_id, "role": ADMIN_ROLE }), Some(&app.admin_api_key())).await;
+    let _ = refresh(&app).await;
+
+    assert_eq!(app.get_roles(None).await.status().as_u16(), 200);
+
+    let response =
+        app.post_revoke_role(&json!({ "userId": user_id, "role": ADMIN_ROLE }), Some(&app.admin_api_key())).await;
+
+    assert_eq!(response.status().as_u16(), 200);
+
+    // tokens carrying the revoked role stop working right away
+    assert_eq!(app.get_roles(None).await.status().as_u16(), 401);
+    assert_eq!(refresh(&app).await, vec![DEFAULT_ROLE.to_owned()]);
+    assert_eq!(app.get_roles(None).await.status().as_u16(), 403);
+
+    app.clean_up().await;
+}
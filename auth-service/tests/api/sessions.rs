@@ -0,0 +1,188 @@
+use {
+    crate::helpers::{TestApp, get_random_email},
+    auth_service::{
+        ErrorResponse,
+        routes::SessionsResponse,
+        utils::constants::{JWT_COOKIE_NAME, REFRESH_COOKIE_NAME},
+    },
+    reqwest::Url,
+    serde_json::json,
+    uuid::Uuid,
+};
+
+/// Returns the auth and refresh tokens of the new session.
+async fn login(app: &TestApp, email: &str) -> (String, String) {
+    let response = app.post_login(&json!({ "email": email, "password": "abcd1234" })).await;
+
+    assert_eq!(response.status().as_u16(), 200);
+
+    let auth_token = response.cookies().find(|cookie| cookie.name() == JWT_COOKIE_NAME).expect("No auth cookie found");
+    let refresh_token =
+        response.cookies().find(|cookie| cookie.name() == REFRESH_COOKIE_NAME).expect("No refresh cookie found");
+
+    (auth_token.value().to_owned(), refresh_token.value().to_owned())
+}
+
+async fn signup(app: &TestApp) -> String {
+    let email = get_random_email();
+    let _ = app
+        .post_signup(&json!({
+            "email": email,
+            "password": "abcd1234",
+            "requires2FA": false
+        }))
+        .await;
+
+    app.verify_email(&email).await;
+
+    email
+}
+
+async fn get_sessions(app: &TestApp) -> SessionsResponse {
+    let response = app.get_sessions().await;
+
+    assert_eq!(response.status().as_u16(), 200);
+
+    response.json::<SessionsResponse>().await.expect("Could not deserialize response body to SessionsResponse")
+}
+
+fn set_cookie(app: &TestApp, name: &str, token: &str) {
+    app.cookie_jar.add_cookie_str(
+        &format!("{name}={token}; HttpOnly; SameSite=Lax; Path=/"),
+        &Url::parse("http://127.0.0.1").expect("Failed to parse URL"),
+    );
+}
+
+#[tokio::test]
+async fn should_list_sessions_and_mark_current() {
+    let mut app = TestApp::new().await;
+    let email = signup(&app).await;
+    let _ = login(&app, &email).await;
+    let _ = login(&app, &email).await;
+    let sessions = get_sessions(&app).await.sessions;
+
+    assert_eq!(sessions.len(), 2);
+    // both logins can land in the same second, so the listing order says nothing about which one is current
+    assert_eq!(sessions.iter().filter(|session| session.current).count(), 1);
+    assert!(sessions.iter().all(|session| session.ip == app.ip));
+
+    let current_session_id = sessions.iter().find(|session| session.current).unwrap().session_id;
+
+    // refreshing continues the session instead of starting a new one
+    let response = app.post_refresh().await;
+
+    assert_eq!(response.status().as_u16(), 200);
+
+    let sessions = get_sessions(&app).await.sessions;
+
+    assert_eq!(sessions.len(), 2);
+    assert_eq!(sessions.iter().find(|session| session.current).unwrap().session_id, current_session_id);
+
+    let response = app.post_logout().await;
+
+    assert_eq!(response.status().as_u16(), 200);
+
+    let _ = login(&app, &email).await;
+
+    assert_eq!(get_sessions(&app).await.sessions.len(), 2);
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_revoke_single_session() {
+    let mut app = TestApp::new().await;
+    let email = signup(&app).await;
+    let (first_token, first_refresh_token) = login(&app, &email).await;
+    let (second_token, _) = login(&app, &email).await;
+    let first_session = get_sessions(&app).await.sessions.into_iter().find(|session| !session.current).unwrap();
+    let response = app.post_revoke_session(&json!({ "sessionId": first_session.session_id })).await;
+
+    assert_eq!(response.status().as_u16(), 200);
+
+    let response = app.post_verify_token(&json!({ "token": first_token })).await;
+
+    assert_eq!(response.status().as_u16(), 401);
+
+    let response = app.post_verify_token(&json!({ "token": second_token })).await;
+
+    assert_eq!(response.status().as_u16(), 200);
+
+    let sessions = get_sessions(&app).await.sessions;
+
+    assert_eq!(sessions.len(), 1);
+    assert!(sessions[0].current);
+
+    set_cookie(&app, REFRESH_COOKIE_NAME, &first_refresh_token);
+
+    let response = app.post_refresh().await;
+
+    assert_eq!(response.status().as_u16(), 401);
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_revoke_other_sessions() {
+    let mut app = TestApp::new().await;
+    let email = signup(&app).await;
+    let (first_token, first_refresh_token) = login(&app, &email).await;
+    let _ = login(&app, &email).await;
+    let (current_token, current_refresh_token) = login(&app, &email).await;
+    let response = app.post_revoke_other_sessions().await;
+
+    assert_eq!(response.status().as_u16(), 200);
+
+    let response = app.post_verify_token(&json!({ "token": first_token })).await;
+
+    assert_eq!(response.status().as_u16(), 401);
+
+    let response = app.post_verify_token(&json!({ "token": current_token })).await;
+
+    assert_eq!(response.status().as_u16(), 200);
+
+    let sessions = get_sessions(&app).await.sessions;
+
+    assert_eq!(sessions.len(), 1);
+    assert!(sessions[0].current);
+
+    set_cookie(&app, REFRESH_COOKIE_NAME, &first_refresh_token);
+
+    let response = app.post_refresh().await;
+
+    assert_eq!(response.status().as_u16(), 401);
+
+    set_cookie(&app, REFRESH_COOKIE_NAME, &current_refresh_token);
+
+    let response = app.post_refresh().await;
+
+    assert_eq!(response.status().as_u16(), 200);
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_return_404_if_session_belongs_to_another_user() {
+    let mut app = TestApp::new().await;
+    let other_email = signup(&app).await;
+    let (other_token, _) = login(&app, &other_email).await;
+    let other_session = get_sessions(&app).await.sessions.remove(0);
+    let email = signup(&app).await;
+    let _ = login(&app, &email).await;
+
+    for session_id in [other_session.session_id, Uuid::new_v4()] {
+        let response = app.post_revoke_session(&json!({ "sessionId": session_id })).await;
+
+        assert_eq!(response.status().as_u16(), 404);
+        assert_eq!(
+            response.json::<ErrorResponse>().await.expect("Could not deserialize response body to ErrorResponse").error,
+            "Session not found".to_owned()
+        );
+    }
+
+    let response = app.post_verify_token(&json!({ "token": other_token })).await;
+
+    assert_eq!(response.status().as_u16(), 200);
+
+    app.clean_up().await;
+}
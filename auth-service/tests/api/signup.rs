@@ -83,6 +83,30 @@ async fn should_return_409_if_email_already_exists() {
     app.clean_up().await;
 }
 
+#[tokio::test]
+async fn should_return_409_if_email_exists_with_different_case() {
+    let mut app = TestApp::new().await;
+    let email = get_random_email();
+    let _ = app
+        .post_signup(&json!({
+            "email": email,
+            "password": "abcd1234",
+            "requires2FA": false
+        }))
+        .await;
+    let response = app
+        .post_signup(&json!({
+            "email": email.to_uppercase(),
+            "password": "abcd1234",
+            "requires2FA": false
+        }))
+        .await;
+
+    assert_eq!(response.status().as_u16(), 409);
+
+    app.clean_up().await;
+}
+
 #[tokio::test]
 async fn should_return_422_if_malformed_input() {
     let mut app = TestApp::new().await;
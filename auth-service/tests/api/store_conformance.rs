@@ -0,0 +1,1113 @@
+//! Every store trait is checked against its in-memory implementation and the Postgres or Redis one the service runs
+//! on, so both behave the same for the routes.
+
+use {
+    crate::helpers::{configure_postgresql, configure_redis, delete_database, get_random_email},
+    auth_service::{
+        domain::{
+            audit::{AuditEvent, AuditEventFilter, AuditEventType},
+            data_stores::{
+                AuditLog, BannedTokenStore, EmailChangeStore, EmailChangeStoreError, EmailChangeToken,
+                EmailVerificationStore, EmailVerificationStoreError, EmailVerificationToken, FailedAttempts,
+                LoginAttemptId, LoginThrottleStore, MagicLinkStore, MagicLinkStoreError, MagicLinkToken,
+                PasskeyCeremonyStore, PasskeyCeremonyStoreError, PasskeyStore, PasskeyStoreError, PasswordResetStore,
+                PasswordResetStoreError, PasswordResetToken, PendingEmailChange, RateLimitStore, RecoveryCode,
+                RecoveryCodeStore, RecoveryCodeStoreError, RefreshToken, RefreshTokenStore, RefreshTokenStoreError,
+                RoleStore, RoleStoreError, Session, SessionStore, SessionStoreError, SigningKeyStore, ThrottleKey,
+                TotpSecretStore, TotpSecretStoreError, TwoFactorCode, TwoFactorStore, TwoFactorStoreError, UserStore,
+                UserStoreError,
+            },
+            email::Email,
+            password::Password,
+            rate_limit::RateLimitPolicy,
+            role::{ADMIN_ROLE, AuditRead, Permission, RolesWrite, UsersRead},
+            totp::TotpSecret,
+            user::{AccountStatus, TwoFactorMethod, User},
+        },
+        get_webauthn,
+        services::{
+            HashmapEmailChangeStore, HashmapEmailVerificationStore, HashmapLoginThrottleStore, HashmapMagicLinkStore,
+            HashmapPasskeyCeremonyStore, HashmapPasskeyStore, HashmapPasswordResetStore, HashmapRecoveryCodeStore,
+            HashmapRefreshTokenStore, HashmapRoleStore, HashmapSessionStore, HashmapSigningKeyStore,
+            HashmapTotpSecretStore, HashmapTwoFactorStore, HashmapUserStore, HashsetBannedTokenStore, InMemoryAuditLog,
+            InMemoryRateLimitStore, PostgresAuditLog, PostgresPasskeyStore, PostgresRecoveryCodeStore,
+            PostgresRefreshTokenStore, PostgresRoleStore, PostgresSigningKeyStore, PostgresTotpSecretStore,
+            PostgresUserStore, RedisBannedTokenStore, RedisEmailChangeStore, RedisEmailVerificationStore,
+            RedisLoginThrottleStore, RedisMagicLinkStore, RedisPasskeyCeremonyStore, RedisPasswordResetStore,
+            RedisRateLimitStore, RedisRefreshTokenStore, RedisSessionStore, RedisTwoFactorStore,
+        },
+        utils::{
+            auth::REFRESH_TOKEN_TTL_SECONDS,
+            constants::{TOTP_ENCRYPTION_KEY, WEBAUTHN_RP_ID, WEBAUTHN_RP_ORIGIN},
+            keys::{JwtKey, SigningKey},
+        },
+    },
+    chrono::Utc,
+    reqwest::Url,
+    secrecy::{ExposeSecret, SecretBox},
+    sqlx::{query, query_scalar},
+    std::{
+        net::{IpAddr, Ipv4Addr, Ipv6Addr},
+        sync::Arc,
+        time::Duration,
+    },
+    tokio::{join, time::sleep},
+    uuid::Uuid,
+    webauthn_authenticator_rs::{WebauthnAuthenticator, softpasskey::SoftPasskey},
+    webauthn_rs::{Webauthn, prelude::Passkey},
+};
+
+fn parse_email(email: &str) -> Email {
+    Email::parse(&SecretBox::new(Box::new(email.to_owned()))).expect("Failed to parse email")
+}
+
+fn parse_password(password: &str) -> Password {
+    Password::parse(&SecretBox::new(Box::new(password.to_owned()))).expect("Failed to parse password")
+}
+
+async fn add_user(store: &dyn UserStore) -> Uuid {
+    let email = parse_email(&get_random_email());
+
+    store.add_user(User::new(&email, &parse_password("abcd1234"), TwoFactorMethod::None)).await.unwrap();
+
+    store.get_user(&email).await.unwrap().user_id
+}
+
+fn webauthn() -> Webauthn {
+    get_webauthn(&WEBAUTHN_RP_ID, &Url::parse(&WEBAUTHN_RP_ORIGIN).expect("Failed to parse URL"))
+        .expect("Failed to configure WebAuthn")
+}
+
+/// Registers a new soft passkey for `user_id`.
+fn new_passkey(webauthn: &Webauthn, user_id: Uuid) -> Passkey {
+    let mut authenticator = WebauthnAuthenticator::new(SoftPasskey::new(true));
+    let (challenge, registration) =
+        webauthn.start_passkey_registration(user_id, "user", "user", None).expect("Failed to start registration");
+    let credential = authenticator
+        .do_registration(Url::parse(&WEBAUTHN_RP_ORIGIN).expect("Failed to parse URL"), challenge)
+        .expect("Failed to register passkey");
+
+    webauthn.finish_passkey_registration(&credential, &registration).expect("Failed to finish registration")
+}
+
+async fn check_user_store(store: &dyn UserStore) {
+    let email = get_random_email();
+    let user = User::new(&parse_email(&email), &parse_password("abcd1234"), TwoFactorMethod::None);
+
+    store.add_user(user.clone()).await.unwrap();
+
+    let upper_case_email = parse_email(&email.to_uppercase());
+    let row = store.get_user(&upper_case_email).await.unwrap();
+
+    assert_eq!(row.email, email);
+    assert!(!row.email_verified);
+    assert_eq!(row.current_status(), AccountStatus::PendingVerification);
+    assert_eq!(store.get_user_by_id(&row.user_id).await.unwrap(), row);
+    assert_eq!(
+        store.add_user(User::new(&upper_case_email, &parse_password("abcd1234"), TwoFactorMethod::None)).await,
+        Err(UserStoreError::UserAlreadyExists)
+    );
+    assert_eq!(store.get_user(&parse_email(&get_random_email())).await, Err(UserStoreError::UserNotFound));
+    assert_eq!(store.get_user_by_id(&Uuid::new_v4()).await, Err(UserStoreError::UserNotFound));
+    assert!(store.validate_user(&user.email, &user.password).await.is_ok());
+    assert!(store.validate_user(&user.email, &parse_password("wrong password")).await.is_err());
+
+    store.update_password(&user.email, parse_password("new password")).await.unwrap();
+    store.verify_email(&user.email).await.unwrap();
+    store.update_two_factor_method(&user.email, TwoFactorMethod::Totp).await.unwrap();
+
+    let row = store.get_user_by_id(&row.user_id).await.unwrap();
+
+    assert!(row.email_verified);
+    assert_eq!(row.current_status(), AccountStatus::Active);
+    assert_eq!(row.two_factor_method, TwoFactorMethod::Totp);
+    assert!(store.validate_user(&user.email, &parse_password("new password")).await.is_ok());
+
+    let unknown_email = parse_email(&get_random_email());
+
+    assert_eq!(store.verify_email(&unknown_email).await, Err(UserStoreError::UserNotFound));
+    assert_eq!(
+        store.update_password(&unknown_email, parse_password("abcd1234")).await,
+        Err(UserStoreError::UserNotFound)
+    );
+
+    // email changes keep addresses unique and count as verified
+    let other_user_id = add_user(store).await;
+    let new_email = parse_email(&get_random_email());
+
+    assert_eq!(store.update_email(&other_user_id, &user.email).await, Err(UserStoreError::UserAlreadyExists));
+
+    store.update_email(&other_user_id, &new_email).await.unwrap();
+
+    let other_row = store.get_user(&new_email).await.unwrap();
+
+    assert_eq!(other_row.user_id, other_user_id);
+    assert!(other_row.email_verified);
+    assert_eq!(store.update_email(&Uuid::new_v4(), &unknown_email).await, Err(UserStoreError::UserNotFound));
+
+    // admins search addresses ignoring case, with wildcards matched literally
+    let page = store.list_users(Some(&email[..8].to_uppercase()), 0, 10).await.unwrap();
+
+    assert_eq!(page.total, 1);
+    assert_eq!(page.users[0].user_id, row.user_id);
+    assert_eq!(store.list_users(Some("%"), 0, 10).await.unwrap().total, 0);
+
+    let page = store.list_users(None, 1, 1).await.unwrap();
+
+    assert_eq!(page.total, 2);
+    assert_eq!(page.users.len(), 1);
+
+    let suspended_until = Utc::now().timestamp() + 3600;
+
+    store
+        .update_status(&row.user_id, AccountStatus::Suspended, Some("spam".to_owned()), Some(suspended_until))
+        .await
+        .unwrap();
+    store.require_password_reset(&row.user_id).await.unwrap();
+
+    let admin_row = store.get_user_by_id(&row.user_id).await.unwrap();
+
+    assert_eq!(admin_row.status, AccountStatus::Suspended);
+    assert_eq!(admin_row.status_reason.as_deref(), Some("spam"));
+    assert_eq!(admin_row.suspended_until, Some(suspended_until));
+    assert_eq!(admin_row.current_status(), AccountStatus::Suspended);
+    assert!(admin_row.password_reset_required);
+
+    // suspensions lift by themselves once their end has passed
+    store.update_status(&row.user_id, AccountStatus::Suspended, None, Some(suspended_until - 7200)).await.unwrap();
+
+    assert_eq!(store.get_user_by_id(&row.user_id).await.unwrap().current_status(), AccountStatus::Active);
+
+    store.update_status(&row.user_id, AccountStatus::Active, None, None).await.unwrap();
+    store.update_password(&user.email, parse_password("new password")).await.unwrap();
+
+    let admin_row = store.get_user_by_id(&row.user_id).await.unwrap();
+
+    assert_eq!(
+        (admin_row.status, admin_row.status_reason, admin_row.suspended_until),
+        (AccountStatus::Active, None, None)
+    );
+    assert!(!admin_row.password_reset_required);
+    assert_eq!(
+        store.update_status(&Uuid::new_v4(), AccountStatus::Disabled, None, None).await,
+        Err(UserStoreError::UserNotFound)
+    );
+    assert_eq!(store.require_password_reset(&Uuid::new_v4()).await, Err(UserStoreError::UserNotFound));
+
+    assert_eq!(store.get_token_version(&row.user_id).await.unwrap(), 0);
+    assert_eq!(store.bump_token_version(&row.user_id, 0).await.unwrap(), 1);
+    // a higher version seen in a cache is carried over
+    assert_eq!(store.bump_token_version(&row.user_id, 5).await.unwrap(), 6);
+    assert_eq!(store.bump_token_version(&row.user_id, 2).await.unwrap(), 7);
+    assert_eq!(store.get_token_version(&row.user_id).await.unwrap(), 7);
+    assert_eq!(store.get_token_version(&Uuid::new_v4()).await, Err(UserStoreError::UserNotFound));
+    assert_eq!(store.bump_token_version(&Uuid::new_v4(), 0).await, Err(UserStoreError::UserNotFound));
+
+    // users scheduled for deletion are hidden until purged
+    store.schedule_deletion(&row.user_id, 3600).await.unwrap();
+
+    assert_eq!(store.list_users(None, 0, 10).await.unwrap().total, 1);
+    assert_eq!(
+        store.update_status(&row.user_id, AccountStatus::Disabled, None, None).await,
+        Err(UserStoreError::UserNotFound)
+    );
+    assert_eq!(store.get_user(&user.email).await, Err(UserStoreError::UserNotFound));
+    assert_eq!(store.get_user_by_id(&row.user_id).await, Err(UserStoreError::UserNotFound));
+    assert_eq!(store.schedule_deletion(&row.user_id, 3600).await, Err(UserStoreError::UserNotFound));
+    assert_eq!(
+        store.add_user(User::new(&user.email, &parse_password("abcd1234"), TwoFactorMethod::None)).await,
+        Err(UserStoreError::UserAlreadyExists)
+    );
+    assert!(store.purge_deleted_users().await.unwrap().is_empty());
+
+    // until the grace period ends the deletion can be cancelled
+    assert_eq!(store.get_deleted_user(&upper_case_email).await.unwrap().user_id, row.user_id);
+
+    store.restore_user(&row.user_id).await.unwrap();
+
+    assert_eq!(store.get_user_by_id(&row.user_id).await.unwrap().user_id, row.user_id);
+    assert_eq!(store.get_deleted_user(&user.email).await, Err(UserStoreError::UserNotFound));
+    assert_eq!(store.restore_user(&row.user_id).await, Err(UserStoreError::UserNotFound));
+
+    store.schedule_deletion(&row.user_id, 3600).await.unwrap();
+    store.schedule_deletion(&other_user_id, 0).await.unwrap();
+
+    assert_eq!(store.get_deleted_user(&new_email).await, Err(UserStoreError::UserNotFound));
+    assert_eq!(store.restore_user(&other_user_id).await, Err(UserStoreError::UserNotFound));
+    assert_eq!(store.purge_deleted_users().await.unwrap(), vec![other_user_id]);
+    assert_eq!(store.delete_user(&other_user_id).await, Err(UserStoreError::UserNotFound));
+
+    store.delete_user(&row.user_id).await.unwrap();
+
+    assert_eq!(store.delete_user(&row.user_id).await, Err(UserStoreError::UserNotFound));
+}
+
+async fn check_banned_token_store(store: &dyn BannedTokenStore) {
+    let jti = Uuid::new_v4().to_string();
+    let token = SecretBox::new(Box::new(Uuid::new_v4().to_string()));
+    let now = Utc::now().timestamp();
+
+    assert!(!store.check(&jti, &token).await.unwrap());
+
+    store.register(&jti, now + 60).await.unwrap();
+
+    assert!(store.check(&jti, &token).await.unwrap());
+
+    // bans last until the token expires anyway
+    let expired_jti = Uuid::new_v4().to_string();
+    let expiring_jti = Uuid::new_v4().to_string();
+
+    store.register(&expired_jti, now - 1).await.unwrap();
+    store.register(&expiring_jti, now + 1).await.unwrap();
+
+    assert!(!store.check(&expired_jti, &token).await.unwrap());
+    assert!(store.check(&expiring_jti, &token).await.unwrap());
+
+    sleep(Duration::from_millis(2100)).await;
+
+    assert!(!store.check(&expiring_jti, &token).await.unwrap());
+
+    let user_id = Uuid::new_v4();
+
+    assert_eq!(store.get_token_version(&user_id).await.unwrap(), None);
+
+    store.cache_token_version(&user_id, 2).await.unwrap();
+
+    assert_eq!(store.get_token_version(&user_id).await.unwrap(), Some(2));
+
+    // a stale read never replaces a newer version
+    store.cache_token_version(&user_id, 1).await.unwrap();
+
+    assert_eq!(store.get_token_version(&user_id).await.unwrap(), Some(2));
+
+    store.cache_token_version(&user_id, 3).await.unwrap();
+
+    assert_eq!(store.get_token_version(&user_id).await.unwrap(), Some(3));
+    assert_eq!(store.get_token_version(&Uuid::new_v4()).await.unwrap(), None);
+
+    let session_id = Uuid::new_v4();
+
+    assert!(!store.check_session(&session_id).await.unwrap());
+
+    store.revoke_session(&session_id).await.unwrap();
+
+    assert!(store.check_session(&session_id).await.unwrap());
+    assert!(!store.check_session(&Uuid::new_v4()).await.unwrap());
+}
+
+async fn check_two_factor_store(store: &dyn TwoFactorStore) {
+    let user_id = Uuid::new_v4();
+
+    assert_eq!(store.get_code(&user_id).await.unwrap_err(), TwoFactorStoreError::LoginAttemptIdNotFound);
+
+    store.add_code(user_id, LoginAttemptId::default(), TwoFactorCode::default()).await.unwrap();
+
+    let attempt_id = LoginAttemptId::default();
+    let code = TwoFactorCode::default();
+
+    store.add_code(user_id, attempt_id.clone(), code.clone()).await.unwrap();
+
+    assert_eq!(store.get_code(&user_id).await.unwrap(), (attempt_id, code));
+
+    store.remove_code(&user_id).await.unwrap();
+    store.remove_code(&user_id).await.unwrap();
+
+    assert_eq!(store.get_code(&user_id).await.unwrap_err(), TwoFactorStoreError::LoginAttemptIdNotFound);
+}
+
+async fn check_password_reset_store(store: &dyn PasswordResetStore) {
+    let email = parse_email(&get_random_email());
+    let token = PasswordResetToken::default();
+
+    assert_eq!(store.get_token(&email).await, Err(PasswordResetStoreError::TokenNotFound));
+
+    store.add_token(email.clone(), PasswordResetToken::default()).await.unwrap();
+    store.add_token(email.clone(), token.clone()).await.unwrap();
+
+    assert_eq!(store.get_token(&email).await, Ok(token.clone()));
+
+    store.remove_token(&email).await.unwrap();
+
+    assert_eq!(store.get_token(&email).await, Err(PasswordResetStoreError::TokenNotFound));
+    assert_eq!(store.take_token(&email).await, Err(PasswordResetStoreError::TokenNotFound));
+
+    store.add_token(email.clone(), token.clone()).await.unwrap();
+
+    assert_eq!(store.take_token(&email).await, Ok(token));
+    assert_eq!(store.take_token(&email).await, Err(PasswordResetStoreError::TokenNotFound));
+    assert_eq!(store.get_token(&email).await, Err(PasswordResetStoreError::TokenNotFound));
+}
+
+async fn check_email_verification_store(store: &dyn EmailVerificationStore) {
+    let email = parse_email(&get_random_email());
+    let token = EmailVerificationToken::default();
+
+    assert_eq!(store.get_token(&email).await, Err(EmailVerificationStoreError::TokenNotFound));
+
+    store.add_token(email.clone(), token.clone()).await.unwrap();
+
+    let (stored_token, issued_at) = store.get_token(&email).await.unwrap();
+
+    assert_eq!(stored_token, token);
+    assert!((Utc::now().timestamp() - issued_at).abs() <= 1);
+
+    store.remove_token(&email).await.unwrap();
+
+    assert_eq!(store.get_token(&email).await, Err(EmailVerificationStoreError::TokenNotFound));
+    assert_eq!(store.take_token(&email).await, Err(EmailVerificationStoreError::TokenNotFound));
+
+    store.add_token(email.clone(), token.clone()).await.unwrap();
+
+    assert_eq!(store.take_token(&email).await, Ok(token));
+    assert_eq!(store.take_token(&email).await, Err(EmailVerificationStoreError::TokenNotFound));
+    assert_eq!(store.get_token(&email).await, Err(EmailVerificationStoreError::TokenNotFound));
+}
+
+async fn check_magic_link_store(store: &dyn MagicLinkStore) {
+    let email = parse_email(&get_random_email());
+    let token = MagicLinkToken::parse(&Uuid::new_v4().to_string()).unwrap();
+
+    assert_eq!(store.take_token(&email).await, Err(MagicLinkStoreError::TokenNotFound));
+
+    store.add_token(email.clone(), MagicLinkToken::parse("earlier token").unwrap()).await.unwrap();
+    store.add_token(email.clone(), token.clone()).await.unwrap();
+
+    assert_eq!(store.take_token(&email).await, Ok(token));
+    assert_eq!(store.take_token(&email).await, Err(MagicLinkStoreError::TokenNotFound));
+}
+
+async fn check_email_change_store(store: &dyn EmailChangeStore) {
+    let user_id = Uuid::new_v4();
+    let change = PendingEmailChange {
+        new_email: parse_email(&get_random_email()),
+        confirm_token: EmailChangeToken::default(),
+        cancel_token: EmailChangeToken::default(),
+    };
+
+    assert_eq!(store.get_change(&user_id).await.unwrap_err(), EmailChangeStoreError::ChangeNotFound);
+
+    store.add_change(user_id, change.clone()).await.unwrap();
+
+    let stored_change = store.get_change(&user_id).await.unwrap();
+
+    assert_eq!(stored_change.new_email, change.new_email);
+    assert_eq!(stored_change.confirm_token, change.confirm_token);
+    assert_eq!(stored_change.cancel_token, change.cancel_token);
+
+    store.remove_change(&user_id).await.unwrap();
+
+    assert_eq!(store.get_change(&user_id).await.unwrap_err(), EmailChangeStoreError::ChangeNotFound);
+}
+
+async fn check_refresh_token_store(store: &dyn RefreshTokenStore, user_id: Uuid) {
+    let session_id = Uuid::new_v4();
+    let token = RefreshToken::default();
+    let rotated_token = RefreshToken::default();
+
+    assert_eq!(
+        store.rotate_token(&RefreshToken::default(), RefreshToken::default()).await,
+        Err(RefreshTokenStoreError::TokenNotFound)
+    );
+
+    store.add_token(user_id, session_id, token.clone()).await.unwrap();
+
+    assert_eq!(store.count_sessions(&user_id).await, Ok(1));
+    assert_eq!(store.rotate_token(&token, rotated_token.clone()).await, Ok((user_id, session_id)));
+
+    // presenting a consumed token revokes the whole family
+    assert_eq!(store.rotate_token(&token, RefreshToken::default()).await, Err(RefreshTokenStoreError::TokenReused));
+    assert_eq!(
+        store.rotate_token(&rotated_token, RefreshToken::default()).await,
+        Err(RefreshTokenStoreError::TokenNotFound)
+    );
+    assert_eq!(store.count_sessions(&user_id).await, Ok(0));
+
+    let revoked_token = RefreshToken::default();
+    let other_token = RefreshToken::default();
+    let kept_token = RefreshToken::default();
+    let kept_session_id = Uuid::new_v4();
+
+    store.add_token(user_id, Uuid::new_v4(), revoked_token.clone()).await.unwrap();
+    store.revoke_token(&revoked_token).await.unwrap();
+
+    assert_eq!(
+        store.rotate_token(&revoked_token, RefreshToken::default()).await,
+        Err(RefreshTokenStoreError::TokenNotFound)
+    );
+
+    store.add_token(user_id, Uuid::new_v4(), other_token.clone()).await.unwrap();
+    store.add_token(user_id, kept_session_id, kept_token.clone()).await.unwrap();
+
+    assert_eq!(store.count_sessions(&user_id).await, Ok(2));
+
+    store.revoke_user(&user_id, Some(&kept_token)).await.unwrap();
+
+    assert_eq!(store.count_sessions(&user_id).await, Ok(1));
+    assert_eq!(
+        store.rotate_token(&other_token, RefreshToken::default()).await,
+        Err(RefreshTokenStoreError::TokenNotFound)
+    );
+
+    store.revoke_session(&kept_session_id).await.unwrap();
+
+    assert_eq!(store.count_sessions(&user_id).await, Ok(0));
+    assert_eq!(
+        store.rotate_token(&kept_token, RefreshToken::default()).await,
+        Err(RefreshTokenStoreError::TokenNotFound)
+    );
+}
+
+async fn check_session_store(store: &dyn SessionStore) {
+    let user_id = Uuid::new_v4();
+    let now = Utc::now().timestamp();
+    let session = |created_at, refreshed_at| Session {
+        session_id: Uuid::new_v4(),
+        user_id,
+        jti: Uuid::new_v4().to_string(),
+        created_at,
+        refreshed_at,
+        ip: IpAddr::V4(Ipv4Addr::LOCALHOST),
+        user_agent: Some("conformance".to_owned()),
+    };
+    let first = session(now - 20, now - 20);
+    let second = session(now - 10, now - 10);
+    let stale = session(now - 2 * REFRESH_TOKEN_TTL_SECONDS, now - 2 * REFRESH_TOKEN_TTL_SECONDS);
+
+    assert_eq!(store.get_sessions(&user_id).await, Ok(Vec::new()));
+
+    store.add_session(second.clone()).await.unwrap();
+    store.add_session(first.clone()).await.unwrap();
+    store.add_session(stale).await.unwrap();
+
+    assert_eq!(store.get_sessions(&user_id).await, Ok(vec![first.clone(), second.clone()]));
+
+    let jti = Uuid::new_v4().to_string();
+
+    store.record_token(&user_id, &first.session_id, jti.clone(), now).await.unwrap();
+
+    let sessions = store.get_sessions(&user_id).await.unwrap();
+
+    assert_eq!(sessions[0].jti, jti);
+    assert_eq!(sessions[0].refreshed_at, now);
+    assert_eq!(
+        store.record_token(&user_id, &Uuid::new_v4(), jti.clone(), now).await,
+        Err(SessionStoreError::SessionNotFound)
+    );
+    assert_eq!(store.remove_session(&Uuid::new_v4(), &first.session_id).await, Err(SessionStoreError::SessionNotFound));
+
+    store.remove_session(&user_id, &first.session_id).await.unwrap();
+
+    assert_eq!(store.remove_session(&user_id, &first.session_id).await, Err(SessionStoreError::SessionNotFound));
+
+    let third = session(now, now);
+
+    store.add_session(third.clone()).await.unwrap();
+    store.remove_sessions(&user_id, Some(&third.session_id)).await.unwrap();
+
+    assert_eq!(store.get_sessions(&user_id).await, Ok(vec![third]));
+
+    store.remove_sessions(&user_id, None).await.unwrap();
+
+    assert_eq!(store.get_sessions(&user_id).await, Ok(Vec::new()));
+}
+
+async fn check_login_throttle_store(store: &dyn LoginThrottleStore) {
+    let key = ThrottleKey::Account(parse_email(&get_random_email()));
+    let other_key = ThrottleKey::TwoFactor(Uuid::new_v4());
+
+    assert_eq!(store.get_failures(&key).await, Ok(FailedAttempts::default()));
+
+    store.record_failure(&key).await.unwrap();
+
+    let attempts = store.record_failure(&key).await.unwrap();
+
+    assert_eq!(attempts.count, 2);
+    assert!((Utc::now().timestamp() - attempts.last_failure_at).abs() <= 1);
+    assert_eq!(store.get_failures(&key).await, Ok(attempts));
+    assert_eq!(store.get_failures(&other_key).await, Ok(FailedAttempts::default()));
+
+    // account counters ignore the casing of the address
+    let email = get_random_email();
+    let upper_key = ThrottleKey::Account(parse_email(&email.to_uppercase()));
+
+    store.record_failure(&ThrottleKey::Account(parse_email(&email))).await.unwrap();
+
+    assert_eq!(store.record_failure(&upper_key).await.unwrap().count, 2);
+
+    store.reset(&key).await.unwrap();
+    store.reset(&upper_key).await.unwrap();
+
+    assert_eq!(store.get_failures(&key).await, Ok(FailedAttempts::default()));
+    assert_eq!(store.get_failures(&ThrottleKey::Account(parse_email(&email))).await, Ok(FailedAttempts::default()));
+}
+
+async fn check_rate_limit_store(store: &dyn RateLimitStore) {
+    let key = Uuid::new_v4().to_string();
+    let policy = RateLimitPolicy { capacity: 2, refill_interval_ms: 60_000 };
+
+    assert!(store.acquire(&key, &policy).await.unwrap().allowed);
+    assert!(store.acquire(&key, &policy).await.unwrap().allowed);
+
+    let decision = store.acquire(&key, &policy).await.unwrap();
+
+    assert!(!decision.allowed);
+    assert_eq!(decision.retry_after, 60);
+    assert!(store.acquire(&Uuid::new_v4().to_string(), &policy).await.unwrap().allowed);
+}
+
+async fn check_totp_secret_store(store: &dyn TotpSecretStore, user_id: Uuid) {
+    let email = parse_email(&get_random_email());
+    let secret = TotpSecret::default();
+    let encoded = |secret: TotpSecret| secret.to_base32(&email).unwrap().expose_secret().to_owned();
+
+    assert_eq!(store.get_secret(&user_id).await.unwrap_err(), TotpSecretStoreError::SecretNotFound);
+    assert_eq!(store.confirm_secret(&user_id).await, Err(TotpSecretStoreError::SecretNotFound));
+
+    store.add_secret(&user_id, secret.clone()).await.unwrap();
+
+    assert_eq!(encoded(store.get_pending_secret(&user_id).await.unwrap()), encoded(secret.clone()));
+    assert_eq!(store.get_secret(&user_id).await.unwrap_err(), TotpSecretStoreError::SecretNotFound);
+
+    store.confirm_secret(&user_id).await.unwrap();
+
+    assert_eq!(encoded(store.get_secret(&user_id).await.unwrap()), encoded(secret.clone()));
+    assert_eq!(store.get_pending_secret(&user_id).await.unwrap_err(), TotpSecretStoreError::SecretNotFound);
+    assert_eq!(store.confirm_secret(&user_id).await, Err(TotpSecretStoreError::SecretNotFound));
+
+    // codes are only accepted for time steps later than the last one accepted
+    store.record_time_step(&user_id, 100).await.unwrap();
+
+    assert_eq!(store.record_time_step(&user_id, 100).await, Err(TotpSecretStoreError::CodeReused));
+    assert_eq!(store.record_time_step(&user_id, 99).await, Err(TotpSecretStoreError::CodeReused));
+
+    store.record_time_step(&user_id, 101).await.unwrap();
+
+    // a new pending secret leaves the active one in place until confirmed, which starts its time steps afresh
+    store.add_secret(&user_id, TotpSecret::default()).await.unwrap();
+
+    assert_eq!(encoded(store.get_secret(&user_id).await.unwrap()), encoded(secret));
+
+    store.confirm_secret(&user_id).await.unwrap();
+    store.record_time_step(&user_id, 100).await.unwrap();
+}
+
+async fn check_recovery_code_store(store: &dyn RecoveryCodeStore, user_id: Uuid) {
+    let code = RecoveryCode::default();
+
+    assert_eq!(store.count_codes(&user_id).await, Ok(0));
+
+    store.replace_codes(&user_id, vec![code.clone(), RecoveryCode::default()]).await.unwrap();
+
+    assert_eq!(store.count_codes(&user_id).await, Ok(2));
+    assert_eq!(store.consume_code(&user_id, &RecoveryCode::default()).await, Err(RecoveryCodeStoreError::CodeNotFound));
+    assert_eq!(store.consume_code(&Uuid::new_v4(), &code).await, Err(RecoveryCodeStoreError::CodeNotFound));
+
+    store.consume_code(&user_id, &code).await.unwrap();
+
+    assert_eq!(store.count_codes(&user_id).await, Ok(1));
+    assert_eq!(store.consume_code(&user_id, &code).await, Err(RecoveryCodeStoreError::CodeNotFound));
+
+    store.replace_codes(&user_id, vec![code.clone()]).await.unwrap();
+
+    assert_eq!(store.count_codes(&user_id).await, Ok(1));
+    assert_eq!(store.consume_code(&user_id, &code).await, Ok(()));
+}
+
+async fn check_passkey_store(store: &dyn PasskeyStore, user_id: Uuid, other_user_id: Uuid) {
+    let webauthn = webauthn();
+    let first = new_passkey(&webauthn, user_id);
+    let second = new_passkey(&webauthn, user_id);
+
+    assert!(store.get_passkeys(&user_id).await.unwrap().is_empty());
+
+    store.add_passkey(&user_id, first.clone()).await.unwrap();
+    store.add_passkey(&user_id, second.clone()).await.unwrap();
+
+    let cred_ids =
+        |passkeys: Vec<Passkey>| passkeys.iter().map(|passkey| passkey.cred_id().clone()).collect::<Vec<_>>();
+
+    assert_eq!(
+        cred_ids(store.get_passkeys(&user_id).await.unwrap()),
+        vec![first.cred_id().clone(), second.cred_id().clone()]
+    );
+    assert_eq!(store.add_passkey(&other_user_id, first.clone()).await, Err(PasskeyStoreError::CredentialAlreadyExists));
+    assert!(store.get_passkeys(&other_user_id).await.unwrap().is_empty());
+
+    store.update_passkey(&user_id, &first).await.unwrap();
+
+    assert_eq!(store.get_passkeys(&user_id).await.unwrap().len(), 2);
+}
+
+async fn check_passkey_ceremony_store(store: &dyn PasskeyCeremonyStore) {
+    let webauthn = webauthn();
+    let email = parse_email(&get_random_email());
+    let (_, registration) = webauthn
+        .start_passkey_registration(Uuid::new_v4(), "user", "user", None)
+        .expect("Failed to start registration");
+    let (_, authentication) = webauthn
+        .start_passkey_authentication(&[new_passkey(&webauthn, Uuid::new_v4())])
+        .expect("Failed to start authentication");
+
+    assert_eq!(store.take_registration(&email).await.unwrap_err(), PasskeyCeremonyStoreError::CeremonyNotFound);
+    assert_eq!(store.take_authentication(&email).await.unwrap_err(), PasskeyCeremonyStoreError::CeremonyNotFound);
+
+    store.add_registration(&email, registration).await.unwrap();
+
+    // registrations and authentications of the same address are kept apart
+    assert_eq!(store.take_authentication(&email).await.unwrap_err(), PasskeyCeremonyStoreError::CeremonyNotFound);
+    assert!(store.take_registration(&email).await.is_ok());
+    assert_eq!(store.take_registration(&email).await.unwrap_err(), PasskeyCeremonyStoreError::CeremonyNotFound);
+
+    store.add_authentication(&email, authentication).await.unwrap();
+
+    assert!(store.take_authentication(&email).await.is_ok());
+    assert_eq!(store.take_authentication(&email).await.unwrap_err(), PasskeyCeremonyStoreError::CeremonyNotFound);
+}
+
+async fn check_role_store(store: &dyn RoleStore, user_id: Uuid) {
+    let admin = ADMIN_ROLE.to_owned();
+    let roles = store.get_roles().await.unwrap();
+
+    assert!(
+        roles
+            .iter()
+            .any(|role| role.name == admin && role.permissions.iter().any(|permission| permission == RolesWrite::NAME))
+    );
+    assert!(store.get_user_roles(&user_id).await.unwrap().is_empty());
+    assert_eq!(store.assign_role(&user_id, "superuser").await, Err(RoleStoreError::RoleNotFound));
+    assert_eq!(store.assign_role(&Uuid::new_v4(), &admin).await, Err(RoleStoreError::UserNotFound));
+
+    store.assign_role(&user_id, &admin).await.unwrap();
+    store.assign_role(&user_id, &admin).await.unwrap();
+
+    assert_eq!(store.get_user_roles(&user_id).await.unwrap(), vec![admin.clone()]);
+
+    let permissions = store.get_permissions(&["user".to_owned(), admin.clone()]).await.unwrap();
+
+    assert!(permissions.iter().any(|permission| permission == UsersRead::NAME));
+    assert!(permissions.iter().any(|permission| permission == AuditRead::NAME));
+    assert!(store.get_permissions(&["user".to_owned()]).await.unwrap().is_empty());
+
+    store.revoke_role(&user_id, &admin).await.unwrap();
+    store.revoke_role(&user_id, &admin).await.unwrap();
+
+    assert!(store.get_user_roles(&user_id).await.unwrap().is_empty());
+}
+
+async fn check_audit_log(store: &dyn AuditLog) {
+    let user_id = Uuid::new_v4();
+    let now = Utc::now().timestamp();
+    let event = |event_type| AuditEvent {
+        event_type,
+        occurred_at: now,
+        user_id: Some(user_id),
+        actor_id: None,
+        ip: IpAddr::V4(Ipv4Addr::LOCALHOST),
+        user_agent: Some("conformance".to_owned()),
+        request_id: Uuid::new_v4(),
+        details: None,
+    };
+    let signup = event(AuditEventType::Signup);
+    let login_failed = event(AuditEventType::LoginFailed).details("Incorrect credentials");
+    let admin_action = AuditEvent {
+        user_id: Some(Uuid::new_v4()),
+        ip: IpAddr::V6(Ipv6Addr::LOCALHOST),
+        user_agent: None,
+        ..event(AuditEventType::AdminAction).actor(Some(user_id)).details("disable")
+    };
+
+    for event in [&signup, &login_failed, &admin_action] {
+        store.record(event.clone()).await.unwrap();
+    }
+
+    // events come back newest first, exactly as recorded
+    let page = store.query(&AuditEventFilter { user_id: Some(user_id), ..Default::default() }, 0, 10).await.unwrap();
+
+    assert_eq!((page.events, page.total), (vec![login_failed.clone(), signup.clone()], 2));
+
+    let filter = AuditEventFilter { event_type: Some(AuditEventType::AdminAction), ..Default::default() };
+    let page = store.query(&filter, 0, 10).await.unwrap();
+
+    assert_eq!((page.events, page.total), (vec![admin_action.clone()], 1));
+
+    let page = store.query(&AuditEventFilter::default(), 1, 1).await.unwrap();
+
+    assert_eq!((page.events, page.total), (vec![login_failed.clone()], 3));
+
+    let filter = AuditEventFilter { user_id: Some(user_id), event_type: Some(AuditEventType::Logout) };
+
+    assert_eq!(store.query(&filter, 0, 10).await.unwrap().total, 0);
+
+    // a removed user is erased from the events, which are kept; admins acting on them keep their address
+    let admin_action_on_user = event(AuditEventType::AdminAction).actor(Some(Uuid::new_v4())).details("delete");
+
+    store.record(admin_action_on_user.clone()).await.unwrap();
+    store.anonymise_user(&user_id).await.unwrap();
+
+    let anonymised = |event: AuditEvent| AuditEvent {
+        user_id: None,
+        ip: IpAddr::V4(Ipv4Addr::UNSPECIFIED),
+        user_agent: None,
+        ..event
+    };
+    let page = store.query(&AuditEventFilter::default(), 0, 10).await.unwrap();
+
+    assert_eq!(
+        page.events,
+        vec![
+            AuditEvent { user_id: None, ..admin_action_on_user },
+            AuditEvent { actor_id: None, ip: IpAddr::V4(Ipv4Addr::UNSPECIFIED), ..admin_action },
+            anonymised(login_failed),
+            anonymised(signup),
+        ]
+    );
+}
+
+async fn check_signing_key_store(store: &dyn SigningKeyStore) {
+    assert!(store.get_keys().await.unwrap().is_empty());
+
+    let active = SigningKey::new(JwtKey::generate().unwrap(), 1_000);
+    let retired = SigningKey::retired(JwtKey::generate().unwrap(), 2_000);
+
+    store.add_key(&active).await.unwrap();
+    store.add_key(&retired).await.unwrap();
+    // adding a stored key again keeps its timestamps
+    store.add_key(&SigningKey::retired(JwtKey::from_pkcs8(retired.key.pkcs8()).unwrap(), 3_000)).await.unwrap();
+
+    let mut keys: Vec<_> = store
+        .get_keys()
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|key| (key.key.kid.clone(), key.key.pkcs8().to_vec(), key.activates_at, key.retired_at))
+        .collect();
+
+    keys.sort_by_key(|key| key.2);
+
+    assert_eq!(
+        keys,
+        vec![
+            (active.key.kid.clone(), active.key.pkcs8().to_vec(), 1_000, None),
+            (retired.key.kid.clone(), retired.key.pkcs8().to_vec(), 2_000, Some(2_000)),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn hashmap_user_store_conforms() {
+    check_user_store(&HashmapUserStore::default()).await;
+}
+
+#[tokio::test]
+async fn postgres_user_store_conforms() {
+    let (pool, database_name) = configure_postgresql().await;
+
+    check_user_store(&PostgresUserStore::new(pool)).await;
+    delete_database(&database_name).await;
+}
+
+#[tokio::test]
+async fn hashset_banned_token_store_conforms() {
+    check_banned_token_store(&HashsetBannedTokenStore::default()).await;
+}
+
+#[tokio::test]
+async fn redis_banned_token_store_conforms() {
+    check_banned_token_store(&RedisBannedTokenStore::new(configure_redis().await)).await;
+}
+
+#[tokio::test]
+async fn hashmap_two_factor_store_conforms() {
+    check_two_factor_store(&HashmapTwoFactorStore::default()).await;
+}
+
+#[tokio::test]
+async fn redis_two_factor_store_conforms() {
+    check_two_factor_store(&RedisTwoFactorStore::new(configure_redis().await)).await;
+}
+
+#[tokio::test]
+async fn hashmap_password_reset_store_conforms() {
+    check_password_reset_store(&HashmapPasswordResetStore::default()).await;
+}
+
+#[tokio::test]
+async fn redis_password_reset_store_conforms() {
+    check_password_reset_store(&RedisPasswordResetStore::new(configure_redis().await)).await;
+}
+
+#[tokio::test]
+async fn hashmap_email_verification_store_conforms() {
+    check_email_verification_store(&HashmapEmailVerificationStore::default()).await;
+}
+
+#[tokio::test]
+async fn redis_email_verification_store_conforms() {
+    check_email_verification_store(&RedisEmailVerificationStore::new(configure_redis().await)).await;
+}
+
+#[tokio::test]
+async fn hashmap_magic_link_store_conforms() {
+    check_magic_link_store(&HashmapMagicLinkStore::default()).await;
+}
+
+#[tokio::test]
+async fn redis_magic_link_store_conforms() {
+    check_magic_link_store(&RedisMagicLinkStore::new(configure_redis().await)).await;
+}
+
+#[tokio::test]
+async fn hashmap_email_change_store_conforms() {
+    check_email_change_store(&HashmapEmailChangeStore::default()).await;
+}
+
+#[tokio::test]
+async fn redis_email_change_store_conforms() {
+    check_email_change_store(&RedisEmailChangeStore::new(configure_redis().await)).await;
+}
+
+#[tokio::test]
+async fn hashmap_refresh_token_store_conforms() {
+    let user_id = add_user(&HashmapUserStore::default()).await;
+
+    check_refresh_token_store(&HashmapRefreshTokenStore::default(), user_id).await;
+}
+
+#[tokio::test]
+async fn postgres_refresh_token_store_conforms() {
+    let (pool, database_name) = configure_postgresql().await;
+    let user_id = add_user(&PostgresUserStore::new(pool.clone())).await;
+
+    let store = PostgresRefreshTokenStore::new(pool.clone());
+
+    check_refresh_token_store(&store, user_id).await;
+
+    // only hashes of the tokens are stored
+    let token = RefreshToken::default();
+
+    store.add_token(user_id, Uuid::new_v4(), token.clone()).await.unwrap();
+
+    let stored: Vec<String> = query_scalar("select token_hash from refresh_tokens;").fetch_all(&pool).await.unwrap();
+
+    assert!(stored.contains(&token.hash()));
+    assert!(!stored.contains(token.as_ref().expose_secret()));
+    delete_database(&database_name).await;
+}
+
+#[tokio::test]
+async fn redis_refresh_token_store_conforms() {
+    check_refresh_token_store(&RedisRefreshTokenStore::new(configure_redis().await), Uuid::new_v4()).await;
+}
+
+#[tokio::test]
+async fn redis_refresh_token_stores_rotate_a_token_once_across_instances() {
+    let first = RedisRefreshTokenStore::new(configure_redis().await);
+    let second = RedisRefreshTokenStore::new(configure_redis().await);
+    let (user_id, session_id) = (Uuid::new_v4(), Uuid::new_v4());
+    let token = RefreshToken::default();
+
+    first.add_token(user_id, session_id, token.clone()).await.unwrap();
+
+    let (first_result, second_result) = join!(
+        first.rotate_token(&token, RefreshToken::default()),
+        second.rotate_token(&token, RefreshToken::default())
+    );
+    let mut results = [first_result, second_result];
+
+    results.sort_by_key(Result::is_err);
+
+    assert_eq!(results, [Ok((user_id, session_id)), Err(RefreshTokenStoreError::TokenReused)]);
+}
+
+#[tokio::test]
+async fn hashmap_session_store_conforms() {
+    check_session_store(&HashmapSessionStore::default()).await;
+}
+
+#[tokio::test]
+async fn redis_session_store_conforms() {
+    check_session_store(&RedisSessionStore::new(configure_redis().await)).await;
+}
+
+#[tokio::test]
+async fn redis_session_stores_keep_removed_sessions_removed_across_instances() {
+    let first = RedisSessionStore::new(configure_redis().await);
+    let second = RedisSessionStore::new(configure_redis().await);
+    let session = Session {
+        session_id: Uuid::new_v4(),
+        user_id: Uuid::new_v4(),
+        jti: Uuid::new_v4().to_string(),
+        created_at: Utc::now().timestamp(),
+        refreshed_at: Utc::now().timestamp(),
+        ip: IpAddr::V4(Ipv4Addr::LOCALHOST),
+        user_agent: None,
+    };
+
+    first.add_session(session.clone()).await.unwrap();
+
+    let (recorded, removed) = join!(
+        first.record_token(&session.user_id, &session.session_id, Uuid::new_v4().to_string(), session.created_at + 1),
+        second.remove_session(&session.user_id, &session.session_id),
+    );
+
+    assert!(matches!(recorded, Ok(()) | Err(SessionStoreError::SessionNotFound)));
+    assert_eq!(removed, Ok(()));
+    assert_eq!(first.get_sessions(&session.user_id).await, Ok(Vec::new()));
+}
+
+#[tokio::test]
+async fn hashmap_login_throttle_store_conforms() {
+    check_login_throttle_store(&HashmapLoginThrottleStore::default()).await;
+}
+
+#[tokio::test]
+async fn redis_login_throttle_store_conforms() {
+    check_login_throttle_store(&RedisLoginThrottleStore::new(configure_redis().await)).await;
+}
+
+#[tokio::test]
+async fn redis_login_throttle_stores_count_every_failure_across_instances() {
+    let first = RedisLoginThrottleStore::new(configure_redis().await);
+    let second = RedisLoginThrottleStore::new(configure_redis().await);
+    let key = ThrottleKey::Account(parse_email(&get_random_email()));
+
+    let _ = join!(
+        first.record_failure(&key),
+        second.record_failure(&key),
+        first.record_failure(&key),
+        second.record_failure(&key),
+    );
+
+    assert_eq!(first.get_failures(&key).await.unwrap().count, 4);
+}
+
+#[tokio::test]
+async fn in_memory_rate_limit_store_conforms() {
+    check_rate_limit_store(&InMemoryRateLimitStore::default()).await;
+}
+
+#[tokio::test]
+async fn redis_rate_limit_store_conforms() {
+    check_rate_limit_store(&RedisRateLimitStore::new(configure_redis().await)).await;
+}
+
+#[tokio::test]
+async fn hashmap_totp_secret_store_conforms() {
+    let user_id = add_user(&HashmapUserStore::default()).await;
+
+    check_totp_secret_store(&HashmapTotpSecretStore::default(), user_id).await;
+}
+
+#[tokio::test]
+async fn postgres_totp_secret_store_conforms() {
+    let (pool, database_name) = configure_postgresql().await;
+    let user_id = add_user(&PostgresUserStore::new(pool.clone())).await;
+
+    check_totp_secret_store(&PostgresTotpSecretStore::new(pool, &TOTP_ENCRYPTION_KEY), user_id).await;
+    delete_database(&database_name).await;
+}
+
+#[tokio::test]
+async fn hashmap_recovery_code_store_conforms() {
+    let user_id = add_user(&HashmapUserStore::default()).await;
+
+    check_recovery_code_store(&HashmapRecoveryCodeStore::default(), user_id).await;
+}
+
+#[tokio::test]
+async fn postgres_recovery_code_store_conforms() {
+    let (pool, database_name) = configure_postgresql().await;
+    let user_id = add_user(&PostgresUserStore::new(pool.clone())).await;
+
+    check_recovery_code_store(&PostgresRecoveryCodeStore::new(pool), user_id).await;
+    delete_database(&database_name).await;
+}
+
+#[tokio::test]
+async fn hashmap_passkey_store_conforms() {
+    let user_store = HashmapUserStore::default();
+    let (user_id, other_user_id) = (add_user(&user_store).await, add_user(&user_store).await);
+
+    check_passkey_store(&HashmapPasskeyStore::default(), user_id, other_user_id).await;
+}
+
+#[tokio::test]
+async fn postgres_passkey_store_conforms() {
+    let (pool, database_name) = configure_postgresql().await;
+    let user_store = PostgresUserStore::new(pool.clone());
+    let (user_id, other_user_id) = (add_user(&user_store).await, add_user(&user_store).await);
+
+    check_passkey_store(&PostgresPasskeyStore::new(pool), user_id, other_user_id).await;
+    delete_database(&database_name).await;
+}
+
+#[tokio::test]
+async fn hashmap_passkey_ceremony_store_conforms() {
+    check_passkey_ceremony_store(&HashmapPasskeyCeremonyStore::default()).await;
+}
+
+#[tokio::test]
+async fn redis_passkey_ceremony_store_conforms() {
+    check_passkey_ceremony_store(&RedisPasskeyCeremonyStore::new(configure_redis().await)).await;
+}
+
+#[tokio::test]
+async fn hashmap_role_store_conforms() {
+    let user_store = Arc::new(HashmapUserStore::default());
+    let user_id = add_user(user_store.as_ref()).await;
+
+    check_role_store(&HashmapRoleStore::new(user_store), user_id).await;
+}
+
+#[tokio::test]
+async fn postgres_role_store_conforms() {
+    let (pool, database_name) = configure_postgresql().await;
+    let user_id = add_user(&PostgresUserStore::new(pool.clone())).await;
+
+    check_role_store(&PostgresRoleStore::new(pool), user_id).await;
+    delete_database(&database_name).await;
+}
+
+#[tokio::test]
+async fn in_memory_audit_log_conforms() {
+    check_audit_log(&InMemoryAuditLog::default()).await;
+}
+
+#[tokio::test]
+async fn postgres_audit_log_conforms() {
+    let (pool, database_name) = configure_postgresql().await;
+
+    check_audit_log(&PostgresAuditLog::new(pool.clone())).await;
+
+    // anonymising is the only change the log accepts
+    assert!(query("update audit_events set details = 'changed'").execute(&pool).await.is_err());
+    assert!(query("update audit_events set user_id = gen_random_uuid()").execute(&pool).await.is_err());
+    assert!(query("delete from audit_events").execute(&pool).await.is_err());
+
+    delete_database(&database_name).await;
+}
+
+#[tokio::test]
+async fn hashmap_signing_key_store_conforms() {
+    check_signing_key_store(&HashmapSigningKeyStore::default()).await;
+}
+
+#[tokio::test]
+async fn postgres_signing_key_store_conforms() {
+    let (pool, database_name) = configure_postgresql().await;
+
+    let store = PostgresSigningKeyStore::new(pool.clone(), &TOTP_ENCRYPTION_KEY);
+
+    check_signing_key_store(&store).await;
+
+    // private keys are only stored encrypted
+    let stored: Vec<Vec<u8>> = query_scalar("select private_key from jwt_signing_keys").fetch_all(&pool).await.unwrap();
+
+    for key in store.get_keys().await.unwrap() {
+        let pkcs8 = key.key.pkcs8();
+
+        assert!(stored.iter().all(|stored| !stored.windows(pkcs8.len()).any(|window| window == pkcs8)));
+    }
+
+    delete_database(&database_name).await;
+}
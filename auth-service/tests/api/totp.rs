@@ -0,0 +1,242 @@
+use {
+    crate::helpers::{TestApp, get_random_email},
+    auth_service::{
+        ErrorResponse,
+        domain::user::TwoFactorMethod,
+        routes::{ConfirmTotpResponse, EnrollTotpResponse, TwoFactorAuthResponse},
+        utils::constants::JWT_COOKIE_NAME,
+    },
+    serde_json::json,
+    totp_rs::TOTP,
+};
+
+async fn signup_and_login(app: &TestApp, email: &str) {
+    let _ = app
+        .post_signup(&json!({
+            "email": email,
+            "password": "abcd1234",
+            "requires2FA": false
+        }))
+        .await;
+
+    app.verify_email(email).await;
+
+    let _ = app
+        .post_login(&json!({
+            "email": email,
+            "password": "abcd1234",
+        }))
+        .await;
+}
+
+async fn enroll(app: &TestApp) -> TOTP {
+    let response = app.post_totp_enroll().await;
+
+    assert_eq!(response.status().as_u16(), 200);
+
+    let body = response.json::<EnrollTotpResponse>().await.expect("Could not deserialize response body");
+    let totp = TOTP::from_url(&body.otpauth_uri).expect("Failed to parse otpauth URI");
+
+    assert!(body.otpauth_uri.starts_with("otpauth://totp/"));
+    assert_eq!(totp.get_secret_base32(), body.secret);
+
+    totp
+}
+
+/// Code of the step after the current one, which is still accepted while differing from the code confirmed with.
+fn next_code(totp: &TOTP) -> String {
+    totp.generate(totp.next_step_current().unwrap())
+}
+
+#[tokio::test]
+async fn should_enable_totp_and_accept_totp_code_on_login() {
+    let mut app = TestApp::new().await;
+    let email = get_random_email();
+
+    signup_and_login(&app, &email).await;
+
+    let totp = enroll(&app).await;
+    let response = app.post_totp_confirm(&json!({ "code": totp.generate_current().unwrap() })).await;
+
+    assert_eq!(response.status().as_u16(), 200);
+
+    let body = response.json::<ConfirmTotpResponse>().await.expect("Could not deserialize response body");
+
+    assert_eq!(body.message, "TOTP enabled successfully!".to_owned());
+    assert_eq!(body.recovery_codes.len(), 10);
+
+    let response = app
+        .post_login(&json!({
+            "email": email,
+            "password": "abcd1234",
+        }))
+        .await;
+
+    assert_eq!(response.status().as_u16(), 206);
+
+    let body = response.json::<TwoFactorAuthResponse>().await.expect("Could not deserialize response body");
+
+    assert_eq!(body.two_factor_method, TwoFactorMethod::Totp);
+
+    let response = app
+        .post_verify_2fa(&json!({
+            "email": email,
+            "loginAttemptId": body.login_attempt_id,
+            "2FACode": next_code(&totp)
+        }))
+        .await;
+
+    assert_eq!(response.status().as_u16(), 200);
+
+    let cookie = response.cookies().find(|cookie| cookie.name() == JWT_COOKIE_NAME).expect("No auth cookie found");
+
+    assert!(!cookie.value().is_empty());
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_reject_emailed_style_code_for_totp_user() {
+    let mut app = TestApp::new().await;
+    let email = get_random_email();
+
+    signup_and_login(&app, &email).await;
+
+    let totp = enroll(&app).await;
+    let _ = app.post_totp_confirm(&json!({ "code": totp.generate_current().unwrap() })).await;
+    let response = app
+        .post_login(&json!({
+            "email": email,
+            "password": "abcd1234",
+        }))
+        .await;
+    let body = response.json::<TwoFactorAuthResponse>().await.expect("Could not deserialize response body");
+    let wrong_code = format!("{:06}", (totp.generate_current().unwrap().parse::<u32>().unwrap() + 1) % 1_000_000);
+    let response = app
+        .post_verify_2fa(&json!({
+            "email": email,
+            "loginAttemptId": body.login_attempt_id,
+            "2FACode": wrong_code
+        }))
+        .await;
+
+    assert_eq!(response.status().as_u16(), 401);
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_reject_reused_totp_code() {
+    let mut app = TestApp::new().await;
+    let email = get_random_email();
+
+    signup_and_login(&app, &email).await;
+
+    let totp = enroll(&app).await;
+    let confirmation_code = totp.generate_current().unwrap();
+    let _ = app.post_totp_confirm(&json!({ "code": confirmation_code })).await;
+    let login_code = next_code(&totp);
+
+    for (code, status) in [(&confirmation_code, 401), (&login_code, 200), (&login_code, 401)] {
+        let response = app
+            .post_login(&json!({
+                "email": email,
+                "password": "abcd1234",
+            }))
+            .await;
+        let body = response.json::<TwoFactorAuthResponse>().await.expect("Could not deserialize response body");
+        let response = app
+            .post_verify_2fa(&json!({
+                "email": email,
+                "loginAttemptId": body.login_attempt_id,
+                "2FACode": code
+            }))
+            .await;
+
+        assert_eq!(response.status().as_u16(), status);
+    }
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_switch_back_to_email_method() {
+    let mut app = TestApp::new().await;
+    let email = get_random_email();
+
+    signup_and_login(&app, &email).await;
+
+    let totp = enroll(&app).await;
+    let _ = app.post_totp_confirm(&json!({ "code": totp.generate_current().unwrap() })).await;
+    let response = app.post_two_factor_method(&json!({ "method": "email" })).await;
+
+    assert_eq!(response.status().as_u16(), 200);
+
+    let response = app
+        .post_login(&json!({
+            "email": email,
+            "password": "abcd1234",
+        }))
+        .await;
+
+    assert_eq!(response.status().as_u16(), 206);
+    assert_eq!(
+        response.json::<TwoFactorAuthResponse>().await.expect("Could not deserialize response body").two_factor_method,
+        TwoFactorMethod::Email
+    );
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_return_400_if_totp_method_set_before_confirmation() {
+    let mut app = TestApp::new().await;
+    let email = get_random_email();
+
+    signup_and_login(&app, &email).await;
+
+    let _ = enroll(&app).await;
+    let response = app.post_two_factor_method(&json!({ "method": "totp" })).await;
+
+    assert_eq!(response.status().as_u16(), 400);
+    assert_eq!(
+        response.json::<ErrorResponse>().await.expect("Could not deserialize response body to ErrorResponse").error,
+        "Invalid credentials".to_owned()
+    );
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_return_401_if_incorrect_confirmation_code() {
+    let mut app = TestApp::new().await;
+    let email = get_random_email();
+
+    signup_and_login(&app, &email).await;
+
+    let totp = enroll(&app).await;
+    let wrong_code = format!("{:06}", (totp.generate_current().unwrap().parse::<u32>().unwrap() + 1) % 1_000_000);
+    let response = app.post_totp_confirm(&json!({ "code": wrong_code })).await;
+
+    assert_eq!(response.status().as_u16(), 401);
+    assert_eq!(
+        response.json::<ErrorResponse>().await.expect("Could not deserialize response body to ErrorResponse").error,
+        "Incorrect credentials".to_owned()
+    );
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_return_400_if_jwt_cookie_missing() {
+    let mut app = TestApp::new().await;
+    let response = app.post_totp_enroll().await;
+
+    assert_eq!(response.status().as_u16(), 400);
+    assert_eq!(
+        response.json::<ErrorResponse>().await.expect("Could not deserialize response body to ErrorResponse").error,
+        "Missing token".to_owned()
+    );
+
+    app.clean_up().await;
+}
@@ -1,7 +1,7 @@
 use {
     crate::helpers::{TestApp, get_random_email},
-    auth_service::{domain::email::Email, utils::constants::JWT_COOKIE_NAME},
-    secrecy::{ExposeSecret, SecretBox},
+    auth_service::utils::constants::{JWT_COOKIE_NAME, MAX_2FA_ATTEMPTS},
+    secrecy::ExposeSecret,
     serde_json::json,
 };
 
@@ -17,6 +17,9 @@ async fn should_return_200_if_correct_code() {
             "requires2FA": true
         }))
         .await;
+
+    app.verify_email(&email).await;
+
     let _ = app
         .post_login(&json!({
             "email": email,
@@ -25,7 +28,7 @@ async fn should_return_200_if_correct_code() {
         .await;
     let (attempt_id, code) = app
         .two_factor_store
-        .get_code(&Email::parse(&SecretBox::new(Box::new(email.clone()))).unwrap())
+        .get_code(&app.get_user_id(&email).await)
         .await
         .expect("Failed to get code from two factor store");
     let response = app
@@ -89,6 +92,9 @@ async fn should_return_401_if_old_code() {
             "requires2FA": true
         }))
         .await;
+
+    app.verify_email(&email).await;
+
     let _ = app
         .post_login(&json!({
             "email": email,
@@ -97,7 +103,7 @@ async fn should_return_401_if_old_code() {
         .await;
     let (attempt_id, code) = app
         .two_factor_store
-        .get_code(&Email::parse(&SecretBox::new(Box::new(email.clone()))).unwrap())
+        .get_code(&app.get_user_id(&email).await)
         .await
         .expect("Failed to get code from two factor store");
     let _ = app
@@ -131,6 +137,9 @@ async fn should_return_401_if_same_code_twice() {
             "requires2FA": true
         }))
         .await;
+
+    app.verify_email(&email).await;
+
     let _ = app
         .post_login(&json!({
             "email": email,
@@ -139,7 +148,7 @@ async fn should_return_401_if_same_code_twice() {
         .await;
     let (attempt_id, code) = app
         .two_factor_store
-        .get_code(&Email::parse(&SecretBox::new(Box::new(email.clone()))).unwrap())
+        .get_code(&app.get_user_id(&email).await)
         .await
         .expect("Failed to get code from two factor store");
     let _ = app
@@ -177,3 +186,59 @@ async fn should_return_422_if_malformed_input() {
 
     app.clean_up().await;
 }
+
+#[tokio::test]
+async fn should_discard_attempt_after_too_many_wrong_codes() {
+    let mut app = TestApp::new().await;
+    let email = get_random_email();
+    let password = "abcd1234";
+    let _ = app
+        .post_signup(&json!({
+            "email": email,
+            "password": password,
+            "requires2FA": true
+        }))
+        .await;
+
+    app.verify_email(&email).await;
+
+    let _ = app
+        .post_login(&json!({
+            "email": email,
+            "password": password,
+        }))
+        .await;
+    let (attempt_id, code) = app
+        .two_factor_store
+        .get_code(&app.get_user_id(&email).await)
+        .await
+        .expect("Failed to get code from two factor store");
+    let wrong_code = match code.as_ref().expose_secret().as_str() {
+        "000000" => "111111",
+        _ => "000000",
+    };
+
+    for _ in 0..*MAX_2FA_ATTEMPTS {
+        let response = app
+            .post_verify_2fa(&json!({
+                "email": email,
+                "loginAttemptId": attempt_id.as_ref().expose_secret(),
+                "2FACode": wrong_code
+            }))
+            .await;
+
+        assert_eq!(response.status().as_u16(), 401);
+    }
+
+    let response = app
+        .post_verify_2fa(&json!({
+            "email": email,
+            "loginAttemptId": attempt_id.as_ref().expose_secret(),
+            "2FACode": code.as_ref().expose_secret()
+        }))
+        .await;
+
+    assert_eq!(response.status().as_u16(), 401);
+
+    app.clean_up().await;
+}
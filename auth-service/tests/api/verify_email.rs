@@ -0,0 +1,207 @@
+use {
+    crate::helpers::{TestApp, get_random_email},
+    auth_service::{ErrorResponse, domain::email::Email, routes::VerifyEmailResponse},
+    secrecy::{ExposeSecret, SecretBox},
+    serde_json::json,
+};
+
+#[tokio::test]
+async fn should_return_403_on_login_if_email_not_verified() {
+    let mut app = TestApp::new().await;
+    let email = get_random_email();
+    let _ = app
+        .post_signup(&json!({
+            "email": email,
+            "password": "abcd1234",
+            "requires2FA": false
+        }))
+        .await;
+    let response = app
+        .post_login(&json!({
+            "email": email,
+            "password": "abcd1234",
+        }))
+        .await;
+
+    assert_eq!(response.status().as_u16(), 403);
+    assert_eq!(
+        response.json::<ErrorResponse>().await.expect("Could not deserialize response body to ErrorResponse").error,
+        "Email not verified".to_owned()
+    );
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_return_200_if_valid_token() {
+    let mut app = TestApp::new().await;
+    let email = get_random_email();
+    let _ = app
+        .post_signup(&json!({
+            "email": email,
+            "password": "abcd1234",
+            "requires2FA": false
+        }))
+        .await;
+    let parsed_email = Email::parse(&SecretBox::new(Box::new(email.clone()))).unwrap();
+    let (token, _) = app
+        .email_verification_store
+        .get_token(&parsed_email)
+        .await
+        .expect("Failed to get token from email verification store");
+    let response = app
+        .post_verify_email(&json!({
+            "email": email,
+            "token": token.as_ref().expose_secret()
+        }))
+        .await;
+
+    assert_eq!(response.status().as_u16(), 200);
+    assert_eq!(
+        response
+            .json::<VerifyEmailResponse>()
+            .await
+            .expect("Could not deserialize response body to VerifyEmailResponse"),
+        VerifyEmailResponse { message: "Email verified successfully!".to_owned() }
+    );
+    assert!(app.email_verification_store.get_token(&parsed_email).await.is_err());
+
+    let response = app
+        .post_login(&json!({
+            "email": email,
+            "password": "abcd1234",
+        }))
+        .await;
+
+    assert_eq!(response.status().as_u16(), 200);
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_return_400_if_invalid_input() {
+    let mut app = TestApp::new().await;
+    let response = app
+        .post_verify_email(&json!({
+            "email": "me",
+            "token": "550e8400-e29b-41d4-a716-446655440000"
+        }))
+        .await;
+
+    assert_eq!(response.status().as_u16(), 400);
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_return_401_if_incorrect_token() {
+    let mut app = TestApp::new().await;
+    let email = get_random_email();
+    let _ = app
+        .post_signup(&json!({
+            "email": email,
+            "password": "abcd1234",
+            "requires2FA": false
+        }))
+        .await;
+    let response = app
+        .post_verify_email(&json!({
+            "email": email,
+            "token": "550e8400-e29b-41d4-a716-446655440000"
+        }))
+        .await;
+
+    assert_eq!(response.status().as_u16(), 401);
+    assert_eq!(
+        response.json::<ErrorResponse>().await.expect("Could not deserialize response body to ErrorResponse").error,
+        "Invalid token".to_owned()
+    );
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_return_401_if_token_used_twice() {
+    let mut app = TestApp::new().await;
+    let email = get_random_email();
+    let _ = app
+        .post_signup(&json!({
+            "email": email,
+            "password": "abcd1234",
+            "requires2FA": false
+        }))
+        .await;
+    let (token, _) = app
+        .email_verification_store
+        .get_token(&Email::parse(&SecretBox::new(Box::new(email.clone()))).unwrap())
+        .await
+        .expect("Failed to get token from email verification store");
+    let body = json!({
+        "email": email,
+        "token": token.as_ref().expose_secret()
+    });
+    let _ = app.post_verify_email(&body).await;
+    let response = app.post_verify_email(&body).await;
+
+    assert_eq!(response.status().as_u16(), 401);
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_not_resend_if_resent_too_soon() {
+    let mut app = TestApp::new().await;
+    let email = get_random_email();
+    let _ = app
+        .post_signup(&json!({
+            "email": email,
+            "password": "abcd1234",
+            "requires2FA": false
+        }))
+        .await;
+    let parsed_email = Email::parse(&SecretBox::new(Box::new(email.clone()))).unwrap();
+    let (token, _) = app
+        .email_verification_store
+        .get_token(&parsed_email)
+        .await
+        .expect("Failed to get token from email verification store");
+    let response = app.post_resend_verification_email(&json!({ "email": email })).await;
+
+    // answered like any other address, without sending another email
+    assert_eq!(response.status().as_u16(), 200);
+
+    let (resent_token, _) = app
+        .email_verification_store
+        .get_token(&parsed_email)
+        .await
+        .expect("Failed to get token from email verification store");
+
+    assert_eq!(resent_token, token);
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_return_200_on_resend_if_user_does_not_exist() {
+    let mut app = TestApp::new().await;
+    let email = get_random_email();
+    let response = app.post_resend_verification_email(&json!({ "email": email })).await;
+
+    assert_eq!(response.status().as_u16(), 200);
+
+    let email = Email::parse(&SecretBox::new(Box::new(email))).unwrap();
+
+    assert!(app.email_verification_store.get_token(&email).await.is_err());
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_return_422_if_malformed_input() {
+    let mut app = TestApp::new().await;
+    let response = app.post_verify_email(&json!({ "email": get_random_email() })).await;
+
+    assert_eq!(response.status().as_u16(), 422);
+
+    app.clean_up().await;
+}
@@ -1,16 +1,32 @@
 use {
-    crate::helpers::{TestApp, get_random_email},
-    auth_service::{domain::email::Email, utils::auth::generate_auth_token},
+    crate::helpers::{TestApp, configure_redis, get_random_email},
+    auth_service::utils::auth::{generate_auth_token, validate_token},
+    redis::AsyncTypedCommands,
     secrecy::{ExposeSecret, SecretBox},
     serde_json::json,
+    uuid::Uuid,
 };
 
+/// Signs a token for a freshly signed up and verified user, since tokens of users that do not exist are rejected.
+async fn user_token(app: &TestApp) -> SecretBox<String> {
+    let email = get_random_email();
+
+    app.post_signup(&json!({
+        "email": email,
+        "password": "abcd1234",
+        "requires2FA": false
+    }))
+    .await;
+
+    app.verify_email(&email).await;
+
+    generate_auth_token(&app.get_user_id(&email).await, &Uuid::new_v4(), 0, Vec::new()).unwrap()
+}
+
 #[tokio::test]
 async fn should_return_200_if_malformed_input() {
     let mut app = TestApp::new().await;
-    let email = get_random_email();
-    let email = Email::parse(&SecretBox::new(Box::new(email))).unwrap();
-    let token = generate_auth_token(&email).unwrap();
+    let token = user_token(&app).await;
     let response = app.post_verify_token(&json!({ "token": token.expose_secret() })).await;
 
     assert_eq!(response.status().as_u16(), 200);
@@ -21,11 +37,29 @@ async fn should_return_200_if_malformed_input() {
 #[tokio::test]
 async fn should_return_401_if_banned_token() {
     let mut app = TestApp::new().await;
-    let email = get_random_email();
-    let email = Email::parse(&SecretBox::new(Box::new(email))).unwrap();
-    let token = generate_auth_token(&email).unwrap();
+    let token = user_token(&app).await;
 
-    assert!(app.banned_token_store.register(vec![&token]).await.is_ok());
+    let claims = validate_token(None, &token).await.expect("Failed to decode auth token");
+
+    assert!(app.banned_token_store.register(&claims.jti, claims.exp as i64).await.is_ok());
+
+    let response = app.post_verify_token(&json!({ "token": token.expose_secret() })).await;
+
+    assert_eq!(response.status().as_u16(), 401);
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_return_401_if_token_banned_under_legacy_key() {
+    let mut app = TestApp::new().await;
+    let token = user_token(&app).await;
+
+    configure_redis()
+        .await
+        .set_ex(format!("banned_token:{}", token.expose_secret()), true, 60)
+        .await
+        .expect("Failed to write legacy ban");
 
     let response = app.post_verify_token(&json!({ "token": token.expose_secret() })).await;
 
@@ -53,3 +87,14 @@ async fn should_return_422_if_malformed_input() {
 
     app.clean_up().await;
 }
+
+#[tokio::test]
+async fn should_return_401_if_user_does_not_exist() {
+    let mut app = TestApp::new().await;
+    let token = generate_auth_token(&Uuid::new_v4(), &Uuid::new_v4(), 0, Vec::new()).unwrap();
+    let response = app.post_verify_token(&json!({ "token": token.expose_secret() })).await;
+
+    assert_eq!(response.status().as_u16(), 401);
+
+    app.clean_up().await;
+}